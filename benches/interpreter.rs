@@ -0,0 +1,93 @@
+//! End-to-end execution of contracts through `StackExecutor`. Run with
+//! `cargo bench --bench interpreter`.
+
+use std::collections::BTreeMap;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use primitive_types::{H160, U256};
+use evm::{Config, ExitReason, ExitSucceed};
+use evm::backend::{MemoryAccount, MemoryBackend, MemoryVicinity};
+use evm::executor::StackExecutor;
+
+const GAS_LIMIT: usize = 100_000_000;
+
+fn vicinity() -> MemoryVicinity {
+	MemoryVicinity {
+		gas_price: U256::zero(),
+		origin: H160::default(),
+		chain_id: U256::zero(),
+		block_hashes: Vec::new(),
+		block_number: U256::zero(),
+		block_coinbase: H160::default(),
+		block_timestamp: U256::zero(),
+		block_difficulty: U256::zero(),
+		block_randomness: None,
+		block_gas_limit: U256::from(GAS_LIMIT),
+		block_base_fee_per_gas: U256::zero(),
+	}
+}
+
+fn bench_contract(c: &mut Criterion, name: &str, source: &str) {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+	let code = evm::assemble(source).unwrap();
+
+	let vicinity = vicinity();
+	let mut state = BTreeMap::new();
+	state.insert(contract, MemoryAccount { code, ..Default::default() });
+	let backend = MemoryBackend::new(&vicinity, state);
+	let config = Config::istanbul();
+
+	c.bench_function(name, |b| b.iter_batched(
+		|| StackExecutor::new(&backend, GAS_LIMIT, &config),
+		|mut executor| {
+			let (reason, _) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), GAS_LIMIT);
+			assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+		},
+		BatchSize::SmallInput,
+	));
+}
+
+fn arithmetic(c: &mut Criterion) {
+	// Count down from 100,000, multiplying and adding into an accumulator.
+	bench_contract(c, "arithmetic", "
+		PUSH1 1 PUSH3 100000
+		loop:
+			SWAP1 PUSH1 3 MUL PUSH1 7 ADD SWAP1
+			PUSH1 1 SWAP1 SUB
+			DUP1 PUSH2 @loop JUMPI
+		STOP
+	");
+}
+
+fn memory(c: &mut Criterion) {
+	// Store each counter value at 32 times its value, expanding memory to 128 KiB,
+	// then copy the first half of memory over the second ten times.
+	bench_contract(c, "memory", "
+		PUSH2 4096
+		store:
+			DUP1 DUP1 PUSH1 32 MUL MSTORE
+			PUSH1 1 SWAP1 SUB
+			DUP1 PUSH2 @store JUMPI
+		PUSH1 10
+		copy:
+			PUSH3 0x010000 PUSH1 0 PUSH3 0x010000 CODECOPY
+			PUSH1 1 SWAP1 SUB
+			DUP1 PUSH2 @copy JUMPI
+		STOP
+	");
+}
+
+fn keccak(c: &mut Criterion) {
+	// Hash 64 bytes of memory 10,000 times, chaining each hash into the input.
+	bench_contract(c, "keccak", "
+		PUSH2 10000
+		loop:
+			PUSH1 64 PUSH1 0 SHA3 PUSH1 0 MSTORE
+			PUSH1 1 SWAP1 SUB
+			DUP1 PUSH2 @loop JUMPI
+		STOP
+	");
+}
+
+criterion_group!(benches, arithmetic, memory, keccak);
+criterion_main!(benches);
@@ -0,0 +1,124 @@
+//! Precompiles of `evm-precompile`, called directly through its dispatcher.
+//! Other dispatchers can be benchmarked by passing them to `bench_precompile`.
+//! Run with `cargo bench --bench precompile`.
+
+use std::collections::BTreeMap;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use primitive_types::{H160, U256};
+use evm::{Config, Context};
+use evm::backend::{MemoryBackend, MemoryVicinity};
+use evm::executor::{EcrecoverInput, PrecompileContext, PrecompileFn};
+use evm_precompile::tron_precompile;
+
+/// G1 and G2 generators, followed by the negated G1 generator and the G2 generator.
+const PAIRING: &str = concat!(
+	"0000000000000000000000000000000000000000000000000000000000000001",
+	"0000000000000000000000000000000000000000000000000000000000000002",
+	"198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2",
+	"1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed",
+	"090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b",
+	"12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa",
+	"0000000000000000000000000000000000000000000000000000000000000001",
+	"30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45",
+	"198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2",
+	"1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed",
+	"090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b",
+	"12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa",
+);
+
+/// Benchmark a precompile of the dispatcher with the given named inputs.
+fn bench_precompile(c: &mut Criterion, name: &str, precompile: PrecompileFn, address: H160, inputs: &[(String, Vec<u8>)]) {
+	let vicinity = MemoryVicinity {
+		gas_price: U256::zero(),
+		origin: H160::default(),
+		chain_id: U256::zero(),
+		block_hashes: Vec::new(),
+		block_number: U256::zero(),
+		block_coinbase: H160::default(),
+		block_timestamp: U256::zero(),
+		block_difficulty: U256::zero(),
+		block_randomness: None,
+		block_gas_limit: U256::zero(),
+		block_base_fee_per_gas: U256::zero(),
+	};
+	let backend = MemoryBackend::new(&vicinity, BTreeMap::new());
+	let config = Config::great_voyage_4_7();
+	let context = Context {
+		address,
+		caller: H160::default(),
+		call_value: U256::zero(),
+		call_token_id: U256::zero(),
+		call_token_value: U256::zero(),
+	};
+	let context = PrecompileContext::new(&context, &backend);
+
+	let mut group = c.benchmark_group(name);
+	for (label, input) in inputs {
+		group.throughput(Throughput::Bytes(input.len() as u64));
+		group.bench_with_input(BenchmarkId::from_parameter(label), input, |b, input| b.iter(|| {
+			let ret = precompile(address, black_box(input), None, &config, &context);
+			assert!(matches!(ret, Some(Ok(_))));
+		}));
+	}
+	group.finish();
+}
+
+/// Inputs of the given sizes, named by size.
+fn sized(sizes: &[usize]) -> Vec<(String, Vec<u8>)> {
+	sizes.iter().map(|size| (size.to_string(), (0..*size).map(|i| i as u8).collect())).collect()
+}
+
+/// Single input decoded from hex.
+fn fixed(label: &str, input: &str) -> Vec<(String, Vec<u8>)> {
+	vec![(label.to_string(), hex::decode(input).unwrap())]
+}
+
+fn precompiles(c: &mut Criterion) {
+	let sizes = [32, 1024, 32 * 1024];
+	let address = H160::from_low_u64_be;
+
+	bench_precompile(c, "ecrecover", tron_precompile, address(1), &fixed("valid", concat!(
+		"456e9aea5e197a1f1af7a3e85a3212fa4049a3ba34c2289b4c860fc0b0c64ef3",
+		"000000000000000000000000000000000000000000000000000000000000001c",
+		"9242685bf161793cc25603c231bc2f568eb630ea16aa137d2664ac8038825608",
+		"4f8ae3bd7535248d0bd448298cc2e2071e56992d0774dc340c368ae950852ada",
+	)));
+	bench_precompile(c, "sha256", tron_precompile, address(2), &sized(&sizes));
+	bench_precompile(c, "ripemd160", tron_precompile, address(3), &sized(&sizes));
+	bench_precompile(c, "identity", tron_precompile, address(4), &sized(&sizes));
+	bench_precompile(c, "modexp", tron_precompile, address(5), &fixed("eip198", concat!(
+		"0000000000000000000000000000000000000000000000000000000000000001",
+		"0000000000000000000000000000000000000000000000000000000000000020",
+		"0000000000000000000000000000000000000000000000000000000000000020",
+		"03",
+		"fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2e",
+		"fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+	)));
+	bench_precompile(c, "bn128_add", tron_precompile, address(6), &fixed("chfast1", concat!(
+		"18b18acfb4c2c30276db5411368e7185b311dd124691610c5d3b74034e093dc9",
+		"063c909c4720840cb5134cb9f59fa749755796819658d32efc0d288198f37266",
+		"07c2b7f58a84bd6145f00c9c2bc0bb1a187f20ff2c92963a88019e7c6a014eed",
+		"06614e20c147e940f2d70da3f74c9a17df361706a4485c742bd6788478fa17d7",
+	)));
+	bench_precompile(c, "bn128_mul", tron_precompile, address(7), &fixed("chfast1", concat!(
+		"2bd3e6d0f3b142924f5ca7b49ce5b9d54c4703d7ae5648e61d02268b1a0a9fb7",
+		"21611ce0a6af85915e2f1d70300909ce2e49dfad4a4619c8390cae66cefdb204",
+		"00000000000000000000000000000000000000000000000011138ce750fa15c2",
+	)));
+	bench_precompile(c, "bn128_pairing", tron_precompile, address(8), &fixed("two_pairs", PAIRING));
+}
+
+fn ecrecover_input(c: &mut Criterion) {
+	let mut input = [0x11u8; 128];
+	input[32..63].iter_mut().for_each(|b| *b = 0);
+	input[63] = 27;
+	input[96] = 0x01;
+	let config = Config::istanbul().builder().ecrecover_strict(true).build().unwrap();
+
+	c.bench_function("ecrecover_input", |b| b.iter(|| {
+		assert!(EcrecoverInput::parse(black_box(&input), &config).is_some());
+	}));
+}
+
+criterion_group!(benches, precompiles, ecrecover_input);
+criterion_main!(benches);
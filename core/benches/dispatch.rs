@@ -0,0 +1,50 @@
+//! Interpreter dispatch throughput. Run with `cargo bench -p evm-core`.
+
+use std::time::Instant;
+use evm_core::{Capture, ExitReason, Machine};
+
+fn bench(name: &str, code: &str, data: &str, iterations: u32) {
+	let code = hex::decode(code).unwrap();
+	let data = hex::decode(data).unwrap();
+
+	let mut steps = 0u64;
+	let start = Instant::now();
+	for _ in 0..iterations {
+		let mut machine = Machine::new(code.clone(), data.clone(), 1024, usize::MAX);
+		loop {
+			steps += 1;
+			match machine.step() {
+				Ok(()) => (),
+				Err(Capture::Exit(ExitReason::Succeed(_))) => break,
+				Err(reason) => panic!("{:?}", reason),
+			}
+		}
+	}
+	let elapsed = start.elapsed();
+
+	println!(
+		"{:<12} {:>10.2?} per run, {:>8.2} Msteps/s",
+		name,
+		elapsed / iterations,
+		steps as f64 / elapsed.as_secs_f64() / 1_000_000.0,
+	);
+}
+
+fn main() {
+	// Count down from 1,000,000.
+	bench("countdown", "620f42405b600190038060045700", "", 10);
+
+	bench(
+		"ackermann",
+		"60e060020a6000350480632839e92814601e57806361047ff414603457005b602a6004356024356047565b8060005260206000f35b603d6004356099565b8060005260206000f35b600082600014605457605e565b8160010190506093565b81600014606957607b565b60756001840360016047565b90506093565b609060018403608c85600186036047565b6047565b90505b92915050565b6000816000148060a95750816001145b60b05760b7565b81905060cf565b60c1600283036099565b60cb600184036099565b0190505b91905056",
+		"2839e92800000000000000000000000000000000000000000000000000000000000000020000000000000000000000000000000000000000000000000000000000000007",
+		1000,
+	);
+
+	bench(
+		"fibonacci",
+		"60e060020a6000350480632839e92814601e57806361047ff414603457005b602a6004356024356047565b8060005260206000f35b603d6004356099565b8060005260206000f35b600082600014605457605e565b8160010190506093565b81600014606957607b565b60756001840360016047565b90506093565b609060018403608c85600186036047565b6047565b90505b92915050565b6000816000148060a95750816001145b60b05760b7565b81905060cf565b60c1600283036099565b60cb600184036099565b0190505b91905056",
+		"61047ff40000000000000000000000000000000000000000000000000000000000000018",
+		10,
+	);
+}
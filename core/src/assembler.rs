@@ -0,0 +1,209 @@
+use core::fmt;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::collections::BTreeMap;
+use primitive_types::U256;
+use crate::{Opcode, ExternalOpcode};
+
+/// Error assembling a program.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AssemblerError {
+	/// Mnemonic is not an opcode.
+	UnknownMnemonic(String),
+	/// `PUSH` without an immediate value.
+	MissingImmediate,
+	/// Immediate value is not a number or label reference.
+	InvalidImmediate(String),
+	/// Immediate value does not fit in the `PUSHn`.
+	ImmediateTooLarge(String),
+	/// Label is referenced but never defined.
+	UnknownLabel(String),
+	/// Label is defined twice.
+	DuplicateLabel(String),
+}
+
+impl fmt::Display for AssemblerError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			AssemblerError::UnknownMnemonic(s) => write!(f, "unknown mnemonic {}", s),
+			AssemblerError::MissingImmediate => write!(f, "missing push immediate"),
+			AssemblerError::InvalidImmediate(s) => write!(f, "invalid immediate {}", s),
+			AssemblerError::ImmediateTooLarge(s) => write!(f, "immediate {} too large", s),
+			AssemblerError::UnknownLabel(s) => write!(f, "unknown label {}", s),
+			AssemblerError::DuplicateLabel(s) => write!(f, "duplicate label {}", s),
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AssemblerError {}
+
+/// Builder of programs from opcodes and labels. Label references are pushed as
+/// `PUSH2` and resolved in `build`.
+#[derive(Clone, Debug, Default)]
+pub struct Assembler {
+	code: Vec<u8>,
+	labels: BTreeMap<String, usize>,
+	references: Vec<(usize, String)>,
+	error: Option<AssemblerError>,
+}
+
+impl Assembler {
+	/// Create an empty program.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Append an opcode. Use `push` for `PUSHn`.
+	pub fn op(mut self, opcode: Opcode) -> Self {
+		self.code.push(opcode.as_u8());
+		self
+	}
+
+	/// Append an external opcode.
+	pub fn external(mut self, opcode: ExternalOpcode) -> Self {
+		self.code.push(opcode.as_u8());
+		self
+	}
+
+	/// Append a `PUSHn` of the value, with the smallest `n` of at least 1.
+	pub fn push(self, value: U256) -> Self {
+		let mut bytes = [0u8; 32];
+		value.to_big_endian(&mut bytes);
+		let start = bytes.iter().position(|b| *b != 0).unwrap_or(31);
+		self.push_bytes(&bytes[start..])
+	}
+
+	/// Append a `PUSHn` of the bytes, where `n` is their length, from 1 to 32.
+	pub fn push_bytes(mut self, bytes: &[u8]) -> Self {
+		if bytes.is_empty() || bytes.len() > 32 {
+			self.error.get_or_insert(AssemblerError::ImmediateTooLarge(hex(bytes)));
+			return self
+		}
+		self.code.push(Opcode::Push(bytes.len() as u8).as_u8());
+		self.code.extend_from_slice(bytes);
+		self
+	}
+
+	/// Define a label at the current position, appending a `JUMPDEST`.
+	pub fn label(mut self, name: &str) -> Self {
+		if self.labels.insert(name.to_string(), self.code.len()).is_some() {
+			self.error.get_or_insert(AssemblerError::DuplicateLabel(name.to_string()));
+		}
+		self.op(Opcode::JumpDest)
+	}
+
+	/// Append a `PUSH2` of the position of a label.
+	pub fn push_label(mut self, name: &str) -> Self {
+		self.code.push(Opcode::Push(2).as_u8());
+		self.references.push((self.code.len(), name.to_string()));
+		self.code.extend_from_slice(&[0, 0]);
+		self
+	}
+
+	/// Append a jump to a label.
+	pub fn jump(self, name: &str) -> Self {
+		self.push_label(name).op(Opcode::Jump)
+	}
+
+	/// Append a conditional jump to a label, on the condition at the top of the stack.
+	pub fn jumpi(self, name: &str) -> Self {
+		self.push_label(name).op(Opcode::JumpI)
+	}
+
+	/// Resolve labels and return the code.
+	pub fn build(mut self) -> Result<Vec<u8>, AssemblerError> {
+		if let Some(error) = self.error {
+			return Err(error)
+		}
+		for (offset, name) in &self.references {
+			let position = *self.labels.get(name)
+				.ok_or_else(|| AssemblerError::UnknownLabel(name.clone()))?;
+			if position > 0xffff {
+				return Err(AssemblerError::ImmediateTooLarge(name.clone()))
+			}
+			self.code[*offset..(*offset + 2)].copy_from_slice(&(position as u16).to_be_bytes());
+		}
+		Ok(self.code)
+	}
+}
+
+fn hex(bytes: &[u8]) -> String {
+	let mut s = String::from("0x");
+	for byte in bytes {
+		s.push_str(&alloc::format!("{:02x}", byte));
+	}
+	s
+}
+
+fn immediate(token: &str) -> Result<U256, AssemblerError> {
+	let value = match token.strip_prefix("0x") {
+		Some(hex) => U256::from_str_radix(hex, 16).ok(),
+		None => U256::from_dec_str(token).ok(),
+	};
+	value.ok_or_else(|| AssemblerError::InvalidImmediate(token.to_string()))
+}
+
+fn mnemonic(name: &str) -> Option<u8> {
+	(0..=255u8).find(|byte| {
+		let parsed = match Opcode::parse(*byte) {
+			Ok(opcode) => opcode.name(),
+			Err(ExternalOpcode::Other(_)) => return false,
+			Err(opcode) => opcode.name(),
+		};
+		parsed.eq_ignore_ascii_case(name)
+	})
+}
+
+/// Assemble a program from whitespace-separated mnemonics. `name:` defines a label
+/// with a `JUMPDEST`. `PUSH1` to `PUSH32` and `PUSH`, which picks the smallest size,
+/// take a decimal or `0x` hex immediate or `@label`. `;` starts a comment.
+pub fn assemble(source: &str) -> Result<Vec<u8>, AssemblerError> {
+	let mut assembler = Assembler::new();
+	let mut tokens = source.lines()
+		.flat_map(|line| line.split(';').next().unwrap_or("").split_whitespace());
+
+	while let Some(token) = tokens.next() {
+		if let Some(label) = token.strip_suffix(':') {
+			assembler = assembler.label(label);
+			continue
+		}
+
+		let upper = token.to_ascii_uppercase();
+		if upper == "INVALID" {
+			assembler.code.push(0xfe);
+			continue
+		}
+		if upper == "PUSH0" {
+			assembler = assembler.op(Opcode::Push(0));
+			continue
+		}
+		if let Some(size) = upper.strip_prefix("PUSH") {
+			let operand = tokens.next().ok_or(AssemblerError::MissingImmediate)?;
+			if let Some(label) = operand.strip_prefix('@') {
+				assembler = assembler.push_label(label);
+				continue
+			}
+			let value = immediate(operand)?;
+			if size.is_empty() {
+				assembler = assembler.push(value);
+				continue
+			}
+			let size = size.parse::<usize>()
+				.ok().filter(|size| (1..=32).contains(size))
+				.ok_or_else(|| AssemblerError::UnknownMnemonic(token.to_string()))?;
+			if value.bits() > size * 8 {
+				return Err(AssemblerError::ImmediateTooLarge(operand.to_string()))
+			}
+			let mut bytes = [0u8; 32];
+			value.to_big_endian(&mut bytes);
+			assembler = assembler.push_bytes(&bytes[(32 - size)..]);
+			continue
+		}
+
+		let byte = mnemonic(token).ok_or_else(|| AssemblerError::UnknownMnemonic(token.to_string()))?;
+		assembler.code.push(byte);
+	}
+
+	assembler.build()
+}
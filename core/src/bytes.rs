@@ -0,0 +1,109 @@
+use core::fmt;
+use core::ops::{Deref, Range};
+use alloc::vec::Vec;
+use crate::Shared;
+
+/// Cheaply cloneable byte buffer. Clones and slices share the underlying buffer.
+#[derive(Clone)]
+pub struct Bytes {
+	data: Shared<Vec<u8>>,
+	range: Range<usize>,
+}
+
+impl Bytes {
+	/// Create an empty buffer.
+	pub fn new() -> Self {
+		Self::from(Vec::new())
+	}
+
+	/// Sub-slice of the buffer, sharing the same underlying data. The range is
+	/// clamped to the buffer length.
+	pub fn slice(&self, range: Range<usize>) -> Self {
+		let len = self.len();
+		let end = range.end.min(len);
+		let start = range.start.min(end);
+
+		Self {
+			data: self.data.clone(),
+			range: (self.range.start + start)..(self.range.start + end),
+		}
+	}
+
+	/// Copy the bytes into a new vector.
+	pub fn to_vec(&self) -> Vec<u8> {
+		self[..].to_vec()
+	}
+
+	/// Convert into a vector, only copying if the buffer is shared or sliced.
+	pub fn into_vec(self) -> Vec<u8> {
+		if self.range.start != 0 || self.range.end != self.data.len() {
+			return self.to_vec()
+		}
+		Shared::try_unwrap(self.data).unwrap_or_else(|data| data[..].to_vec())
+	}
+}
+
+impl Default for Bytes {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Deref for Bytes {
+	type Target = [u8];
+
+	fn deref(&self) -> &[u8] {
+		&self.data[self.range.clone()]
+	}
+}
+
+impl AsRef<[u8]> for Bytes {
+	fn as_ref(&self) -> &[u8] {
+		self
+	}
+}
+
+impl From<Vec<u8>> for Bytes {
+	fn from(data: Vec<u8>) -> Self {
+		Self::from(Shared::new(data))
+	}
+}
+
+impl From<Shared<Vec<u8>>> for Bytes {
+	fn from(data: Shared<Vec<u8>>) -> Self {
+		let range = 0..data.len();
+		Self { data, range }
+	}
+}
+
+impl PartialEq for Bytes {
+	fn eq(&self, other: &Self) -> bool {
+		self[..] == other[..]
+	}
+}
+
+impl Eq for Bytes { }
+
+impl fmt::Debug for Bytes {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "Bytes(0x")?;
+		for byte in &self[..] {
+			write!(f, "{:02x}", byte)?;
+		}
+		write!(f, ")")
+	}
+}
+
+#[cfg(feature = "with-serde")]
+impl serde::Serialize for Bytes {
+	fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		self[..].serialize(serializer)
+	}
+}
+
+#[cfg(feature = "with-serde")]
+impl<'de> serde::Deserialize<'de> for Bytes {
+	fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		Vec::<u8>::deserialize(deserializer).map(Self::from)
+	}
+}
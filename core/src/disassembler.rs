@@ -0,0 +1,70 @@
+use core::fmt;
+use core::cmp::min;
+use alloc::vec::Vec;
+use crate::{Opcode, ExternalOpcode};
+
+/// A disassembled instruction.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Disassembled {
+	/// Position of the opcode in the code.
+	pub pc: usize,
+	/// The opcode.
+	pub opcode: Result<Opcode, ExternalOpcode>,
+	/// Immediate data of a `PUSHn`, shorter than `n` if the code is truncated.
+	pub push_data: Vec<u8>,
+	/// Whether the opcode is a valid jump destination.
+	pub is_jumpdest: bool,
+}
+
+impl Disassembled {
+	/// Mnemonic of the opcode.
+	pub fn name(&self) -> &'static str {
+		match self.opcode {
+			Ok(opcode) => opcode.name(),
+			Err(opcode) => opcode.name(),
+		}
+	}
+}
+
+impl fmt::Display for Disassembled {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{:06x}: {}", self.pc, self.name())?;
+		if let Ok(Opcode::Push(n)) = self.opcode {
+			if n > 0 {
+				write!(f, " 0x")?;
+				for byte in &self.push_data {
+					write!(f, "{:02x}", byte)?;
+				}
+			}
+		}
+		if let Err(ExternalOpcode::Other(byte)) = self.opcode {
+			write!(f, " 0x{:02x}", byte)?;
+		}
+		Ok(())
+	}
+}
+
+/// Disassemble code into instructions. Immediate data of `PUSHn` is not decoded
+/// as opcodes.
+pub fn disassemble(code: &[u8]) -> Vec<Disassembled> {
+	let mut ret = Vec::new();
+	let mut pc = 0;
+	while pc < code.len() {
+		let opcode = Opcode::parse(code[pc]);
+		let push_data = match opcode {
+			Ok(Opcode::Push(n)) => code[min(pc + 1, code.len())..min(pc + 1 + n as usize, code.len())].to_vec(),
+			_ => Vec::new(),
+		};
+		ret.push(Disassembled {
+			pc,
+			opcode,
+			is_jumpdest: opcode == Ok(Opcode::JumpDest),
+			push_data,
+		});
+		pc += match opcode {
+			Ok(Opcode::Push(n)) => 1 + n as usize,
+			_ => 1,
+		};
+	}
+	ret
+}
@@ -5,6 +5,7 @@ pub type Trap = ExternalOpcode;
 
 /// Capture represents the result of execution.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Capture<E, T> {
 	/// The machine has exited. It cannot be executed again.
 	Exit(E),
@@ -15,6 +16,7 @@ pub enum Capture<E, T> {
 
 /// Exit reason.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ExitReason {
 	/// Machine has succeeded.
 	Succeed(ExitSucceed),
@@ -39,6 +41,7 @@ impl ExitReason {
 
 /// Exit succeed reason.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ExitSucceed {
 	/// Machine encountered an explict stop.
 	Stopped,
@@ -56,6 +59,7 @@ impl From<ExitSucceed> for ExitReason {
 
 /// Exit revert reason.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ExitRevert {
 	/// Machine encountered an explict revert.
 	Reverted,
@@ -69,6 +73,7 @@ impl From<ExitRevert> for ExitReason {
 
 /// Exit error reason.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ExitError {
 	/// Trying to pop from an empty stack.
 	StackUnderflow,
@@ -86,6 +91,8 @@ pub enum ExitError {
 	CreateCollision,
 	/// Create init code exceeds limit (runtime).
 	CreateContractLimit,
+	/// EIP-3540: init code or deployed code is an invalid EOF container (runtime).
+	InvalidCode,
 
 	/// TRON: ILLEGAL_OPERATION
 	IllegalOperation,
@@ -93,6 +100,12 @@ pub enum ExitError {
 	TransferException,
 	/// TRON: Unknown
 	Unknown,
+	/// TRON: StaticCallModificationException, a state modification inside a
+	/// static call (runtime).
+	StaticCallModification,
+	/// TRON: PrecompiledContractException, a precompile rejected its input
+	/// (runtime).
+	PrecompileFailure,
 
 	///	An opcode accesses external information, but the request is off offset
 	///	limit (runtime).
@@ -101,6 +114,10 @@ pub enum ExitError {
 	OutOfGas,
 	/// Not enough fund to start the execution (runtime).
 	OutOfFund,
+	/// TRON: Execution exceeds its step or time budget (runtime).
+	OutOfTime,
+	/// Execution is cancelled by the host (runtime).
+	Cancelled,
 
 	/// PC underflowed (unused).
 	PCUnderflow,
@@ -108,7 +125,10 @@ pub enum ExitError {
 	CreateEmpty,
 
 	/// Other normal errors.
-	Other(&'static str),
+	Other(
+		#[cfg_attr(feature = "with-serde", serde(deserialize_with = "deserialize_other"))]
+		&'static core::primitive::str
+	),
 }
 
 impl From<ExitError> for ExitReason {
@@ -119,6 +139,7 @@ impl From<ExitError> for ExitReason {
 
 /// Exit fatal reason.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ExitFatal {
 	/// The operation is not supported.
 	NotSupported,
@@ -128,7 +149,10 @@ pub enum ExitFatal {
 	CallErrorAsFatal(ExitError),
 
 	/// Other fatal errors.
-	Other(&'static str),
+	Other(
+		#[cfg_attr(feature = "with-serde", serde(deserialize_with = "deserialize_other"))]
+		&'static core::primitive::str
+	),
 }
 
 impl From<ExitFatal> for ExitReason {
@@ -136,3 +160,15 @@ impl From<ExitFatal> for ExitReason {
 		Self::Fatal(s)
 	}
 }
+
+/// `Other` errors carry static messages, which a deserialized message cannot be
+/// turned into without leaking it, and no `Other` message is raised by this crate.
+/// Deserialized messages therefore read as `"unknown"`. The field is spelled
+/// `&'static core::primitive::str` so that serde does not treat it as borrowed.
+#[cfg(feature = "with-serde")]
+fn deserialize_other<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<&'static str, D::Error> {
+	use serde::Deserialize;
+
+	alloc::string::String::deserialize(deserializer)?;
+	Ok("unknown")
+}
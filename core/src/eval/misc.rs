@@ -4,7 +4,7 @@ use super::Control;
 use crate::{Machine, ExitError, ExitSucceed, ExitFatal, ExitRevert};
 
 pub fn codesize(state: &mut Machine) -> Control {
-	let size = U256::from(state.code.len());
+	let size = U256::from(state.container().len());
 	push_u256!(state, size);
 	Control::Continue(1)
 }
@@ -13,7 +13,8 @@ pub fn codecopy(state: &mut Machine) -> Control {
 	pop_u256!(state, memory_offset, code_offset, len);
 
 	try_or_fail!(state.memory.resize_offset(memory_offset, len));
-	match state.memory.copy_large(memory_offset, code_offset, len, &state.code) {
+	let code = state.container.as_ref().unwrap_or(&state.code);
+	match state.memory.copy_large(memory_offset, code_offset, len, code) {
 		Ok(()) => Control::Continue(1),
 		Err(e) => Control::Exit(e.into()),
 	}
@@ -6,61 +6,240 @@ mod misc;
 
 use core::ops::{BitAnd, BitOr, BitXor};
 use primitive_types::{H256, U256};
-use crate::{ExitReason, ExitSucceed, ExitError, Machine, Opcode};
+use crate::{ExitReason, ExitSucceed, ExitError, ExternalOpcode, Machine, Opcode};
 
+/// Result of evaluating an opcode.
 pub enum Control {
+	/// Continue at the given offset from the current position.
 	Continue(usize),
+	/// Exit the machine.
 	Exit(ExitReason),
+	/// Jump to the given position.
 	Jump(usize),
+	/// Trap to the runtime for an external opcode.
+	Trap(ExternalOpcode),
 }
 
-pub fn eval(state: &mut Machine, opcode: Opcode, position: usize) -> Control {
-	match opcode {
-		Opcode::Stop => Control::Exit(ExitSucceed::Stopped.into()),
-		Opcode::Add => op2_u256_tuple!(state, overflowing_add),
-		Opcode::Mul => op2_u256_tuple!(state, overflowing_mul),
-		Opcode::Sub => op2_u256_tuple!(state, overflowing_sub),
-		Opcode::Div => op2_u256_fn!(state, self::arithmetic::div),
-		Opcode::SDiv => op2_u256_fn!(state, self::arithmetic::sdiv),
-		Opcode::Mod => op2_u256_fn!(state, self::arithmetic::rem),
-		Opcode::SMod => op2_u256_fn!(state, self::arithmetic::srem),
-		Opcode::AddMod => op3_u256_fn!(state, self::arithmetic::addmod),
-		Opcode::MulMod => op3_u256_fn!(state, self::arithmetic::mulmod),
-		Opcode::Exp => op2_u256_fn!(state, self::arithmetic::exp),
-		Opcode::SignExtend => op2_u256_fn!(state, self::arithmetic::signextend),
-		Opcode::Lt => op2_u256_bool_ref!(state, lt),
-		Opcode::Gt => op2_u256_bool_ref!(state, gt),
-		Opcode::SLt => op2_u256_fn!(state, self::bitwise::slt),
-		Opcode::SGt => op2_u256_fn!(state, self::bitwise::sgt),
-		Opcode::Eq => op2_u256_bool_ref!(state, eq),
-		Opcode::IsZero => op1_u256_fn!(state, self::bitwise::iszero),
-		Opcode::And => op2_u256!(state, bitand),
-		Opcode::Or => op2_u256!(state, bitor),
-		Opcode::Xor => op2_u256!(state, bitxor),
-		Opcode::Not => op1_u256_fn!(state, self::bitwise::not),
-		Opcode::Byte => op2_u256_fn!(state, self::bitwise::byte),
-		Opcode::Shl => op2_u256_fn!(state, self::bitwise::shl),
-		Opcode::Shr => op2_u256_fn!(state, self::bitwise::shr),
-		Opcode::Sar => op2_u256_fn!(state, self::bitwise::sar),
-		Opcode::CodeSize => self::misc::codesize(state),
-		Opcode::CodeCopy => self::misc::codecopy(state),
-		Opcode::CallDataLoad => self::misc::calldataload(state),
-		Opcode::CallDataSize => self::misc::calldatasize(state),
-		Opcode::CallDataCopy => self::misc::calldatacopy(state),
-		Opcode::Pop => self::misc::pop(state),
-		Opcode::MLoad => self::misc::mload(state),
-		Opcode::MStore => self::misc::mstore(state),
-		Opcode::MStore8 => self::misc::mstore8(state),
-		Opcode::Jump => self::misc::jump(state),
-		Opcode::JumpI => self::misc::jumpi(state),
-		Opcode::PC => self::misc::pc(state, position),
-		Opcode::MSize => self::misc::msize(state),
-		Opcode::JumpDest => Control::Continue(1),
-		Opcode::Push(n) => self::misc::push(state, n as usize, position),
-		Opcode::Dup(n) => self::misc::dup(state, n as usize),
-		Opcode::Swap(n) => self::misc::swap(state, n as usize),
-		Opcode::Return => self::misc::ret(state),
-		Opcode::Revert => self::misc::revert(state),
-		Opcode::Invalid => Control::Exit(ExitError::DesignatedInvalid.into()),
+/// Function evaluating the opcode at the given position.
+pub type Instruction = fn(&mut Machine, usize) -> Control;
+
+macro_rules! instruction {
+	( $name:ident, |$state:ident| $body:expr ) => {
+		fn $name($state: &mut Machine, _position: usize) -> Control { $body }
+	};
+	( $name:ident, |$state:ident, $position:ident| $body:expr ) => {
+		fn $name($state: &mut Machine, $position: usize) -> Control { $body }
+	};
+}
+
+instruction!(stop, |_state| Control::Exit(ExitSucceed::Stopped.into()));
+instruction!(add, |state| op2_u256_tuple!(state, overflowing_add));
+instruction!(mul, |state| op2_u256_tuple!(state, overflowing_mul));
+instruction!(sub, |state| op2_u256_tuple!(state, overflowing_sub));
+instruction!(div, |state| op2_u256_fn!(state, self::arithmetic::div));
+instruction!(sdiv, |state| op2_u256_fn!(state, self::arithmetic::sdiv));
+instruction!(rem, |state| op2_u256_fn!(state, self::arithmetic::rem));
+instruction!(srem, |state| op2_u256_fn!(state, self::arithmetic::srem));
+instruction!(addmod, |state| op3_u256_fn!(state, self::arithmetic::addmod));
+instruction!(mulmod, |state| op3_u256_fn!(state, self::arithmetic::mulmod));
+instruction!(exp, |state| op2_u256_fn!(state, self::arithmetic::exp));
+instruction!(signextend, |state| op2_u256_fn!(state, self::arithmetic::signextend));
+instruction!(lt, |state| op2_u256_bool_ref!(state, lt));
+instruction!(gt, |state| op2_u256_bool_ref!(state, gt));
+instruction!(slt, |state| op2_u256_fn!(state, self::bitwise::slt));
+instruction!(sgt, |state| op2_u256_fn!(state, self::bitwise::sgt));
+instruction!(eq, |state| op2_u256_bool_ref!(state, eq));
+instruction!(iszero, |state| op1_u256_fn!(state, self::bitwise::iszero));
+instruction!(and, |state| op2_u256!(state, bitand));
+instruction!(or, |state| op2_u256!(state, bitor));
+instruction!(xor, |state| op2_u256!(state, bitxor));
+instruction!(not, |state| op1_u256_fn!(state, self::bitwise::not));
+instruction!(byte, |state| op2_u256_fn!(state, self::bitwise::byte));
+instruction!(shl, |state| op2_u256_fn!(state, self::bitwise::shl));
+instruction!(shr, |state| op2_u256_fn!(state, self::bitwise::shr));
+instruction!(sar, |state| op2_u256_fn!(state, self::bitwise::sar));
+instruction!(calldataload, |state| self::misc::calldataload(state));
+instruction!(calldatasize, |state| self::misc::calldatasize(state));
+instruction!(calldatacopy, |state| self::misc::calldatacopy(state));
+instruction!(codesize, |state| self::misc::codesize(state));
+instruction!(codecopy, |state| self::misc::codecopy(state));
+instruction!(pop, |state| self::misc::pop(state));
+instruction!(mload, |state| self::misc::mload(state));
+instruction!(mstore, |state| self::misc::mstore(state));
+instruction!(mstore8, |state| self::misc::mstore8(state));
+instruction!(jump, |state| self::misc::jump(state));
+instruction!(jumpi, |state| self::misc::jumpi(state));
+instruction!(pc, |state, position| self::misc::pc(state, position));
+instruction!(msize, |state| self::misc::msize(state));
+instruction!(jumpdest, |_state| Control::Continue(1));
+instruction!(ret, |state| self::misc::ret(state));
+instruction!(revert, |state| self::misc::revert(state));
+instruction!(external, |state, position| {
+	let opcode = state.code[position];
+	Control::Trap(Opcode::parse(opcode).err().unwrap_or(ExternalOpcode::Other(opcode)))
+});
+instruction!(illegal, |_state| Control::Exit(ExitError::IllegalOperation.into()));
+
+fn push<const N: usize>(state: &mut Machine, position: usize) -> Control {
+	self::misc::push(state, N, position)
+}
+
+fn dup<const N: usize>(state: &mut Machine, _position: usize) -> Control {
+	self::misc::dup(state, N)
+}
+
+fn swap<const N: usize>(state: &mut Machine, _position: usize) -> Control {
+	self::misc::swap(state, N)
+}
+
+/// Dispatch table from opcode byte to its evaluation function. Opcodes not
+/// evaluated by the machine trap to the runtime.
+#[derive(Clone, Copy, Debug)]
+pub struct InstructionTable([Instruction; 256]);
+
+impl InstructionTable {
+	/// Table of all opcodes of the machine.
+	pub const fn new() -> Self {
+		let mut table = [external as Instruction; 256];
+
+		table[0x00] = stop;
+		table[0x01] = add;
+		table[0x02] = mul;
+		table[0x03] = sub;
+		table[0x04] = div;
+		table[0x05] = sdiv;
+		table[0x06] = rem;
+		table[0x07] = srem;
+		table[0x08] = addmod;
+		table[0x09] = mulmod;
+		table[0x0a] = exp;
+		table[0x0b] = signextend;
+		table[0x10] = lt;
+		table[0x11] = gt;
+		table[0x12] = slt;
+		table[0x13] = sgt;
+		table[0x14] = eq;
+		table[0x15] = iszero;
+		table[0x16] = and;
+		table[0x17] = or;
+		table[0x18] = xor;
+		table[0x19] = not;
+		table[0x1a] = byte;
+		table[0x1b] = shl;
+		table[0x1c] = shr;
+		table[0x1d] = sar;
+		table[0x35] = calldataload;
+		table[0x36] = calldatasize;
+		table[0x37] = calldatacopy;
+		table[0x38] = codesize;
+		table[0x39] = codecopy;
+		table[0x50] = pop;
+		table[0x51] = mload;
+		table[0x52] = mstore;
+		table[0x53] = mstore8;
+		table[0x56] = jump;
+		table[0x57] = jumpi;
+		table[0x58] = pc;
+		table[0x59] = msize;
+		table[0x5b] = jumpdest;
+		table[0xf3] = ret;
+		table[0xfd] = revert;
+
+		table[0x5f] = push::<0>;
+		table[0x60] = push::<1>;
+		table[0x61] = push::<2>;
+		table[0x62] = push::<3>;
+		table[0x63] = push::<4>;
+		table[0x64] = push::<5>;
+		table[0x65] = push::<6>;
+		table[0x66] = push::<7>;
+		table[0x67] = push::<8>;
+		table[0x68] = push::<9>;
+		table[0x69] = push::<10>;
+		table[0x6a] = push::<11>;
+		table[0x6b] = push::<12>;
+		table[0x6c] = push::<13>;
+		table[0x6d] = push::<14>;
+		table[0x6e] = push::<15>;
+		table[0x6f] = push::<16>;
+		table[0x70] = push::<17>;
+		table[0x71] = push::<18>;
+		table[0x72] = push::<19>;
+		table[0x73] = push::<20>;
+		table[0x74] = push::<21>;
+		table[0x75] = push::<22>;
+		table[0x76] = push::<23>;
+		table[0x77] = push::<24>;
+		table[0x78] = push::<25>;
+		table[0x79] = push::<26>;
+		table[0x7a] = push::<27>;
+		table[0x7b] = push::<28>;
+		table[0x7c] = push::<29>;
+		table[0x7d] = push::<30>;
+		table[0x7e] = push::<31>;
+		table[0x7f] = push::<32>;
+
+		table[0x80] = dup::<1>;
+		table[0x81] = dup::<2>;
+		table[0x82] = dup::<3>;
+		table[0x83] = dup::<4>;
+		table[0x84] = dup::<5>;
+		table[0x85] = dup::<6>;
+		table[0x86] = dup::<7>;
+		table[0x87] = dup::<8>;
+		table[0x88] = dup::<9>;
+		table[0x89] = dup::<10>;
+		table[0x8a] = dup::<11>;
+		table[0x8b] = dup::<12>;
+		table[0x8c] = dup::<13>;
+		table[0x8d] = dup::<14>;
+		table[0x8e] = dup::<15>;
+		table[0x8f] = dup::<16>;
+
+		table[0x90] = swap::<1>;
+		table[0x91] = swap::<2>;
+		table[0x92] = swap::<3>;
+		table[0x93] = swap::<4>;
+		table[0x94] = swap::<5>;
+		table[0x95] = swap::<6>;
+		table[0x96] = swap::<7>;
+		table[0x97] = swap::<8>;
+		table[0x98] = swap::<9>;
+		table[0x99] = swap::<10>;
+		table[0x9a] = swap::<11>;
+		table[0x9b] = swap::<12>;
+		table[0x9c] = swap::<13>;
+		table[0x9d] = swap::<14>;
+		table[0x9e] = swap::<15>;
+		table[0x9f] = swap::<16>;
+
+		Self(table)
+	}
+
+	/// Replace the evaluation function of an opcode.
+	pub const fn with(mut self, opcode: u8, instruction: Instruction) -> Self {
+		self.0[opcode as usize] = instruction;
+		self
+	}
+
+	/// Make an opcode exit with `IllegalOperation`, for opcodes not enabled in a fork.
+	pub const fn without(self, opcode: u8) -> Self {
+		self.with(opcode, illegal)
+	}
+
+	/// Evaluation function of an opcode.
+	#[inline]
+	pub fn get(&self, opcode: u8) -> Instruction {
+		self.0[opcode as usize]
+	}
+}
+
+impl Default for InstructionTable {
+	fn default() -> Self {
+		Self::new()
 	}
 }
+
+/// Table of the opcodes of the machine before any fork-gated ones. `PUSH0` is
+/// only enabled by passing a table with it to `Machine::step_with`.
+pub static DEFAULT_TABLE: InstructionTable = InstructionTable::new().without(0x5f);
@@ -15,31 +15,54 @@ mod opcode;
 mod error;
 mod eval;
 mod utils;
+mod bytes;
+mod disassembler;
+mod assembler;
+mod superinstruction;
 
 pub use crate::memory::Memory;
 pub use crate::stack::Stack;
 pub use crate::valids::Valids;
+pub use crate::eval::{Control, Instruction, InstructionTable};
+pub use crate::bytes::Bytes;
+pub use crate::disassembler::{Disassembled, disassemble};
+pub use crate::assembler::{Assembler, AssemblerError, assemble};
+pub use crate::superinstruction::{Superinstruction, Superinstructions};
 pub use crate::opcode::{Opcode, ExternalOpcode};
 pub use crate::error::{Trap, Capture, ExitReason, ExitSucceed, ExitError, ExitRevert, ExitFatal};
 
 use core::ops::Range;
 use alloc::vec::Vec;
-use alloc::rc::Rc;
-use primitive_types::U256;
-use crate::eval::{eval, Control};
+use primitive_types::{H256, U256};
+use crate::eval::DEFAULT_TABLE;
+
+/// Shared pointer to code and data. `Arc` with the `arc` feature, so that machines
+/// can be sent across threads.
+#[cfg(not(feature = "arc"))]
+pub type Shared<T> = alloc::rc::Rc<T>;
+/// Shared pointer to code and data. `Arc` with the `arc` feature, so that machines
+/// can be sent across threads.
+#[cfg(feature = "arc")]
+pub type Shared<T> = alloc::sync::Arc<T>;
 
 /// Core execution layer for EVM.
+#[derive(Clone)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Machine {
 	/// Program data.
-	data: Rc<Vec<u8>>,
+	data: Bytes,
 	/// Program code.
-	code: Rc<Vec<u8>>,
+	code: Bytes,
 	/// Program counter.
 	position: Result<usize, ExitReason>,
 	/// Return value.
 	return_range: Range<U256>,
 	/// Code validity maps.
-	valids: Valids,
+	valids: Shared<Valids>,
+	/// Superinstructions of the program code, if any.
+	superinstructions: Option<Shared<Superinstructions>>,
+	/// EOF container of the program code, if the code is its code section.
+	container: Option<Bytes>,
 	/// Memory.
 	memory: Memory,
 	/// Stack.
@@ -55,15 +78,58 @@ impl Machine {
 	pub fn memory(&self) -> &Memory { &self.memory }
 	/// Mutable reference of machine memory.
 	pub fn memory_mut(&mut self) -> &mut Memory { &mut self.memory }
+	/// Program counter, or the exit reason if the machine has stopped.
+	pub fn position(&self) -> &Result<usize, ExitReason> { &self.position }
+
+	/// Program code.
+	pub fn code(&self) -> &Bytes { &self.code }
+	/// Code seen by `CODESIZE` and `CODECOPY`: the EOF container if set, otherwise
+	/// the program code.
+	pub fn container(&self) -> &Bytes { self.container.as_ref().unwrap_or(&self.code) }
+	/// Program data.
+	pub fn data(&self) -> &Bytes { &self.data }
+	/// Jump destination map of the program code.
+	pub fn valids(&self) -> &Shared<Valids> { &self.valids }
+	/// Superinstructions of the program code, if set.
+	pub fn superinstructions(&self) -> Option<&Shared<Superinstructions>> { self.superinstructions.as_ref() }
 
 	/// Create a new machine with given code and data.
-	pub fn new(
-		code: Rc<Vec<u8>>,
-		data: Rc<Vec<u8>>,
+	pub fn new<C: Into<Bytes>, D: Into<Bytes>>(
+		code: C,
+		data: D,
+		stack_limit: usize,
+		memory_limit: usize
+	) -> Self {
+		let code = code.into();
+		let valids = Shared::new(Valids::new(&code[..]));
+
+		Self::with_valids(code, data, valids, stack_limit, memory_limit)
+	}
+
+	/// Create a new machine with given code, data and a precomputed jump
+	/// destination map of the code.
+	pub fn with_valids<C: Into<Bytes>, D: Into<Bytes>>(
+		code: C,
+		data: D,
+		valids: Shared<Valids>,
 		stack_limit: usize,
 		memory_limit: usize
 	) -> Self {
-		let valids = Valids::new(&code[..]);
+		Self::with_buffers(code, data, valids, Stack::new(stack_limit), Memory::new(memory_limit))
+	}
+
+	/// Create a new machine with given code, data, jump destination map of the code,
+	/// and empty stack and memory, which can be recycled from a previous machine.
+	pub fn with_buffers<C: Into<Bytes>, D: Into<Bytes>>(
+		code: C,
+		data: D,
+		valids: Shared<Valids>,
+		stack: Stack,
+		memory: Memory,
+	) -> Self {
+		let code = code.into();
+		let data = data.into();
+		debug_assert_eq!(valids.len(), code.len());
 
 		Self {
 			data,
@@ -71,11 +137,31 @@ impl Machine {
 			position: Ok(0),
 			return_range: U256::zero()..U256::zero(),
 			valids,
-			memory: Memory::new(memory_limit),
-			stack: Stack::new(stack_limit),
+			superinstructions: None,
+			container: None,
+			memory,
+			stack,
 		}
 	}
 
+	/// Set the EOF container whose code section is the program code (EIP-3540).
+	/// `CODESIZE` and `CODECOPY` see the whole container, data section included.
+	pub fn set_container(&mut self, container: Bytes) {
+		self.container = Some(container);
+	}
+
+	/// Set the superinstructions of the program code, evaluated by
+	/// `step_superinstruction`.
+	pub fn set_superinstructions(&mut self, superinstructions: Shared<Superinstructions>) {
+		debug_assert_eq!(superinstructions.len(), self.code.len());
+		self.superinstructions = Some(superinstructions);
+	}
+
+	/// Consume the machine, returning its stack and memory for reuse.
+	pub fn into_buffers(self) -> (Stack, Memory) {
+		(self.stack, self.memory)
+	}
+
 	/// Explict exit of the machine. Further step will return error.
 	pub fn exit(&mut self, reason: ExitReason) {
 		self.position = Err(reason);
@@ -90,6 +176,47 @@ impl Machine {
 		self.code.get(position).map(|v| (Opcode::parse(*v), &self.stack))
 	}
 
+	/// Superinstruction at the program counter, if set and the stack is such that
+	/// its opcodes cannot fail.
+	pub fn superinstruction(&self) -> Option<Superinstruction> {
+		let position = *self.position.as_ref().ok()?;
+		self.superinstructions.as_ref()?.get(position).filter(|s| s.is_ready(&self.stack))
+	}
+
+	/// Step the machine, executing the superinstruction at the program counter as
+	/// returned by `superinstruction`. Gas of its opcodes is not charged here.
+	pub fn step_superinstruction(&mut self, superinstruction: Superinstruction) -> Result<(), ExitReason> {
+		let position = self.position?;
+
+		self.position = Ok(match superinstruction {
+			Superinstruction::PushJump { dest, .. } => dest,
+			Superinstruction::PushJumpI { dest, .. } => {
+				if self.stack.pop()? != H256::zero() {
+					dest
+				} else {
+					position + superinstruction.size()
+				}
+			},
+			Superinstruction::PushAdd { n } => {
+				let start = position + 1;
+				let value = U256::from(&self.code[start..start + n as usize]);
+				let (sum, _) = U256::from_big_endian(&self.stack.peek(0)?[..]).overflowing_add(value);
+				let mut top = H256::default();
+				sum.to_big_endian(&mut top[..]);
+				self.stack.set(0, top)?;
+				position + superinstruction.size()
+			},
+			Superinstruction::DupSwap { dup, swap } => {
+				self.stack.push(self.stack.peek(dup as usize - 1)?)?;
+				let (top, other) = (self.stack.peek(0)?, self.stack.peek(swap as usize)?);
+				self.stack.set(0, other)?;
+				self.stack.set(swap as usize, top)?;
+				position + superinstruction.size()
+			},
+		});
+		Ok(())
+	}
+
 	/// Copy and get the return value of the machine, if any.
 	pub fn return_value(&self) -> Vec<u8> {
 		if self.return_range.start > U256::from(usize::max_value()) {
@@ -125,11 +252,16 @@ impl Machine {
 
 	/// Step the machine, executing one opcode. It then returns.
 	pub fn step(&mut self) -> Result<(), Capture<ExitReason, Trap>> {
+		self.step_with(&DEFAULT_TABLE)
+	}
+
+	/// Step the machine with the given dispatch table, executing one opcode.
+	pub fn step_with(&mut self, table: &InstructionTable) -> Result<(), Capture<ExitReason, Trap>> {
 		let position = self.position.map_err(|reason| Capture::Exit(reason))?;
 
-		match self.code.get(position).map(|v| Opcode::parse(*v)) {
-			Some(Ok(opcode)) => {
-				match eval(self, opcode, position) {
+		match self.code.get(position) {
+			Some(&opcode) => {
+				match table.get(opcode)(self, position) {
 					Control::Continue(p) => {
 						self.position = Ok(position + p);
 						Ok(())
@@ -142,12 +274,12 @@ impl Machine {
 						self.position = Ok(p);
 						Ok(())
 					},
+					Control::Trap(external) => {
+						self.position = Ok(position + 1);
+						Err(Capture::Trap(external))
+					},
 				}
 			},
-			Some(Err(external)) => {
-				self.position = Ok(position + 1);
-				Err(Capture::Trap(external))
-			},
 			None => {
 				self.position = Err(ExitSucceed::Stopped.into());
 				Err(Capture::Exit(ExitSucceed::Stopped.into()))
@@ -1,13 +1,24 @@
 use primitive_types::U256;
 use core::cmp::{min, max};
+use alloc::boxed::Box;
+use alloc::vec;
 use alloc::vec::Vec;
 use crate::{ExitError, ExitFatal};
 
-/// A sequencial memory. It uses Rust's `Vec` for internal
-/// representation.
+/// Size of a memory page.
+const PAGE_SIZE: usize = 4096;
+/// Number of low pages kept by `Memory::reset`, so that a reused memory does not
+/// hold on to the peak of an earlier frame.
+const RETAINED_PAGES: usize = 32;
+
+/// A sequencial memory. It is split into fixed-size pages that are allocated,
+/// zero-filled, on first write, so that touching a high offset only allocates the
+/// page table and the pages written to.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Memory {
-	data: Vec<u8>,
+	pages: Vec<Option<Box<[u8]>>>,
+	len: usize,
 	effective_len: U256,
 	limit: usize,
 }
@@ -16,12 +27,26 @@ impl Memory {
 	/// Create a new memory with the given limit.
 	pub fn new(limit: usize) -> Self {
 		Self {
-			data: Vec::new(),
+			pages: Vec::new(),
+			len: 0,
 			effective_len: U256::zero(),
 			limit,
 		}
 	}
 
+	/// Empty the memory for reuse with the given limit. Allocated pages of the first
+	/// 128 KiB are zeroed and kept; the others are freed.
+	pub fn reset(&mut self, limit: usize) {
+		self.pages.truncate(RETAINED_PAGES);
+		self.pages.shrink_to_fit();
+		for page in self.pages.iter_mut().flatten() {
+			page.fill(0);
+		}
+		self.len = 0;
+		self.effective_len = U256::zero();
+		self.limit = limit;
+	}
+
 	/// Memory limit.
 	pub fn limit(&self) -> usize {
 		self.limit
@@ -29,7 +54,12 @@ impl Memory {
 
 	/// Get the length of the current memory range.
 	pub fn len(&self) -> usize {
-		self.data.len()
+		self.len
+	}
+
+	/// Number of pages allocated.
+	pub fn allocated_pages(&self) -> usize {
+		self.pages.iter().filter(|page| page.is_some()).count()
 	}
 
 	/// Get the effective length.
@@ -80,13 +110,16 @@ impl Memory {
 		let mut ret = Vec::new();
 		ret.resize(size, 0);
 
-		for index in 0..size {
-			let position = offset + index;
-			if position >= self.data.len() {
-				break
+		let end = min(offset.saturating_add(size), self.len);
+		let mut position = offset;
+		while position < end {
+			let page_offset = position % PAGE_SIZE;
+			let chunk = min(PAGE_SIZE - page_offset, end - position);
+			if let Some(Some(page)) = self.pages.get(position / PAGE_SIZE) {
+				ret[(position - offset)..(position - offset + chunk)]
+					.copy_from_slice(&page[page_offset..(page_offset + chunk)]);
 			}
-
-			ret[index] = self.data[position];
+			position += chunk;
 		}
 
 		ret
@@ -108,19 +141,47 @@ impl Memory {
 			return Err(ExitFatal::NotSupported)
 		}
 
-		if self.data.len() < offset + target_size {
-			self.data.resize(offset + target_size, 0);
+		let value = &value[..min(value.len(), target_size)];
+		self.len = max(self.len, offset + target_size);
+		self.write(offset, value);
+		self.zero(offset + value.len(), target_size - value.len());
+
+		Ok(())
+	}
+
+	/// Page of the given index, allocating it if needed.
+	fn page_mut(&mut self, index: usize) -> &mut [u8] {
+		if self.pages.len() <= index {
+			self.pages.resize(index + 1, None);
+		}
+		self.pages[index].get_or_insert_with(|| vec![0; PAGE_SIZE].into_boxed_slice())
+	}
+
+	/// Write bytes at the given offset, allocating pages as needed.
+	fn write(&mut self, offset: usize, mut value: &[u8]) {
+		let mut position = offset;
+		while !value.is_empty() {
+			let page_offset = position % PAGE_SIZE;
+			let chunk = min(PAGE_SIZE - page_offset, value.len());
+			self.page_mut(position / PAGE_SIZE)[page_offset..(page_offset + chunk)]
+				.copy_from_slice(&value[..chunk]);
+			value = &value[chunk..];
+			position += chunk;
 		}
+	}
 
-		for index in 0..target_size {
-			if self.data.len() > offset + index && value.len() > index {
-				self.data[offset + index] = value[index];
-			} else {
-				self.data[offset + index] = 0;
+	/// Zero the given region. Pages not yet allocated are already zero.
+	fn zero(&mut self, offset: usize, len: usize) {
+		let end = offset + len;
+		let mut position = offset;
+		while position < end {
+			let page_offset = position % PAGE_SIZE;
+			let chunk = min(PAGE_SIZE - page_offset, end - position);
+			if let Some(Some(page)) = self.pages.get_mut(position / PAGE_SIZE) {
+				page[page_offset..(page_offset + chunk)].fill(0);
 			}
+			position += chunk;
 		}
-
-		Ok(())
 	}
 
 	/// Copy `data` into the memory, of given `len`.
@@ -1,5 +1,25 @@
+const PUSH_NAMES: [&str; 33] = [
+	"PUSH0", "PUSH1", "PUSH2", "PUSH3", "PUSH4", "PUSH5", "PUSH6", "PUSH7",
+	"PUSH8", "PUSH9", "PUSH10", "PUSH11", "PUSH12", "PUSH13", "PUSH14", "PUSH15",
+	"PUSH16", "PUSH17", "PUSH18", "PUSH19", "PUSH20", "PUSH21", "PUSH22", "PUSH23",
+	"PUSH24", "PUSH25", "PUSH26", "PUSH27", "PUSH28", "PUSH29", "PUSH30", "PUSH31",
+	"PUSH32",
+];
+const DUP_NAMES: [&str; 16] = [
+	"DUP1", "DUP2", "DUP3", "DUP4", "DUP5", "DUP6", "DUP7", "DUP8",
+	"DUP9", "DUP10", "DUP11", "DUP12", "DUP13", "DUP14", "DUP15", "DUP16",
+];
+const SWAP_NAMES: [&str; 16] = [
+	"SWAP1", "SWAP2", "SWAP3", "SWAP4", "SWAP5", "SWAP6", "SWAP7", "SWAP8",
+	"SWAP9", "SWAP10", "SWAP11", "SWAP12", "SWAP13", "SWAP14", "SWAP15", "SWAP16",
+];
+const LOG_NAMES: [&str; 5] = [
+	"LOG0", "LOG1", "LOG2", "LOG3", "LOG4",
+];
+
 /// Opcode enum. One-to-one corresponding to an `u8` value.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Opcode {
 	/// `STOP`
 	Stop,
@@ -86,7 +106,7 @@ pub enum Opcode {
 	/// `JUMPDEST`
 	JumpDest,
 
-	/// `PUSHn`
+	/// `PUSHn`, `PUSH0` is `Push(0)`
 	Push(u8),
 	/// `DUPn`
 	Dup(u8),
@@ -162,6 +182,7 @@ impl Opcode {
 			0x45 => Err(ExternalOpcode::GasLimit),
 			0x46 => Err(ExternalOpcode::ChainId),
 			0x47 => Err(ExternalOpcode::SelfBalance),
+			0x48 => Err(ExternalOpcode::BaseFee),
 
 			0x50 => Ok(Opcode::Pop),
 			0x51 => Ok(Opcode::MLoad),
@@ -175,6 +196,7 @@ impl Opcode {
 			0x59 => Ok(Opcode::MSize),
 			0x5a => Err(ExternalOpcode::Gas),
 			0x5b => Ok(Opcode::JumpDest),
+			0x5f => Ok(Opcode::Push(0)),
 
 			0x60 => Ok(Opcode::Push(1)),
 			0x61 => Ok(Opcode::Push(2)),
@@ -261,6 +283,10 @@ impl Opcode {
 			0xd9 => Err(ExternalOpcode::IsWitness),
 			0xda => Err(ExternalOpcode::AssetIssue),
 			0xdb => Err(ExternalOpcode::UpdateAsset),
+			0xdc => Err(ExternalOpcode::CancelAllUnfreezeV2),
+			0xdd => Err(ExternalOpcode::WithdrawExpireUnfreeze),
+			0xde => Err(ExternalOpcode::DelegateResource),
+			0xdf => Err(ExternalOpcode::UnDelegateResource),
 
 			0xf0 => Err(ExternalOpcode::Create),
 			0xf1 => Err(ExternalOpcode::Call),
@@ -275,10 +301,115 @@ impl Opcode {
 			other => Err(ExternalOpcode::Other(other)),
 		}
 	}
+
+	/// Get the byte value of the opcode.
+	pub fn as_u8(&self) -> u8 {
+		match self {
+			Opcode::Stop => 0x00,
+			Opcode::Add => 0x01,
+			Opcode::Mul => 0x02,
+			Opcode::Sub => 0x03,
+			Opcode::Div => 0x04,
+			Opcode::SDiv => 0x05,
+			Opcode::Mod => 0x06,
+			Opcode::SMod => 0x07,
+			Opcode::AddMod => 0x08,
+			Opcode::MulMod => 0x09,
+			Opcode::Exp => 0x0a,
+			Opcode::SignExtend => 0x0b,
+			Opcode::Lt => 0x10,
+			Opcode::Gt => 0x11,
+			Opcode::SLt => 0x12,
+			Opcode::SGt => 0x13,
+			Opcode::Eq => 0x14,
+			Opcode::IsZero => 0x15,
+			Opcode::And => 0x16,
+			Opcode::Or => 0x17,
+			Opcode::Xor => 0x18,
+			Opcode::Not => 0x19,
+			Opcode::Byte => 0x1a,
+			Opcode::Shl => 0x1b,
+			Opcode::Shr => 0x1c,
+			Opcode::Sar => 0x1d,
+			Opcode::CallDataLoad => 0x35,
+			Opcode::CallDataSize => 0x36,
+			Opcode::CallDataCopy => 0x37,
+			Opcode::CodeSize => 0x38,
+			Opcode::CodeCopy => 0x39,
+			Opcode::Pop => 0x50,
+			Opcode::MLoad => 0x51,
+			Opcode::MStore => 0x52,
+			Opcode::MStore8 => 0x53,
+			Opcode::Jump => 0x56,
+			Opcode::JumpI => 0x57,
+			Opcode::PC => 0x58,
+			Opcode::MSize => 0x59,
+			Opcode::JumpDest => 0x5b,
+			Opcode::Return => 0xf3,
+			Opcode::Revert => 0xfd,
+			Opcode::Push(n) => 0x5f + n,
+			Opcode::Dup(n) => 0x7f + n,
+			Opcode::Swap(n) => 0x8f + n,
+			Opcode::Invalid => 0xfe,
+		}
+	}
+
+	/// Get the mnemonic of the opcode.
+	pub fn name(&self) -> &'static str {
+		match self {
+			Opcode::Stop => "STOP",
+			Opcode::Add => "ADD",
+			Opcode::Mul => "MUL",
+			Opcode::Sub => "SUB",
+			Opcode::Div => "DIV",
+			Opcode::SDiv => "SDIV",
+			Opcode::Mod => "MOD",
+			Opcode::SMod => "SMOD",
+			Opcode::AddMod => "ADDMOD",
+			Opcode::MulMod => "MULMOD",
+			Opcode::Exp => "EXP",
+			Opcode::SignExtend => "SIGNEXTEND",
+			Opcode::Lt => "LT",
+			Opcode::Gt => "GT",
+			Opcode::SLt => "SLT",
+			Opcode::SGt => "SGT",
+			Opcode::Eq => "EQ",
+			Opcode::IsZero => "ISZERO",
+			Opcode::And => "AND",
+			Opcode::Or => "OR",
+			Opcode::Xor => "XOR",
+			Opcode::Not => "NOT",
+			Opcode::Byte => "BYTE",
+			Opcode::CallDataLoad => "CALLDATALOAD",
+			Opcode::CallDataSize => "CALLDATASIZE",
+			Opcode::CallDataCopy => "CALLDATACOPY",
+			Opcode::CodeSize => "CODESIZE",
+			Opcode::CodeCopy => "CODECOPY",
+			Opcode::Shl => "SHL",
+			Opcode::Shr => "SHR",
+			Opcode::Sar => "SAR",
+			Opcode::Pop => "POP",
+			Opcode::MLoad => "MLOAD",
+			Opcode::MStore => "MSTORE",
+			Opcode::MStore8 => "MSTORE8",
+			Opcode::Jump => "JUMP",
+			Opcode::JumpI => "JUMPI",
+			Opcode::PC => "PC",
+			Opcode::MSize => "MSIZE",
+			Opcode::JumpDest => "JUMPDEST",
+			Opcode::Return => "RETURN",
+			Opcode::Revert => "REVERT",
+			Opcode::Invalid => "INVALID",
+			Opcode::Push(n) => PUSH_NAMES.get(*n as usize).copied().unwrap_or("PUSH"),
+			Opcode::Dup(n) => DUP_NAMES.get((*n as usize).wrapping_sub(1)).copied().unwrap_or("DUP"),
+			Opcode::Swap(n) => SWAP_NAMES.get((*n as usize).wrapping_sub(1)).copied().unwrap_or("SWAP"),
+		}
+	}
 }
 
 /// External opcodes.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ExternalOpcode {
 	/// `SHA3`
 	Sha3,
@@ -288,6 +419,8 @@ pub enum ExternalOpcode {
 	Balance,
 	/// `SELFBALANCE`
 	SelfBalance,
+	/// `BASEFEE`
+	BaseFee,
 	/// `ORIGIN`
 	Origin,
 	/// `CALLER`
@@ -342,7 +475,7 @@ pub enum ExternalOpcode {
 	Stake,
 	/// `UNSTAKE`
 	Unstake,
-	/// `WITHDRAWREWARD`
+	/// `WITHDRAWREWARD`, at the byte of `ISSRCANDIDATE` since vote upgrade
 	WithdrawReward,
 	/// `REWARDBALANCE`
 	RewardBalance,
@@ -352,6 +485,26 @@ pub enum ExternalOpcode {
 	AssetIssue,
 	/// `UPDATEASSET`
 	UpdateAsset,
+	/// `FREEZE`, shares its byte with `STAKE`
+	Freeze,
+	/// `UNFREEZE`, shares its byte with `UNSTAKE`
+	Unfreeze,
+	/// `FREEZEEXPIRETIME`, shares its byte with legacy `WITHDRAWREWARD`
+	FreezeExpireTime,
+	/// `VOTEWITNESS`, shares its byte with `REWARDBALANCE`
+	VoteWitness,
+	/// `FREEZEBALANCEV2`, shares its byte with `ASSETISSUE`
+	FreezeBalanceV2,
+	/// `UNFREEZEBALANCEV2`, shares its byte with `UPDATEASSET`
+	UnfreezeBalanceV2,
+	/// `CANCELALLUNFREEZEV2`
+	CancelAllUnfreezeV2,
+	/// `WITHDRAWEXPIREUNFREEZE`
+	WithdrawExpireUnfreeze,
+	/// `DELEGATERESOURCE`
+	DelegateResource,
+	/// `UNDELEGATERESOURCE`
+	UnDelegateResource,
 	// end For TRON
 
 	/// `CREATE`
@@ -373,3 +526,128 @@ pub enum ExternalOpcode {
 	/// Other unknown opcodes.
 	Other(u8),
 }
+
+impl ExternalOpcode {
+	/// Get the byte value of the opcode. Opcodes sharing a byte with an older TRON opcode
+	/// get that byte, `WITHDRAWREWARD` gets its legacy byte.
+	pub fn as_u8(&self) -> u8 {
+		match self {
+			ExternalOpcode::Sha3 => 0x20,
+			ExternalOpcode::Address => 0x30,
+			ExternalOpcode::Balance => 0x31,
+			ExternalOpcode::Origin => 0x32,
+			ExternalOpcode::Caller => 0x33,
+			ExternalOpcode::CallValue => 0x34,
+			ExternalOpcode::GasPrice => 0x3a,
+			ExternalOpcode::ExtCodeSize => 0x3b,
+			ExternalOpcode::ExtCodeCopy => 0x3c,
+			ExternalOpcode::ReturnDataSize => 0x3d,
+			ExternalOpcode::ReturnDataCopy => 0x3e,
+			ExternalOpcode::ExtCodeHash => 0x3f,
+			ExternalOpcode::BlockHash => 0x40,
+			ExternalOpcode::Coinbase => 0x41,
+			ExternalOpcode::Timestamp => 0x42,
+			ExternalOpcode::Number => 0x43,
+			ExternalOpcode::Difficulty => 0x44,
+			ExternalOpcode::GasLimit => 0x45,
+			ExternalOpcode::ChainId => 0x46,
+			ExternalOpcode::SelfBalance => 0x47,
+			ExternalOpcode::BaseFee => 0x48,
+			ExternalOpcode::SLoad => 0x54,
+			ExternalOpcode::SStore => 0x55,
+			ExternalOpcode::Gas => 0x5a,
+			ExternalOpcode::CallToken => 0xd0,
+			ExternalOpcode::TokenBalance => 0xd1,
+			ExternalOpcode::CallTokenValue => 0xd2,
+			ExternalOpcode::CallTokenId => 0xd3,
+			ExternalOpcode::IsContract => 0xd4,
+			ExternalOpcode::Stake => 0xd5,
+			ExternalOpcode::Unstake => 0xd6,
+			ExternalOpcode::WithdrawReward => 0xd7,
+			ExternalOpcode::RewardBalance => 0xd8,
+			ExternalOpcode::IsWitness => 0xd9,
+			ExternalOpcode::AssetIssue => 0xda,
+			ExternalOpcode::UpdateAsset => 0xdb,
+			ExternalOpcode::CancelAllUnfreezeV2 => 0xdc,
+			ExternalOpcode::WithdrawExpireUnfreeze => 0xdd,
+			ExternalOpcode::DelegateResource => 0xde,
+			ExternalOpcode::UnDelegateResource => 0xdf,
+			ExternalOpcode::Create => 0xf0,
+			ExternalOpcode::Call => 0xf1,
+			ExternalOpcode::CallCode => 0xf2,
+			ExternalOpcode::DelegateCall => 0xf4,
+			ExternalOpcode::Create2 => 0xf5,
+			ExternalOpcode::StaticCall => 0xfa,
+			ExternalOpcode::Suicide => 0xff,
+			ExternalOpcode::Log(n) => 0xa0 + n,
+			ExternalOpcode::Freeze => 0xd5,
+			ExternalOpcode::Unfreeze => 0xd6,
+			ExternalOpcode::FreezeExpireTime => 0xd7,
+			ExternalOpcode::VoteWitness => 0xd8,
+			ExternalOpcode::FreezeBalanceV2 => 0xda,
+			ExternalOpcode::UnfreezeBalanceV2 => 0xdb,
+			ExternalOpcode::Other(n) => *n,
+		}
+	}
+
+	/// Get the mnemonic of the opcode, `INVALID` for unknown opcodes.
+	pub fn name(&self) -> &'static str {
+		match self {
+			ExternalOpcode::Sha3 => "SHA3",
+			ExternalOpcode::Address => "ADDRESS",
+			ExternalOpcode::Balance => "BALANCE",
+			ExternalOpcode::SelfBalance => "SELFBALANCE",
+			ExternalOpcode::BaseFee => "BASEFEE",
+			ExternalOpcode::Origin => "ORIGIN",
+			ExternalOpcode::Caller => "CALLER",
+			ExternalOpcode::CallValue => "CALLVALUE",
+			ExternalOpcode::GasPrice => "GASPRICE",
+			ExternalOpcode::ExtCodeSize => "EXTCODESIZE",
+			ExternalOpcode::ExtCodeCopy => "EXTCODECOPY",
+			ExternalOpcode::ExtCodeHash => "EXTCODEHASH",
+			ExternalOpcode::ReturnDataSize => "RETURNDATASIZE",
+			ExternalOpcode::ReturnDataCopy => "RETURNDATACOPY",
+			ExternalOpcode::BlockHash => "BLOCKHASH",
+			ExternalOpcode::Coinbase => "COINBASE",
+			ExternalOpcode::Timestamp => "TIMESTAMP",
+			ExternalOpcode::Number => "NUMBER",
+			ExternalOpcode::Difficulty => "DIFFICULTY",
+			ExternalOpcode::GasLimit => "GASLIMIT",
+			ExternalOpcode::SLoad => "SLOAD",
+			ExternalOpcode::SStore => "SSTORE",
+			ExternalOpcode::Gas => "GAS",
+			ExternalOpcode::CallToken => "CALLTOKEN",
+			ExternalOpcode::TokenBalance => "TOKENBALANCE",
+			ExternalOpcode::CallTokenValue => "CALLTOKENVALUE",
+			ExternalOpcode::CallTokenId => "CALLTOKENID",
+			ExternalOpcode::IsContract => "ISCONTRACT",
+			ExternalOpcode::Stake => "STAKE",
+			ExternalOpcode::Unstake => "UNSTAKE",
+			ExternalOpcode::WithdrawReward => "WITHDRAWREWARD",
+			ExternalOpcode::RewardBalance => "REWARDBALANCE",
+			ExternalOpcode::IsWitness => "ISWITNESS",
+			ExternalOpcode::AssetIssue => "ASSETISSUE",
+			ExternalOpcode::UpdateAsset => "UPDATEASSET",
+			ExternalOpcode::Freeze => "FREEZE",
+			ExternalOpcode::Unfreeze => "UNFREEZE",
+			ExternalOpcode::FreezeExpireTime => "FREEZEEXPIRETIME",
+			ExternalOpcode::VoteWitness => "VOTEWITNESS",
+			ExternalOpcode::FreezeBalanceV2 => "FREEZEBALANCEV2",
+			ExternalOpcode::UnfreezeBalanceV2 => "UNFREEZEBALANCEV2",
+			ExternalOpcode::CancelAllUnfreezeV2 => "CANCELALLUNFREEZEV2",
+			ExternalOpcode::WithdrawExpireUnfreeze => "WITHDRAWEXPIREUNFREEZE",
+			ExternalOpcode::DelegateResource => "DELEGATERESOURCE",
+			ExternalOpcode::UnDelegateResource => "UNDELEGATERESOURCE",
+			ExternalOpcode::Create => "CREATE",
+			ExternalOpcode::Create2 => "CREATE2",
+			ExternalOpcode::Call => "CALL",
+			ExternalOpcode::CallCode => "CALLCODE",
+			ExternalOpcode::DelegateCall => "DELEGATECALL",
+			ExternalOpcode::StaticCall => "STATICCALL",
+			ExternalOpcode::Suicide => "SUICIDE",
+			ExternalOpcode::ChainId => "CHAINID",
+			ExternalOpcode::Log(n) => LOG_NAMES.get(*n as usize).copied().unwrap_or("LOG"),
+			ExternalOpcode::Other(_) => "INVALID",
+		}
+	}
+}
@@ -1,36 +1,64 @@
 use primitive_types::H256;
+use core::cmp::min;
 use alloc::vec::Vec;
 use crate::ExitError;
 
-/// EVM stack.
-#[derive(Clone, Debug)]
+/// Largest limit for which the stack buffer is allocated upfront.
+const MAX_PREALLOCATED: usize = 1024;
+
+/// EVM stack. The buffer is allocated upfront to the stack limit, so pushes never
+/// reallocate.
+#[derive(Debug)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Stack {
 	data: Vec<H256>,
 	limit: usize,
 }
 
+impl Clone for Stack {
+	fn clone(&self) -> Self {
+		let mut data = Vec::with_capacity(self.data.capacity());
+		data.extend_from_slice(&self.data);
+		Self { data, limit: self.limit }
+	}
+}
+
 impl Stack {
 	/// Create a new stack with given limit.
 	pub fn new(limit: usize) -> Self {
 		Self {
-			data: Vec::new(),
+			data: Vec::with_capacity(min(limit, MAX_PREALLOCATED)),
 			limit,
 		}
 	}
 
+	/// Empty the stack for reuse with the given limit, keeping its buffer.
+	pub fn reset(&mut self, limit: usize) {
+		self.data.clear();
+		self.data.reserve(min(limit, MAX_PREALLOCATED));
+		self.limit = limit;
+	}
+
 	/// Stack limit.
 	pub fn limit(&self) -> usize {
 		self.limit
 	}
 
+	/// Stack data, with the top of the stack last.
+	pub fn data(&self) -> &Vec<H256> {
+		&self.data
+	}
+
 	/// Pop a value from the stack. If the stack is already empty, returns the
 	/// `StackUnderflow` error.
+	#[inline]
 	pub fn pop(&mut self) -> Result<H256, ExitError> {
 		self.data.pop().ok_or(ExitError::StackUnderflow)
 	}
 
 	/// Push a new value into the stack. If it will exceed the stack limit,
 	/// returns `StackOverflow` error and leaves the stack unchanged.
+	#[inline]
 	pub fn push(&mut self, value: H256) -> Result<(), ExitError> {
 		if self.data.len() + 1 > self.limit {
 			return Err(ExitError::StackOverflow)
@@ -42,24 +70,25 @@ impl Stack {
 	/// Peek a value at given index for the stack, where the top of
 	/// the stack is at index `0`. If the index is too large,
 	/// `StackError::Underflow` is returned.
+	#[inline]
 	pub fn peek(&self, no_from_top: usize) -> Result<H256, ExitError> {
-		if self.data.len() > no_from_top {
-			Ok(self.data[self.data.len() - no_from_top - 1])
-		} else {
-			Err(ExitError::StackUnderflow)
+		match no_from_top.checked_add(1).and_then(|n| self.data.len().checked_sub(n)) {
+			Some(index) => Ok(self.data[index]),
+			None => Err(ExitError::StackUnderflow),
 		}
 	}
 
 	/// Set a value at given index for the stack, where the top of the
 	/// stack is at index `0`. If the index is too large,
 	/// `StackError::Underflow` is returned.
+	#[inline]
 	pub fn set(&mut self, no_from_top: usize, val: H256) -> Result<(), ExitError> {
-		if self.data.len() > no_from_top {
-			let len = self.data.len();
-			self.data[len - no_from_top - 1] = val;
-			Ok(())
-		} else {
-			Err(ExitError::StackUnderflow)
+		match no_from_top.checked_add(1).and_then(|n| self.data.len().checked_sub(n)) {
+			Some(index) => {
+				self.data[index] = val;
+				Ok(())
+			},
+			None => Err(ExitError::StackUnderflow),
 		}
 	}
 }
@@ -0,0 +1,131 @@
+use alloc::vec::Vec;
+use primitive_types::U256;
+use crate::{disassemble, Opcode, Stack, Valids};
+
+/// Sequence of two opcodes evaluated as one instruction.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Superinstruction {
+	/// `PUSHn` of a valid jump destination, then `JUMP`.
+	PushJump {
+		/// Size of the immediate.
+		n: u8,
+		/// The pushed jump destination.
+		dest: usize,
+	},
+	/// `PUSHn` of a valid jump destination, then `JUMPI`.
+	PushJumpI {
+		/// Size of the immediate.
+		n: u8,
+		/// The pushed jump destination.
+		dest: usize,
+	},
+	/// `PUSHn`, then `ADD`.
+	PushAdd {
+		/// Size of the immediate.
+		n: u8,
+	},
+	/// `DUPn`, then `SWAPm`.
+	DupSwap {
+		/// Depth of the duplicated item.
+		dup: u8,
+		/// Depth of the item swapped with the top.
+		swap: u8,
+	},
+}
+
+impl Superinstruction {
+	/// Opcodes of the sequence, in order.
+	pub fn opcodes(&self) -> [Opcode; 2] {
+		match *self {
+			Superinstruction::PushJump { n, .. } => [Opcode::Push(n), Opcode::Jump],
+			Superinstruction::PushJumpI { n, .. } => [Opcode::Push(n), Opcode::JumpI],
+			Superinstruction::PushAdd { n } => [Opcode::Push(n), Opcode::Add],
+			Superinstruction::DupSwap { dup, swap } => [Opcode::Dup(dup), Opcode::Swap(swap)],
+		}
+	}
+
+	/// Size of the sequence in the code.
+	pub fn size(&self) -> usize {
+		match *self {
+			Superinstruction::PushJump { n, .. } |
+			Superinstruction::PushJumpI { n, .. } |
+			Superinstruction::PushAdd { n } => n as usize + 2,
+			Superinstruction::DupSwap { .. } => 2,
+		}
+	}
+
+	/// Whether the sequence cannot overflow or underflow the stack, so that it can
+	/// be evaluated without checks between the opcodes.
+	pub fn is_ready(&self, stack: &Stack) -> bool {
+		let len = stack.data().len();
+		len < stack.limit() && match *self {
+			Superinstruction::PushJump { .. } => true,
+			Superinstruction::PushJumpI { .. } | Superinstruction::PushAdd { .. } => len >= 1,
+			Superinstruction::DupSwap { dup, swap } => len >= dup as usize && len >= swap as usize,
+		}
+	}
+}
+
+/// Mapping of superinstructions from code, by position of their first opcode.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Superinstructions(Vec<Option<Superinstruction>>);
+
+impl Superinstructions {
+	/// Find the superinstructions of code with its jump destination map. `PUSHn`
+	/// then `JUMP` or `JUMPI` is only fused if it pushes a valid jump destination.
+	pub fn new(code: &[u8], valids: &Valids) -> Self {
+		let mut superinstructions = Vec::with_capacity(code.len());
+		superinstructions.resize(code.len(), None);
+
+		let listing = disassemble(code);
+		for pair in listing.windows(2) {
+			let (first, second) = (&pair[0], &pair[1]);
+			let dest = || {
+				let dest = U256::from_big_endian(&first.push_data);
+				Some(dest)
+					.filter(|dest| *dest <= U256::from(usize::MAX))
+					.map(|dest| dest.as_usize())
+					.filter(|dest| valids.is_valid(*dest))
+			};
+
+			superinstructions[first.pc] = match (first.opcode, second.opcode) {
+				(Ok(Opcode::Push(0)), _) => None,
+				(Ok(Opcode::Push(n)), _) if first.push_data.len() < n as usize => None,
+				(Ok(Opcode::Push(n)), Ok(Opcode::Jump)) =>
+					dest().map(|dest| Superinstruction::PushJump { n, dest }),
+				(Ok(Opcode::Push(n)), Ok(Opcode::JumpI)) =>
+					dest().map(|dest| Superinstruction::PushJumpI { n, dest }),
+				(Ok(Opcode::Push(n)), Ok(Opcode::Add)) => Some(Superinstruction::PushAdd { n }),
+				(Ok(Opcode::Dup(dup)), Ok(Opcode::Swap(swap))) => Some(Superinstruction::DupSwap { dup, swap }),
+				_ => None,
+			};
+		}
+
+		Superinstructions(superinstructions)
+	}
+
+	/// Get the length of the mapping. This is the same as the code bytes.
+	#[inline]
+	pub fn len(&self) -> usize {
+		self.0.len()
+	}
+
+	/// Returns true if the mapping is empty.
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Superinstruction starting at the position, if any.
+	#[inline]
+	pub fn get(&self, position: usize) -> Option<Superinstruction> {
+		self.0.get(position).cloned().flatten()
+	}
+
+	/// Number of superinstructions found.
+	pub fn count(&self) -> usize {
+		self.0.iter().filter(|s| s.is_some()).count()
+	}
+}
@@ -0,0 +1,39 @@
+use primitive_types::U256;
+use evm_core::{assemble, Assembler, AssemblerError, Capture, ExitSucceed, Machine, Opcode};
+
+#[test]
+fn assemble_with_labels() {
+	let code = assemble("
+		PUSH1 10        ; counter
+		loop:
+		PUSH1 1 SWAP1 SUB
+		DUP1 PUSH @loop JUMPI
+		PUSH1 0 MSTORE
+		PUSH1 32 PUSH1 0 RETURN
+	").unwrap();
+	assert_eq!(hex::encode(&code), "600a5b60019003806100025760005260206000f3");
+
+	let mut machine = Machine::new(code, Vec::new(), 1024, 10000);
+	assert_eq!(machine.run(), Capture::Exit(ExitSucceed::Returned.into()));
+	assert_eq!(U256::from_big_endian(&machine.return_value()), U256::zero());
+}
+
+#[test]
+fn builder_matches_text() {
+	let built = Assembler::new()
+		.push(U256::from(0x1234))
+		.label("end")
+		.jump("end")
+		.op(Opcode::Stop)
+		.build()
+		.unwrap();
+	assert_eq!(built, assemble("PUSH2 0x1234 end: PUSH2 @end JUMP STOP").unwrap());
+}
+
+#[test]
+fn assemble_errors() {
+	assert_eq!(assemble("PUSH1 256"), Err(AssemblerError::ImmediateTooLarge("256".into())));
+	assert_eq!(assemble("FOO"), Err(AssemblerError::UnknownMnemonic("FOO".into())));
+	assert_eq!(assemble("PUSH @nowhere"), Err(AssemblerError::UnknownLabel("nowhere".into())));
+	assert_eq!(assemble("a: a:"), Err(AssemblerError::DuplicateLabel("a".into())));
+}
@@ -0,0 +1,34 @@
+use evm_core::{Bytes, Capture, ExitReason, ExitSucceed, Machine};
+
+#[test]
+fn slice_shares_buffer() {
+	let bytes = Bytes::from(vec![1, 2, 3, 4, 5]);
+	let slice = bytes.slice(1..4);
+	assert_eq!(&slice[..], &[2, 3, 4]);
+	assert_eq!(&slice.slice(1..10)[..], &[3, 4]);
+	assert!(slice.slice(5..10).is_empty());
+	assert_eq!(bytes.clone(), bytes);
+}
+
+#[test]
+fn into_vec_reuses_unshared_buffer() {
+	let data = vec![1, 2, 3];
+	let ptr = data.as_ptr();
+	let data = Bytes::from(data).into_vec();
+	assert_eq!(data.as_ptr(), ptr);
+
+	let bytes = Bytes::from(vec![1, 2, 3]);
+	assert_eq!(bytes.slice(1..3).into_vec(), vec![2, 3]);
+	assert_eq!(bytes.clone().into_vec(), bytes.to_vec());
+}
+
+#[test]
+fn machine_from_bytes() {
+	let code = Bytes::from(hex::decode("3660005260206000f3").unwrap());
+	let data = Bytes::from(vec![0u8; 64]).slice(0..7);
+
+	let mut machine = Machine::new(code.clone(), data, 1024, 10000);
+	assert_eq!(machine.run(), Capture::Exit(ExitReason::Succeed(ExitSucceed::Returned)));
+	assert_eq!(machine.return_value()[31], 7);
+	assert_eq!(machine.code(), &code);
+}
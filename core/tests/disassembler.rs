@@ -0,0 +1,26 @@
+use evm_core::{disassemble, ExternalOpcode, Opcode};
+
+#[test]
+fn disassemble_listing() {
+	// PUSH1 0x04, JUMP, INVALID, JUMPDEST, SLOAD, PUSH2 truncated
+	let code = hex::decode("600456fe5b546101").unwrap();
+	let listing = disassemble(&code);
+
+	assert_eq!(listing.len(), 6);
+	assert_eq!(listing[0].push_data, vec![0x04]);
+	assert_eq!(listing[3].pc, 4);
+	assert!(listing[3].is_jumpdest);
+	assert_eq!(listing[4].opcode, Err(ExternalOpcode::SLoad));
+	assert_eq!(listing[5].opcode, Ok(Opcode::Push(2)));
+	assert_eq!(listing[5].push_data, vec![0x01]);
+
+	let lines = listing.iter().map(|i| i.to_string()).collect::<Vec<_>>();
+	assert_eq!(lines, vec![
+		"000000: PUSH1 0x04",
+		"000002: JUMP",
+		"000003: INVALID 0xfe",
+		"000004: JUMPDEST",
+		"000005: SLOAD",
+		"000006: PUSH2 0x01",
+	]);
+}
@@ -0,0 +1,32 @@
+use evm_core::Memory;
+
+#[test]
+fn pages_allocated_on_write() {
+	let mut memory = Memory::new(usize::MAX);
+	memory.set(10_000_000, &[1, 2, 3], Some(32)).unwrap();
+	assert_eq!(memory.len(), 10_000_032);
+	assert_eq!(memory.allocated_pages(), 1);
+
+	memory.set(4094, &[4, 5, 6, 7], None).unwrap();
+	assert_eq!(memory.allocated_pages(), 3);
+	assert_eq!(memory.get(4093, 6), vec![0, 4, 5, 6, 7, 0]);
+	assert_eq!(memory.get(9_999_999, 5), vec![0, 1, 2, 3, 0]);
+	assert_eq!(memory.get(10_000_030, 4), vec![0, 0, 0, 0]);
+
+	memory.set(4095, &[], Some(2)).unwrap();
+	assert_eq!(memory.get(4094, 4), vec![4, 0, 0, 7]);
+}
+
+#[test]
+fn reset_frees_high_pages() {
+	let mut memory = Memory::new(usize::MAX);
+	memory.set(0, &[1], Some(32)).unwrap();
+	memory.set(10_000_000, &[1], Some(32)).unwrap();
+	assert_eq!(memory.allocated_pages(), 2);
+
+	memory.reset(1024);
+	assert_eq!(memory.len(), 0);
+	assert_eq!(memory.limit(), 1024);
+	assert_eq!(memory.allocated_pages(), 1);
+	assert_eq!(memory.get(0, 1), vec![0]);
+}
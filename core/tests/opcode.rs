@@ -0,0 +1,37 @@
+use evm_core::{Capture, ExitError, ExitReason, InstructionTable, Machine, Opcode};
+
+#[test]
+fn parse_as_u8_roundtrip() {
+	for byte in 0..=255u8 {
+		let value = match Opcode::parse(byte) {
+			Ok(opcode) => opcode.as_u8(),
+			Err(opcode) => opcode.as_u8(),
+		};
+		assert_eq!(value, byte);
+	}
+}
+
+#[test]
+fn table_traps_external_opcodes() {
+	for byte in 0..=255u8 {
+		let mut machine = Machine::new(vec![byte], Vec::new(), 1024, 10000);
+		match (machine.step(), Opcode::parse(byte)) {
+			(Err(Capture::Trap(trap)), Err(external)) => assert_eq!(trap, external),
+			(Err(Capture::Trap(_)), Ok(opcode)) => panic!("{:?} trapped", opcode),
+			(_, Err(external)) => panic!("{:?} not trapped", external),
+			_ => (),
+		}
+	}
+}
+
+#[test]
+fn table_without_opcode() {
+	let table = InstructionTable::new().without(0x5f);
+	let mut machine = Machine::new(vec![0x5f], Vec::new(), 1024, 10000);
+	assert_eq!(machine.step_with(&table), Err(Capture::Exit(ExitReason::Error(ExitError::IllegalOperation))));
+
+	let mut machine = Machine::new(vec![0x5f], Vec::new(), 1024, 10000);
+	assert_eq!(machine.step(), Err(Capture::Exit(ExitReason::Error(ExitError::IllegalOperation))));
+	let mut machine = Machine::new(vec![0x5f], Vec::new(), 1024, 10000);
+	assert_eq!(machine.step_with(&InstructionTable::new()), Ok(()));
+}
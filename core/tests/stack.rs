@@ -0,0 +1,18 @@
+use primitive_types::H256;
+use evm_core::{ExitError, Stack};
+
+#[test]
+fn peek_and_set_out_of_range() {
+	let mut stack = Stack::new(1024);
+	stack.push(H256::from_low_u64_be(1)).unwrap();
+	stack.push(H256::from_low_u64_be(2)).unwrap();
+
+	assert_eq!(stack.peek(1), Ok(H256::from_low_u64_be(1)));
+	stack.set(1, H256::from_low_u64_be(3)).unwrap();
+	assert_eq!(stack.peek(1), Ok(H256::from_low_u64_be(3)));
+
+	for index in [2, usize::MAX].iter() {
+		assert_eq!(stack.peek(*index), Err(ExitError::StackUnderflow));
+		assert_eq!(stack.set(*index, H256::zero()), Err(ExitError::StackUnderflow));
+	}
+}
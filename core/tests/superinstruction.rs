@@ -0,0 +1,47 @@
+use primitive_types::U256;
+use evm_core::{assemble, Capture, ExitError, Machine, Shared, Superinstruction, Superinstructions, Valids};
+
+#[test]
+fn fuse_and_step_superinstructions() {
+	let code = assemble("
+		PUSH @start JUMP
+		INVALID
+		start:
+		PUSH1 1 PUSH1 2 ADD     ; only the second PUSH is fused
+		DUP1 SWAP1
+		PUSH1 0 JUMPI           ; not a jump destination
+	").unwrap();
+
+	let superinstructions = Superinstructions::new(&code, &Valids::new(&code));
+	assert_eq!(superinstructions.count(), 3);
+	assert_eq!(superinstructions.get(0), Some(Superinstruction::PushJump { n: 2, dest: 5 }));
+	assert_eq!(superinstructions.get(6), None);
+	assert_eq!(superinstructions.get(8), Some(Superinstruction::PushAdd { n: 1 }));
+	assert_eq!(superinstructions.get(11), Some(Superinstruction::DupSwap { dup: 1, swap: 1 }));
+	assert_eq!(superinstructions.get(13), None);
+
+	let superinstructions = Shared::new(superinstructions);
+	let mut machine = Machine::new(code.clone(), Vec::new(), 1024, 10000);
+	machine.set_superinstructions(superinstructions.clone());
+
+	let superinstruction = machine.superinstruction().unwrap();
+	machine.step_superinstruction(superinstruction).unwrap();
+	assert_eq!(machine.position(), &Ok(5));
+	machine.step().unwrap();
+	machine.step().unwrap();
+
+	let superinstruction = machine.superinstruction().unwrap();
+	machine.step_superinstruction(superinstruction).unwrap();
+	let superinstruction = machine.superinstruction().unwrap();
+	machine.step_superinstruction(superinstruction).unwrap();
+	assert_eq!(machine.position(), &Ok(13));
+	let stack = machine.stack().data().iter().map(|v| U256::from_big_endian(&v[..])).collect::<Vec<_>>();
+	assert_eq!(stack, vec![U256::from(3), U256::from(3)]);
+	assert_eq!(machine.run(), Capture::Exit(ExitError::InvalidJump.into()));
+
+	// A superinstruction that would overflow the stack is left to single steps.
+	let mut machine = Machine::new(code, Vec::new(), 0, 10000);
+	machine.set_superinstructions(superinstructions);
+	assert_eq!(machine.superinstruction(), None);
+	assert_eq!(machine.run(), Capture::Exit(ExitError::StackOverflow.into()));
+}
@@ -0,0 +1,216 @@
+//! Command line runner of EVM bytecode, executing a call into the given code and
+//! printing the exit reason, output and gas used.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::process;
+use primitive_types::{H160, H256, U256};
+use serde_json::Value;
+use evm::Config;
+use evm::backend::{MemoryAccount, MemoryBackend, MemoryVicinity};
+use evm::executor::StackExecutor;
+
+const USAGE: &str = "\
+Usage: evm-exec [OPTIONS] <CODE>
+
+Execute a call into CODE, given as hex or as @FILE containing hex.
+
+Options:
+  --input <HEX>         Call data
+  --config <PRESET>     Config preset, e.g. istanbul or great_voyage_4_7 [default: great_voyage_4_7]
+  --prestate <FILE>     JSON of accounts by address, with balance, nonce, code and storage
+  --gas <N>             Gas limit [default: 10000000]
+  --value <N>           Call value [default: 0]
+  --caller <ADDRESS>    Caller [default: 0x0000000000000000000000000000000000000001]
+  --address <ADDRESS>   Address of the code [default: 0x0000000000000000000000000000000000000100]
+  --trace               Print a JSON line per step (requires the `trace` feature)
+  --disassemble         Print the disassembled code instead of executing it
+";
+
+struct Options {
+	code: Vec<u8>,
+	input: Vec<u8>,
+	config: Config,
+	prestate: BTreeMap<H160, MemoryAccount>,
+	gas: usize,
+	value: U256,
+	caller: H160,
+	address: H160,
+	trace: bool,
+	disassemble: bool,
+}
+
+fn hex_bytes(value: &str) -> Result<Vec<u8>, String> {
+	hex::decode(value.trim().trim_start_matches("0x")).map_err(|e| format!("invalid hex {:?}: {}", value, e))
+}
+
+fn address(value: &str) -> Result<H160, String> {
+	let bytes = hex_bytes(value)?;
+	if bytes.len() != 20 {
+		return Err(format!("invalid address {:?}", value))
+	}
+	Ok(H160::from_slice(&bytes))
+}
+
+fn u256(value: &Value) -> Result<U256, String> {
+	match value {
+		Value::Number(n) => n.as_u64().map(U256::from).ok_or_else(|| format!("invalid number {}", n)),
+		Value::String(s) if s.starts_with("0x") =>
+			U256::from_str_radix(&s[2..], 16).map_err(|_| format!("invalid number {:?}", s)),
+		Value::String(s) => U256::from_dec_str(s).map_err(|_| format!("invalid number {:?}", s)),
+		_ => Err(format!("invalid number {}", value)),
+	}
+}
+
+fn h256(value: &str) -> Result<H256, String> {
+	let value = u256(&Value::String(value.to_string()))?;
+	let mut bytes = [0u8; 32];
+	value.to_big_endian(&mut bytes);
+	Ok(H256::from(bytes))
+}
+
+fn prestate(path: &str) -> Result<BTreeMap<H160, MemoryAccount>, String> {
+	let json = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+	let json: Value = serde_json::from_str(&json).map_err(|e| format!("{}: {}", path, e))?;
+	let accounts = json.as_object().ok_or_else(|| format!("{}: expected an object", path))?;
+
+	let mut state = BTreeMap::new();
+	for (key, account) in accounts {
+		let mut memory_account = MemoryAccount::default();
+		if let Some(balance) = account.get("balance") {
+			memory_account.balance = u256(balance)?;
+		}
+		if let Some(nonce) = account.get("nonce") {
+			memory_account.nonce = u256(nonce)?;
+		}
+		if let Some(code) = account.get("code").and_then(Value::as_str) {
+			memory_account.code = hex_bytes(code)?;
+		}
+		if let Some(storage) = account.get("storage").and_then(Value::as_object) {
+			for (index, value) in storage {
+				let value = value.as_str().ok_or_else(|| format!("invalid storage value {}", value))?;
+				memory_account.storage.insert(h256(index)?, h256(value)?);
+			}
+		}
+		state.insert(address(key)?, memory_account);
+	}
+	Ok(state)
+}
+
+fn parse(args: &[String]) -> Result<Options, String> {
+	let mut options = Options {
+		code: Vec::new(),
+		input: Vec::new(),
+		config: Config::great_voyage_4_7(),
+		prestate: BTreeMap::new(),
+		gas: 10_000_000,
+		value: U256::zero(),
+		caller: H160::from_low_u64_be(1),
+		address: H160::from_low_u64_be(0x100),
+		trace: false,
+		disassemble: false,
+	};
+	let mut code = None;
+
+	let mut args = args.iter();
+	while let Some(arg) = args.next() {
+		let mut value = || args.next().ok_or_else(|| format!("missing value of {}", arg));
+		match arg.as_str() {
+			"--input" => options.input = hex_bytes(value()?)?,
+			"--config" => {
+				let name = value()?;
+				options.config = Config::preset(name).ok_or_else(|| format!("unknown preset {:?}", name))?;
+			},
+			"--prestate" => options.prestate = prestate(value()?)?,
+			"--gas" => options.gas = value()?.parse().map_err(|_| "invalid gas".to_string())?,
+			"--value" => options.value = u256(&Value::String(value()?.clone()))?,
+			"--caller" => options.caller = address(value()?)?,
+			"--address" => options.address = address(value()?)?,
+			"--trace" => options.trace = true,
+			"--disassemble" => options.disassemble = true,
+			"-h" | "--help" => return Err(String::new()),
+			other if other.starts_with("--") => return Err(format!("unknown option {}", other)),
+			other => code = Some(other.to_string()),
+		}
+	}
+
+	let code = code.ok_or_else(|| "missing code".to_string())?;
+	options.code = match code.strip_prefix('@') {
+		Some(path) => hex_bytes(&fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?)?,
+		None => hex_bytes(&code)?,
+	};
+	Ok(options)
+}
+
+fn run(options: Options) -> Result<(), String> {
+	let Options { code, input, config, prestate, gas, value, caller, address, trace: enable_trace, disassemble } = options;
+
+	if disassemble {
+		for instruction in evm::disassemble(&code) {
+			println!("{}", instruction);
+		}
+		return Ok(())
+	}
+
+	let vicinity = MemoryVicinity {
+		gas_price: U256::zero(),
+		origin: caller,
+		chain_id: U256::zero(),
+		block_hashes: Vec::new(),
+		block_number: U256::zero(),
+		block_coinbase: H160::default(),
+		block_timestamp: U256::zero(),
+		block_difficulty: U256::zero(),
+		block_randomness: None,
+		block_gas_limit: U256::from(gas),
+		block_base_fee_per_gas: U256::zero(),
+	};
+
+	let mut state = prestate;
+	state.entry(address).or_default().code = code;
+	let account = state.entry(caller).or_default();
+	account.balance = account.balance.saturating_add(value);
+	let backend = MemoryBackend::new(&vicinity, state);
+
+	let mut executor = StackExecutor::new(&backend, gas, &config);
+	let transact = || executor.transact_call(caller, address, value, input, gas);
+	let (reason, output) = if enable_trace {
+		trace(transact)?
+	} else {
+		transact()
+	};
+
+	println!("result: {:?}", reason);
+	println!("output: 0x{}", hex::encode(output));
+	println!("gas used: {}", executor.used_gas());
+	Ok(())
+}
+
+#[cfg(feature = "trace")]
+fn trace<R>(f: impl FnOnce() -> R) -> Result<R, String> {
+	use evm::tracer::{StructLogger, StructLoggerConfig};
+
+	let logger = StructLogger::new(StructLoggerConfig { enable_memory: true, ..Default::default() });
+	let ret = logger.trace(f);
+	print!("{}", logger.to_json_lines());
+	Ok(ret)
+}
+
+#[cfg(not(feature = "trace"))]
+fn trace<R>(_f: impl FnOnce() -> R) -> Result<R, String> {
+	Err("--trace requires evm-exec built with the `trace` feature".to_string())
+}
+
+fn main() {
+	let args = env::args().skip(1).collect::<Vec<_>>();
+	let result = parse(&args).and_then(run);
+	if let Err(e) = result {
+		if e.is_empty() {
+			print!("{}", USAGE);
+			return
+		}
+		eprintln!("error: {}\n\n{}", e, USAGE);
+		process::exit(1);
+	}
+}
@@ -0,0 +1,13 @@
+//! Disassemble and validate arbitrary code, which must never panic.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use evm::Config;
+
+fuzz_target!(|input: (Config, &[u8])| {
+	let (config, code) = input;
+	let instructions = evm::disassemble(code);
+	assert_eq!(instructions.iter().map(|i| 1 + i.push_data.len()).sum::<usize>(), code.len());
+	let _ = evm::validate(code, &config);
+});
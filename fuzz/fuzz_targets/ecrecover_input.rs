@@ -0,0 +1,15 @@
+//! Decode arbitrary ecrecover input, which must never panic.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use evm::Config;
+use evm::executor::EcrecoverInput;
+
+fuzz_target!(|input: (bool, &[u8])| {
+	let (strict, data) = input;
+	let config = Config::great_voyage_4_7().builder().ecrecover_strict(strict).build().unwrap();
+	if let Some(parsed) = EcrecoverInput::parse(data, &config) {
+		assert!(parsed.recovery_id < 2);
+	}
+});
@@ -0,0 +1,50 @@
+//! Call into arbitrary code with arbitrary call data, under an arbitrary preset,
+//! with the precompiles of `evm-precompile`.
+
+#![no_main]
+
+use std::collections::BTreeMap;
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use primitive_types::{H160, U256};
+use evm::Config;
+use evm::backend::{MemoryAccount, MemoryBackend, MemoryVicinity};
+use evm::executor::StackExecutor;
+use evm_precompile::tron_precompile;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+	config: Config,
+	code: Vec<u8>,
+	data: Vec<u8>,
+	value: u64,
+	gas: u32,
+}
+
+fuzz_target!(|input: Input| {
+	let vicinity = MemoryVicinity {
+		gas_price: U256::zero(),
+		origin: H160::default(),
+		chain_id: U256::one(),
+		block_hashes: Vec::new(),
+		block_number: U256::one(),
+		block_coinbase: H160::default(),
+		block_timestamp: U256::one(),
+		block_difficulty: U256::zero(),
+		block_randomness: None,
+		block_gas_limit: U256::from(u32::MAX),
+		block_base_fee_per_gas: U256::zero(),
+	};
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(0x100);
+
+	let mut state = BTreeMap::new();
+	state.insert(caller, MemoryAccount { balance: U256::from(u64::MAX), ..Default::default() });
+	state.insert(contract, MemoryAccount { code: input.code, ..Default::default() });
+	let backend = MemoryBackend::new(&vicinity, state);
+
+	let gas = input.gas as usize;
+	let mut executor = StackExecutor::new_with_precompile(&backend, gas, &input.config, tron_precompile);
+	executor.set_step_limit(1_000_000);
+	let _ = executor.transact_call(caller, contract, U256::from(input.value), input.data, gas);
+});
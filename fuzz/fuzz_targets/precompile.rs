@@ -0,0 +1,56 @@
+//! Call the precompiles with arbitrary input and target energy, under an arbitrary
+//! preset. They must never panic, nor charge more than the target energy.
+
+#![no_main]
+
+use std::collections::BTreeMap;
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use primitive_types::{H160, U256};
+use evm::{Config, Context};
+use evm::backend::{MemoryBackend, MemoryVicinity};
+use evm::executor::PrecompileContext;
+use evm_precompile::tron_precompile;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+	config: Config,
+	address: u8,
+	input: Vec<u8>,
+	target_gas: Option<u32>,
+}
+
+fuzz_target!(|input: Input| {
+	let vicinity = MemoryVicinity {
+		gas_price: U256::zero(),
+		origin: H160::default(),
+		chain_id: U256::one(),
+		block_hashes: Vec::new(),
+		block_number: U256::one(),
+		block_coinbase: H160::default(),
+		block_timestamp: U256::one(),
+		block_difficulty: U256::zero(),
+		block_randomness: None,
+		block_gas_limit: U256::from(u32::MAX),
+		block_base_fee_per_gas: U256::zero(),
+	};
+	let backend = MemoryBackend::new(&vicinity, BTreeMap::new());
+
+	// Unbounded energy would let modexp allocate arbitrary lengths.
+	let target_gas = input.target_gas.unwrap_or(u32::MAX) as usize;
+	let address = H160::from_low_u64_be(input.address as u64);
+	let context = Context {
+		address,
+		caller: H160::default(),
+		call_value: U256::zero(),
+		call_token_id: U256::zero(),
+		call_token_value: U256::zero(),
+	};
+	let context = PrecompileContext::new(&context, &backend);
+
+	match tron_precompile(address, &input.input, Some(target_gas), &input.config, &context) {
+		Some(Ok((_, _, cost))) => assert!(cost <= target_gas),
+		Some(Err(_)) => assert!((1..=0x0a).contains(&input.address)),
+		None => assert!(!(1..=0x08).contains(&input.address)),
+	}
+});
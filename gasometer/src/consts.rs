@@ -12,12 +12,12 @@ pub const G_CREATE: usize = 32000;
 pub const G_CALLVALUE: usize = 9000;
 pub const G_NEWACCOUNT: usize = 25000;
 pub const G_EXP: usize = 10;
-pub const G_MEMORY: usize = 3;
 pub const G_LOG: usize = 375;
 pub const G_LOGDATA: usize = 8;
 pub const G_LOGTOPIC: usize = 375;
 pub const G_SHA3: usize = 30;
 pub const G_SHA3WORD: usize = 6;
+pub const G_INITCODE_WORD: usize = 2;
 pub const G_COPY: usize = 3;
 pub const G_BLOCKHASH: usize = 20;
 pub const G_CODEDEPOSIT: usize = 200;
@@ -25,3 +25,9 @@ pub const G_STAKE: usize = 35000;
 pub const G_WITHDRAWREWARD: usize = 25000;
 pub const G_ASSETISSUE: usize = 25000;
 pub const G_UPDATEASSET: usize = 5000;
+pub const G_STAKE_V2: usize = 10000;
+pub const G_VOTEWITNESS: usize = 30000;
+pub const G_FREEZE: usize = 20000;
+pub const G_UNFREEZE: usize = 20000;
+pub const G_FREEZEEXPIRETIME: usize = 50;
+pub const G_WITHDRAWREWARD_VOTE: usize = 20000;
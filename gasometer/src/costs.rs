@@ -58,7 +58,29 @@ pub fn sstore_refund(original: H256, current: Option<H256>, new: H256, config: &
 	}
 }
 
-pub fn create2_cost(len: U256) -> Result<usize, ExitError> {
+pub fn create_cost(len: U256, config: &Config) -> Result<usize, ExitError> {
+	let initcode = initcode_cost(len, config)?;
+	G_CREATE.checked_add(initcode).ok_or(ExitError::OutOfGas)
+}
+
+/// EIP-3860: init code above the limit fails, and each word of it is charged.
+pub fn initcode_cost(len: U256, config: &Config) -> Result<usize, ExitError> {
+	let max_initcode_size = match config.max_initcode_size {
+		Some(max_initcode_size) => max_initcode_size,
+		None => return Ok(0),
+	};
+	if len > U256::from(max_initcode_size) {
+		return Err(ExitError::CreateContractLimit)
+	}
+
+	// ceil(len / 32.0)
+	let words = len / U256::from(32) +
+		if len % U256::from(32) == U256::zero() { U256::zero() } else { U256::one() };
+	Ok(G_INITCODE_WORD * words.as_usize())
+}
+
+pub fn create2_cost(len: U256, config: &Config) -> Result<usize, ExitError> {
+	let initcode = initcode_cost(len, config)?;
 	let base = U256::from(G_CREATE);
 	// ceil(len / 32.0)
 	let sha_addup_base = len / U256::from(32) +
@@ -71,7 +93,7 @@ pub fn create2_cost(len: U256) -> Result<usize, ExitError> {
 		return Err(ExitError::OutOfGas)
 	}
 
-	Ok(gas.as_usize())
+	gas.as_usize().checked_add(initcode).ok_or(ExitError::OutOfGas)
 }
 
 pub fn exp_cost(power: U256, config: &Config) -> Result<usize, ExitError> {
@@ -113,11 +135,11 @@ pub fn verylowcopy_cost(len: U256) -> Result<usize, ExitError> {
 	Ok(gas.as_usize())
 }
 
-pub fn extcodecopy_cost(len: U256, config: &Config) -> Result<usize, ExitError> {
+pub fn extcodecopy_cost(len: U256, is_cold: bool, config: &Config) -> Result<usize, ExitError> {
 	let wordd = len / U256::from(32);
 	let wordr = len % U256::from(32);
 
-	let gas = U256::from(config.gas_ext_code).checked_add(
+	let gas = U256::from(address_access_cost(is_cold, config.gas_ext_code, config)).checked_add(
 		U256::from(G_COPY).checked_mul(
 			if wordr == U256::zero() {
 				wordd
@@ -169,12 +191,48 @@ pub fn sha3_cost(len: U256) -> Result<usize, ExitError> {
 	Ok(gas.as_usize())
 }
 
-pub fn sstore_cost(original: H256, current: Option<H256>, new: H256, gas: usize, config: &Config) -> Result<usize, ExitError> {
+pub fn sload_cost(is_cold: bool, config: &Config) -> usize {
+	if config.increase_state_access_gas && is_cold {
+		config.gas_sload_cold
+	} else {
+		config.gas_sload
+	}
+}
+
+pub fn address_access_cost(is_cold: bool, regular_value: usize, config: &Config) -> usize {
+	if config.increase_state_access_gas {
+		if is_cold {
+			config.gas_account_access_cold
+		} else {
+			config.gas_storage_read_warm
+		}
+	} else {
+		regular_value
+	}
+}
+
+pub fn sstore_cost(
+	original: H256,
+	current: Option<H256>,
+	new: H256,
+	gas: usize,
+	is_cold: bool,
+	config: &Config,
+) -> Result<usize, ExitError> {
+	let gas_cost = sstore_cost_warm(original, current, new, gas, config)?;
+	if config.increase_state_access_gas && is_cold {
+		Ok(gas_cost + config.gas_sload_cold)
+	} else {
+		Ok(gas_cost)
+	}
+}
+
+fn sstore_cost_warm(original: H256, current: Option<H256>, new: H256, gas: usize, config: &Config) -> Result<usize, ExitError> {
 	if config.sstore_gas_metering {
-		if config.sstore_revert_under_stipend {
-			if gas < config.call_stipend {
-				return Err(ExitError::OutOfGas)
-			}
+		// EIP-2200: fail when no more than the call stipend is left, so that a
+		// stipend-only call cannot modify storage.
+		if config.sstore_revert_under_stipend && gas <= config.call_stipend {
+			return Err(ExitError::OutOfGas)
 		}
 		let current = current.unwrap_or_default();
 		Ok(if new == current {
@@ -199,7 +257,7 @@ pub fn sstore_cost(original: H256, current: Option<H256>, new: H256, gas: usize,
 	}
 }
 
-pub fn suicide_cost(value: U256, target_exists: bool, config: &Config) -> usize {
+pub fn suicide_cost(value: U256, is_cold: bool, target_exists: bool, config: &Config) -> usize {
 	let eip161 = !config.empty_considered_exists;
 	let should_charge_topup = if eip161 {
 		value != U256::zero() && !target_exists
@@ -213,18 +271,25 @@ pub fn suicide_cost(value: U256, target_exists: bool, config: &Config) -> usize
 		0
 	};
 
-	config.gas_suicide + suicide_gas_topup
+	let suicide_gas_cold = if config.increase_state_access_gas && is_cold {
+		config.gas_account_access_cold
+	} else {
+		0
+	};
+
+	config.gas_suicide + suicide_gas_topup + suicide_gas_cold
 }
 
 pub fn call_cost(
 	value: U256,
+	is_cold: bool,
 	is_call_or_callcode: bool,
 	is_call_or_staticcall: bool,
 	new_account: bool,
 	config: &Config,
 ) -> usize {
 	let transfers_value = value != U256::default();
-	config.gas_call +
+	address_access_cost(is_cold, config.gas_call, config) +
 		xfer_cost(is_call_or_callcode, transfers_value) +
 		new_cost(is_call_or_staticcall, new_account, transfers_value, config)
 }
@@ -1,10 +1,28 @@
 //! EVM gasometer.
 
 #![deny(warnings)]
-#![forbid(unsafe_code, missing_docs, unused_variables, unused_imports)]
+#![forbid(unsafe_code, missing_docs, unused_variables)]
+// `environmental!` allows unused imports in its expansion.
+#![cfg_attr(not(feature = "tracing"), forbid(unused_imports))]
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(feature = "tracing")]
+pub mod tracing;
+
+#[cfg(feature = "tracing")]
+macro_rules! event {
+	($x:expr) => {
+		use crate::tracing::Event::*;
+		$x.emit();
+	}
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! event {
+	($x:expr) => { }
+}
+
 mod consts;
 mod costs;
 mod memory;
@@ -31,6 +49,7 @@ macro_rules! try_or_fail {
 #[derive(Clone)]
 pub struct Gasometer<'config> {
 	gas_limit: usize,
+	energy_factor: usize,
 	config: &'config Config,
 	inner: Result<Inner<'config>, ExitError>
 }
@@ -40,6 +59,7 @@ impl<'config> Gasometer<'config> {
 	pub fn new(gas_limit: usize, config: &'config Config) -> Self {
 		Self {
 			gas_limit,
+			energy_factor: 0,
 			config,
 			inner: Ok(Inner {
 				memory_cost: 0,
@@ -66,7 +86,7 @@ impl<'config> Gasometer<'config> {
 		match self.inner.as_ref() {
 			Ok(inner) => {
 				self.gas_limit - inner.used_gas -
-					memory::memory_gas(inner.memory_cost).expect("Checked via record")
+					memory::memory_gas(inner.memory_cost, self.config).expect("Checked via record")
 			},
 			Err(_) => 0,
 		}
@@ -76,7 +96,7 @@ impl<'config> Gasometer<'config> {
 	pub fn total_used_gas(&self) -> usize {
 		match self.inner.as_ref() {
 			Ok(inner) => inner.used_gas +
-				memory::memory_gas(inner.memory_cost).expect("Checked via record"),
+				memory::memory_gas(inner.memory_cost, self.config).expect("Checked via record"),
 			Err(_) => self.gas_limit,
 		}
 	}
@@ -89,6 +109,16 @@ impl<'config> Gasometer<'config> {
 		}
 	}
 
+	/// Snapshot of the gasometer, `None` if it has failed.
+	pub fn snapshot(&self) -> Option<Snapshot> {
+		self.inner.as_ref().ok().map(|inner| Snapshot {
+			gas_limit: self.gas_limit,
+			memory_gas: memory::memory_gas(inner.memory_cost, self.config).expect("Checked via record"),
+			used_gas: inner.used_gas,
+			refunded_gas: inner.refunded_gas,
+		})
+	}
+
 	/// Explictly fail the gasometer with out of gas. Return `OutOfGas` error.
 	pub fn fail(&mut self) -> ExitError {
 		self.inner = Err(ExitError::OutOfGas);
@@ -100,6 +130,11 @@ impl<'config> Gasometer<'config> {
 		&mut self,
 		cost: usize
 	) -> Result<(), ExitError> {
+		event!(RecordCost {
+			cost,
+			snapshot: self.snapshot(),
+		});
+
 		let all_gas_cost = self.total_used_gas() + cost;
 		if self.gas_limit < all_gas_cost {
 			self.inner = Err(ExitError::OutOfGas);
@@ -115,6 +150,11 @@ impl<'config> Gasometer<'config> {
 		&mut self,
 		refund: isize,
 	) -> Result<(), ExitError> {
+		event!(RecordRefund {
+			refund,
+			snapshot: self.snapshot(),
+		});
+
 		self.inner_mut()?.refunded_gas += refund;
 		Ok(())
 	}
@@ -128,6 +168,12 @@ impl<'config> Gasometer<'config> {
 		self.record_cost(cost)
 	}
 
+	/// Set energy factor of the executing contract, in 1/10000. Opcode costs are
+	/// charged an extra `cost * factor / 10000` as penalty.
+	pub fn set_energy_factor(&mut self, energy_factor: usize) {
+		self.energy_factor = energy_factor;
+	}
+
 	/// Record opcode gas cost.
 	pub fn record_opcode(
 		&mut self,
@@ -140,11 +186,22 @@ impl<'config> Gasometer<'config> {
 			Some(memory) => try_or_fail!(self.inner, self.inner_mut()?.memory_cost(memory)),
 			None => self.inner_mut()?.memory_cost,
 		};
-		let memory_gas = try_or_fail!(self.inner, memory::memory_gas(memory_cost));
+		let memory_gas = try_or_fail!(self.inner, memory::memory_gas(memory_cost, self.config));
 		let gas_cost = try_or_fail!(self.inner, self.inner_mut()?.gas_cost(cost.clone(), gas));
+		let gas_cost = match self.energy_factor {
+			0 => gas_cost,
+			factor => gas_cost.saturating_add(gas_cost.saturating_mul(factor) / 10000),
+		};
 		let gas_refund = self.inner_mut()?.gas_refund(cost.clone());
 		let used_gas = self.inner_mut()?.used_gas;
 
+		event!(RecordDynamicCost {
+			gas_cost,
+			memory_gas,
+			gas_refund,
+			snapshot: self.snapshot(),
+		});
+
 		let all_gas_cost = memory_gas + used_gas + gas_cost;
 		if self.gas_limit < all_gas_cost {
 			self.inner = Err(ExitError::OutOfGas);
@@ -166,6 +223,11 @@ impl<'config> Gasometer<'config> {
 		&mut self,
 		stipend: usize,
 	) -> Result<(), ExitError> {
+		event!(RecordStipend {
+			stipend,
+			snapshot: self.snapshot(),
+		});
+
 		self.inner_mut()?.used_gas -= stipend;
 		Ok(())
 	}
@@ -182,12 +244,19 @@ impl<'config> Gasometer<'config> {
 					non_zero_data_len * self.config.gas_transaction_non_zero_data
 			},
 			TransactionCost::Create { zero_data_len, non_zero_data_len } => {
+				let len = U256::from(zero_data_len + non_zero_data_len);
 				self.config.gas_transaction_create +
 					zero_data_len * self.config.gas_transaction_zero_data +
-					non_zero_data_len * self.config.gas_transaction_non_zero_data
+					non_zero_data_len * self.config.gas_transaction_non_zero_data +
+					costs::initcode_cost(len, self.config)?
 			},
 		};
 
+		event!(RecordTransaction {
+			cost: gas_cost,
+			snapshot: self.snapshot(),
+		});
+
 		if self.gas() < gas_cost {
 			self.inner = Err(ExitError::OutOfGas);
 			return Err(ExitError::OutOfGas);
@@ -218,7 +287,7 @@ pub fn create_transaction_cost(
 	TransactionCost::Create { zero_data_len, non_zero_data_len }
 }
 
-/// Calculate the opcode cost.
+/// Calculate the opcode cost, and the account or storage slot it accesses.
 pub fn opcode_cost<H: Handler>(
 	address: H160,
 	opcode: Result<Opcode, ExternalOpcode>,
@@ -226,7 +295,25 @@ pub fn opcode_cost<H: Handler>(
 	is_static: bool,
 	config: &Config,
 	handler: &H
-) -> Result<(GasCost, Option<MemoryCost>), ExitError> {
+) -> Result<(GasCost, StorageTarget, Option<MemoryCost>), ExitError> {
+	let target = match opcode {
+		Err(ExternalOpcode::Balance) | Err(ExternalOpcode::ExtCodeSize) |
+		Err(ExternalOpcode::ExtCodeHash) | Err(ExternalOpcode::ExtCodeCopy) |
+		Err(ExternalOpcode::Suicide) => StorageTarget::Address(stack.peek(0)?.into()),
+		Err(ExternalOpcode::Call) | Err(ExternalOpcode::CallCode) |
+		Err(ExternalOpcode::DelegateCall) | Err(ExternalOpcode::StaticCall) |
+		Err(ExternalOpcode::CallToken) => StorageTarget::Address(stack.peek(1)?.into()),
+		Err(ExternalOpcode::SLoad) | Err(ExternalOpcode::SStore) =>
+			StorageTarget::Slot(address, stack.peek(0)?),
+		_ => StorageTarget::None,
+	};
+	// EIP-2929: whether the target has not been accessed in this transaction.
+	let target_is_cold = config.increase_state_access_gas && match target {
+		StorageTarget::Address(address) => handler.is_cold(address, None),
+		StorageTarget::Slot(address, index) => handler.is_cold(address, Some(index)),
+		StorageTarget::None => false,
+	};
+
 	let gas_cost = match opcode {
 		Ok(Opcode::Stop) | Ok(Opcode::Return) => GasCost::Zero,
 
@@ -241,9 +328,15 @@ pub fn opcode_cost<H: Handler>(
 		Err(ExternalOpcode::GasLimit) | Ok(Opcode::Pop) | Ok(Opcode::PC) |
 		Ok(Opcode::MSize) | Err(ExternalOpcode::Gas) => GasCost::Base,
 
+		Ok(Opcode::Push(0)) if config.has_push0 => GasCost::Base,
+		Ok(Opcode::Push(0)) => GasCost::Invalid,
+
 		Err(ExternalOpcode::ChainId) if config.has_chain_id => GasCost::Base,
 		Err(ExternalOpcode::ChainId) => GasCost::Invalid,
 
+		Err(ExternalOpcode::BaseFee) if config.has_base_fee => GasCost::Base,
+		Err(ExternalOpcode::BaseFee) => GasCost::Invalid,
+
 		Ok(Opcode::Add) | Ok(Opcode::Sub) | Ok(Opcode::Not) | Ok(Opcode::Lt) |
 		Ok(Opcode::Gt) | Ok(Opcode::SLt) | Ok(Opcode::SGt) | Ok(Opcode::Eq) |
 		Ok(Opcode::IsZero) | Ok(Opcode::And) | Ok(Opcode::Or) | Ok(Opcode::Xor) |
@@ -267,26 +360,30 @@ pub fn opcode_cost<H: Handler>(
 
 		Ok(Opcode::JumpI) => GasCost::High,
 
-		Err(ExternalOpcode::ExtCodeSize) => GasCost::ExtCodeSize,
-		Err(ExternalOpcode::Balance) => GasCost::Balance,
+		Err(ExternalOpcode::ExtCodeSize) => GasCost::ExtCodeSize { target_is_cold },
+		Err(ExternalOpcode::Balance) => GasCost::Balance { target_is_cold },
 		Err(ExternalOpcode::BlockHash) => GasCost::BlockHash,
 
-		Err(ExternalOpcode::ExtCodeHash) if config.has_ext_code_hash => GasCost::ExtCodeHash,
+		Err(ExternalOpcode::ExtCodeHash) if config.has_ext_code_hash =>
+			GasCost::ExtCodeHash { target_is_cold },
 		Err(ExternalOpcode::ExtCodeHash) => GasCost::Invalid,
 
 		Err(ExternalOpcode::CallCode) => GasCost::CallCode {
 			value: U256::from_big_endian(&stack.peek(2)?[..]),
 			gas: U256::from_big_endian(&stack.peek(0)?[..]),
+			target_is_cold,
 			target_exists: handler.exists(stack.peek(1)?.into()),
 		},
 		Err(ExternalOpcode::StaticCall) => GasCost::StaticCall {
 			gas: U256::from_big_endian(&stack.peek(0)?[..]),
+			target_is_cold,
 			target_exists: handler.exists(stack.peek(1)?.into()),
 		},
 		Err(ExternalOpcode::Sha3) => GasCost::Sha3 {
 			len: U256::from_big_endian(&stack.peek(1)?[..]),
 		},
 		Err(ExternalOpcode::ExtCodeCopy) => GasCost::ExtCodeCopy {
+			target_is_cold,
 			len: U256::from_big_endian(&stack.peek(3)?[..]),
 		},
 		// TRON: tire energy = 0, has copy energy
@@ -297,10 +394,11 @@ pub fn opcode_cost<H: Handler>(
 			power: U256::from_big_endian(&stack.peek(1)?[..]),
 		},
 		Ok(Opcode::JumpDest) => GasCost::JumpDest,
-		Err(ExternalOpcode::SLoad) => GasCost::SLoad,
+		Err(ExternalOpcode::SLoad) => GasCost::SLoad { target_is_cold },
 
 		Err(ExternalOpcode::DelegateCall) if config.has_delegate_call => GasCost::DelegateCall {
 			gas: U256::from_big_endian(&stack.peek(0)?[..]),
+			target_is_cold,
 			target_exists: handler.exists(stack.peek(1)?.into()),
 		},
 		Err(ExternalOpcode::DelegateCall) => GasCost::Invalid,
@@ -319,18 +417,22 @@ pub fn opcode_cost<H: Handler>(
 				original: handler.original_storage(address, index),
 				current: handler.storage(address, index),
 				new: value,
+				target_is_cold,
 			}
 		},
 		Err(ExternalOpcode::Log(n)) if !is_static => GasCost::Log {
 			n,
 			len: U256::from_big_endian(&stack.peek(1)?[..]),
 		},
-		Err(ExternalOpcode::Create) if !is_static => GasCost::Create,
+		Err(ExternalOpcode::Create) if !is_static => GasCost::Create {
+			len: U256::from_big_endian(&stack.peek(2)?[..]),
+		},
 		Err(ExternalOpcode::Create2) if !is_static && config.has_create2 => GasCost::Create2 {
 			len: U256::from_big_endian(&stack.peek(2)?[..]),
 		},
 		Err(ExternalOpcode::Suicide) if !is_static => GasCost::Suicide {
 			value: handler.balance(address),
+			target_is_cold,
 			target_exists: handler.exists(stack.peek(0)?.into()),
 			already_removed: handler.deleted(address),
 		},
@@ -340,6 +442,7 @@ pub fn opcode_cost<H: Handler>(
 			GasCost::Call {
 				value: U256::from_big_endian(&stack.peek(2)?[..]),
 				gas: U256::from_big_endian(&stack.peek(0)?[..]),
+				target_is_cold,
 				target_exists: handler.exists(stack.peek(1)?.into()),
 			},
 
@@ -352,34 +455,70 @@ pub fn opcode_cost<H: Handler>(
 			GasCost::Call {
 				value: U256::from_big_endian(&stack.peek(2)?[..]),
 				gas: U256::from_big_endian(&stack.peek(0)?[..]),
+				target_is_cold,
 				target_exists: handler.exists(stack.peek(1)?.into()),
 			},
 		Err(ExternalOpcode::CallTokenValue) | Err(ExternalOpcode::CallTokenId)
 			if config.has_token_transfer => GasCost::Base,
-		Err(ExternalOpcode::TokenBalance) if config.has_token_transfer => GasCost::Balance,
-		Err(ExternalOpcode::IsContract) if config.has_iscontract => GasCost::Balance,
+		Err(ExternalOpcode::TokenBalance) if config.has_token_transfer =>
+			GasCost::Balance { target_is_cold: false },
+		Err(ExternalOpcode::IsContract) if config.has_iscontract =>
+			GasCost::Balance { target_is_cold: false },
 
 		Err(ExternalOpcode::Stake) | Err(ExternalOpcode::Unstake) if config.has_stake => GasCost::Stake,
-		Err(ExternalOpcode::WithdrawReward) if config.has_stake => GasCost::WithdrawReward,
+		Err(ExternalOpcode::WithdrawReward) if (config.has_stake || config.has_vote) && !is_static =>
+			GasCost::WithdrawReward,
+		Err(ExternalOpcode::VoteWitness) if config.has_vote && !is_static => GasCost::VoteWitness,
+		Err(ExternalOpcode::Freeze) if config.has_freeze && !is_static => GasCost::Freeze,
+		Err(ExternalOpcode::Unfreeze) if config.has_freeze && !is_static => GasCost::Unfreeze,
+		Err(ExternalOpcode::FreezeExpireTime) if config.has_freeze => GasCost::FreezeExpireTime,
 		Err(ExternalOpcode::AssetIssue) if config.has_token_issue => GasCost::AssetIssue,
 		Err(ExternalOpcode::UpdateAsset) if config.has_token_issue => GasCost::UpdateAsset,
-		Err(ExternalOpcode::RewardBalance) if config.has_stake => GasCost::Balance,
-		Err(ExternalOpcode::IsWitness) if config.has_iswitness => GasCost::Balance,
-
+		Err(ExternalOpcode::RewardBalance) if config.has_stake =>
+			GasCost::Balance { target_is_cold: false },
+		Err(ExternalOpcode::IsWitness) if config.has_iswitness =>
+			GasCost::Balance { target_is_cold: false },
+		Err(ExternalOpcode::FreezeBalanceV2) | Err(ExternalOpcode::UnfreezeBalanceV2) |
+		Err(ExternalOpcode::CancelAllUnfreezeV2) | Err(ExternalOpcode::WithdrawExpireUnfreeze) |
+		Err(ExternalOpcode::DelegateResource) | Err(ExternalOpcode::UnDelegateResource)
+			if config.has_stake_v2 && !is_static => GasCost::StakeV2,
+
+		// Enabled state modifications not matched above are in a static call.
+		Err(ExternalOpcode::Create) | Err(ExternalOpcode::SStore) | Err(ExternalOpcode::Log(_)) |
+		Err(ExternalOpcode::Suicide) | Err(ExternalOpcode::Call) =>
+			return Err(ExitError::StaticCallModification),
+		Err(ExternalOpcode::Create2) if config.has_create2 => return Err(ExitError::StaticCallModification),
+		Err(ExternalOpcode::CallToken) if config.has_token_transfer =>
+			return Err(ExitError::StaticCallModification),
+		Err(ExternalOpcode::WithdrawReward) if config.has_stake || config.has_vote =>
+			return Err(ExitError::StaticCallModification),
+		Err(ExternalOpcode::VoteWitness) if config.has_vote => return Err(ExitError::StaticCallModification),
+		Err(ExternalOpcode::Freeze) | Err(ExternalOpcode::Unfreeze) if config.has_freeze =>
+			return Err(ExitError::StaticCallModification),
+		Err(ExternalOpcode::FreezeBalanceV2) | Err(ExternalOpcode::UnfreezeBalanceV2) |
+		Err(ExternalOpcode::CancelAllUnfreezeV2) | Err(ExternalOpcode::WithdrawExpireUnfreeze) |
+		Err(ExternalOpcode::DelegateResource) | Err(ExternalOpcode::UnDelegateResource)
+			if config.has_stake_v2 => return Err(ExitError::StaticCallModification),
 
 		Err(ExternalOpcode::CallTokenValue) | Err(ExternalOpcode::CallTokenId) |
 		Err(ExternalOpcode::TokenBalance) | Err(ExternalOpcode::CallToken) |
 		Err(ExternalOpcode::IsContract) | Err(ExternalOpcode::IsWitness) |
 		Err(ExternalOpcode::Stake) | Err(ExternalOpcode::Unstake) |
 		Err(ExternalOpcode::WithdrawReward) | Err(ExternalOpcode::RewardBalance) |
-		Err(ExternalOpcode::AssetIssue) | Err(ExternalOpcode::UpdateAsset)
+		Err(ExternalOpcode::AssetIssue) | Err(ExternalOpcode::UpdateAsset) |
+		Err(ExternalOpcode::VoteWitness) | Err(ExternalOpcode::Freeze) |
+		Err(ExternalOpcode::Unfreeze) | Err(ExternalOpcode::FreezeExpireTime) |
+		Err(ExternalOpcode::FreezeBalanceV2) | Err(ExternalOpcode::UnfreezeBalanceV2) |
+		Err(ExternalOpcode::CancelAllUnfreezeV2) | Err(ExternalOpcode::WithdrawExpireUnfreeze) |
+		Err(ExternalOpcode::DelegateResource) | Err(ExternalOpcode::UnDelegateResource)
 		=> GasCost::Invalid,
 
-		Err(ExternalOpcode::Create) | Err(ExternalOpcode::Create2) |
-		Err(ExternalOpcode::SStore) | Err(ExternalOpcode::Log(_)) |
-		Err(ExternalOpcode::Suicide) | Err(ExternalOpcode::Call) |
+		Err(ExternalOpcode::Create2) | Err(ExternalOpcode::Other(_)) => GasCost::Invalid,
+	};
 
-		Err(ExternalOpcode::Other(_)) => GasCost::Invalid,
+	let gas_cost = match config.gas_overrides.get(&config.opcode_byte(opcode)) {
+		Some(&cost) if gas_cost.is_overridable(config) => GasCost::Overridden(cost),
+		_ => gas_cost,
 	};
 
 	let memory_cost = match opcode {
@@ -423,6 +562,25 @@ pub fn opcode_cost<H: Handler>(
 			len: U256::from_big_endian(&stack.peek(6)?[..]),
 		})),
 
+		// Dynamic arrays of witnesses and amounts, with length words.
+		Err(ExternalOpcode::VoteWitness) => Some(MemoryCost {
+			offset: U256::from_big_endian(&stack.peek(1)?[..]),
+			len: U256::from_big_endian(&stack.peek(0)?[..]).saturating_mul(U256::from(32))
+				.saturating_add(U256::from(32)),
+		}.join(MemoryCost {
+			offset: U256::from_big_endian(&stack.peek(3)?[..]),
+			len: U256::from_big_endian(&stack.peek(2)?[..]).saturating_mul(U256::from(32))
+				.saturating_add(U256::from(32)),
+		})),
+
+		Err(ExternalOpcode::CallToken) => Some(MemoryCost {
+			offset: U256::from_big_endian(&stack.peek(4)?[..]),
+			len: U256::from_big_endian(&stack.peek(5)?[..]),
+		}.join(MemoryCost {
+			offset: U256::from_big_endian(&stack.peek(6)?[..]),
+			len: U256::from_big_endian(&stack.peek(7)?[..]),
+		})),
+
 		Err(ExternalOpcode::DelegateCall) |
 		Err(ExternalOpcode::StaticCall) => Some(MemoryCost {
 			offset: U256::from_big_endian(&stack.peek(2)?[..]),
@@ -435,7 +593,7 @@ pub fn opcode_cost<H: Handler>(
 		_ => None,
 	};
 
-	Ok((gas_cost, memory_cost))
+	Ok((gas_cost, target, memory_cost))
 }
 
 #[derive(Clone)]
@@ -464,6 +622,9 @@ impl<'config> Inner<'config> {
 			return Err(ExitError::OutOfGas)
 		}
 		let end = end.as_usize();
+		if end > self.config.memory_limit {
+			return Err(ExitError::InvalidRange)
+		}
 
 		let rem = end % 32;
 		let new = if rem == 0 {
@@ -495,28 +656,29 @@ impl<'config> Inner<'config> {
 		gas: usize,
 	) -> Result<usize, ExitError> {
 		Ok(match cost {
-			GasCost::Call { value, target_exists, .. } =>
-				costs::call_cost(value, true, true, !target_exists, self.config),
-			GasCost::CallCode { value, target_exists, .. } =>
-				costs::call_cost(value, true, false, !target_exists, self.config),
-			GasCost::DelegateCall { target_exists, .. } =>
-				costs::call_cost(U256::zero(), false, false, !target_exists, self.config),
-			GasCost::StaticCall { target_exists, .. } =>
-				costs::call_cost(U256::zero(), false, true, !target_exists, self.config),
-			GasCost::Suicide { value, target_exists, .. } =>
-				costs::suicide_cost(value, target_exists, self.config),
-			GasCost::SStore { original, current, new } =>
-				costs::sstore_cost(original, current, new, gas, self.config)?,
+			GasCost::Call { value, target_is_cold, target_exists, .. } =>
+				costs::call_cost(value, target_is_cold, true, true, !target_exists, self.config),
+			GasCost::CallCode { value, target_is_cold, target_exists, .. } =>
+				costs::call_cost(value, target_is_cold, true, false, !target_exists, self.config),
+			GasCost::DelegateCall { target_is_cold, target_exists, .. } =>
+				costs::call_cost(U256::zero(), target_is_cold, false, false, !target_exists, self.config),
+			GasCost::StaticCall { target_is_cold, target_exists, .. } =>
+				costs::call_cost(U256::zero(), target_is_cold, false, true, !target_exists, self.config),
+			GasCost::Suicide { value, target_is_cold, target_exists, .. } =>
+				costs::suicide_cost(value, target_is_cold, target_exists, self.config),
+			GasCost::SStore { original, current, new, target_is_cold } =>
+				costs::sstore_cost(original, current, new, gas, target_is_cold, self.config)?,
 
 			GasCost::Sha3 { len } => costs::sha3_cost(len)?,
 			GasCost::Log { n, len } => costs::log_cost(n, len)?,
-			GasCost::ExtCodeCopy { len } => costs::extcodecopy_cost(len, self.config)?,
+			GasCost::ExtCodeCopy { target_is_cold, len } =>
+				costs::extcodecopy_cost(len, target_is_cold, self.config)?,
 			GasCost::VeryLowCopy { len } => costs::verylowcopy_cost(len)?,
 			GasCost::Exp { power } => costs::exp_cost(power, self.config)?,
-			GasCost::Create => consts::G_CREATE,
-			GasCost::Create2 { len } => costs::create2_cost(len)?,
+			GasCost::Create { len } => costs::create_cost(len, self.config)?,
+			GasCost::Create2 { len } => costs::create2_cost(len, self.config)?,
 			GasCost::JumpDest => consts::G_JUMPDEST,
-			GasCost::SLoad => self.config.gas_sload,
+			GasCost::SLoad { target_is_cold } => costs::sload_cost(target_is_cold, self.config),
 
 			GasCost::Zero => consts::G_ZERO,
 			GasCost::Base => consts::G_BASE,
@@ -527,14 +689,27 @@ impl<'config> Inner<'config> {
 			// TRON: Not an OutOfGas, but an IllegalOperation.
 			GasCost::Invalid => return Err(ExitError::IllegalOperation),
 
-			GasCost::ExtCodeSize => self.config.gas_ext_code,
-			GasCost::Balance => self.config.gas_balance,
+			GasCost::ExtCodeSize { target_is_cold } =>
+				costs::address_access_cost(target_is_cold, self.config.gas_ext_code, self.config),
+			GasCost::Balance { target_is_cold } =>
+				costs::address_access_cost(target_is_cold, self.config.gas_balance, self.config),
 			GasCost::BlockHash => consts::G_BLOCKHASH,
-			GasCost::ExtCodeHash => self.config.gas_ext_code_hash,
+			GasCost::ExtCodeHash { target_is_cold } =>
+				costs::address_access_cost(target_is_cold, self.config.gas_ext_code_hash, self.config),
 			GasCost::Stake => consts::G_STAKE,
-			GasCost::WithdrawReward => consts::G_WITHDRAWREWARD,
+			GasCost::WithdrawReward => if self.config.has_vote {
+				consts::G_WITHDRAWREWARD_VOTE
+			} else {
+				consts::G_WITHDRAWREWARD
+			},
+			GasCost::VoteWitness => consts::G_VOTEWITNESS,
+			GasCost::Freeze => consts::G_FREEZE,
+			GasCost::Unfreeze => consts::G_UNFREEZE,
+			GasCost::FreezeExpireTime => consts::G_FREEZEEXPIRETIME,
 			GasCost::AssetIssue => consts::G_ASSETISSUE,
 			GasCost::UpdateAsset => consts::G_UPDATEASSET,
+			GasCost::StakeV2 => consts::G_STAKE_V2,
+			GasCost::Overridden(cost) => cost,
 		})
 	}
 
@@ -543,7 +718,7 @@ impl<'config> Inner<'config> {
 		cost: GasCost
 	) -> isize {
 		match cost {
-			GasCost::SStore { original, current, new } =>
+			GasCost::SStore { original, current, new, .. } =>
 				costs::sstore_refund(original, current, new, self.config),
 			GasCost::Suicide { already_removed, .. } =>
 				costs::suicide_refund(already_removed),
@@ -571,13 +746,22 @@ pub enum GasCost {
 	Invalid,
 
 	/// Gas cost for `EXTCODESIZE`.
-	ExtCodeSize,
+	ExtCodeSize {
+		/// Whether the target is cold.
+		target_is_cold: bool
+	},
 	/// Gas cost for `BALANCE`.
-	Balance,
+	Balance {
+		/// Whether the target is cold.
+		target_is_cold: bool
+	},
 	/// Gas cost for `BLOCKHASH`.
 	BlockHash,
 	/// Gas cost for `EXTBLOCKHASH`.
-	ExtCodeHash,
+	ExtCodeHash {
+		/// Whether the target is cold.
+		target_is_cold: bool
+	},
 
 	/// Gas cost for `CALL`.
 	Call {
@@ -585,6 +769,8 @@ pub enum GasCost {
 		value: U256,
 		/// Call gas.
 		gas: U256,
+		/// Whether the target is cold.
+		target_is_cold: bool,
 		/// Whether the target exists.
 		target_exists: bool
 	},
@@ -594,6 +780,8 @@ pub enum GasCost {
 		value: U256,
 		/// Call gas.
 		gas: U256,
+		/// Whether the target is cold.
+		target_is_cold: bool,
 		/// Whether the target exists.
 		target_exists: bool
 	},
@@ -601,6 +789,8 @@ pub enum GasCost {
 	DelegateCall {
 		/// Call gas.
 		gas: U256,
+		/// Whether the target is cold.
+		target_is_cold: bool,
 		/// Whether the target exists.
 		target_exists: bool
 	},
@@ -608,6 +798,8 @@ pub enum GasCost {
 	StaticCall {
 		/// Call gas.
 		gas: U256,
+		/// Whether the target is cold.
+		target_is_cold: bool,
 		/// Whether the target exists.
 		target_exists: bool
 	},
@@ -615,6 +807,8 @@ pub enum GasCost {
 	Suicide {
 		/// Value.
 		value: U256,
+		/// Whether the target is cold.
+		target_is_cold: bool,
 		/// Whether the target exists.
 		target_exists: bool,
 		/// Whether the target has already been removed.
@@ -627,7 +821,9 @@ pub enum GasCost {
 		/// Current value.
 		current: Option<H256>,
 		/// New value.
-		new: H256
+		new: H256,
+		/// Whether the storage slot is cold.
+		target_is_cold: bool
 	},
 	/// Gas cost for `SHA3`.
 	Sha3 {
@@ -643,6 +839,8 @@ pub enum GasCost {
 	},
 	/// Gas cost for `EXTCODECOPY`.
 	ExtCodeCopy {
+		/// Whether the target is cold.
+		target_is_cold: bool,
 		/// Length.
 		len: U256
 	},
@@ -657,7 +855,10 @@ pub enum GasCost {
 		power: U256
 	},
 	/// Gas cost for `CREATE`.
-	Create,
+	Create {
+		/// Length.
+		len: U256
+	},
 	/// Gas cost for `CREATE2`.
 	Create2 {
 		/// Length.
@@ -666,7 +867,10 @@ pub enum GasCost {
 	/// Gas cost for `JUMPDEST`.
 	JumpDest,
 	/// Gas cost for `SLOAD`.
-	SLoad,
+	SLoad {
+		/// Whether the storage slot is cold.
+		target_is_cold: bool
+	},
 	// TVM ext.
 	/// Gas cost for `STAKE`/`UNSTAKE`.
 	Stake,
@@ -676,6 +880,66 @@ pub enum GasCost {
 	AssetIssue,
 	/// Gas cost for `UPDATEASSET`.
 	UpdateAsset,
+	/// Gas cost for Stake 2.0 opcodes.
+	StakeV2,
+	/// Gas cost for `VOTEWITNESS`.
+	VoteWitness,
+	/// Gas cost for `FREEZE`.
+	Freeze,
+	/// Gas cost for `UNFREEZE`.
+	Unfreeze,
+	/// Gas cost for `FREEZEEXPIRETIME`.
+	FreezeExpireTime,
+	/// Gas cost overridden by config.
+	Overridden(usize),
+}
+
+impl GasCost {
+	/// Whether the cost does not depend on operands, and can be overridden by config.
+	fn is_overridable(&self, config: &Config) -> bool {
+		match self {
+			GasCost::ExtCodeSize { .. } | GasCost::Balance { .. } |
+			GasCost::ExtCodeHash { .. } | GasCost::SLoad { .. } => !config.increase_state_access_gas,
+			GasCost::Create { .. } => config.max_initcode_size.is_none(),
+			GasCost::Invalid | GasCost::Call { .. } | GasCost::CallCode { .. } |
+			GasCost::DelegateCall { .. } | GasCost::StaticCall { .. } | GasCost::Suicide { .. } |
+			GasCost::SStore { .. } | GasCost::Sha3 { .. } | GasCost::Log { .. } |
+			GasCost::ExtCodeCopy { .. } | GasCost::VeryLowCopy { .. } | GasCost::Exp { .. } |
+			GasCost::Create2 { .. } | GasCost::Overridden(_) => false,
+			_ => true,
+		}
+	}
+}
+
+/// Snapshot of a gasometer.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Snapshot {
+	/// Gas limit.
+	pub gas_limit: usize,
+	/// Memory gas.
+	pub memory_gas: usize,
+	/// Used gas, excluding memory gas.
+	pub used_gas: usize,
+	/// Refunded gas.
+	pub refunded_gas: isize,
+}
+
+impl Snapshot {
+	/// Remaining gas.
+	pub fn gas(&self) -> usize {
+		self.gas_limit.saturating_sub(self.used_gas).saturating_sub(self.memory_gas)
+	}
+}
+
+/// Account or storage slot accessed by an opcode, warmed after execution for EIP-2929.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum StorageTarget {
+	/// No account or storage is accessed.
+	None,
+	/// An account is accessed.
+	Address(H160),
+	/// A storage slot of an account is accessed.
+	Slot(H160, H256),
 }
 
 /// Memory cost.
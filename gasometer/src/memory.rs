@@ -1,10 +1,11 @@
 use evm_core::ExitError;
-use crate::consts::*;
+use crate::Config;
 
-pub fn memory_gas(a: usize) -> Result<usize, ExitError> {
-	G_MEMORY
-		.checked_mul(a).ok_or(ExitError::OutOfGas)?
+/// Gas of memory of the given number of words, linear plus quadratic.
+pub fn memory_gas(words: usize, config: &Config) -> Result<usize, ExitError> {
+	config.gas_memory_word
+		.checked_mul(words).ok_or(ExitError::OutOfGas)?
 		.checked_add(
-			a.checked_mul(a).ok_or(ExitError::OutOfGas)? / 512
+			words.checked_mul(words).ok_or(ExitError::OutOfGas)? / config.memory_quad_divisor
 		).ok_or(ExitError::OutOfGas)
 }
@@ -0,0 +1,66 @@
+//! Allows to listen to gasometer events.
+
+use super::Snapshot;
+
+environmental::environmental!(listener: dyn EventListener + 'static);
+
+/// Listener of gasometer events.
+pub trait EventListener {
+	/// Handle an event.
+	fn event(&mut self, event: Event);
+}
+
+/// Gasometer event. Snapshots are taken before the gas is recorded.
+#[derive(Debug, Copy, Clone)]
+pub enum Event {
+	/// An explicit cost is recorded.
+	RecordCost {
+		/// Recorded cost.
+		cost: usize,
+		/// Gasometer snapshot.
+		snapshot: Option<Snapshot>,
+	},
+	/// An explicit refund is recorded.
+	RecordRefund {
+		/// Recorded refund.
+		refund: isize,
+		/// Gasometer snapshot.
+		snapshot: Option<Snapshot>,
+	},
+	/// Gas left by a substate is given back.
+	RecordStipend {
+		/// Recorded stipend.
+		stipend: usize,
+		/// Gasometer snapshot.
+		snapshot: Option<Snapshot>,
+	},
+	/// Opcode cost is recorded.
+	RecordDynamicCost {
+		/// Opcode cost, excluding memory expansion.
+		gas_cost: usize,
+		/// Memory gas after the opcode.
+		memory_gas: usize,
+		/// Refund of the opcode.
+		gas_refund: isize,
+		/// Gasometer snapshot.
+		snapshot: Option<Snapshot>,
+	},
+	/// Transaction cost is recorded.
+	RecordTransaction {
+		/// Recorded cost.
+		cost: usize,
+		/// Gasometer snapshot.
+		snapshot: Option<Snapshot>,
+	},
+}
+
+impl Event {
+	pub(crate) fn emit(self) {
+		listener::with(|listener| listener.event(self));
+	}
+}
+
+/// Run closure with provided listener.
+pub fn using<R, F: FnOnce() -> R>(new: &mut (dyn EventListener + 'static), f: F) -> R {
+	listener::using(new, f)
+}
@@ -0,0 +1,34 @@
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use primitive_types::U256;
+
+/// Word at `offset`, right-padded with zeros past the end of the input.
+pub fn word(input: &[u8], offset: usize) -> [u8; 32] {
+	let mut word = [0u8; 32];
+	if offset < input.len() {
+		let len = (input.len() - offset).min(32);
+		word[..len].copy_from_slice(&input[offset..offset + len]);
+	}
+	word
+}
+
+/// Word at `offset` as an offset or length, `None` if it does not fit `usize`.
+pub fn usize_at(input: &[u8], offset: usize) -> Option<usize> {
+	usize::try_from(U256::from_big_endian(&word(input, offset))).ok()
+}
+
+/// Head of a dynamic array whose offset is at `offset`: its length and the
+/// position of its first element.
+pub fn array_at(input: &[u8], offset: usize) -> Option<(usize, usize)> {
+	let start = usize_at(input, offset)?;
+	let len = usize_at(input, start)?;
+	Some((len, start.checked_add(32)?))
+}
+
+/// `bytes` whose offset is at `offset`, relative to `base`.
+pub fn bytes_at(input: &[u8], base: usize, offset: usize) -> Option<Vec<u8>> {
+	let start = base.checked_add(usize_at(input, offset)?)?;
+	let len = usize_at(input, start)?;
+	let data = start.checked_add(32)?;
+	input.get(data..data.checked_add(len)?).map(|bytes| bytes.to_vec())
+}
@@ -0,0 +1,68 @@
+use evm::ExitError;
+use substrate_bn::{pairing_batch, AffineG1, AffineG2, Fq, Fq2, Fr, G1, G2, Group, Gt};
+use alloc::vec::Vec;
+use crate::abi;
+
+/// Field element of the word at `offset`, failing if it is not below the modulus.
+fn fq(input: &[u8], offset: usize) -> Result<Fq, ExitError> {
+	Fq::from_slice(&abi::word(input, offset)).map_err(|_| ExitError::PrecompileFailure)
+}
+
+/// G1 point of the words `x ++ y` at `offset`, with `(0, 0)` as infinity.
+fn g1(input: &[u8], offset: usize) -> Result<G1, ExitError> {
+	let (x, y) = (fq(input, offset)?, fq(input, offset + 32)?);
+	if x.is_zero() && y.is_zero() {
+		Ok(G1::zero())
+	} else {
+		AffineG1::new(x, y).map(Into::into).map_err(|_| ExitError::PrecompileFailure)
+	}
+}
+
+/// G2 point of the words `x_imaginary ++ x_real ++ y_imaginary ++ y_real` at
+/// `offset`, with all zeros as infinity.
+fn g2(input: &[u8], offset: usize) -> Result<G2, ExitError> {
+	let x = Fq2::new(fq(input, offset + 32)?, fq(input, offset)?);
+	let y = Fq2::new(fq(input, offset + 96)?, fq(input, offset + 64)?);
+	if x.is_zero() && y.is_zero() {
+		Ok(G2::zero())
+	} else {
+		AffineG2::new(x, y).map(Into::into).map_err(|_| ExitError::PrecompileFailure)
+	}
+}
+
+/// Encode a G1 point as `x ++ y`, with infinity as zeros.
+fn encode(point: G1) -> [u8; 64] {
+	let mut output = [0u8; 64];
+	if let Some(point) = AffineG1::from_jacobian(point) {
+		point.x().to_big_endian(&mut output[..32]).expect("slice is 32 bytes");
+		point.y().to_big_endian(&mut output[32..]).expect("slice is 32 bytes");
+	}
+	output
+}
+
+/// EIP-196 addition of the two G1 points `x1 ++ y1 ++ x2 ++ y2`, right-padded
+/// with zeros. Fails if a point is not on the curve.
+pub fn bn128_add(input: &[u8]) -> Result<[u8; 64], ExitError> {
+	Ok(encode(g1(input, 0)? + g1(input, 64)?))
+}
+
+/// EIP-196 scalar multiplication of the G1 point `x ++ y ++ scalar`, right-padded
+/// with zeros. Fails if the point is not on the curve.
+pub fn bn128_mul(input: &[u8]) -> Result<[u8; 64], ExitError> {
+	let scalar = Fr::from_slice(&abi::word(input, 64)).map_err(|_| ExitError::PrecompileFailure)?;
+	Ok(encode(g1(input, 0)? * scalar))
+}
+
+/// EIP-197 pairing check of consecutive 192-byte G1 and G2 pairs, true if the
+/// product of the pairings is one. Fails if the input is not a multiple of 192
+/// bytes or a point is not on its curve.
+pub fn bn128_pairing(input: &[u8]) -> Result<bool, ExitError> {
+	if input.len() % 192 != 0 {
+		return Err(ExitError::PrecompileFailure)
+	}
+
+	let pairs = input.chunks(192)
+		.map(|pair| Ok((g1(pair, 0)?, g2(pair, 64)?)))
+		.collect::<Result<Vec<_>, ExitError>>()?;
+	Ok(pairing_batch(&pairs) == Gt::one())
+}
@@ -0,0 +1,147 @@
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use primitive_types::{H160, U256};
+use evm::{Config, ExitError, ExitSucceed};
+use evm::executor::{EcrecoverInput, PrecompileContext, PrecompileResult};
+use crate::{abi, bn128, ecrecover, hash, modexp, multisign};
+
+/// Divisor of the EIP-198 `modexp` energy.
+const MODEXP_QUAD_DIVISOR: usize = 20;
+
+/// Number of 32-byte words covering `len` bytes.
+fn words(len: usize) -> usize {
+	len.saturating_add(31) / 32
+}
+
+/// Base energy plus energy for every word of the input.
+fn linear_cost(input: &[u8], base: usize, word: usize) -> usize {
+	base.saturating_add(words(input.len()).saturating_mul(word))
+}
+
+/// EIP-198 energy of `modexp`, saturating at `usize::MAX`.
+fn modexp_cost(input: &[u8]) -> usize {
+	let length = |offset| U256::from_big_endian(&abi::word(input, offset));
+	let (base_len, exp_len, mod_len) = (length(0), length(32), length(64));
+
+	let len = base_len.max(mod_len);
+	let complexity = if len <= U256::from(64) {
+		len * len
+	} else if len <= U256::from(1024) {
+		len * len / 4 + len * 96 - 3072
+	} else if len < U256::from(u64::MAX) {
+		len * len / 16 + len * 480 - 199_680
+	} else {
+		return usize::MAX
+	};
+
+	// Bit length of the first word of the exponent, adjusted for the rest. A
+	// base too long for `usize` already costs more than any energy.
+	let offset = match usize::try_from(base_len) {
+		Ok(base_len) => base_len.saturating_add(96),
+		Err(_) => return usize::MAX,
+	};
+	let head_len = usize::try_from(exp_len.min(U256::from(32))).unwrap_or(32);
+	let mut head = [0u8; 32];
+	head[32 - head_len..].copy_from_slice(&modexp::padded(input, offset, head_len));
+	let head = U256::from_big_endian(&head);
+	let head_bits = U256::from(head.bits().saturating_sub(1));
+	let adjusted_exp_len = if exp_len <= U256::from(32) {
+		head_bits
+	} else {
+		(exp_len - 32).saturating_mul(U256::from(8)).saturating_add(head_bits)
+	};
+
+	let cost = complexity.saturating_mul(adjusted_exp_len.max(U256::one())) / MODEXP_QUAD_DIVISOR;
+	if cost > U256::from(usize::MAX) {
+		usize::MAX
+	} else {
+		cost.as_usize()
+	}
+}
+
+/// Dispatcher of the TRON precompiles `0x01` to `0x0a`, charging the precompile
+/// energy of the config. Addresses not enabled by the config, as checked by
+/// `Config::has_tron_precompile`, are not precompiles.
+///
+/// Every precompile fails with `OutOfGas` if its energy is above the target
+/// energy, and invalid input of `bn128` or `modexp` fails with
+/// `PrecompileFailure`. Invalid signatures are not failures: `ecrecover` returns
+/// empty output and the signature validators return a zero word.
+pub fn tron_precompile(
+	address: H160,
+	input: &[u8],
+	target_gas: Option<usize>,
+	config: &Config,
+	context: &PrecompileContext,
+) -> PrecompileResult {
+	if address[..19].iter().any(|b| *b != 0) || !config.has_tron_precompile(address) {
+		return None
+	}
+
+	let cost = match address[19] {
+		0x01 => config.gas_ecrecover,
+		0x02 => linear_cost(input, config.gas_sha256, config.gas_sha256_word),
+		0x03 => linear_cost(input, config.gas_ripemd160, config.gas_ripemd160_word),
+		0x04 => linear_cost(input, config.gas_identity, config.gas_identity_word),
+		0x05 => modexp_cost(input),
+		0x06 => config.gas_bn128_add,
+		0x07 => config.gas_bn128_mul,
+		0x08 => (input.len() / 192).saturating_mul(config.gas_bn128_pairing_point)
+			.saturating_add(config.gas_bn128_pairing),
+		0x09 => ((input.len() / 32).saturating_sub(5) / 6).saturating_mul(config.gas_validate_sign),
+		0x0a => ((input.len() / 32).saturating_sub(5) / 5).saturating_mul(config.gas_validate_sign),
+		_ => return None,
+	};
+	if target_gas.map(|target| cost > target).unwrap_or(false) {
+		return Some(Err(ExitError::OutOfGas))
+	}
+
+	let output = match address[19] {
+		0x01 => EcrecoverInput::parse(input, config)
+			.and_then(|input| ecrecover::ecrecover(&input))
+			.map(|signer| {
+				let mut output = alloc::vec![0u8; 32];
+				output[12..].copy_from_slice(signer.as_bytes());
+				output
+			})
+			.unwrap_or_default(),
+		0x02 => hash::sha256(input).as_bytes().to_vec(),
+		0x03 => hash::ripemd160(input).as_bytes().to_vec(),
+		0x04 => input.to_vec(),
+		0x05 => match modexp::modexp(input) {
+			Ok(output) => output,
+			Err(e) => return Some(Err(e)),
+		},
+		0x06 => match bn128::bn128_add(input) {
+			Ok(output) => output.to_vec(),
+			Err(e) => return Some(Err(e)),
+		},
+		0x07 => match bn128::bn128_mul(input) {
+			Ok(output) => output.to_vec(),
+			Err(e) => return Some(Err(e)),
+		},
+		0x08 => match bn128::bn128_pairing(input) {
+			Ok(paired) => bool_word(paired),
+			Err(e) => return Some(Err(e)),
+		},
+		0x09 => ecrecover::BatchValidateSignInput::decode(input)
+			.map(|input| ecrecover::batch_validate_sign(&input))
+			.unwrap_or_default()
+			.to_vec(),
+		_ => {
+			let valid = multisign::ValidateMultiSignInput::decode(input)
+				.map(|input| multisign::validate_multi_sign(&input, context.backend()))
+				.unwrap_or(false);
+			bool_word(valid)
+		},
+	};
+
+	Some(Ok((ExitSucceed::Returned, output, cost)))
+}
+
+/// Word of 1 if `value` is true, else zero.
+fn bool_word(value: bool) -> Vec<u8> {
+	let mut output = alloc::vec![0u8; 32];
+	output[31] = value as u8;
+	output
+}
@@ -0,0 +1,100 @@
+use alloc::vec::Vec;
+use primitive_types::{H160, H256};
+use sha3::{Digest, Keccak256};
+use libsecp256k1::{recover, Message, RecoveryId, Signature};
+use evm::executor::EcrecoverInput;
+use crate::abi;
+
+/// Maximum number of signatures of `batchvalidatesign`.
+pub const BATCH_VALIDATE_SIGN_LIMIT: usize = 16;
+
+/// Recover the address that signed the input, as returned by `ecrecover`
+/// left-padded to 32 bytes. `None` if no key can be recovered.
+pub fn ecrecover(input: &EcrecoverInput) -> Option<H160> {
+	let mut signature = [0u8; 64];
+	signature[..32].copy_from_slice(input.r.as_bytes());
+	signature[32..].copy_from_slice(input.s.as_bytes());
+
+	let message = Message::parse(&input.hash.0);
+	let signature = Signature::parse_overflowing(&signature);
+	let recovery_id = RecoveryId::parse(input.recovery_id).ok()?;
+	let public = recover(&message, &signature, &recovery_id).ok()?;
+	Some(H160::from_slice(&Keccak256::digest(&public.serialize()[1..])[12..]))
+}
+
+/// Recover the signer of a 65-byte `r ++ s ++ v` signature over a hash, with `v`
+/// either 0 or 1, or 27 or 28.
+pub fn recover_signature(hash: H256, signature: &[u8]) -> Option<H160> {
+	if signature.len() != 65 {
+		return None
+	}
+	let v = signature[64];
+	let recovery_id = if v >= 27 { v - 27 } else { v };
+	if recovery_id > 1 {
+		return None
+	}
+
+	let r = H256::from_slice(&signature[..32]);
+	let s = H256::from_slice(&signature[32..64]);
+	if r.is_zero() || s.is_zero() {
+		return None
+	}
+	ecrecover(&EcrecoverInput { hash, recovery_id, r, s })
+}
+
+/// Input of the `batchvalidatesign` precompile, ABI-encoded as
+/// `(bytes32 hash, bytes[] signatures, address[] addresses)`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BatchValidateSignInput {
+	/// Signed hash.
+	pub hash: H256,
+	/// Signatures, `r ++ s ++ v`.
+	pub signatures: Vec<Vec<u8>>,
+	/// Expected signers, by position of the signature.
+	pub addresses: Vec<H160>,
+}
+
+impl BatchValidateSignInput {
+	/// Decode the ABI-encoded input. `None` if offsets or lengths point past the
+	/// input.
+	pub fn decode(input: &[u8]) -> Option<Self> {
+		let hash = H256::from(abi::word(input, 0));
+
+		let (count, base) = abi::array_at(input, 32)?;
+		if count > input.len() / 32 {
+			return None
+		}
+		let signatures = (0..count)
+			.map(|i| abi::bytes_at(input, base, base + i * 32))
+			.collect::<Option<Vec<_>>>()?;
+
+		let (count, base) = abi::array_at(input, 64)?;
+		if count > input.len() / 32 {
+			return None
+		}
+		let addresses = (0..count)
+			.map(|i| H160::from_slice(&abi::word(input, base + i * 32)[12..]))
+			.collect();
+
+		Some(Self { hash, signatures, addresses })
+	}
+}
+
+/// Validate each signature against the address at the same position. Byte `i` of
+/// the result is 1 if signature `i` is valid. All bytes are zero if there are no
+/// signatures, more than `BATCH_VALIDATE_SIGN_LIMIT`, or not as many signatures as
+/// addresses.
+pub fn batch_validate_sign(input: &BatchValidateSignInput) -> [u8; 32] {
+	let mut result = [0u8; 32];
+	let count = input.signatures.len();
+	if count == 0 || count > BATCH_VALIDATE_SIGN_LIMIT || count != input.addresses.len() {
+		return result
+	}
+
+	for (i, (signature, address)) in input.signatures.iter().zip(&input.addresses).enumerate() {
+		if recover_signature(input.hash, signature) == Some(*address) {
+			result[i] = 1;
+		}
+	}
+	result
+}
@@ -0,0 +1,16 @@
+use primitive_types::H256;
+use ripemd160::Ripemd160;
+use sha2::{Digest, Sha256};
+
+/// SHA-256 digest of the input, as returned by the `sha256` precompile.
+pub fn sha256(input: &[u8]) -> H256 {
+	H256::from_slice(&Sha256::digest(input))
+}
+
+/// RIPEMD-160 digest of the input, left-padded to 32 bytes as returned by the
+/// `ripemd160` precompile.
+pub fn ripemd160(input: &[u8]) -> H256 {
+	let mut output = H256::default();
+	output[12..].copy_from_slice(&Ripemd160::digest(input));
+	output
+}
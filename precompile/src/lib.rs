@@ -0,0 +1,36 @@
+//! Pure functions of the TRON precompiles, so that wallets and off-chain services
+//! can verify the same computations as the EVM, and `tron_precompile`, the
+//! dispatcher that the executor calls with them.
+//!
+//! Functions take the precompile input or its decoded form and return the
+//! computed value. Energy is not charged by them: callers bound input sizes, such
+//! as the lengths of `modexp`, before calling. The dispatcher charges the
+//! precompile energy of the `Config` first.
+//!
+//! The shielded transaction verifiers need the Sapling proving parameters and are
+//! not part of this crate.
+//!
+//! Without the default `std` feature, the crate is `no_std` and only needs `alloc`.
+
+#![deny(missing_docs)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+mod abi;
+mod bn128;
+mod dispatch;
+mod ecrecover;
+mod hash;
+mod modexp;
+mod multisign;
+
+pub use crate::bn128::{bn128_add, bn128_mul, bn128_pairing};
+pub use crate::dispatch::tron_precompile;
+pub use crate::ecrecover::{
+	ecrecover, recover_signature, batch_validate_sign, BatchValidateSignInput, BATCH_VALIDATE_SIGN_LIMIT,
+};
+pub use crate::hash::{sha256, ripemd160};
+pub use crate::modexp::modexp;
+pub use crate::multisign::{validate_multi_sign, ValidateMultiSignInput, VALIDATE_MULTI_SIGN_LIMIT};
+pub use evm::executor::EcrecoverInput;
@@ -0,0 +1,46 @@
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use num_bigint::BigUint;
+use primitive_types::U256;
+use evm::ExitError;
+use crate::abi;
+
+/// Bytes of `input` from `offset`, right-padded with zeros to `len`.
+pub(crate) fn padded(input: &[u8], offset: usize, len: usize) -> Vec<u8> {
+	let mut bytes = alloc::vec![0u8; len];
+	if offset < input.len() {
+		let available = (input.len() - offset).min(len);
+		bytes[..available].copy_from_slice(&input[offset..offset + available]);
+	}
+	bytes
+}
+
+/// EIP-198 modular exponentiation. The input is the lengths of base, exponent and
+/// modulus as words, followed by the three values, and the output is
+/// `base ^ exponent % modulus` left-padded to the length of the modulus.
+///
+/// Lengths are not bounded here; callers charge energy on them first. Fails if a
+/// length does not fit `usize`.
+pub fn modexp(input: &[u8]) -> Result<Vec<u8>, ExitError> {
+	let length = |offset| usize::try_from(U256::from_big_endian(&abi::word(input, offset)))
+		.map_err(|_| ExitError::PrecompileFailure);
+	let (base_len, exp_len, mod_len) = (length(0)?, length(32)?, length(64)?);
+	if mod_len == 0 {
+		return Ok(Vec::new())
+	}
+
+	let exp_offset = base_len.checked_add(96).ok_or(ExitError::PrecompileFailure)?;
+	let mod_offset = exp_offset.checked_add(exp_len).ok_or(ExitError::PrecompileFailure)?;
+
+	let modulus = BigUint::from_bytes_be(&padded(input, mod_offset, mod_len));
+	let mut output = alloc::vec![0u8; mod_len];
+	if modulus.bits() == 0 {
+		return Ok(output)
+	}
+
+	let base = BigUint::from_bytes_be(&padded(input, 96, base_len));
+	let exponent = BigUint::from_bytes_be(&padded(input, exp_offset, exp_len));
+	let value = base.modpow(&exponent, &modulus).to_bytes_be();
+	output[mod_len - value.len()..].copy_from_slice(&value);
+	Ok(output)
+}
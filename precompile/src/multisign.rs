@@ -0,0 +1,55 @@
+use alloc::vec::Vec;
+use primitive_types::{H160, H256, U256};
+use evm::backend::Backend;
+use crate::abi;
+
+/// Maximum number of signatures of `validatemultisign`.
+pub const VALIDATE_MULTI_SIGN_LIMIT: usize = 5;
+
+/// Input of the `validatemultisign` precompile, ABI-encoded as
+/// `(address account, uint256 permission_id, bytes32 data, bytes[] signatures)`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ValidateMultiSignInput {
+	/// Account whose permission signs.
+	pub account: H160,
+	/// Id of the permission of the account.
+	pub permission_id: U256,
+	/// Signed data.
+	pub data: H256,
+	/// Signatures, `r ++ s ++ v`.
+	pub signatures: Vec<Vec<u8>>,
+}
+
+impl ValidateMultiSignInput {
+	/// Decode the ABI-encoded input. `None` if offsets or lengths point past the
+	/// input.
+	pub fn decode(input: &[u8]) -> Option<Self> {
+		let (count, base) = abi::array_at(input, 96)?;
+		if count > input.len() / 32 {
+			return None
+		}
+		let signatures = (0..count)
+			.map(|i| abi::bytes_at(input, base, base + i * 32))
+			.collect::<Option<Vec<_>>>()?;
+
+		Some(Self {
+			account: H160::from_slice(&abi::word(input, 0)[12..]),
+			permission_id: U256::from_big_endian(&abi::word(input, 32)),
+			data: H256::from(abi::word(input, 64)),
+			signatures,
+		})
+	}
+}
+
+/// Check the signatures against the permission of the account, which the backend
+/// holds. False if there are no signatures or more than
+/// `VALIDATE_MULTI_SIGN_LIMIT`.
+pub fn validate_multi_sign(input: &ValidateMultiSignInput, backend: &dyn Backend) -> bool {
+	let count = input.signatures.len();
+	if count == 0 || count > VALIDATE_MULTI_SIGN_LIMIT {
+		return false
+	}
+
+	let signatures = input.signatures.iter().map(|signature| &signature[..]).collect::<Vec<_>>();
+	backend.validate_multisig(input.account, input.permission_id, input.data, &signatures)
+}
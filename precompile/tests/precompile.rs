@@ -0,0 +1,211 @@
+use std::collections::BTreeMap;
+use primitive_types::{H160, H256, U256};
+use evm::{Config, Context, ExitError, ExitSucceed};
+use evm::backend::{MemoryBackend, MemoryVicinity};
+use evm::executor::{PrecompileContext, PrecompileResult};
+use evm_precompile::*;
+
+fn h256(s: &str) -> H256 {
+	H256::from_slice(&hex::decode(s).unwrap())
+}
+
+const HASH: &str = "456e9aea5e197a1f1af7a3e85a3212fa4049a3ba34c2289b4c860fc0b0c64ef3";
+const R: &str = "9242685bf161793cc25603c231bc2f568eb630ea16aa137d2664ac8038825608";
+const S: &str = "4f8ae3bd7535248d0bd448298cc2e2071e56992d0774dc340c368ae950852ada";
+const SIGNER: &str = "7156526fbd7a3c72969b54f64e42c10fbb768c8a";
+
+#[test]
+fn ecrecover_signer() {
+	let input = hex::decode(format!("{}{:064x}{}{}", HASH, 28, R, S)).unwrap();
+	let parsed = EcrecoverInput::parse(&input, &Config::istanbul()).unwrap();
+	let signer = H160::from_slice(&hex::decode(SIGNER).unwrap());
+	assert_eq!(ecrecover(&parsed), Some(signer));
+
+	let signature = hex::decode(format!("{}{}01", R, S)).unwrap();
+	assert_eq!(recover_signature(h256(HASH), &signature), Some(signer));
+	assert_eq!(recover_signature(h256(HASH), &signature[..64]), None);
+}
+
+#[test]
+fn batch_validate_sign_marks_valid_signatures() {
+	let signature = format!("{}{}1c{}", R, S, "00".repeat(31));
+	let wrong = format!("{}{}1b{}", R, S, "00".repeat(31));
+	// hash, offsets of signatures and addresses, then both arrays.
+	let input = hex::decode(format!(
+		"{}{:064x}{:064x}{:064x}{:064x}{:064x}{:064x}{}{:064x}{}{:064x}{:024x}{}{:024x}{}",
+		HASH, 0x60, 0x1c0,
+		2, 0x40, 0xc0,
+		65, signature, 65, wrong,
+		2, 0, SIGNER, 0, SIGNER,
+	)).unwrap();
+
+	let decoded = BatchValidateSignInput::decode(&input).unwrap();
+	assert_eq!(decoded.signatures.len(), 2);
+	assert_eq!(decoded.addresses.len(), 2);
+	let result = batch_validate_sign(&decoded);
+	assert_eq!(&result[..3], &[1, 0, 0]);
+
+	let mut mismatched = decoded.clone();
+	mismatched.addresses.pop();
+	assert_eq!(batch_validate_sign(&mismatched), [0; 32]);
+	assert_eq!(BatchValidateSignInput::decode(&input[..0x100]), None);
+}
+
+#[test]
+fn hash_digests() {
+	assert_eq!(sha256(b""), h256("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"));
+	assert_eq!(sha256(b"abc"), h256("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"));
+	assert_eq!(ripemd160(b""), h256("0000000000000000000000009c1185a5c5e9fc54612808977ee8f548b2258d31"));
+	assert_eq!(ripemd160(b"abc"), h256("0000000000000000000000008eb208f7e05d987a9b044a8e98c6b087f15a0bfc"));
+}
+
+#[test]
+fn modexp_values() {
+	// 3 ^ (p - 1) % p for the secp256k1 field prime p.
+	let input = hex::decode(format!(
+		"{:064x}{:064x}{:064x}03{}{}",
+		1, 32, 32,
+		"fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2e",
+		"fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+	)).unwrap();
+	let mut one = vec![0u8; 32];
+	one[31] = 1;
+	assert_eq!(modexp(&input).unwrap(), one);
+
+	// Zero modulus, and missing values read as zeros.
+	let input = hex::decode(format!("{:064x}{:064x}{:064x}0203", 1, 1, 2)).unwrap();
+	assert_eq!(modexp(&input).unwrap(), vec![0, 0]);
+	assert_eq!(modexp(&[]).unwrap(), Vec::<u8>::new());
+
+	let input = hex::decode(format!("{}{:064x}{:064x}", "ff".repeat(32), 0, 1)).unwrap();
+	assert!(modexp(&input).is_err());
+}
+
+#[test]
+fn bn128_operations() {
+	let generator = format!("{:064x}{:064x}", 1, 2);
+	let double = hex::decode(concat!(
+		"030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd3",
+		"15ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4",
+	)).unwrap();
+
+	let input = hex::decode(format!("{}{}", generator, generator)).unwrap();
+	assert_eq!(&bn128_add(&input).unwrap()[..], &double[..]);
+	let input = hex::decode(format!("{}{:064x}", generator, 2)).unwrap();
+	assert_eq!(&bn128_mul(&input).unwrap()[..], &double[..]);
+	assert_eq!(bn128_add(&[]).unwrap(), [0; 64]);
+
+	let off_curve = hex::decode(format!("{:064x}{:064x}", 1, 3)).unwrap();
+	assert!(bn128_add(&off_curve).is_err());
+
+	assert_eq!(bn128_pairing(&[]), Ok(true));
+	assert!(bn128_pairing(&[0; 64]).is_err());
+	assert_eq!(bn128_pairing(&[0; 192]), Ok(true));
+}
+
+/// Call the dispatcher at `address` with an empty state.
+fn dispatch(address: u64, input: &[u8], target_gas: Option<usize>, config: &Config) -> PrecompileResult {
+	let vicinity = MemoryVicinity {
+		gas_price: U256::zero(),
+		origin: H160::default(),
+		chain_id: U256::zero(),
+		block_hashes: Vec::new(),
+		block_number: U256::zero(),
+		block_coinbase: H160::default(),
+		block_timestamp: U256::zero(),
+		block_difficulty: U256::zero(),
+		block_randomness: None,
+		block_gas_limit: U256::zero(),
+		block_base_fee_per_gas: U256::zero(),
+	};
+	let backend = MemoryBackend::new(&vicinity, BTreeMap::new());
+	let address = H160::from_low_u64_be(address);
+	let context = Context {
+		address,
+		caller: H160::default(),
+		call_value: U256::zero(),
+		call_token_id: U256::zero(),
+		call_token_value: U256::zero(),
+	};
+	tron_precompile(address, input, target_gas, config, &PrecompileContext::new(&context, &backend))
+}
+
+#[test]
+fn dispatch_charges_config_energy() {
+	let config = Config::great_voyage_4_7();
+	let returned = |output: Vec<u8>, energy| Some(Ok((ExitSucceed::Returned, output, energy)));
+
+	let input = hex::decode(format!("{}{:064x}{}{}", HASH, 28, R, S)).unwrap();
+	let signer = format!("{:0>64}", SIGNER);
+	assert_eq!(dispatch(0x01, &input, None, &config), returned(hex::decode(&signer).unwrap(), 3000));
+	assert_eq!(dispatch(0x01, &[], None, &config), returned(Vec::new(), 3000));
+	assert_eq!(dispatch(0x02, b"abc", None, &config), returned(sha256(b"abc").as_bytes().to_vec(), 72));
+	assert_eq!(dispatch(0x03, b"abc", None, &config), returned(ripemd160(b"abc").as_bytes().to_vec(), 720));
+	assert_eq!(dispatch(0x04, &[1; 33], None, &config), returned(vec![1; 33], 21));
+
+	// EIP-198 example: 32-byte modulus and a 255-bit exponent.
+	let input = hex::decode(format!(
+		"{:064x}{:064x}{:064x}03{}{}",
+		1, 32, 32,
+		"fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2e",
+		"fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+	)).unwrap();
+	assert_eq!(dispatch(0x05, &input, None, &config), returned(modexp(&input).unwrap(), 13056));
+	let huge = hex::decode(format!("{:064x}{:064x}{}", 1, 1, "ff".repeat(32))).unwrap();
+	assert_eq!(dispatch(0x05, &huge, Some(1_000_000), &config), Some(Err(ExitError::OutOfGas)));
+	let long_base = hex::decode(format!("{:064x}{:064x}{:064x}", 1u64 << 32, 1, 1)).unwrap();
+	assert_eq!(dispatch(0x05, &long_base, Some(1_000_000), &config), Some(Err(ExitError::OutOfGas)));
+
+	assert_eq!(dispatch(0x04, &[], Some(14), &config), Some(Err(ExitError::OutOfGas)));
+	assert_eq!(dispatch(0x06, &[1; 64], None, &config), Some(Err(ExitError::PrecompileFailure)));
+	assert_eq!(dispatch(0x0b, &[], None, &config), None);
+	assert_eq!(dispatch(0x0100_0001, &[], None, &config), None);
+}
+
+#[test]
+fn bn128_energy_repriced_by_istanbul() {
+	let mut one = vec![0u8; 32];
+	one[31] = 1;
+	let cases = |config: &Config| vec![
+		dispatch(0x06, &[], None, config),
+		dispatch(0x07, &[], None, config),
+		dispatch(0x08, &[0; 192], None, config),
+	];
+	let returned = |output: Vec<u8>, energy| Some(Ok((ExitSucceed::Returned, output, energy)));
+
+	assert_eq!(cases(&Config::great_voyage_4_0()), vec![
+		returned(vec![0; 64], 500),
+		returned(vec![0; 64], 40000),
+		returned(one.clone(), 100000 + 80000),
+	]);
+	assert_eq!(cases(&Config::great_voyage_4_1()), vec![
+		returned(vec![0; 64], 150),
+		returned(vec![0; 64], 6000),
+		returned(one, 45000 + 34000),
+	]);
+}
+
+#[test]
+fn signature_validators_gated_by_fork() {
+	let before = Config::tvm();
+	let after = Config::odyssey_3_7();
+
+	assert_eq!(dispatch(0x09, &[], None, &before), None);
+	assert_eq!(dispatch(0x0a, &[], None, &before), None);
+	assert_eq!(dispatch(0x09, &[], None, &after), Some(Ok((ExitSucceed::Returned, vec![0; 32], 0))));
+	assert_eq!(dispatch(0x0a, &[], None, &after), Some(Ok((ExitSucceed::Returned, vec![0; 32], 0))));
+	assert!(dispatch(0x08, &[], None, &before).is_some());
+
+	// validatemultisign of one signature, accepted by the memory backend.
+	let signature = format!("{}{}1c{}", R, S, "00".repeat(31));
+	let input = hex::decode(format!(
+		"{:0>64}{:064x}{}{:064x}{:064x}{:064x}{:064x}{}",
+		SIGNER, 2, HASH, 0x80,
+		1, 0x20,
+		65, signature,
+	)).unwrap();
+	let mut one = vec![0u8; 32];
+	one[31] = 1;
+	assert_eq!(dispatch(0x0a, &input, None, &after), Some(Ok((ExitSucceed::Returned, one, 1500))));
+	assert_eq!(dispatch(0x0a, &input, Some(1499), &after), Some(Err(ExitError::OutOfGas)));
+}
@@ -0,0 +1,145 @@
+//! TRON addresses. An account is identified on chain by 21 bytes, the prefix
+//! `0x41` followed by the 20-byte EVM address, and shown as base58check, e.g.
+//! `T9yD14Nj9j7xAB4dbGeiX9h8unkKHxuWwb`.
+
+use core::fmt;
+use alloc::string::String;
+use alloc::vec::Vec;
+use primitive_types::{H160, H256};
+use sha2::Sha256;
+use sha3::{Digest, Keccak256};
+
+/// Prefix byte of TRON mainnet addresses.
+pub const ADDRESS_PREFIX: u8 = 0x41;
+
+const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Error decoding a TRON address.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AddressError {
+	/// Character is not in the base58 alphabet.
+	InvalidCharacter(char),
+	/// Decoded address is not 21 bytes.
+	InvalidLength(usize),
+	/// First byte is not `0x41`.
+	InvalidPrefix(u8),
+	/// Checksum does not match.
+	InvalidChecksum,
+}
+
+impl fmt::Display for AddressError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			AddressError::InvalidCharacter(c) => write!(f, "invalid base58 character {:?}", c),
+			AddressError::InvalidLength(len) => write!(f, "invalid address length {}", len),
+			AddressError::InvalidPrefix(prefix) => write!(f, "invalid address prefix 0x{:02x}", prefix),
+			AddressError::InvalidChecksum => write!(f, "invalid address checksum"),
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AddressError {}
+
+/// Get the 21-byte TRON form of an address.
+pub fn to_tron_bytes(address: H160) -> [u8; 21] {
+	let mut bytes = [ADDRESS_PREFIX; 21];
+	bytes[1..].copy_from_slice(address.as_bytes());
+	bytes
+}
+
+/// Get the address from its 21-byte TRON form.
+pub fn from_tron_bytes(bytes: &[u8]) -> Result<H160, AddressError> {
+	if bytes.len() != 21 {
+		return Err(AddressError::InvalidLength(bytes.len()))
+	}
+	if bytes[0] != ADDRESS_PREFIX {
+		return Err(AddressError::InvalidPrefix(bytes[0]))
+	}
+	Ok(H160::from_slice(&bytes[1..]))
+}
+
+fn checksum(data: &[u8]) -> [u8; 4] {
+	let hash = Sha256::digest(&Sha256::digest(data));
+	let mut ret = [0u8; 4];
+	ret.copy_from_slice(&hash[..4]);
+	ret
+}
+
+/// Encode an address as base58check of its TRON form.
+pub fn to_base58(address: H160) -> String {
+	let mut data = to_tron_bytes(address).to_vec();
+	data.extend_from_slice(&checksum(&data));
+
+	// Digits in base 58, least significant first.
+	let mut digits: Vec<u8> = Vec::new();
+	for byte in &data {
+		let mut carry = *byte as u32;
+		for digit in digits.iter_mut() {
+			carry += (*digit as u32) << 8;
+			*digit = (carry % 58) as u8;
+			carry /= 58;
+		}
+		while carry > 0 {
+			digits.push((carry % 58) as u8);
+			carry /= 58;
+		}
+	}
+
+	let zeros = data.iter().take_while(|b| **b == 0).count();
+	let mut ret = String::with_capacity(zeros + digits.len());
+	ret.extend(core::iter::repeat('1').take(zeros));
+	ret.extend(digits.iter().rev().map(|d| ALPHABET[*d as usize] as char));
+	ret
+}
+
+/// Decode an address from base58check of its TRON form.
+pub fn from_base58(s: &str) -> Result<H160, AddressError> {
+	// Bytes, least significant first.
+	let mut bytes: Vec<u8> = Vec::new();
+	for c in s.chars() {
+		let mut carry = ALPHABET.iter().position(|a| *a as char == c)
+			.ok_or(AddressError::InvalidCharacter(c))? as u32;
+		for byte in bytes.iter_mut() {
+			carry += (*byte as u32) * 58;
+			*byte = carry as u8;
+			carry >>= 8;
+		}
+		while carry > 0 {
+			bytes.push(carry as u8);
+			carry >>= 8;
+		}
+	}
+	let zeros = s.chars().take_while(|c| *c == '1').count();
+	bytes.extend(core::iter::repeat(0).take(zeros));
+	bytes.reverse();
+
+	if bytes.len() != 25 {
+		return Err(AddressError::InvalidLength(bytes.len().saturating_sub(4)))
+	}
+	let (data, check) = bytes.split_at(21);
+	if checksum(data)[..] != check[..] {
+		return Err(AddressError::InvalidChecksum)
+	}
+	from_tron_bytes(data)
+}
+
+/// Address of a contract created by `CREATE`, or by a transaction, as in
+/// java-tron: the last 20 bytes of keccak of the transaction root hash and the
+/// nonce of the create in the transaction.
+pub fn create_address(transaction_root_hash: H256, nonce: u64) -> H160 {
+	let mut hasher = Keccak256::new();
+	hasher.update(transaction_root_hash.as_bytes());
+	hasher.update(&nonce.to_be_bytes()[..]);
+	H256::from_slice(&hasher.finalize()).into()
+}
+
+/// Address of a contract created by `CREATE2`. Unlike EIP-1014, the caller is
+/// hashed in its 21-byte TRON form in place of the `0xff` prefix.
+pub fn create2_address(caller: H160, salt: H256, code_hash: H256) -> H160 {
+	let mut hasher = Keccak256::new();
+	hasher.update(&to_tron_bytes(caller)[..]);
+	hasher.update(&salt[..]);
+	hasher.update(&code_hash[..]);
+	H256::from_slice(&hasher.finalize()).into()
+}
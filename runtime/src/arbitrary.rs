@@ -0,0 +1,36 @@
+use arbitrary::{Arbitrary, Result, Unstructured};
+use primitive_types::{H160, U256};
+use crate::{Config, Context};
+
+fn h160(u: &mut Unstructured) -> Result<H160> {
+	Ok(H160::from(u.arbitrary::<[u8; 20]>()?))
+}
+
+fn u256(u: &mut Unstructured) -> Result<U256> {
+	Ok(U256::from_big_endian(&u.arbitrary::<[u8; 32]>()?))
+}
+
+impl<'a> Arbitrary<'a> for Context {
+	fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+		Ok(Context {
+			address: h160(u)?,
+			caller: h160(u)?,
+			call_value: u256(u)?,
+			call_token_id: u256(u)?,
+			call_token_value: u256(u)?,
+		})
+	}
+}
+
+/// A preset, with some opcode gas costs overridden. Arbitrary field combinations
+/// would mostly fail `Config::validate`.
+impl<'a> Arbitrary<'a> for Config {
+	fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+		let name = u.choose(Config::PRESETS)?;
+		let mut builder = Config::preset(name).expect("listed preset").builder();
+		for _ in 0..u.int_in_range(0..=4)? {
+			builder = builder.gas_override(u.arbitrary()?, u.int_in_range(0..=100_000)?);
+		}
+		builder.build().map_err(|_| arbitrary::Error::IncorrectFormat)
+	}
+}
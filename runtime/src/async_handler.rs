@@ -0,0 +1,51 @@
+use core::future::Future;
+use primitive_types::{H160, H256, U256};
+use crate::{Context, ExternalOpcode, Opcode, Stack};
+
+/// State an opcode is about to read.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StateAccess {
+	/// Balance, code and existence of an account.
+	Account(H160),
+	/// A storage slot.
+	Storage(H160, H256),
+	/// A block hash.
+	BlockHash(U256),
+}
+
+impl StateAccess {
+	/// State read by the opcode, given the stack before it executes.
+	pub fn of(
+		context: &Context,
+		opcode: Result<Opcode, ExternalOpcode>,
+		stack: &Stack,
+	) -> Option<Self> {
+		let address = |n| stack.peek(n).ok().map(H160::from);
+
+		match opcode {
+			Err(ExternalOpcode::Balance) | Err(ExternalOpcode::ExtCodeSize) |
+			Err(ExternalOpcode::ExtCodeHash) | Err(ExternalOpcode::ExtCodeCopy) |
+			Err(ExternalOpcode::Suicide) | Err(ExternalOpcode::IsContract) =>
+				address(0).map(StateAccess::Account),
+			Err(ExternalOpcode::SelfBalance) => Some(StateAccess::Account(context.address)),
+			Err(ExternalOpcode::Call) | Err(ExternalOpcode::CallCode) |
+			Err(ExternalOpcode::DelegateCall) | Err(ExternalOpcode::StaticCall) |
+			Err(ExternalOpcode::CallToken) =>
+				address(1).map(StateAccess::Account),
+			Err(ExternalOpcode::SLoad) | Err(ExternalOpcode::SStore) =>
+				stack.peek(0).ok().map(|index| StateAccess::Storage(context.address, index)),
+			Err(ExternalOpcode::BlockHash) =>
+				stack.peek(0).ok().map(|number| StateAccess::BlockHash(U256::from_big_endian(&number[..]))),
+			_ => None,
+		}
+	}
+}
+
+/// Handler whose state may be served asynchronously, such as over RPC or from a
+/// database. Before each opcode, `Runtime::run_async` awaits `prefetch` for the state
+/// the opcode reads, after which the synchronous `Handler` methods are expected to
+/// answer from local state.
+pub trait AsyncHandler {
+	/// Fetch the state into the handler.
+	fn prefetch(&mut self, access: StateAccess) -> impl Future<Output = ()>;
+}
@@ -0,0 +1,211 @@
+use core::fmt;
+use crate::Config;
+
+/// Inconsistent combination of config fields.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConfigError {
+	/// `sstore_revert_under_stipend` (EIP-1706) requires `sstore_gas_metering` (EIP-1283).
+	SStoreRevertWithoutGasMetering,
+	/// `has_real_create2` requires `has_create2`.
+	RealCreate2WithoutCreate2,
+	/// `warm_coinbase_address` (EIP-3651) requires `increase_state_access_gas` (EIP-2929).
+	WarmCoinbaseWithoutStateAccessGas,
+	/// `increase_state_access_gas` requires non-zero cold access costs.
+	ZeroColdAccessGas,
+	/// `max_refund_quotient` must not be zero.
+	ZeroRefundQuotient,
+	/// `stack_limit` must not be zero.
+	ZeroStackLimit,
+	/// `memory_quad_divisor` must not be zero.
+	ZeroMemoryQuadDivisor,
+	/// `has_token_id_check` requires `has_token_transfer`.
+	TokenIdCheckWithoutTokenTransfer,
+	/// Preset name is unknown.
+	UnknownPreset,
+}
+
+impl fmt::Display for ConfigError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			ConfigError::SStoreRevertWithoutGasMetering =>
+				write!(f, "sstore_revert_under_stipend requires sstore_gas_metering"),
+			ConfigError::RealCreate2WithoutCreate2 =>
+				write!(f, "has_real_create2 requires has_create2"),
+			ConfigError::WarmCoinbaseWithoutStateAccessGas =>
+				write!(f, "warm_coinbase_address requires increase_state_access_gas"),
+			ConfigError::ZeroColdAccessGas =>
+				write!(f, "increase_state_access_gas requires non-zero cold access gas"),
+			ConfigError::ZeroRefundQuotient =>
+				write!(f, "max_refund_quotient is zero"),
+			ConfigError::ZeroStackLimit =>
+				write!(f, "stack_limit is zero"),
+			ConfigError::ZeroMemoryQuadDivisor =>
+				write!(f, "memory_quad_divisor is zero"),
+			ConfigError::TokenIdCheckWithoutTokenTransfer =>
+				write!(f, "has_token_id_check requires has_token_transfer"),
+			ConfigError::UnknownPreset =>
+				write!(f, "unknown preset"),
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ConfigError {}
+
+macro_rules! setters {
+	( $( $field:ident: $ty:ty ),* $(,)? ) => {
+		$(
+			#[doc = concat!("Set `", stringify!($field), "`.")]
+			pub fn $field(mut self, $field: $ty) -> Self {
+				self.config.$field = $field;
+				self
+			}
+		)*
+	}
+}
+
+/// Builder of `Config`, starting from a preset with individual fields overridden.
+#[derive(Clone, Debug)]
+pub struct ConfigBuilder {
+	config: Config,
+}
+
+impl ConfigBuilder {
+	/// Create a builder from the given preset.
+	pub fn new(preset: Config) -> Self {
+		Self { config: preset }
+	}
+
+	/// Override the gas cost of an opcode by its byte value.
+	pub fn gas_override(mut self, opcode: u8, cost: usize) -> Self {
+		self.config.gas_overrides.insert(opcode, cost);
+		self
+	}
+
+	/// Validate and build the config.
+	pub fn build(self) -> Result<Config, ConfigError> {
+		self.config.validate()?;
+		Ok(self.config)
+	}
+
+	setters! {
+		gas_ext_code: usize,
+		gas_ext_code_hash: usize,
+		gas_sstore_set: usize,
+		gas_sstore_reset: usize,
+		refund_sstore_clears: isize,
+		gas_balance: usize,
+		gas_sload: usize,
+		gas_suicide: usize,
+		gas_suicide_new_account: usize,
+		gas_call: usize,
+		gas_expbyte: usize,
+		gas_memory_word: usize,
+		memory_quad_divisor: usize,
+		gas_transaction_create: usize,
+		gas_transaction_call: usize,
+		gas_transaction_zero_data: usize,
+		gas_transaction_non_zero_data: usize,
+		gas_sload_cold: usize,
+		gas_storage_read_warm: usize,
+		gas_account_access_cold: usize,
+		gas_ecrecover: usize,
+		gas_sha256: usize,
+		gas_sha256_word: usize,
+		gas_ripemd160: usize,
+		gas_ripemd160_word: usize,
+		gas_identity: usize,
+		gas_identity_word: usize,
+		gas_bn128_add: usize,
+		gas_bn128_mul: usize,
+		gas_bn128_pairing: usize,
+		gas_bn128_pairing_point: usize,
+		gas_validate_sign: usize,
+		ecrecover_strict: bool,
+		sstore_gas_metering: bool,
+		sstore_revert_under_stipend: bool,
+		increase_state_access_gas: bool,
+		warm_coinbase_address: bool,
+		warm_precompiles: u64,
+		max_refund_quotient: usize,
+		err_on_call_with_more_gas: bool,
+		call_l64_after_gas: bool,
+		empty_considered_exists: bool,
+		create_increase_nonce: bool,
+		stack_limit: usize,
+		memory_limit: usize,
+		call_stack_limit: usize,
+		create_contract_limit: Option<usize>,
+		max_initcode_size: Option<usize>,
+		disallow_executable_format: bool,
+		call_stipend: usize,
+		has_delegate_call: bool,
+		has_create2: bool,
+		has_real_create2: bool,
+		has_revert: bool,
+		has_return_data: bool,
+		has_bitwise_shifting: bool,
+		has_chain_id: bool,
+		has_self_balance: bool,
+		has_ext_code_hash: bool,
+		has_push0: bool,
+		has_prev_randao: bool,
+		has_eof: bool,
+		has_token_transfer: bool,
+		create_account_if_not_exist: bool,
+		has_iscontract: bool,
+		has_transfer_exception: bool,
+		has_stake: bool,
+		has_token_issue: bool,
+		has_iswitness: bool,
+		has_buggy_origin: bool,
+		has_buggy_blockhash: bool,
+		has_token_id_check: bool,
+		has_token_balance_check: bool,
+		has_stake_v2: bool,
+		has_vote: bool,
+		has_freeze: bool,
+		has_base_fee: bool,
+		has_dynamic_energy: bool,
+		has_validate_signature: bool,
+		has_shielded: bool,
+	}
+}
+
+impl Config {
+	/// Create a builder starting from this config.
+	pub fn builder(self) -> ConfigBuilder {
+		ConfigBuilder::new(self)
+	}
+
+	/// Check the config for inconsistent combinations of fields.
+	pub fn validate(&self) -> Result<(), ConfigError> {
+		if self.sstore_revert_under_stipend && !self.sstore_gas_metering {
+			return Err(ConfigError::SStoreRevertWithoutGasMetering)
+		}
+		if self.has_real_create2 && !self.has_create2 {
+			return Err(ConfigError::RealCreate2WithoutCreate2)
+		}
+		if self.warm_coinbase_address && !self.increase_state_access_gas {
+			return Err(ConfigError::WarmCoinbaseWithoutStateAccessGas)
+		}
+		if self.increase_state_access_gas &&
+			(self.gas_sload_cold == 0 || self.gas_account_access_cold == 0)
+		{
+			return Err(ConfigError::ZeroColdAccessGas)
+		}
+		if self.max_refund_quotient == 0 {
+			return Err(ConfigError::ZeroRefundQuotient)
+		}
+		if self.stack_limit == 0 {
+			return Err(ConfigError::ZeroStackLimit)
+		}
+		if self.memory_quad_divisor == 0 {
+			return Err(ConfigError::ZeroMemoryQuadDivisor)
+		}
+		if self.has_token_id_check && !self.has_token_transfer {
+			return Err(ConfigError::TokenIdCheckWithoutTokenTransfer)
+		}
+		Ok(())
+	}
+}
@@ -1,7 +1,10 @@
 use primitive_types::{H160, U256, H256};
+use sha3::{Digest, Keccak256};
+use crate::address;
 
 /// Create scheme.
 #[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CreateScheme {
 	/// Legacy create scheme of `CREATE`.
 	Legacy {
@@ -23,8 +26,31 @@ pub enum CreateScheme {
 	Fixed(H160),
 }
 
+impl CreateScheme {
+	/// Scheme of `CREATE2` with init code, hashing the code.
+	pub fn create2(caller: H160, salt: H256, init_code: &[u8]) -> Self {
+		CreateScheme::Create2 {
+			caller,
+			code_hash: H256::from_slice(&Keccak256::digest(init_code)),
+			salt,
+		}
+	}
+
+	/// Get the address of the contract created with the scheme.
+	pub fn address(&self) -> H160 {
+		match *self {
+			CreateScheme::Legacy { transaction_root_hash, nonce } =>
+				address::create_address(transaction_root_hash, nonce),
+			CreateScheme::Create2 { caller, code_hash, salt } =>
+				address::create2_address(caller, salt, code_hash),
+			CreateScheme::Fixed(address) => address,
+		}
+	}
+}
+
 /// Call scheme.
 #[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CallScheme {
 	/// `CALL`
 	Call,
@@ -40,6 +66,7 @@ pub enum CallScheme {
 
 /// Context of the runtime.
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Context {
 	/// Execution address.
 	pub address: H160,
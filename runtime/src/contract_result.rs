@@ -0,0 +1,137 @@
+use core::fmt;
+use core::convert::TryFrom;
+use crate::{ExitError, ExitReason};
+
+/// Result code of a contract execution in a java-tron receipt,
+/// `Transaction.Result.contractResult`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ContractResult {
+	/// `DEFAULT`, not executed.
+	Default,
+	/// `SUCCESS`.
+	Success,
+	/// `REVERT`.
+	Revert,
+	/// `BAD_JUMP_DESTINATION`.
+	BadJumpDestination,
+	/// `OUT_OF_MEMORY`.
+	OutOfMemory,
+	/// `PRECOMPILED_CONTRACT`.
+	PrecompiledContract,
+	/// `STACK_TOO_SMALL`.
+	StackTooSmall,
+	/// `STACK_TOO_LARGE`.
+	StackTooLarge,
+	/// `ILLEGAL_OPERATION`.
+	IllegalOperation,
+	/// `STACK_OVERFLOW`.
+	StackOverflow,
+	/// `OUT_OF_ENERGY`.
+	OutOfEnergy,
+	/// `OUT_OF_TIME`.
+	OutOfTime,
+	/// `JVM_STACK_OVER_FLOW`.
+	JvmStackOverFlow,
+	/// `UNKNOWN`.
+	Unknown,
+	/// `TRANSFER_FAILED`.
+	TransferFailed,
+	/// `INVALID_CODE`.
+	InvalidCode,
+}
+
+impl ContractResult {
+	/// All results, in order of their code.
+	pub const ALL: [ContractResult; 16] = [
+		ContractResult::Default,
+		ContractResult::Success,
+		ContractResult::Revert,
+		ContractResult::BadJumpDestination,
+		ContractResult::OutOfMemory,
+		ContractResult::PrecompiledContract,
+		ContractResult::StackTooSmall,
+		ContractResult::StackTooLarge,
+		ContractResult::IllegalOperation,
+		ContractResult::StackOverflow,
+		ContractResult::OutOfEnergy,
+		ContractResult::OutOfTime,
+		ContractResult::JvmStackOverFlow,
+		ContractResult::Unknown,
+		ContractResult::TransferFailed,
+		ContractResult::InvalidCode,
+	];
+
+	/// Protobuf code of the result.
+	pub fn code(&self) -> i32 {
+		*self as i32
+	}
+
+	/// Get the result of a protobuf code.
+	pub fn from_code(code: i32) -> Option<Self> {
+		Self::ALL.get(usize::try_from(code).ok()?).copied()
+	}
+
+	/// Protobuf name of the result, e.g. `OUT_OF_ENERGY`.
+	pub fn name(&self) -> &'static str {
+		match self {
+			ContractResult::Default => "DEFAULT",
+			ContractResult::Success => "SUCCESS",
+			ContractResult::Revert => "REVERT",
+			ContractResult::BadJumpDestination => "BAD_JUMP_DESTINATION",
+			ContractResult::OutOfMemory => "OUT_OF_MEMORY",
+			ContractResult::PrecompiledContract => "PRECOMPILED_CONTRACT",
+			ContractResult::StackTooSmall => "STACK_TOO_SMALL",
+			ContractResult::StackTooLarge => "STACK_TOO_LARGE",
+			ContractResult::IllegalOperation => "ILLEGAL_OPERATION",
+			ContractResult::StackOverflow => "STACK_OVERFLOW",
+			ContractResult::OutOfEnergy => "OUT_OF_ENERGY",
+			ContractResult::OutOfTime => "OUT_OF_TIME",
+			ContractResult::JvmStackOverFlow => "JVM_STACK_OVER_FLOW",
+			ContractResult::Unknown => "UNKNOWN",
+			ContractResult::TransferFailed => "TRANSFER_FAILED",
+			ContractResult::InvalidCode => "INVALID_CODE",
+		}
+	}
+}
+
+impl fmt::Display for ContractResult {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(self.name())
+	}
+}
+
+impl From<ExitError> for ContractResult {
+	/// Result of a transaction failing with the error, as java-tron classifies its
+	/// exceptions. Errors without a dedicated exception are `UNKNOWN`.
+	fn from(error: ExitError) -> Self {
+		match error {
+			ExitError::StackUnderflow => ContractResult::StackTooSmall,
+			ExitError::StackOverflow => ContractResult::StackTooLarge,
+			ExitError::InvalidJump => ContractResult::BadJumpDestination,
+			ExitError::InvalidRange => ContractResult::OutOfMemory,
+			ExitError::DesignatedInvalid | ExitError::IllegalOperation => ContractResult::IllegalOperation,
+			ExitError::CallTooDeep => ContractResult::JvmStackOverFlow,
+			ExitError::TransferException | ExitError::OutOfFund => ContractResult::TransferFailed,
+			ExitError::PrecompileFailure => ContractResult::PrecompiledContract,
+			ExitError::OutOfGas => ContractResult::OutOfEnergy,
+			ExitError::OutOfTime => ContractResult::OutOfTime,
+			ExitError::InvalidCode => ContractResult::InvalidCode,
+			ExitError::CreateCollision | ExitError::CreateContractLimit | ExitError::Unknown |
+			ExitError::Cancelled |
+			ExitError::StaticCallModification | ExitError::OutOfOffset | ExitError::PCUnderflow |
+			ExitError::CreateEmpty | ExitError::Other(_) => ContractResult::Unknown,
+		}
+	}
+}
+
+impl From<ExitReason> for ContractResult {
+	fn from(reason: ExitReason) -> Self {
+		match reason {
+			ExitReason::Succeed(_) => ContractResult::Success,
+			ExitReason::Revert(_) => ContractResult::Revert,
+			ExitReason::Error(error) => error.into(),
+			ExitReason::Fatal(_) => ContractResult::Unknown,
+		}
+	}
+}
@@ -0,0 +1,146 @@
+use core::fmt;
+use crate::{disassemble, Config, ExternalOpcode, Opcode};
+use crate::validator::is_enabled;
+
+/// Magic prefix of EOF containers.
+pub const EOF_MAGIC: [u8; 2] = [0xef, 0x00];
+
+const KIND_TERMINATOR: u8 = 0x00;
+const KIND_CODE: u8 = 0x01;
+const KIND_DATA: u8 = 0x02;
+
+/// Error of an invalid EOF container.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EofError {
+	/// Code does not start with `EOF_MAGIC`.
+	InvalidMagic,
+	/// Version other than 1.
+	UnsupportedVersion(u8),
+	/// Section headers end before the terminator.
+	TruncatedHeader,
+	/// Section kind is unknown, repeated or out of order.
+	InvalidSectionKind(u8),
+	/// No code section.
+	MissingCodeSection,
+	/// Section of zero size.
+	EmptySection,
+	/// Container size differs from the size of the header and sections.
+	SizeMismatch {
+		/// Size given by the header.
+		expected: usize,
+		/// Size of the container.
+		actual: usize,
+	},
+	/// EIP-3670: opcode is not defined, or not enabled in the config.
+	UndefinedOpcode {
+		/// Position of the opcode in the code section.
+		pc: usize,
+		/// The opcode byte.
+		opcode: u8,
+	},
+	/// EIP-3670: `PUSHn` runs past the end of the code section.
+	TruncatedPush {
+		/// Position of the opcode in the code section.
+		pc: usize,
+	},
+}
+
+impl fmt::Display for EofError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			EofError::InvalidMagic => write!(f, "invalid magic"),
+			EofError::UnsupportedVersion(version) => write!(f, "unsupported version {}", version),
+			EofError::TruncatedHeader => write!(f, "truncated header"),
+			EofError::InvalidSectionKind(kind) => write!(f, "invalid section kind 0x{:02x}", kind),
+			EofError::MissingCodeSection => write!(f, "missing code section"),
+			EofError::EmptySection => write!(f, "empty section"),
+			EofError::SizeMismatch { expected, actual } =>
+				write!(f, "container size {} does not match header size {}", actual, expected),
+			EofError::UndefinedOpcode { pc, opcode } =>
+				write!(f, "{:06x}: undefined opcode 0x{:02x}", pc, opcode),
+			EofError::TruncatedPush { pc } => write!(f, "{:06x}: truncated PUSH", pc),
+		}
+	}
+}
+
+/// Sections of an EOF container (EIP-3540).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EofContainer<'a> {
+	/// Format version.
+	pub version: u8,
+	/// Code section, executed from its first byte.
+	pub code: &'a [u8],
+	/// Data section, empty if absent.
+	pub data: &'a [u8],
+}
+
+/// Whether the code starts with `EOF_MAGIC`.
+pub fn is_eof(code: &[u8]) -> bool {
+	code.starts_with(&EOF_MAGIC)
+}
+
+/// Parse an EOF container: the magic, version 1, one code section and an optional
+/// data section, each with a non-zero 2-byte size, the terminator, then the
+/// sections.
+pub fn parse_eof(code: &[u8]) -> Result<EofContainer<'_>, EofError> {
+	if !is_eof(code) {
+		return Err(EofError::InvalidMagic)
+	}
+	let version = *code.get(2).ok_or(EofError::TruncatedHeader)?;
+	if version != 1 {
+		return Err(EofError::UnsupportedVersion(version))
+	}
+
+	let mut position = 3;
+	let mut code_size = None;
+	let mut data_size = 0;
+	loop {
+		let kind = *code.get(position).ok_or(EofError::TruncatedHeader)?;
+		position += 1;
+		if kind == KIND_TERMINATOR {
+			break
+		}
+
+		let size = code.get(position..position + 2).ok_or(EofError::TruncatedHeader)?;
+		let size = usize::from(u16::from_be_bytes([size[0], size[1]]));
+		position += 2;
+		if size == 0 {
+			return Err(EofError::EmptySection)
+		}
+		match kind {
+			KIND_CODE if code_size.is_none() => code_size = Some(size),
+			KIND_DATA if code_size.is_some() && data_size == 0 => data_size = size,
+			kind => return Err(EofError::InvalidSectionKind(kind)),
+		}
+	}
+
+	let code_size = code_size.ok_or(EofError::MissingCodeSection)?;
+	let expected = position + code_size + data_size;
+	if code.len() != expected {
+		return Err(EofError::SizeMismatch { expected, actual: code.len() })
+	}
+
+	Ok(EofContainer {
+		version,
+		code: &code[position..position + code_size],
+		data: &code[position + code_size..],
+	})
+}
+
+/// Parse an EOF container and validate its code section (EIP-3670): every opcode
+/// is enabled in the config, and the last `PUSHn` is not truncated. The designated
+/// `INVALID` (`0xfe`) is allowed.
+pub fn validate_eof<'a>(code: &'a [u8], config: &Config) -> Result<EofContainer<'a>, EofError> {
+	let container = parse_eof(code)?;
+	for instruction in disassemble(container.code) {
+		match instruction.opcode {
+			Ok(Opcode::Push(n)) if instruction.push_data.len() < n as usize =>
+				return Err(EofError::TruncatedPush { pc: instruction.pc }),
+			Err(ExternalOpcode::Other(0xfe)) => (),
+			opcode if !is_enabled(opcode, config) =>
+				return Err(EofError::UndefinedOpcode { pc: instruction.pc, opcode: container.code[instruction.pc] }),
+			_ => (),
+		}
+	}
+	Ok(container)
+}
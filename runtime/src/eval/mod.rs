@@ -2,7 +2,9 @@
 mod macros;
 mod system;
 
-use crate::{Handler, Runtime, ExitReason, ExternalOpcode, CallScheme};
+use crate::{Handler, Runtime, ExitReason, ExitError, ExternalOpcode, CallScheme};
+
+pub use self::system::{finish_call, finish_create};
 
 pub enum Control<H: Handler> {
 	Continue,
@@ -16,6 +18,8 @@ pub fn eval<H: Handler>(state: &mut Runtime, opcode: ExternalOpcode, handler: &m
 		ExternalOpcode::Sha3 => system::sha3(state),
 		ExternalOpcode::Address => system::address(state),
 		ExternalOpcode::Balance => system::balance(state, handler),
+		ExternalOpcode::SelfBalance if !state._config.has_self_balance =>
+			Control::Exit(ExitError::IllegalOperation.into()),
 		ExternalOpcode::SelfBalance => system::selfbalance(state, handler),
 		ExternalOpcode::Origin => system::origin(state, handler),
 		ExternalOpcode::Caller => system::caller(state),
@@ -44,21 +48,36 @@ pub fn eval<H: Handler>(state: &mut Runtime, opcode: ExternalOpcode, handler: &m
 		ExternalOpcode::DelegateCall => system::call(state, CallScheme::DelegateCall, handler),
 		ExternalOpcode::StaticCall => system::call(state, CallScheme::StaticCall, handler),
 
+		ExternalOpcode::ChainId if !state._config.has_chain_id => Control::Exit(ExitError::IllegalOperation.into()),
 		ExternalOpcode::ChainId => system::chainid(state, handler),
+		ExternalOpcode::BaseFee if !state._config.has_base_fee => Control::Exit(ExitError::IllegalOperation.into()),
+		ExternalOpcode::BaseFee => system::basefee(state, handler),
 
 		// TRC10 token extension
+		ExternalOpcode::CallTokenId | ExternalOpcode::CallTokenValue |
+		ExternalOpcode::CallToken | ExternalOpcode::TokenBalance
+			if !state._config.has_token_transfer => Control::Exit(ExitError::IllegalOperation.into()),
 		ExternalOpcode::CallTokenId => system::calltokenid(state),
 		ExternalOpcode::CallTokenValue => system::calltokenvalue(state),
 		ExternalOpcode::CallToken => system::call(state, CallScheme::CallToken, handler),
 		ExternalOpcode::TokenBalance => system::tokenbalance(state, handler),
 
-		ExternalOpcode::IsContract => system::iscontract(state),
+		ExternalOpcode::IsContract if !state._config.has_iscontract =>
+			Control::Exit(ExitError::IllegalOperation.into()),
+		ExternalOpcode::IsContract => system::iscontract(state, handler),
 
 		// EVM 4.1
-		ExternalOpcode::IsWitness => system::iswitness(state),
+		ExternalOpcode::IsWitness => system::iswitness(state, handler),
 
 		ExternalOpcode::RewardBalance => system::rewardbalance(state, handler),
 		ExternalOpcode::WithdrawReward => system::withdrawreward(state, handler),
+		ExternalOpcode::Freeze | ExternalOpcode::Unfreeze | ExternalOpcode::FreezeExpireTime
+			if !state._config.has_freeze => Control::Exit(ExitError::IllegalOperation.into()),
+		ExternalOpcode::Freeze => system::freeze(state, handler),
+		ExternalOpcode::Unfreeze => system::unfreeze(state, handler),
+		ExternalOpcode::FreezeExpireTime => system::freezeexpiretime(state, handler),
+		ExternalOpcode::VoteWitness if !state._config.has_vote => Control::Exit(ExitError::IllegalOperation.into()),
+		ExternalOpcode::VoteWitness => system::votewitness(state, handler),
 
 		ExternalOpcode::Stake => system::stake(state, handler),
 		ExternalOpcode::Unstake => system::unstake(state, handler),
@@ -66,6 +85,18 @@ pub fn eval<H: Handler>(state: &mut Runtime, opcode: ExternalOpcode, handler: &m
 		ExternalOpcode::AssetIssue => system::assetissue(state, handler),
 		ExternalOpcode::UpdateAsset => system::updateasset(state, handler),
 
+		// Stake 2.0
+		ExternalOpcode::FreezeBalanceV2 | ExternalOpcode::UnfreezeBalanceV2 |
+		ExternalOpcode::CancelAllUnfreezeV2 | ExternalOpcode::WithdrawExpireUnfreeze |
+		ExternalOpcode::DelegateResource | ExternalOpcode::UnDelegateResource
+			if !state._config.has_stake_v2 => Control::Exit(ExitError::IllegalOperation.into()),
+		ExternalOpcode::FreezeBalanceV2 => system::freezebalancev2(state, handler),
+		ExternalOpcode::UnfreezeBalanceV2 => system::unfreezebalancev2(state, handler),
+		ExternalOpcode::CancelAllUnfreezeV2 => system::cancelallunfreezev2(state, handler),
+		ExternalOpcode::WithdrawExpireUnfreeze => system::withdrawexpireunfreeze(state, handler),
+		ExternalOpcode::DelegateResource => system::delegateresource(state, handler),
+		ExternalOpcode::UnDelegateResource => system::undelegateresource(state, handler),
+
 		ExternalOpcode::Other(opcode) => {
 			match handler.other(
 				opcode,
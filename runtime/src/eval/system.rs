@@ -1,11 +1,19 @@
 use core::cmp::min;
 use alloc::vec::Vec;
-use primitive_types::{H256, U256};
+use primitive_types::{H160, H256, U256};
 use sha3::{Keccak256, Digest};
-use crate::{Runtime, ExitError, Handler, Capture, Transfer, ExitReason,
-			CreateScheme, CallScheme, Context, ExitSucceed, ExitFatal};
+use crate::{Runtime, ExitError, Handler, Capture, Transfer, Log, Suicide, ExitReason,
+			CreateScheme, CallScheme, Context, ExitSucceed, ExitFatal, PendingInterrupt, Bytes};
 use super::Control;
 
+/// TRON: TRC-10 token ids are greater than this value.
+const MIN_TOKEN_ID: u64 = 1_000_000;
+
+/// TRON: token id must be in (MIN_TOKEN_ID, i64::MAX] once multisig is allowed.
+fn is_valid_token_id(token_id: U256) -> bool {
+	token_id > U256::from(MIN_TOKEN_ID) && token_id <= U256::from(i64::MAX)
+}
+
 pub fn sha3<H: Handler>(runtime: &mut Runtime) -> Control<H> {
 	pop_u256!(runtime, from, len);
 
@@ -20,7 +28,7 @@ pub fn sha3<H: Handler>(runtime: &mut Runtime) -> Control<H> {
 	};
 
 	let ret = Keccak256::digest(data.as_slice());
-	push!(runtime, H256::from_slice(ret.as_slice()));
+	push!(runtime, H256::from_slice(&ret));
 
 	Control::Continue
 }
@@ -31,6 +39,12 @@ pub fn chainid<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
 	Control::Continue
 }
 
+pub fn basefee<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
+	push_u256!(runtime, handler.block_base_fee_per_gas());
+
+	Control::Continue
+}
+
 pub fn address<H: Handler>(runtime: &mut Runtime) -> Control<H> {
 	let ret = H256::from(runtime.context.address);
 	push!(runtime, ret);
@@ -46,7 +60,7 @@ pub fn balance<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
 }
 
 pub fn selfbalance<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
-	push_u256!(runtime, handler.balance(runtime.context.address));
+	push_u256!(runtime, handler.self_balance(runtime.context.address));
 
 	Control::Continue
 }
@@ -143,7 +157,23 @@ pub fn returndatacopy<H: Handler>(runtime: &mut Runtime) -> Control<H> {
 
 pub fn blockhash<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
 	pop_u256!(runtime, number);
-	push!(runtime, handler.block_hash(number));
+
+	// TRON: java-tron reads the number as a signed 32-bit integer, so numbers that
+	// do not fit are out of range instead of truncated.
+	let number = if runtime._config.has_buggy_blockhash {
+		Some(number).filter(|n| *n <= U256::from(i32::MAX))
+	} else {
+		Some(number)
+	};
+
+	// Only the 256 most recent blocks are available.
+	let current = handler.block_number();
+	let hash = match number {
+		Some(number) if number < current && number >= current.saturating_sub(U256::from(256)) =>
+			handler.block_hash(number),
+		_ => H256::default(),
+	};
+	push!(runtime, hash);
 
 	Control::Continue
 }
@@ -167,7 +197,11 @@ pub fn number<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
 }
 
 pub fn difficulty<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
-	push_u256!(runtime, handler.block_difficulty());
+	// EIP-4399: PREVRANDAO, falling back to the difficulty if the randomness is unknown.
+	match handler.block_randomness() {
+		Some(randomness) if runtime._config.has_prev_randao => { push!(runtime, randomness); },
+		_ => { push_u256!(runtime, handler.block_difficulty()); },
+	}
 	Control::Continue
 }
 
@@ -178,13 +212,27 @@ pub fn gaslimit<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
 
 pub fn sload<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
 	pop!(runtime, index);
-	push!(runtime, handler.storage(runtime.context.address, index).unwrap_or_default());
+	let value = handler.storage(runtime.context.address, index).unwrap_or_default();
+	push!(runtime, value);
+
+	event!(SLoad {
+		address: runtime.context.address,
+		index,
+		value
+	});
 
 	Control::Continue
 }
 
 pub fn sstore<H: Handler>(runtime: &mut Runtime, handler: &mut H) -> Control<H> {
 	pop!(runtime, index, value);
+
+	event!(SStore {
+		address: runtime.context.address,
+		index,
+		value
+	});
+
 	match handler.set_storage(runtime.context.address, index, value) {
 		Ok(()) => Control::Continue,
 		Err(e) => Control::Exit(e.into()),
@@ -218,7 +266,14 @@ pub fn log<H: Handler>(runtime: &mut Runtime, n: u8, handler: &mut H) -> Control
 		}
 	}
 
-	match handler.log(runtime.context.address, topics, data) {
+	let address = runtime.context.address;
+	let ret = if runtime.capture_logs {
+		let log = Log { address, topics: topics.clone(), data: data.clone() };
+		handler.log(address, topics, data).map(|()| runtime.logs.push(log))
+	} else {
+		handler.log(address, topics, data)
+	};
+	match ret {
 		Ok(()) => Control::Continue,
 		Err(e) => Control::Exit(e.into()),
 	}
@@ -227,7 +282,16 @@ pub fn log<H: Handler>(runtime: &mut Runtime, n: u8, handler: &mut H) -> Control
 pub fn suicide<H: Handler>(runtime: &mut Runtime, handler: &mut H) -> Control<H> {
 	pop!(runtime, target);
 
-	match handler.mark_delete(runtime.context.address, target.into()) {
+	let address = runtime.context.address;
+	let target = H160::from(target);
+	let suicide = Suicide {
+		address,
+		target,
+		beneficiary: Some(target).filter(|target| *target != address),
+		// TRON: AllowTvmTransferTrc10 also moves TRC-10 balances
+		sweep_tokens: runtime._config.has_token_transfer,
+	};
+	match handler.mark_delete(suicide) {
 		Ok(()) => (),
 		Err(e) => return Control::Exit(e.into()),
 	}
@@ -258,7 +322,7 @@ pub fn create<H: Handler>(
 
 	let scheme = if is_create2 {
 		pop!(runtime, salt);
-		let code_hash = H256::from_slice(Keccak256::digest(&code).as_slice());
+		let code_hash = H256::from_slice(&Keccak256::digest(&code));
 		if runtime._config.has_real_create2 {
 			CreateScheme::Create2 {
 				caller: runtime.context.address,
@@ -284,34 +348,44 @@ pub fn create<H: Handler>(
 
 	match handler.create(runtime.context.address, scheme, value, code, None) {
 		Capture::Exit((reason, address, return_data)) => {
-			runtime.return_data_buffer = return_data;
-			let create_address: H256 = address.map(|a| a.into()).unwrap_or_default();
-			match reason {
-				ExitReason::Succeed(_) => {
-					push!(runtime, create_address.into());
-					Control::Continue
-				},
-				ExitReason::Revert(_) => {
-					push!(runtime, H256::default());
-					Control::Continue
-				},
-				ExitReason::Error(_) => {
-					push!(runtime, H256::default());
-					Control::Continue
-				},
-				ExitReason::Fatal(e) => {
-					push!(runtime, H256::default());
-					Control::Exit(e.into())
-				},
+			match finish_create(runtime, reason, address, return_data) {
+				Ok(()) => Control::Continue,
+				Err(e) => Control::Exit(e),
 			}
 		},
 		Capture::Trap(interrupt) => {
-			push!(runtime, H256::default());
+			runtime.pending = Some(PendingInterrupt::Create);
 			Control::CreateInterrupt(interrupt)
 		},
 	}
 }
 
+/// Apply the result of a `CREATE` to the runtime, returning the exit reason if the
+/// runtime exits.
+pub fn finish_create(
+	runtime: &mut Runtime,
+	reason: ExitReason,
+	address: Option<H160>,
+	return_data: Vec<u8>,
+) -> Result<(), ExitReason> {
+	runtime.return_data_buffer = return_data;
+	let create_address: H256 = address.map(|a| a.into()).unwrap_or_default();
+	match reason {
+		ExitReason::Succeed(_) => {
+			runtime.machine.stack_mut().push(create_address)?;
+			Ok(())
+		},
+		ExitReason::Revert(_) | ExitReason::Error(_) => {
+			runtime.machine.stack_mut().push(H256::default())?;
+			Ok(())
+		},
+		ExitReason::Fatal(e) => {
+			runtime.machine.stack_mut().push(H256::default())?;
+			Err(e.into())
+		},
+	}
+}
+
 pub fn call<'config, H: Handler>(
 	runtime: &mut Runtime,
 	scheme: CallScheme,
@@ -330,15 +404,26 @@ pub fn call<'config, H: Handler>(
 	let (value, token_id, token_value) = match scheme {
 		CallScheme::Call | CallScheme::CallCode => {
 			pop_u256!(runtime, value);
-			(value, U256::zero(), U256::zero())
+			(value, None, U256::zero())
 		},
 		CallScheme::DelegateCall | CallScheme::StaticCall => {
-			(U256::zero(), U256::zero(), U256::zero())
+			(U256::zero(), None, U256::zero())
 		},
 		CallScheme::CallToken => {
 			pop_u256!(runtime, token_value);
 			pop_u256!(runtime, token_id);
-			(U256::zero(), token_id, token_value)
+			if runtime._config.has_token_id_check {
+				// TRON: BytecodeExecutionException as Unknown
+				if !is_valid_token_id(token_id) {
+					return Control::Exit(ExitError::Unknown.into())
+				}
+				(U256::zero(), Some(token_id), token_value)
+			} else if token_id == U256::zero() {
+				// TRON: before multisig, token id 0 means a plain TRX transfer
+				(token_value, None, U256::zero())
+			} else {
+				(U256::zero(), Some(token_id), token_value)
+			}
 		}
 	};
 
@@ -348,27 +433,20 @@ pub fn call<'config, H: Handler>(
 	try_or_fail!(runtime.machine.memory_mut().resize_offset(out_offset, out_len));
 
 	let input = if in_len == U256::zero() {
-		Vec::new()
+		Bytes::new()
 	} else {
 		let in_offset = as_usize_or_fail!(in_offset);
 		let in_len = as_usize_or_fail!(in_len);
 
-		runtime.machine.memory().get(in_offset, in_len)
+		runtime.machine.memory().get(in_offset, in_len).into()
 	};
 
 	let context = match scheme {
-		CallScheme::Call | CallScheme::StaticCall => Context {
-			address: to.into(),
-			caller: runtime.context.address,
-			call_value: value,
-			call_token_id: U256::from(0),
-			call_token_value: U256::from(0),
-		},
-		CallScheme::CallToken => Context {
+		CallScheme::Call | CallScheme::StaticCall | CallScheme::CallToken => Context {
 			address: to.into(),
 			caller: runtime.context.address,
 			call_value: value,
-			call_token_id: token_id,
+			call_token_id: token_id.unwrap_or_default(),
 			call_token_value: token_value,
 		},
 		CallScheme::CallCode => Context {
@@ -387,30 +465,28 @@ pub fn call<'config, H: Handler>(
 		},
 	};
 
-	let transfer = if scheme == CallScheme::Call {
-		Some(Transfer {
+	let transfer = if scheme == CallScheme::Call || scheme == CallScheme::CallToken {
+		let transfer = Transfer {
 			source: runtime.context.address,
 			target: to.into(),
-			value: value.into(),
-			token_id: U256::from(0),
-			token_value: U256::from(0),
-		})
+			value,
+			token_id,
+			token_value,
+		};
+		if let Some(token_id) = token_id {
+			if let Err(e) = handler.validate_token(token_id).and_then(|()| handler.validate_token_transfer(&transfer)) {
+				return Control::Exit(e.into())
+			}
+		}
+		Some(transfer)
 	} else if scheme == CallScheme::CallCode {
 		Some(Transfer {
 			source: runtime.context.address,
 			target: runtime.context.address,
-			value: value.into(),
-			token_id: U256::from(0),
+			value,
+			token_id: None,
 			token_value: U256::from(0),
 		})
-	} else if scheme == CallScheme::CallToken {
-		Some(Transfer {
-			source: runtime.context.address,
-			target: to.into(),
-			value: U256::from(0),
-			token_id,
-			token_value,
-		})
 	} else {
 		None
 	};
@@ -419,58 +495,63 @@ pub fn call<'config, H: Handler>(
 
 	match handler.call(to.into(), transfer, input, gas, scheme == CallScheme::StaticCall, context) {
 		Capture::Exit((reason, return_data)) => {
-			runtime.return_data_buffer = return_data;
-			let target_len = min(out_len, U256::from(runtime.return_data_buffer.len()));
-
-			match reason {
-				ExitReason::Succeed(_) => {
-					match runtime.machine.memory_mut().copy_large(
-						out_offset,
-						U256::zero(),
-						target_len,
-						&runtime.return_data_buffer[..],
-					) {
-						Ok(()) => {
-							push_u256!(runtime, U256::one());
-							Control::Continue
-						},
-						Err(_) => {
-							push_u256!(runtime, U256::zero());
-							Control::Continue
-						},
-					}
-				},
-				ExitReason::Revert(_) => {
-					push_u256!(runtime, U256::zero());
-
-					let _ = runtime.machine.memory_mut().copy_large(
-						out_offset,
-						U256::zero(),
-						target_len,
-						&runtime.return_data_buffer[..],
-					);
-
-					Control::Continue
-				},
-				ExitReason::Error(_) => {
-					push_u256!(runtime, U256::zero());
-
-					Control::Continue
-				},
-				ExitReason::Fatal(e) => {
-					push_u256!(runtime, U256::zero());
-
-					Control::Exit(e.into())
-				},
+			match finish_call(runtime, reason, return_data, out_offset, out_len) {
+				Ok(()) => Control::Continue,
+				Err(e) => Control::Exit(e),
 			}
 		},
 		Capture::Trap(interrupt) => {
-			push!(runtime, H256::default());
+			runtime.pending = Some(PendingInterrupt::Call { out_offset, out_len });
 			Control::CallInterrupt(interrupt)
 		},
 	}
 }
 
+/// Apply the result of a call to the runtime, returning the exit reason if the
+/// runtime exits.
+pub fn finish_call(
+	runtime: &mut Runtime,
+	reason: ExitReason,
+	return_data: Vec<u8>,
+	out_offset: U256,
+	out_len: U256,
+) -> Result<(), ExitReason> {
+	runtime.return_data_buffer = return_data;
+	let target_len = min(out_len, U256::from(runtime.return_data_buffer.len()));
+
+	let success = match reason {
+		ExitReason::Succeed(_) => {
+			runtime.machine.memory_mut().copy_large(
+				out_offset,
+				U256::zero(),
+				target_len,
+				&runtime.return_data_buffer[..],
+			).is_ok()
+		},
+		ExitReason::Revert(_) => {
+			let _ = runtime.machine.memory_mut().copy_large(
+				out_offset,
+				U256::zero(),
+				target_len,
+				&runtime.return_data_buffer[..],
+			);
+			false
+		},
+		ExitReason::Error(_) | ExitReason::Fatal(_) => false,
+	};
+
+	let mut value = H256::default();
+	if success {
+		U256::one().to_big_endian(&mut value[..]);
+	}
+	runtime.machine.stack_mut().push(value)?;
+
+	match reason {
+		ExitReason::Fatal(e) => Err(e.into()),
+		_ => Ok(()),
+	}
+}
+
 pub fn calltokenid<H: Handler>(runtime: &mut Runtime) -> Control<H> {
 	let mut ret = H256::default();
 	runtime.context.call_token_id.to_big_endian(&mut ret[..]);
@@ -491,45 +572,111 @@ pub fn tokenbalance<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H
 	pop_u256!(runtime, token_id);
 	pop!(runtime, address);
 
+	if runtime._config.has_token_id_check && !is_valid_token_id(token_id) {
+		// TRON: BytecodeExecutionException as Unknown
+		return Control::Exit(ExitError::Unknown.into())
+	}
+	if runtime._config.has_token_balance_check {
+		if let Err(e) = handler.validate_token(token_id) {
+			return Control::Exit(e.into())
+		}
+	}
+
 	push_u256!(runtime, handler.token_balance(address.into(), token_id));
 
 	Control::Continue
 }
 
-pub fn iscontract<H: Handler>(runtime: &mut Runtime) -> Control<H> {
-	pop!(runtime, _address);
-	// TODO: impl address type
-	let is_contract = false;
-	push!(runtime, H256::from_low_u64_be(is_contract as _));
+pub fn iscontract<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
+	pop!(runtime, address);
+
+	let ret = handler.is_contract(address.into());
+	push_bool(runtime, ret)
+}
+
+pub fn iswitness<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
+	pop!(runtime, address);
+
+	let ret = handler.is_witness(address.into());
+	push_bool(runtime, ret)
+}
+
+pub fn rewardbalance<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
+	pop!(runtime, address);
+
+	push_u256!(runtime, handler.reward_balance(address.into()));
 
 	Control::Continue
 }
 
-pub fn iswitness<H: Handler>(runtime: &mut Runtime) -> Control<H> {
-	pop!(runtime, _address);
-	// TODO: impl address type
-	let is_witness = false;
-	push!(runtime, H256::from_low_u64_be(is_witness as _));
+pub fn withdrawreward<H: Handler>(runtime: &mut Runtime, handler: &mut H) -> Control<H> {
+	handler.incr_nonce();
+	let amount = handler.withdraw_reward(runtime.context.address);
+	push_u256!(runtime, amount);
 
 	Control::Continue
 }
 
-pub fn rewardbalance<H: Handler>(runtime: &mut Runtime, _handler: &H) -> Control<H> {
-	pop!(runtime, _address);
+pub fn freeze<H: Handler>(runtime: &mut Runtime, handler: &mut H) -> Control<H> {
+	pop_u256!(runtime, resource_type, amount);
+	pop!(runtime, receiver);
 
-	// push_u256!(runtime, handler.reward_balance(address.into(), token_id));
-	push_u256!(runtime, U256::zero());
+	handler.incr_nonce();
+	let ret = handler.freeze(runtime.context.address, receiver.into(), amount, resource_type);
+	push_bool(runtime, ret)
+}
 
-	Control::Continue
+pub fn unfreeze<H: Handler>(runtime: &mut Runtime, handler: &mut H) -> Control<H> {
+	pop_u256!(runtime, resource_type);
+	pop!(runtime, receiver);
+
+	handler.incr_nonce();
+	let ret = handler.unfreeze(runtime.context.address, receiver.into(), resource_type);
+	push_bool(runtime, ret)
 }
 
-pub fn withdrawreward<H: Handler>(runtime: &mut Runtime, _handler: &H) -> Control<H> {
-	let _address = runtime.context.address;
-	push_u256!(runtime, U256::zero());
+pub fn freezeexpiretime<H: Handler>(runtime: &mut Runtime, handler: &H) -> Control<H> {
+	pop_u256!(runtime, resource_type);
+	pop!(runtime, target);
+
+	push_u256!(runtime, handler.freeze_expire_time(runtime.context.address, target.into(), resource_type));
 
 	Control::Continue
 }
 
+pub fn votewitness<H: Handler>(runtime: &mut Runtime, handler: &mut H) -> Control<H> {
+	pop_u256!(runtime, amount_len, amount_offset, witness_len, witness_offset);
+
+	handler.incr_nonce();
+	if amount_len != witness_len {
+		return push_bool(runtime, false)
+	}
+
+	// Dynamic arrays, data starts after the length word.
+	let len = as_usize_or_fail!(witness_len, ExitError::OutOfOffset);
+	let size = try_or_fail!(len.checked_mul(32).ok_or(ExitError::OutOfOffset));
+	let witness_offset = try_or_fail!(witness_offset.checked_add(U256::from(32)).ok_or(ExitError::OutOfOffset));
+	let amount_offset = try_or_fail!(amount_offset.checked_add(U256::from(32)).ok_or(ExitError::OutOfOffset));
+
+	try_or_fail!(runtime.machine.memory_mut().resize_offset(witness_offset, U256::from(size)));
+	try_or_fail!(runtime.machine.memory_mut().resize_offset(amount_offset, U256::from(size)));
+
+	let (witnesses, amounts) = if size == 0 {
+		(Vec::new(), Vec::new())
+	} else {
+		let witness_offset = as_usize_or_fail!(witness_offset, ExitError::OutOfOffset);
+		let amount_offset = as_usize_or_fail!(amount_offset, ExitError::OutOfOffset);
+		(runtime.machine.memory().get(witness_offset, size), runtime.machine.memory().get(amount_offset, size))
+	};
+
+	let votes = witnesses.chunks(32).zip(amounts.chunks(32))
+		.map(|(witness, amount)| (H256::from_slice(witness).into(), U256::from_big_endian(amount)))
+		.collect::<Vec<_>>();
+
+	let ret = handler.vote_witness(runtime.context.address, votes);
+	push_bool(runtime, ret)
+}
+
 pub fn stake<H: Handler>(runtime: &mut Runtime, _handler: &H) -> Control<H> {
 	pop!(runtime, _witness_address);
 	pop_u256!(runtime, _amount);
@@ -572,9 +719,67 @@ pub fn updateasset<H: Handler>(runtime: &mut Runtime, _handler: &H) -> Control<H
 	let url = runtime.machine.memory_mut().get(url_offset.as_usize() + 32, url_len.as_usize());
 	let desc = runtime.machine.memory_mut().get(desc_offset.as_usize() + 32, desc_len.as_usize());
 
-	eprintln!("URL: {:?}\nDESC: {:?}", url, desc);
+	debug!("updateasset address={:?} url={:?} desc={:?}", runtime.context.address, url, desc);
 
 	push_u256!(runtime, U256::zero());
 
 	Control::Continue
 }
+
+fn push_bool<H: Handler>(runtime: &mut Runtime, value: bool) -> Control<H> {
+	if value {
+		push_u256!(runtime, U256::one());
+	} else {
+		push_u256!(runtime, U256::zero());
+	}
+
+	Control::Continue
+}
+
+pub fn freezebalancev2<H: Handler>(runtime: &mut Runtime, handler: &mut H) -> Control<H> {
+	pop_u256!(runtime, resource_type, amount);
+
+	handler.incr_nonce();
+	let ret = handler.freeze_balance_v2(runtime.context.address, amount, resource_type);
+	push_bool(runtime, ret)
+}
+
+pub fn unfreezebalancev2<H: Handler>(runtime: &mut Runtime, handler: &mut H) -> Control<H> {
+	pop_u256!(runtime, resource_type, amount);
+
+	handler.incr_nonce();
+	let ret = handler.unfreeze_balance_v2(runtime.context.address, amount, resource_type);
+	push_bool(runtime, ret)
+}
+
+pub fn cancelallunfreezev2<H: Handler>(runtime: &mut Runtime, handler: &mut H) -> Control<H> {
+	handler.incr_nonce();
+	let ret = handler.cancel_all_unfreeze_v2(runtime.context.address);
+	push_bool(runtime, ret)
+}
+
+pub fn withdrawexpireunfreeze<H: Handler>(runtime: &mut Runtime, handler: &mut H) -> Control<H> {
+	handler.incr_nonce();
+	let amount = handler.withdraw_expire_unfreeze(runtime.context.address);
+	push_u256!(runtime, amount);
+
+	Control::Continue
+}
+
+pub fn delegateresource<H: Handler>(runtime: &mut Runtime, handler: &mut H) -> Control<H> {
+	pop_u256!(runtime, resource_type, amount);
+	pop!(runtime, receiver);
+
+	handler.incr_nonce();
+	let ret = handler.delegate_resource(runtime.context.address, receiver.into(), amount, resource_type);
+	push_bool(runtime, ret)
+}
+
+pub fn undelegateresource<H: Handler>(runtime: &mut Runtime, handler: &mut H) -> Control<H> {
+	pop_u256!(runtime, resource_type, amount);
+	pop!(runtime, receiver);
+
+	handler.incr_nonce();
+	let ret = handler.undelegate_resource(runtime.context.address, receiver.into(), amount, resource_type);
+	push_bool(runtime, ret)
+}
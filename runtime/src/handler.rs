@@ -1,9 +1,9 @@
 use alloc::vec::Vec;
 use primitive_types::{H160, H256, U256};
 use crate::{Capture, Stack, ExitError, Opcode, ExternalOpcode,
-			CreateScheme, Context, Machine, ExitReason};
+			CreateScheme, Context, Machine, ExitReason, Bytes};
 
-/// Transfer from source to target, with given value.
+/// Transfer from source to target, with given value and optional TRC-10 token.
 #[derive(Clone, Debug, Default)]
 pub struct Transfer {
 	/// Source address.
@@ -12,12 +12,38 @@ pub struct Transfer {
 	pub target: H160,
 	/// Transfer value.
 	pub value: U256,
-	/// Transfer token id.
-	pub token_id: U256,
+	/// Transfer token id, `None` if no TRC-10 token is transferred.
+	pub token_id: Option<U256>,
 	/// Transfer token value.
 	pub token_value: U256,
 }
 
+/// Log information.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Log {
+	/// Source address.
+	pub address: H160,
+	/// Topics.
+	pub topics: Vec<H256>,
+	/// Log data.
+	pub data: Vec<u8>,
+}
+
+/// Self-destruct of a contract, with the recipient of its funds.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Suicide {
+	/// Address of the destroyed contract.
+	pub address: H160,
+	/// Target of `SELFDESTRUCT`.
+	pub target: H160,
+	/// Recipient of the balance, `None` if the target is the contract itself and
+	/// the balance is burnt.
+	pub beneficiary: Option<H160>,
+	/// TRON: whether TRC-10 balances go along with the balance.
+	pub sweep_tokens: bool,
+}
+
 /// EVM context handler.
 pub trait Handler {
 	/// Type of `CREATE` interrupt.
@@ -31,12 +57,17 @@ pub trait Handler {
 
 	/// Get balance of address.
 	fn balance(&self, address: H160) -> U256;
+	/// Get balance of the executing address, for `SELFBALANCE`. Defaults to
+	/// `balance`.
+	fn self_balance(&self, address: H160) -> U256 {
+		self.balance(address)
+	}
 	/// Get code size of address.
 	fn code_size(&self, address: H160) -> U256;
 	/// Get code hash of address.
 	fn code_hash(&self, address: H160) -> H256;
 	/// Get code of address.
-	fn code(&self, address: H160) -> Vec<u8>;
+	fn code(&self, address: H160) -> Bytes;
 	/// Get storage value of address at index.
 	fn storage(&self, address: H160, index: H256) -> Option<H256>;
 	/// Get original storage value of address at index.
@@ -48,7 +79,8 @@ pub trait Handler {
 	fn gas_price(&self) -> U256;
 	/// Get execution origin.
 	fn origin(&self) -> H160;
-	/// Get environmental block hash.
+	/// Get environmental block hash. Only called for the 256 blocks before the
+	/// current one.
 	fn block_hash(&self, number: U256) -> H256;
 	/// Get environmental block number.
 	fn block_number(&self) -> U256;
@@ -58,22 +90,32 @@ pub trait Handler {
 	fn block_timestamp(&self) -> U256;
 	/// Get environmental block difficulty.
 	fn block_difficulty(&self) -> U256;
+	/// Get environmental block randomness (EIP-4399), if known. Defaults to
+	/// unknown.
+	fn block_randomness(&self) -> Option<H256> {
+		None
+	}
 	/// Get environmental gas limit.
 	fn block_gas_limit(&self) -> U256;
-	/// Get environmental chain ID.
+	/// Get environmental chain ID. TRON uses the genesis block hash.
 	fn chain_id(&self) -> U256;
+	/// Get environmental base fee per gas, energy price for TRON.
+	fn block_base_fee_per_gas(&self) -> U256;
 
 	/// Check whether an address exists.
 	fn exists(&self, address: H160) -> bool;
 	/// Check whether an address has already been deleted.
 	fn deleted(&self, address: H160) -> bool;
+	/// Check whether an address, or the storage slot of address at index, is not yet
+	/// accessed in the transaction (EIP-2929).
+	fn is_cold(&self, address: H160, index: Option<H256>) -> bool;
 
 	/// Set storage value of address at index.
 	fn set_storage(&mut self, address: H160, index: H256, value: H256) -> Result<(), ExitError>;
 	/// Create a log owned by address with given topics and data.
 	fn log(&mut self, address: H160, topcis: Vec<H256>, data: Vec<u8>) -> Result<(), ExitError>;
-	/// Mark an address to be deleted, with funds transferred to target.
-	fn mark_delete(&mut self, address: H160, target: H160) -> Result<(), ExitError>;
+	/// Mark an address to be deleted, with funds transferred to the beneficiary.
+	fn mark_delete(&mut self, suicide: Suicide) -> Result<(), ExitError>;
 	/// Invoke a create operation.
 	fn create(
 		&mut self,
@@ -95,7 +137,7 @@ pub trait Handler {
 		&mut self,
 		code_address: H160,
 		transfer: Option<Transfer>,
-		input: Vec<u8>,
+		input: Bytes,
 		target_gas: Option<usize>,
 		is_static: bool,
 		context: Context,
@@ -128,6 +170,48 @@ pub trait Handler {
 
 	/// Get token balance of address.
 	fn token_balance(&self, address: H160, token_id: U256) -> U256;
+	/// Validate the TRC-10 asset of `CALLTOKEN` or `TOKENBALANCE`, such as that it
+	/// exists.
+	fn validate_token(&self, _token_id: U256) -> Result<(), ExitError> {
+		Ok(())
+	}
+	/// Validate the TRC-10 token transfer of `CALLTOKEN` before it is made, such as
+	/// its value against the precision and transfer rules of the asset.
+	fn validate_token_transfer(&self, _transfer: &Transfer) -> Result<(), ExitError> {
+		Ok(())
+	}
+
+	/// Whether the address is a smart contract.
+	fn is_contract(&self, address: H160) -> bool;
+
+	/// Freeze balance of address for receiver's resource.
+	fn freeze(&mut self, address: H160, receiver: H160, amount: U256, resource_type: U256) -> bool;
+	/// Unfreeze balance of address frozen for receiver's resource.
+	fn unfreeze(&mut self, address: H160, receiver: H160, resource_type: U256) -> bool;
+	/// Expire time in seconds of balance of address frozen for target's resource.
+	fn freeze_expire_time(&self, address: H160, target: H160, resource_type: U256) -> U256;
+
+	/// Whether the address is a witness (SR candidate).
+	fn is_witness(&self, address: H160) -> bool;
+	/// Get unwithdrawn voting reward of address.
+	fn reward_balance(&self, address: H160) -> U256;
+	/// Withdraw voting reward of address, returning the withdrawn amount.
+	fn withdraw_reward(&mut self, address: H160) -> U256;
+	/// Replace votes of address with the given witness and vote count pairs.
+	fn vote_witness(&mut self, address: H160, votes: Vec<(H160, U256)>) -> bool;
+
+	/// Stake 2.0: freeze balance of address for resource.
+	fn freeze_balance_v2(&mut self, address: H160, amount: U256, resource_type: U256) -> bool;
+	/// Stake 2.0: unfreeze balance of address for resource.
+	fn unfreeze_balance_v2(&mut self, address: H160, amount: U256, resource_type: U256) -> bool;
+	/// Stake 2.0: cancel all pending unfreezes of address.
+	fn cancel_all_unfreeze_v2(&mut self, address: H160) -> bool;
+	/// Stake 2.0: withdraw expired unfreezes of address, returning the withdrawn amount.
+	fn withdraw_expire_unfreeze(&mut self, address: H160) -> U256;
+	/// Stake 2.0: delegate frozen resource from address to receiver.
+	fn delegate_resource(&mut self, address: H160, receiver: H160, amount: U256, resource_type: U256) -> bool;
+	/// Stake 2.0: undelegate resource from receiver back to address.
+	fn undelegate_resource(&mut self, address: H160, receiver: H160, amount: U256, resource_type: U256) -> bool;
 
 	/// Nonce of current transaction root.
 	fn nonce(&self) -> u64;
@@ -0,0 +1,22 @@
+use crate::{Context, ExitReason, ExternalOpcode, Machine, Opcode};
+
+/// Hooks called by `Runtime` around each opcode. All methods default to no-op.
+pub trait Inspector {
+	/// Called before the opcode is validated and executed.
+	fn step(
+		&mut self,
+		_context: &Context,
+		_opcode: Result<Opcode, ExternalOpcode>,
+		_machine: &Machine,
+	) { }
+
+	/// Called after the opcode is executed or failed, with `Ok` if the runtime continues.
+	fn step_result(
+		&mut self,
+		_context: &Context,
+		_result: &Result<(), ExitReason>,
+		_machine: &Machine,
+	) { }
+}
+
+impl Inspector for () { }
@@ -1,4 +1,7 @@
-use crate::{Runtime, Handler, ExitFatal};
+use core::mem;
+use alloc::vec::Vec;
+use primitive_types::{H160, U256};
+use crate::{Runtime, Handler, ExitFatal, ExitReason};
 
 /// Interrupt resolution.
 pub enum Resolve<'a, 'config, H: Handler> {
@@ -8,6 +11,46 @@ pub enum Resolve<'a, 'config, H: Handler> {
 	Call(H::CallInterrupt, ResolveCall<'a, 'config>),
 }
 
+impl<'a, 'config, H: Handler> Resolve<'a, 'config, H> {
+	/// Detach the interrupt from the runtime, so that it can be stored and resolved
+	/// later with `Runtime::feedback_call` or `Runtime::feedback_create`.
+	pub fn detach(self) -> Interrupt<H> {
+		match self {
+			Resolve::Create(interrupt, resolve) => {
+				mem::forget(resolve);
+				Interrupt::Create(interrupt)
+			},
+			Resolve::Call(interrupt, resolve) => {
+				mem::forget(resolve);
+				Interrupt::Call(interrupt)
+			},
+		}
+	}
+}
+
+/// Interrupt detached from the runtime.
+pub enum Interrupt<H: Handler> {
+	/// Create interrupt.
+	Create(H::CreateInterrupt),
+	/// Call interrupt.
+	Call(H::CallInterrupt),
+}
+
+/// Interrupt the runtime is waiting for, with what is needed to apply its result.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PendingInterrupt {
+	/// `CREATE` or `CREATE2`, pushing the created address.
+	Create,
+	/// A call, copying return data to the output range of memory.
+	Call {
+		/// Output memory offset.
+		out_offset: U256,
+		/// Output memory length.
+		out_len: U256,
+	},
+}
+
 /// Create interrupt resolution.
 pub struct ResolveCreate<'a, 'config> {
 	runtime: &'a mut Runtime<'config>,
@@ -17,6 +60,18 @@ impl<'a, 'config> ResolveCreate<'a, 'config> {
 	pub(crate) fn new(runtime: &'a mut Runtime<'config>) -> Self {
 		Self { runtime }
 	}
+
+	/// Resolve the interrupt with the result of the create.
+	pub fn resolve(
+		self,
+		reason: ExitReason,
+		address: Option<H160>,
+		return_data: Vec<u8>,
+	) -> Result<(), ExitReason> {
+		let ret = self.runtime.feedback_create(reason, address, return_data);
+		mem::forget(self);
+		ret
+	}
 }
 
 impl<'a, 'config> Drop for ResolveCreate<'a, 'config> {
@@ -35,6 +90,13 @@ impl<'a, 'config> ResolveCall<'a, 'config> {
 	pub(crate) fn new(runtime: &'a mut Runtime<'config>) -> Self {
 		Self { runtime }
 	}
+
+	/// Resolve the interrupt with the result of the call.
+	pub fn resolve(self, reason: ExitReason, return_data: Vec<u8>) -> Result<(), ExitReason> {
+		let ret = self.runtime.feedback_call(reason, return_data);
+		mem::forget(self);
+		ret
+	}
 }
 
 impl<'a, 'config> Drop for ResolveCall<'a, 'config> {
@@ -1,31 +1,98 @@
 //! Runtime layer for EVM.
 
 // #![deny(warnings)]
-#![forbid(unsafe_code, missing_docs, unused_variables, unused_imports)]
+#![forbid(unsafe_code, missing_docs, unused_variables)]
+// `environmental!` allows unused imports in its expansion.
+#![cfg_attr(not(feature = "tracing"), forbid(unused_imports))]
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
 extern crate alloc;
 
+#[cfg(feature = "tracing")]
+pub mod tracing;
+
+#[cfg(feature = "tracing")]
+macro_rules! event {
+	($x:expr) => {
+		use crate::tracing::Event::*;
+		$x.emit();
+	}
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! event {
+	($x:expr) => { }
+}
+
+#[cfg(feature = "log")]
+macro_rules! debug {
+	($($arg:tt)*) => { log::debug!(target: "evm::runtime", $($arg)*) }
+}
+
+#[cfg(not(feature = "log"))]
+macro_rules! debug {
+	($($arg:tt)*) => { { let _ = format_args!($($arg)*); } }
+}
+
+pub mod address;
+
 mod eval;
 mod context;
 mod interrupt;
 mod handler;
+mod builder;
+mod inspector;
+mod revert;
+mod contract_result;
+mod validator;
+mod eof;
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
+#[cfg(feature = "with-serde")]
+mod params;
+#[cfg(feature = "async")]
+mod async_handler;
 
 pub use evm_core::*;
 
 pub use crate::context::{CreateScheme, CallScheme, Context};
-pub use crate::interrupt::{Resolve, ResolveCall, ResolveCreate};
-pub use crate::handler::{Transfer, Handler};
+pub use crate::interrupt::{Resolve, ResolveCall, ResolveCreate, Interrupt, PendingInterrupt};
+pub use crate::handler::{Transfer, Log, Suicide, Handler};
+pub use crate::builder::{ConfigBuilder, ConfigError};
+pub use crate::inspector::Inspector;
+pub use crate::revert::RevertReason;
+pub use crate::contract_result::ContractResult;
+pub use crate::validator::{Diagnostic, Severity, Validation, validate};
+pub use crate::eof::{EofContainer, EofError, EOF_MAGIC, is_eof, parse_eof, validate_eof};
+#[cfg(feature = "async")]
+pub use crate::async_handler::{AsyncHandler, StateAccess};
+#[cfg(feature = "with-serde")]
+pub use crate::params::{ChainParams, Fork, ForkConfig, ForkSchedule};
 
 use alloc::vec::Vec;
-use alloc::rc::Rc;
+use alloc::collections::BTreeMap;
+use primitive_types::{H160, U256};
 
 macro_rules! step {
-	( $self:expr, $handler:expr, $return:tt $($err:path)?; $($ok:path)? ) => ({
+	( $self:expr, $handler:expr, $inspector:expr, $return:tt $($err:path)?; $($ok:path)? ) => ({
+		// A detached interrupt must be fed back before the machine can continue.
+		if $self.pending.is_some() {
+			#[allow(unused_parens)]
+			$return $($err)*(Capture::Exit(ExitFatal::UnhandledInterrupt.into()))
+		}
+
 		if let Some((opcode, stack)) = $self.machine.inspect() {
-			// println!("S: {:?}", stack);
-			// println!("   {:?}", opcode); // $self.machine.position.unwrap_or_default()
+			let opcode = opcode.map_err(|opcode| $self._config.external_opcode(opcode));
+			event!(Step {
+				context: &$self.context,
+				config: $self._config,
+				opcode,
+				position: $self.machine.position(),
+				stack,
+				memory: $self.machine.memory(),
+			});
+			$inspector.step(&$self.context, opcode, &$self.machine);
 			match $handler.pre_validate(&$self.context, opcode, stack) {
 				Ok(()) => (),
 				Err(e) => {
@@ -38,27 +105,44 @@ macro_rules! step {
 		match $self.status.clone() {
 			Ok(()) => (),
 			Err(e) => {
+				event!(StepResult { result: &$self.status, return_value: &[] });
+				$inspector.step_result(&$self.context, &$self.status, &$self.machine);
 				#[allow(unused_parens)]
 				$return $($err)*(Capture::Exit(e))
 			},
 		}
 
-		match $self.machine.step() {
-			Ok(()) => $($ok)?(()),
+		match $self.machine.step_with($self.table) {
+			Ok(()) => {
+				event!(StepResult { result: &$self.status, return_value: &[] });
+				$inspector.step_result(&$self.context, &$self.status, &$self.machine);
+				$($ok(()))?
+			},
 			Err(Capture::Exit(e)) => {
 				$self.status = Err(e);
+				event!(StepResult { result: &$self.status, return_value: &$self.machine.return_value() });
+				$inspector.step_result(&$self.context, &$self.status, &$self.machine);
 				#[allow(unused_parens)]
 				$return $($err)*(Capture::Exit(e))
 			},
 			Err(Capture::Trap(opcode)) => {
+				let opcode = $self._config.external_opcode(opcode);
 				match eval::eval($self, opcode, $handler) {
-					eval::Control::Continue => $($ok)?(()),
+					eval::Control::Continue => {
+						event!(StepResult { result: &$self.status, return_value: &[] });
+						$inspector.step_result(&$self.context, &$self.status, &$self.machine);
+						$($ok(()))?
+					},
 					eval::Control::CallInterrupt(interrupt) => {
+						event!(StepResult { result: &$self.status, return_value: &[] });
+						$inspector.step_result(&$self.context, &$self.status, &$self.machine);
 						let resolve = ResolveCall::new($self);
 						#[allow(unused_parens)]
 						$return $($err)*(Capture::Trap(Resolve::Call(interrupt, resolve)))
 					},
 					eval::Control::CreateInterrupt(interrupt) => {
+						event!(StepResult { result: &$self.status, return_value: &[] });
+						$inspector.step_result(&$self.context, &$self.status, &$self.machine);
 						let resolve = ResolveCreate::new($self);
 						#[allow(unused_parens)]
 						$return $($err)*(Capture::Trap(Resolve::Create(interrupt, resolve)))
@@ -66,6 +150,8 @@ macro_rules! step {
 					eval::Control::Exit(exit) => {
 						$self.machine.exit(exit.into());
 						$self.status = Err(exit);
+						event!(StepResult { result: &$self.status, return_value: &$self.machine.return_value() });
+						$inspector.step_result(&$self.context, &$self.status, &$self.machine);
 						#[allow(unused_parens)]
 						$return $($err)*(Capture::Exit(exit))
 					},
@@ -75,6 +161,29 @@ macro_rules! step {
 	});
 }
 
+/// Snapshot of the runtime state, excluding the config, to pause and resume
+/// execution.
+#[derive(Clone)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RuntimeSnapshot {
+	/// Machine state, including code, program counter, stack and memory.
+	pub machine: Machine,
+	/// Runtime status.
+	pub status: Result<(), ExitReason>,
+	/// Return data of the last call.
+	pub return_data_buffer: Vec<u8>,
+	/// Context of the runtime.
+	pub context: Context,
+	/// Interrupt the runtime is waiting for.
+	pub pending: Option<PendingInterrupt>,
+	/// Logs emitted so far, if captured.
+	pub logs: Vec<Log>,
+	/// Whether logs are captured.
+	pub capture_logs: bool,
+	/// Gas left when the runtime started, if it has been run.
+	pub initial_gas: Option<U256>,
+}
+
 /// EVM runtime.
 ///
 /// The runtime wraps an EVM `Machine` with support of return data and context.
@@ -83,22 +192,50 @@ pub struct Runtime<'config> {
 	status: Result<(), ExitReason>,
 	return_data_buffer: Vec<u8>,
 	context: Context,
+	pending: Option<PendingInterrupt>,
+	logs: Vec<Log>,
+	capture_logs: bool,
+	initial_gas: Option<U256>,
+	table: &'static InstructionTable,
 	_config: &'config Config,
 }
 
 impl<'config> Runtime<'config> {
 	/// Create a new runtime with given code and data.
-	pub fn new(
-		code: Rc<Vec<u8>>,
-		data: Rc<Vec<u8>>,
+	pub fn new<C: Into<Bytes>, D: Into<Bytes>>(
+		code: C,
+		data: D,
 		context: Context,
 		config: &'config Config,
 	) -> Self {
+		Self::from_machine(Machine::new(code, data, config.stack_limit, config.memory_limit), context, config)
+	}
+
+	/// Create a new runtime with given code, data and a precomputed jump destination
+	/// map of the code.
+	pub fn with_valids<C: Into<Bytes>, D: Into<Bytes>>(
+		code: C,
+		data: D,
+		valids: Shared<Valids>,
+		context: Context,
+		config: &'config Config,
+	) -> Self {
+		let machine = Machine::with_valids(code, data, valids, config.stack_limit, config.memory_limit);
+		Self::from_machine(machine, context, config)
+	}
+
+	/// Create a new runtime around a machine that has not been run.
+	pub fn from_machine(machine: Machine, context: Context, config: &'config Config) -> Self {
 		Self {
-			machine: Machine::new(code, data, config.stack_limit, config.memory_limit),
+			machine,
 			status: Ok(()),
 			return_data_buffer: Vec::new(),
 			context,
+			pending: None,
+			logs: Vec::new(),
+			capture_logs: false,
+			initial_gas: None,
+			table: config.instruction_table(),
 			_config: config,
 		}
 	}
@@ -108,27 +245,239 @@ impl<'config> Runtime<'config> {
 		&self.machine
 	}
 
+	/// Consume the runtime, returning its machine.
+	pub fn into_machine(self) -> Machine {
+		self.machine
+	}
+
+	/// Take a snapshot of the runtime state.
+	pub fn snapshot(&self) -> RuntimeSnapshot {
+		RuntimeSnapshot {
+			machine: self.machine.clone(),
+			status: self.status,
+			return_data_buffer: self.return_data_buffer.clone(),
+			context: self.context.clone(),
+			pending: self.pending,
+			logs: self.logs.clone(),
+			capture_logs: self.capture_logs,
+			initial_gas: self.initial_gas,
+		}
+	}
+
+	/// Restore a runtime from a snapshot, to be resumed with the given config.
+	pub fn from_snapshot(snapshot: RuntimeSnapshot, config: &'config Config) -> Self {
+		Self {
+			machine: snapshot.machine,
+			status: snapshot.status,
+			return_data_buffer: snapshot.return_data_buffer,
+			context: snapshot.context,
+			pending: snapshot.pending,
+			logs: snapshot.logs,
+			capture_logs: snapshot.capture_logs,
+			initial_gas: snapshot.initial_gas,
+			table: config.instruction_table(),
+			_config: config,
+		}
+	}
+
+	/// Decoded revert reason, if the runtime exited with `ExitReason::Revert` and
+	/// returned data.
+	pub fn revert_reason(&self) -> Option<RevertReason> {
+		match self.status {
+			Err(ExitReason::Revert(_)) => RevertReason::decode(&self.machine.return_value()),
+			_ => None,
+		}
+	}
+
+	/// Context of the runtime.
+	pub fn context(&self) -> &Context {
+		&self.context
+	}
+
+	/// Stack of the machine, as left when it stopped.
+	pub fn stack(&self) -> &Stack {
+		self.machine.stack()
+	}
+
+	/// Memory of the machine, as left when it stopped.
+	pub fn memory(&self) -> &Memory {
+		self.machine.memory()
+	}
+
+	/// Exit reason, if the runtime has stopped.
+	pub fn exit_reason(&self) -> Option<ExitReason> {
+		self.status.err()
+	}
+
+	/// Data returned by `RETURN` or `REVERT` once the runtime has stopped, empty if
+	/// it is still running or failed with an error.
+	pub fn output(&self) -> Vec<u8> {
+		match self.status {
+			Err(ExitReason::Succeed(_)) | Err(ExitReason::Revert(_)) => self.machine.return_value(),
+			_ => Vec::new(),
+		}
+	}
+
+	/// Return data of the last call or create.
+	pub fn return_data(&self) -> &[u8] {
+		&self.return_data_buffer
+	}
+
+	/// Logs emitted by `LOGn` of this runtime, in order, if captured with
+	/// `set_capture_logs`. They are kept even if the runtime reverts; the handler
+	/// decides which logs are committed.
+	pub fn logs(&self) -> &[Log] {
+		&self.logs
+	}
+
+	/// Whether to keep a copy of the logs passed to the handler, for `logs`. Off by
+	/// default.
+	pub fn set_capture_logs(&mut self, capture_logs: bool) {
+		self.capture_logs = capture_logs;
+	}
+
+	/// Gas used since the runtime was first run, as metered by the handler. Zero if
+	/// it has not been run. Call before the handler merges or discards the frame.
+	pub fn used_gas<H: Handler>(&self, handler: &H) -> U256 {
+		self.initial_gas.map(|gas| gas.saturating_sub(handler.gas_left())).unwrap_or_default()
+	}
+
+	fn start<H: Handler>(&mut self, handler: &H) {
+		if self.initial_gas.is_none() {
+			self.initial_gas = Some(handler.gas_left());
+		}
+	}
+
+	/// Interrupt the runtime is waiting for, if any.
+	pub fn pending(&self) -> Option<PendingInterrupt> {
+		self.pending
+	}
+
+	/// Feed the result of a detached call interrupt back into the runtime. Returns the
+	/// exit reason if the runtime exits.
+	pub fn feedback_call(&mut self, reason: ExitReason, return_data: Vec<u8>) -> Result<(), ExitReason> {
+		let ret = match self.pending.take() {
+			Some(PendingInterrupt::Call { out_offset, out_len }) =>
+				eval::finish_call(self, reason, return_data, out_offset, out_len),
+			_ => Err(ExitFatal::UnhandledInterrupt.into()),
+		};
+		self.feedback_exit(ret)
+	}
+
+	/// Feed the result of a detached create interrupt back into the runtime. Returns
+	/// the exit reason if the runtime exits.
+	pub fn feedback_create(
+		&mut self,
+		reason: ExitReason,
+		address: Option<H160>,
+		return_data: Vec<u8>,
+	) -> Result<(), ExitReason> {
+		let ret = match self.pending.take() {
+			Some(PendingInterrupt::Create) =>
+				eval::finish_create(self, reason, address, return_data),
+			_ => Err(ExitFatal::UnhandledInterrupt.into()),
+		};
+		self.feedback_exit(ret)
+	}
+
+	fn feedback_exit(&mut self, ret: Result<(), ExitReason>) -> Result<(), ExitReason> {
+		if let Err(e) = ret {
+			self.machine.exit(e);
+			self.status = Err(e);
+		}
+		ret
+	}
+
 	/// Step the runtime.
 	pub fn step<'a, H: Handler>(
 		&'a mut self,
 		handler: &mut H,
 	) -> Result<(), Capture<ExitReason, Resolve<'a, 'config, H>>> {
-		step!(self, handler, return Err; Ok)
+		self.step_inspect(handler, &mut ())
 	}
 
-	/// Loop stepping the runtime until it stops.
+	/// Loop stepping the runtime until it stops. Superinstructions set on the machine
+	/// are evaluated as one step, unless the `tracing` feature is enabled.
 	pub fn run<'a, H: Handler>(
 		&'a mut self,
 		handler: &mut H,
 	) -> Capture<ExitReason, Resolve<'a, 'config, H>> {
+		self.start(handler);
 		loop {
-			step!(self, handler, return;)
+			#[cfg(not(feature = "tracing"))]
+			if let Some(superinstruction) = self.machine.superinstruction().filter(|_| self.pending.is_none()) {
+				if let Err(e) = self.step_superinstruction(handler, superinstruction) {
+					return Capture::Exit(e)
+				}
+				continue
+			}
+
+			step!(self, handler, &mut (), return;)
+		}
+	}
+
+	/// Validate each opcode of the superinstruction with the handler, then evaluate it.
+	#[cfg(not(feature = "tracing"))]
+	fn step_superinstruction<H: Handler>(
+		&mut self,
+		handler: &mut H,
+		superinstruction: Superinstruction,
+	) -> Result<(), ExitReason> {
+		for opcode in superinstruction.opcodes() {
+			if let Err(e) = handler.pre_validate(&self.context, Ok(opcode), self.machine.stack()) {
+				return self.feedback_exit(Err(e.into()))
+			}
+		}
+		let ret = self.machine.step_superinstruction(superinstruction);
+		self.feedback_exit(ret)
+	}
+
+	/// Loop stepping the runtime until it stops, awaiting the handler to fetch the
+	/// state each opcode reads.
+	#[cfg(feature = "async")]
+	pub async fn run_async<'a, H: Handler + AsyncHandler>(
+		&'a mut self,
+		handler: &mut H,
+	) -> Capture<ExitReason, Resolve<'a, 'config, H>> {
+		self.start(handler);
+		loop {
+			if let Some((opcode, stack)) = self.machine.inspect() {
+				let opcode = opcode.map_err(|opcode| self._config.external_opcode(opcode));
+				if let Some(access) = StateAccess::of(&self.context, opcode, stack) {
+					handler.prefetch(access).await;
+				}
+			}
+
+			step!(self, handler, &mut (), return;)
+		}
+	}
+
+	/// Step the runtime, calling the inspector around the opcode.
+	pub fn step_inspect<'a, H: Handler, I: Inspector + ?Sized>(
+		&'a mut self,
+		handler: &mut H,
+		inspector: &mut I,
+	) -> Result<(), Capture<ExitReason, Resolve<'a, 'config, H>>> {
+		self.start(handler);
+		step!(self, handler, inspector, return Err; Ok)
+	}
+
+	/// Loop stepping the runtime until it stops, calling the inspector around each opcode.
+	pub fn run_inspect<'a, H: Handler, I: Inspector + ?Sized>(
+		&'a mut self,
+		handler: &mut H,
+		inspector: &mut I,
+	) -> Capture<ExitReason, Resolve<'a, 'config, H>> {
+		self.start(handler);
+		loop {
+			step!(self, handler, inspector, return;)
 		}
 	}
 }
 
 /// Runtime configuration.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Config {
 	/// Gas paid for extcode.
 	pub gas_ext_code: usize,
@@ -152,6 +501,10 @@ pub struct Config {
 	pub gas_call: usize,
 	/// Gas paid for EXP opcode for every byte.
 	pub gas_expbyte: usize,
+	/// Gas paid for every word of memory.
+	pub gas_memory_word: usize,
+	/// Divisor of the quadratic memory cost, the number of words squared over it.
+	pub memory_quad_divisor: usize,
 	/// Gas paid for a contract creation transaction.
 	pub gas_transaction_create: usize,
 	/// Gas paid for a message call transaction.
@@ -160,10 +513,52 @@ pub struct Config {
 	pub gas_transaction_zero_data: usize,
 	/// Gas paid for non-zero data in a transaction.
 	pub gas_transaction_non_zero_data: usize,
+	/// Gas paid for SLOAD of a cold storage slot.
+	pub gas_sload_cold: usize,
+	/// Gas paid for accessing a warm account or storage slot.
+	pub gas_storage_read_warm: usize,
+	/// Gas paid for accessing a cold account.
+	pub gas_account_access_cold: usize,
+	/// Gas paid for ecrecover precompile.
+	pub gas_ecrecover: usize,
+	/// Base gas paid for sha256 precompile.
+	pub gas_sha256: usize,
+	/// Gas paid for sha256 precompile for every word.
+	pub gas_sha256_word: usize,
+	/// Base gas paid for ripemd160 precompile.
+	pub gas_ripemd160: usize,
+	/// Gas paid for ripemd160 precompile for every word.
+	pub gas_ripemd160_word: usize,
+	/// Base gas paid for identity precompile.
+	pub gas_identity: usize,
+	/// Gas paid for identity precompile for every word.
+	pub gas_identity_word: usize,
+	/// Gas paid for bn128 addition precompile.
+	pub gas_bn128_add: usize,
+	/// Gas paid for bn128 scalar multiplication precompile.
+	pub gas_bn128_mul: usize,
+	/// Base gas paid for bn128 pairing precompile.
+	pub gas_bn128_pairing: usize,
+	/// Gas paid for bn128 pairing precompile for every point pair.
+	pub gas_bn128_pairing_point: usize,
+	/// Gas paid for TRON batchvalidatesign/validatemultisign precompiles for every signature.
+	pub gas_validate_sign: usize,
+	/// Reject ecrecover signatures whose `v` word is not exactly 27 or 28, or
+	/// whose `s` is in the upper half of the curve order (EIP-2).
+	pub ecrecover_strict: bool,
 	/// EIP-1283.
 	pub sstore_gas_metering: bool,
 	/// EIP-1706.
 	pub sstore_revert_under_stipend: bool,
+	/// EIP-2929.
+	pub increase_state_access_gas: bool,
+	/// EIP-3651.
+	pub warm_coinbase_address: bool,
+	/// EIP-2929: the precompiles, at addresses `1` to `warm_precompiles`, are warm
+	/// from the start of a transaction.
+	pub warm_precompiles: u64,
+	/// Maximum gas refund quotient of used gas, 2 before EIP-3529 and 5 after.
+	pub max_refund_quotient: usize,
 	/// Whether to throw out of gas error when
 	/// CALL/CALLCODE/DELEGATECALL requires more than maximum amount
 	/// of gas.
@@ -176,14 +571,21 @@ pub struct Config {
 	pub create_increase_nonce: bool,
 	/// Stack limit.
 	pub stack_limit: usize,
-	/// Memory limit.
+	/// Memory limit in bytes. Expanding memory beyond it fails with `InvalidRange`.
 	pub memory_limit: usize,
 	/// Call limit.
 	pub call_stack_limit: usize,
 	/// Create contract limit.
 	pub create_contract_limit: Option<usize>,
+	/// EIP-3860: init code size limit. Init code words are charged when set.
+	pub max_initcode_size: Option<usize>,
+	/// EIP-3541: reject new code starting with the `0xef` byte.
+	pub disallow_executable_format: bool,
 	/// Call stipend.
 	pub call_stipend: usize,
+	/// Overridden gas costs of opcodes by byte value, as repriced by chain parameters.
+	/// Only applies to opcodes whose cost does not depend on their operands.
+	pub gas_overrides: BTreeMap<u8, usize>,
 	/// Has delegate call.
 	pub has_delegate_call: bool,
 	/// Has create2.
@@ -202,6 +604,13 @@ pub struct Config {
 	pub has_self_balance: bool,
 	/// Has ext code hash.
 	pub has_ext_code_hash: bool,
+	/// Has push0.
+	pub has_push0: bool,
+	/// EIP-4399, DIFFICULTY returns the randomness of the block as PREVRANDAO.
+	pub has_prev_randao: bool,
+	/// EIP-3540 and EIP-3670, EOF containers are validated at deployment and their
+	/// code section is executed.
+	pub has_eof: bool,
 	// TRON extensions:
 	/// Has token transfer.
 	pub has_token_transfer: bool,
@@ -219,9 +628,108 @@ pub struct Config {
 	pub has_iswitness: bool,
 	/// Has a buggy origin opcode. (21 bytes origin address)
 	pub has_buggy_origin: bool,
+	/// Has a buggy blockhash opcode. (block number read as a signed 32-bit integer)
+	pub has_buggy_blockhash: bool,
+	/// Whether CALLTOKEN checks token id range, or treats token id 0 as TRX.
+	pub has_token_id_check: bool,
+	/// Whether TOKENBALANCE of an asset that does not exist is rejected. Unset in
+	/// all presets, as java-tron returns zero for it.
+	pub has_token_balance_check: bool,
+	/// Has Stake 2.0 freezebalancev2/unfreezebalancev2/delegateresource family.
+	pub has_stake_v2: bool,
+	/// Has votewitness/withdrawreward.
+	pub has_vote: bool,
+	/// Has freeze/unfreeze/freezeexpiretime.
+	pub has_freeze: bool,
+	/// Has base fee.
+	pub has_base_fee: bool,
+	/// Charge energy penalty by contract energy factor.
+	pub has_dynamic_energy: bool,
+	/// Has the batchvalidatesign precompile.
+	pub has_validate_signature: bool,
+	/// Has the shielded TRC-20 precompiles.
+	pub has_shielded: bool,
 }
 
+static TABLE: InstructionTable = InstructionTable::new();
+static TABLE_WITHOUT_PUSH0: InstructionTable = InstructionTable::new().without(0x5f);
+
 impl Config {
+	/// Resolve an external opcode whose meaning depends on the enabled TRON upgrades.
+	pub fn external_opcode(&self, opcode: ExternalOpcode) -> ExternalOpcode {
+		match opcode {
+			ExternalOpcode::Stake if self.has_freeze => ExternalOpcode::Freeze,
+			ExternalOpcode::Unstake if self.has_freeze => ExternalOpcode::Unfreeze,
+			ExternalOpcode::WithdrawReward if self.has_freeze => ExternalOpcode::FreezeExpireTime,
+			ExternalOpcode::WithdrawReward if self.has_vote => ExternalOpcode::Other(0xd7),
+			ExternalOpcode::RewardBalance if self.has_vote => ExternalOpcode::VoteWitness,
+			ExternalOpcode::IsWitness if self.has_vote => ExternalOpcode::WithdrawReward,
+			ExternalOpcode::AssetIssue if self.has_stake_v2 => ExternalOpcode::FreezeBalanceV2,
+			ExternalOpcode::UpdateAsset if self.has_stake_v2 => ExternalOpcode::UnfreezeBalanceV2,
+			opcode => opcode,
+		}
+	}
+
+	/// Dispatch table of the machine opcodes enabled in this config.
+	pub fn instruction_table(&self) -> &'static InstructionTable {
+		if self.has_push0 {
+			&TABLE
+		} else {
+			&TABLE_WITHOUT_PUSH0
+		}
+	}
+
+	/// Whether a dispatcher of TRON precompiles exposes the address: batchvalidatesign
+	/// (`0x09`) and validatemultisign (`0x0a`) need `has_validate_signature`, and the
+	/// shielded range (`0x1000001` to `0x1000004`) needs `has_shielded`. Other
+	/// addresses are not gated.
+	pub fn has_tron_precompile(&self, address: H160) -> bool {
+		if address[..16].iter().any(|b| *b != 0) {
+			return true
+		}
+		match u32::from_be_bytes([address[16], address[17], address[18], address[19]]) {
+			0x09 | 0x0a => self.has_validate_signature,
+			0x0100_0001..=0x0100_0004 => self.has_shielded,
+			_ => true,
+		}
+	}
+
+	/// Get the byte value of an opcode, resolved with the enabled TRON upgrades.
+	pub fn opcode_byte(&self, opcode: Result<Opcode, ExternalOpcode>) -> u8 {
+		match opcode {
+			Ok(opcode) => opcode.as_u8(),
+			Err(ExternalOpcode::WithdrawReward) if self.has_vote => 0xd9,
+			Err(opcode) => opcode.as_u8(),
+		}
+	}
+
+	/// Names of the presets accepted by `preset`.
+	pub const PRESETS: &'static [&'static str] = &[
+		"frontier", "istanbul", "berlin", "london", "shanghai", "tvm", "odyssey_3_7",
+		"great_voyage_4_0", "great_voyage_4_1", "great_voyage_4_2", "great_voyage_4_5",
+		"great_voyage_4_6", "great_voyage_4_7",
+	];
+
+	/// Get a preset config by its constructor name, such as `"istanbul"` or `"great_voyage_4_2"`.
+	pub fn preset(name: &str) -> Option<Config> {
+		Some(match name {
+			"frontier" => Self::frontier(),
+			"istanbul" => Self::istanbul(),
+			"berlin" => Self::berlin(),
+			"london" => Self::london(),
+			"shanghai" => Self::shanghai(),
+			"tvm" => Self::tvm(),
+			"odyssey_3_7" => Self::odyssey_3_7(),
+			"great_voyage_4_0" => Self::great_voyage_4_0(),
+			"great_voyage_4_1" => Self::great_voyage_4_1(),
+			"great_voyage_4_2" => Self::great_voyage_4_2(),
+			"great_voyage_4_5" => Self::great_voyage_4_5(),
+			"great_voyage_4_6" => Self::great_voyage_4_6(),
+			"great_voyage_4_7" => Self::great_voyage_4_7(),
+			_ => return None,
+		})
+	}
+
 	// TRON configuration
 
 	/// AllowTvmIstanbulUpgrade, 4.1 unreleased.
@@ -229,6 +737,11 @@ impl Config {
 		self.has_chain_id = true;
 		self.has_self_balance = true;
 		self.has_real_create2 = true;
+		// EIP-1108
+		self.gas_bn128_add = 150;
+		self.gas_bn128_mul = 6000;
+		self.gas_bn128_pairing = 45000;
+		self.gas_bn128_pairing_point = 34000;
 	}
 
 	/// AllowTvmAssetIssueUpgrade, 4.1 unreleased.
@@ -242,11 +755,42 @@ impl Config {
 		self.has_iswitness = true;
 	}
 
+	/// AllowTvmFreeze.
+	pub fn allow_tvm_freeze(&mut self) {
+		self.has_freeze = true;
+	}
+
+	/// AllowTvmLondon.
+	pub fn allow_tvm_london(&mut self) {
+		self.has_base_fee = true;
+	}
+
+	/// AllowDynamicEnergy.
+	pub fn allow_dynamic_energy(&mut self) {
+		self.has_dynamic_energy = true;
+	}
+
+	/// AllowTvmVote.
+	pub fn allow_tvm_vote(&mut self) {
+		self.has_vote = true;
+	}
+
+	/// AllowTvmFreezeV2, Stake 2.0.
+	pub fn allow_tvm_freeze_v2(&mut self) {
+		self.has_stake_v2 = true;
+	}
+
 	/// AllowTvmSolidity059Upgrade.
 	pub fn allow_tvm_solidity059(&mut self) {
 		self.create_account_if_not_exist = true;
 		self.has_iscontract = true;
 		self.has_transfer_exception = true;
+		self.has_validate_signature = true;
+	}
+
+	/// AllowShieldedTRC20Transaction.
+	pub fn allow_shielded_trc20(&mut self) {
+		self.has_shielded = true;
 	}
 
 	/// AllowTvmConstantinopleUpgrade.
@@ -257,11 +801,70 @@ impl Config {
 		self.has_real_create2 = false;
 	}
 
+	/// AllowMultiSign.
+	pub fn allow_multisig(&mut self) {
+		self.has_buggy_origin = false;
+		self.has_token_id_check = true;
+	}
+
 	/// AllowTvmTransferTrc10Upgrade.
 	pub fn allow_tvm_asset_transfer(&mut self) {
 		self.has_token_transfer = true;
 	}
 
+	/// Odyssey 3.7 TVM config, with TRC-10 transfer, multisig, Constantinople and Solidity 0.5.9 upgrades.
+	pub fn odyssey_3_7() -> Config {
+		let mut config = Self::tvm();
+		config.allow_tvm_asset_transfer();
+		config.allow_multisig();
+		config.allow_tvm_constantinople();
+		config.allow_tvm_solidity059();
+		config
+	}
+
+	/// GreatVoyage 4.0 TVM config, with shielded TRC-20 precompiles.
+	pub fn great_voyage_4_0() -> Config {
+		let mut config = Self::odyssey_3_7();
+		config.allow_shielded_trc20();
+		config
+	}
+
+	/// GreatVoyage 4.1 TVM config, with Istanbul upgrade.
+	pub fn great_voyage_4_1() -> Config {
+		let mut config = Self::great_voyage_4_0();
+		config.allow_tvm_istanbul();
+		config
+	}
+
+	/// GreatVoyage 4.2 TVM config, with freeze and vote opcodes.
+	pub fn great_voyage_4_2() -> Config {
+		let mut config = Self::great_voyage_4_1();
+		config.allow_tvm_freeze();
+		config.allow_tvm_vote();
+		config
+	}
+
+	/// GreatVoyage 4.5 TVM config, with London upgrade.
+	pub fn great_voyage_4_5() -> Config {
+		let mut config = Self::great_voyage_4_2();
+		config.allow_tvm_london();
+		config
+	}
+
+	/// GreatVoyage 4.6 TVM config, with Stake 2.0.
+	pub fn great_voyage_4_6() -> Config {
+		let mut config = Self::great_voyage_4_5();
+		config.allow_tvm_freeze_v2();
+		config
+	}
+
+	/// GreatVoyage 4.7 TVM config, with dynamic energy.
+	pub fn great_voyage_4_7() -> Config {
+		let mut config = Self::great_voyage_4_6();
+		config.allow_dynamic_energy();
+		config
+	}
+
 	/// Initial TVM config.
 	pub const fn tvm() -> Config {
 		Config {
@@ -276,21 +879,47 @@ impl Config {
 			gas_suicide_new_account: 0,
 			gas_call: 40,
 			gas_expbyte: 10,
+			gas_memory_word: 3,
+			memory_quad_divisor: 512,
 			gas_transaction_create: 21000,
 			gas_transaction_call: 21000,
 			gas_transaction_zero_data: 4,
 			gas_transaction_non_zero_data: 68,
+			gas_sload_cold: 0,
+			gas_storage_read_warm: 0,
+			gas_account_access_cold: 0,
+			gas_ecrecover: 3000,
+			gas_sha256: 60,
+			gas_sha256_word: 12,
+			gas_ripemd160: 600,
+			gas_ripemd160_word: 120,
+			gas_identity: 15,
+			gas_identity_word: 3,
+			gas_bn128_add: 500,
+			gas_bn128_mul: 40000,
+			gas_bn128_pairing: 100000,
+			gas_bn128_pairing_point: 80000,
+			gas_validate_sign: 1500,
+			ecrecover_strict: false,
 			sstore_gas_metering: false,
 			sstore_revert_under_stipend: false,
+			increase_state_access_gas: false,
+			warm_coinbase_address: false,
+			warm_precompiles: 0,
+			max_refund_quotient: 2,
 			err_on_call_with_more_gas: false,
 			empty_considered_exists: false,
 			create_increase_nonce: true,
 			call_l64_after_gas: false,
 			stack_limit: 1024,
-			memory_limit: usize::max_value(),
+			// TRON: java-tron `EnergyCost.MEM_LIMIT`.
+			memory_limit: 3 * 1024 * 1024,
 			call_stack_limit: 1024,
 			create_contract_limit: None,
+			max_initcode_size: None,
+			disallow_executable_format: false,
 			call_stipend: 2300,
+			gas_overrides: BTreeMap::new(),
 			has_delegate_call: true,
 			has_create2: false,
 			has_real_create2: false,
@@ -300,6 +929,9 @@ impl Config {
 			has_chain_id: false,
 			has_self_balance: false,
 			has_ext_code_hash: false,
+			has_push0: false,
+			has_prev_randao: false,
+			has_eof: false,
 			has_token_transfer: false,
 			create_account_if_not_exist: false,
 			has_iscontract: false,
@@ -308,6 +940,16 @@ impl Config {
 			has_token_issue: false,
 			has_iswitness: false,
 			has_buggy_origin: true,
+			has_buggy_blockhash: true,
+			has_token_id_check: false,
+			has_token_balance_check: false,
+			has_stake_v2: false,
+			has_vote: false,
+			has_freeze: false,
+			has_base_fee: false,
+			has_dynamic_energy: false,
+			has_validate_signature: false,
+			has_shielded: false,
 		}
 	}
 	/// Frontier hard fork configuration.
@@ -324,21 +966,46 @@ impl Config {
 			gas_suicide_new_account: 0,
 			gas_call: 40,
 			gas_expbyte: 10,
+			gas_memory_word: 3,
+			memory_quad_divisor: 512,
 			gas_transaction_create: 21000,
 			gas_transaction_call: 21000,
 			gas_transaction_zero_data: 4,
 			gas_transaction_non_zero_data: 68,
+			gas_sload_cold: 0,
+			gas_storage_read_warm: 0,
+			gas_account_access_cold: 0,
+			gas_ecrecover: 3000,
+			gas_sha256: 60,
+			gas_sha256_word: 12,
+			gas_ripemd160: 600,
+			gas_ripemd160_word: 120,
+			gas_identity: 15,
+			gas_identity_word: 3,
+			gas_bn128_add: 500,
+			gas_bn128_mul: 40000,
+			gas_bn128_pairing: 100000,
+			gas_bn128_pairing_point: 80000,
+			gas_validate_sign: 1500,
+			ecrecover_strict: false,
 			sstore_gas_metering: false,
 			sstore_revert_under_stipend: false,
+			increase_state_access_gas: false,
+			warm_coinbase_address: false,
+			warm_precompiles: 0,
+			max_refund_quotient: 2,
 			err_on_call_with_more_gas: true,
 			empty_considered_exists: true,
 			create_increase_nonce: false,
 			call_l64_after_gas: false,
 			stack_limit: 1024,
-			memory_limit: usize::max_value(),
+			memory_limit: usize::MAX,
 			call_stack_limit: 1024,
 			create_contract_limit: None,
+			max_initcode_size: None,
+			disallow_executable_format: false,
 			call_stipend: 2300,
+			gas_overrides: BTreeMap::new(),
 			has_delegate_call: false,
 			has_create2: false,
 			has_real_create2: false,
@@ -348,6 +1015,9 @@ impl Config {
 			has_chain_id: false,
 			has_self_balance: false,
 			has_ext_code_hash: false,
+			has_push0: false,
+			has_prev_randao: false,
+			has_eof: false,
 			has_token_transfer: false,
 			create_account_if_not_exist: false,
 			has_iscontract: false,
@@ -356,6 +1026,16 @@ impl Config {
 			has_token_issue: false,
 			has_iswitness: false,
 			has_buggy_origin: false,
+			has_buggy_blockhash: false,
+			has_token_id_check: false,
+			has_token_balance_check: false,
+			has_stake_v2: false,
+			has_vote: false,
+			has_freeze: false,
+			has_base_fee: false,
+			has_dynamic_energy: false,
+			has_validate_signature: false,
+			has_shielded: false,
 		}
 	}
 
@@ -373,21 +1053,307 @@ impl Config {
 			gas_suicide_new_account: 25000,
 			gas_call: 700,
 			gas_expbyte: 50,
+			gas_memory_word: 3,
+			memory_quad_divisor: 512,
+			gas_transaction_create: 53000,
+			gas_transaction_call: 21000,
+			gas_transaction_zero_data: 4,
+			gas_transaction_non_zero_data: 16,
+			gas_sload_cold: 0,
+			gas_storage_read_warm: 0,
+			gas_account_access_cold: 0,
+			gas_ecrecover: 3000,
+			gas_sha256: 60,
+			gas_sha256_word: 12,
+			gas_ripemd160: 600,
+			gas_ripemd160_word: 120,
+			gas_identity: 15,
+			gas_identity_word: 3,
+			gas_bn128_add: 150,
+			gas_bn128_mul: 6000,
+			gas_bn128_pairing: 45000,
+			gas_bn128_pairing_point: 34000,
+			gas_validate_sign: 1500,
+			ecrecover_strict: false,
+			sstore_gas_metering: true,
+			sstore_revert_under_stipend: true,
+			increase_state_access_gas: false,
+			warm_coinbase_address: false,
+			warm_precompiles: 0,
+			max_refund_quotient: 2,
+			err_on_call_with_more_gas: false,
+			empty_considered_exists: false,
+			create_increase_nonce: true,
+			call_l64_after_gas: true,
+			stack_limit: 1024,
+			memory_limit: usize::MAX,
+			call_stack_limit: 1024,
+			create_contract_limit: Some(0x6000),
+			max_initcode_size: None,
+			disallow_executable_format: false,
+			call_stipend: 2300,
+			gas_overrides: BTreeMap::new(),
+			has_delegate_call: true,
+			has_create2: true,
+			has_real_create2: true,
+			has_revert: true,
+			has_return_data: true,
+			has_bitwise_shifting: true,
+			has_chain_id: true,
+			has_self_balance: true,
+			has_ext_code_hash: true,
+			has_push0: false,
+			has_prev_randao: false,
+			has_eof: false,
+			has_token_transfer: false,
+			create_account_if_not_exist: false,
+			has_iscontract: false,
+			has_transfer_exception: false,
+			has_stake: false,
+			has_token_issue: false,
+			has_iswitness: false,
+			has_buggy_origin: false,
+			has_buggy_blockhash: false,
+			has_token_id_check: false,
+			has_token_balance_check: false,
+			has_stake_v2: false,
+			has_vote: false,
+			has_freeze: false,
+			has_base_fee: false,
+			has_dynamic_energy: false,
+			has_validate_signature: false,
+			has_shielded: false,
+		}
+	}
+
+	/// Berlin hard fork configuration.
+	pub const fn berlin() -> Config {
+		Config {
+			gas_ext_code: 0,
+			gas_ext_code_hash: 0,
+			gas_balance: 0,
+			gas_sload: 100,
+			gas_sstore_set: 20000,
+			gas_sstore_reset: 2900,
+			refund_sstore_clears: 15000,
+			gas_suicide: 5000,
+			gas_suicide_new_account: 25000,
+			gas_call: 0,
+			gas_expbyte: 50,
+			gas_memory_word: 3,
+			memory_quad_divisor: 512,
+			gas_transaction_create: 53000,
+			gas_transaction_call: 21000,
+			gas_transaction_zero_data: 4,
+			gas_transaction_non_zero_data: 16,
+			gas_sload_cold: 2100,
+			gas_storage_read_warm: 100,
+			gas_account_access_cold: 2600,
+			gas_ecrecover: 3000,
+			gas_sha256: 60,
+			gas_sha256_word: 12,
+			gas_ripemd160: 600,
+			gas_ripemd160_word: 120,
+			gas_identity: 15,
+			gas_identity_word: 3,
+			gas_bn128_add: 150,
+			gas_bn128_mul: 6000,
+			gas_bn128_pairing: 45000,
+			gas_bn128_pairing_point: 34000,
+			gas_validate_sign: 1500,
+			ecrecover_strict: false,
+			sstore_gas_metering: true,
+			sstore_revert_under_stipend: true,
+			increase_state_access_gas: true,
+			warm_coinbase_address: false,
+			warm_precompiles: 9,
+			max_refund_quotient: 2,
+			err_on_call_with_more_gas: false,
+			empty_considered_exists: false,
+			create_increase_nonce: true,
+			call_l64_after_gas: true,
+			stack_limit: 1024,
+			memory_limit: usize::MAX,
+			call_stack_limit: 1024,
+			create_contract_limit: Some(0x6000),
+			max_initcode_size: None,
+			disallow_executable_format: false,
+			call_stipend: 2300,
+			gas_overrides: BTreeMap::new(),
+			has_delegate_call: true,
+			has_create2: true,
+			has_real_create2: true,
+			has_revert: true,
+			has_return_data: true,
+			has_bitwise_shifting: true,
+			has_chain_id: true,
+			has_self_balance: true,
+			has_ext_code_hash: true,
+			has_push0: false,
+			has_prev_randao: false,
+			has_eof: false,
+			has_token_transfer: false,
+			create_account_if_not_exist: false,
+			has_iscontract: false,
+			has_transfer_exception: false,
+			has_stake: false,
+			has_token_issue: false,
+			has_iswitness: false,
+			has_buggy_origin: false,
+			has_buggy_blockhash: false,
+			has_token_id_check: false,
+			has_token_balance_check: false,
+			has_stake_v2: false,
+			has_vote: false,
+			has_freeze: false,
+			has_base_fee: false,
+			has_dynamic_energy: false,
+			has_validate_signature: false,
+			has_shielded: false,
+		}
+	}
+
+	/// London hard fork configuration.
+	pub const fn london() -> Config {
+		Config {
+			gas_ext_code: 0,
+			gas_ext_code_hash: 0,
+			gas_balance: 0,
+			gas_sload: 100,
+			gas_sstore_set: 20000,
+			gas_sstore_reset: 2900,
+			refund_sstore_clears: 4800,
+			gas_suicide: 5000,
+			gas_suicide_new_account: 25000,
+			gas_call: 0,
+			gas_expbyte: 50,
+			gas_memory_word: 3,
+			memory_quad_divisor: 512,
+			gas_transaction_create: 53000,
+			gas_transaction_call: 21000,
+			gas_transaction_zero_data: 4,
+			gas_transaction_non_zero_data: 16,
+			gas_sload_cold: 2100,
+			gas_storage_read_warm: 100,
+			gas_account_access_cold: 2600,
+			gas_ecrecover: 3000,
+			gas_sha256: 60,
+			gas_sha256_word: 12,
+			gas_ripemd160: 600,
+			gas_ripemd160_word: 120,
+			gas_identity: 15,
+			gas_identity_word: 3,
+			gas_bn128_add: 150,
+			gas_bn128_mul: 6000,
+			gas_bn128_pairing: 45000,
+			gas_bn128_pairing_point: 34000,
+			gas_validate_sign: 1500,
+			ecrecover_strict: false,
+			sstore_gas_metering: true,
+			sstore_revert_under_stipend: true,
+			increase_state_access_gas: true,
+			warm_coinbase_address: false,
+			warm_precompiles: 9,
+			max_refund_quotient: 5,
+			err_on_call_with_more_gas: false,
+			empty_considered_exists: false,
+			create_increase_nonce: true,
+			call_l64_after_gas: true,
+			stack_limit: 1024,
+			memory_limit: usize::MAX,
+			call_stack_limit: 1024,
+			create_contract_limit: Some(0x6000),
+			max_initcode_size: None,
+			disallow_executable_format: true,
+			call_stipend: 2300,
+			gas_overrides: BTreeMap::new(),
+			has_delegate_call: true,
+			has_create2: true,
+			has_real_create2: true,
+			has_revert: true,
+			has_return_data: true,
+			has_bitwise_shifting: true,
+			has_chain_id: true,
+			has_self_balance: true,
+			has_ext_code_hash: true,
+			has_push0: false,
+			has_prev_randao: false,
+			has_eof: false,
+			has_token_transfer: false,
+			create_account_if_not_exist: false,
+			has_iscontract: false,
+			has_transfer_exception: false,
+			has_stake: false,
+			has_token_issue: false,
+			has_iswitness: false,
+			has_buggy_origin: false,
+			has_buggy_blockhash: false,
+			has_token_id_check: false,
+			has_token_balance_check: false,
+			has_stake_v2: false,
+			has_vote: false,
+			has_freeze: false,
+			has_base_fee: true,
+			has_dynamic_energy: false,
+			has_validate_signature: false,
+			has_shielded: false,
+		}
+	}
+
+	/// Shanghai hard fork configuration.
+	pub const fn shanghai() -> Config {
+		Config {
+			gas_ext_code: 0,
+			gas_ext_code_hash: 0,
+			gas_balance: 0,
+			gas_sload: 100,
+			gas_sstore_set: 20000,
+			gas_sstore_reset: 2900,
+			refund_sstore_clears: 4800,
+			gas_suicide: 5000,
+			gas_suicide_new_account: 25000,
+			gas_call: 0,
+			gas_expbyte: 50,
+			gas_memory_word: 3,
+			memory_quad_divisor: 512,
 			gas_transaction_create: 53000,
 			gas_transaction_call: 21000,
 			gas_transaction_zero_data: 4,
 			gas_transaction_non_zero_data: 16,
+			gas_sload_cold: 2100,
+			gas_storage_read_warm: 100,
+			gas_account_access_cold: 2600,
+			gas_ecrecover: 3000,
+			gas_sha256: 60,
+			gas_sha256_word: 12,
+			gas_ripemd160: 600,
+			gas_ripemd160_word: 120,
+			gas_identity: 15,
+			gas_identity_word: 3,
+			gas_bn128_add: 150,
+			gas_bn128_mul: 6000,
+			gas_bn128_pairing: 45000,
+			gas_bn128_pairing_point: 34000,
+			gas_validate_sign: 1500,
+			ecrecover_strict: false,
 			sstore_gas_metering: true,
 			sstore_revert_under_stipend: true,
+			increase_state_access_gas: true,
+			warm_coinbase_address: true,
+			warm_precompiles: 9,
+			max_refund_quotient: 5,
 			err_on_call_with_more_gas: false,
 			empty_considered_exists: false,
 			create_increase_nonce: true,
 			call_l64_after_gas: true,
 			stack_limit: 1024,
-			memory_limit: usize::max_value(),
+			memory_limit: usize::MAX,
 			call_stack_limit: 1024,
 			create_contract_limit: Some(0x6000),
+			max_initcode_size: Some(0xc000),
+			disallow_executable_format: true,
 			call_stipend: 2300,
+			gas_overrides: BTreeMap::new(),
 			has_delegate_call: true,
 			has_create2: true,
 			has_real_create2: true,
@@ -397,6 +1363,9 @@ impl Config {
 			has_chain_id: true,
 			has_self_balance: true,
 			has_ext_code_hash: true,
+			has_push0: true,
+			has_prev_randao: true,
+			has_eof: false,
 			has_token_transfer: false,
 			create_account_if_not_exist: false,
 			has_iscontract: false,
@@ -405,6 +1374,16 @@ impl Config {
 			has_token_issue: false,
 			has_iswitness: false,
 			has_buggy_origin: false,
+			has_buggy_blockhash: false,
+			has_token_id_check: false,
+			has_token_balance_check: false,
+			has_stake_v2: false,
+			has_vote: false,
+			has_freeze: false,
+			has_base_fee: true,
+			has_dynamic_energy: false,
+			has_validate_signature: false,
+			has_shielded: false,
 		}
 	}
 }
@@ -0,0 +1,60 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Serialize, Deserialize};
+use crate::{Config, ConfigError};
+
+/// Config of a fork, either a preset name or explicit parameters.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ForkConfig {
+	/// Preset by constructor name, see `Config::preset`.
+	Preset(String),
+	/// Explicit parameters.
+	Explicit(Config),
+}
+
+/// A fork activated at a block number.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Fork {
+	/// Activation block number.
+	pub block_number: u64,
+	/// Config of the fork.
+	pub config: ForkConfig,
+}
+
+/// Chain parameters, deserializable from any serde format such as JSON or TOML.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ChainParams {
+	/// Forks of the chain.
+	pub forks: Vec<Fork>,
+}
+
+impl ChainParams {
+	/// Resolve presets and validate the config of every fork.
+	pub fn load(self) -> Result<ForkSchedule, ConfigError> {
+		let mut forks = self.forks.into_iter().map(|fork| {
+			let config = match fork.config {
+				ForkConfig::Preset(name) => Config::preset(&name).ok_or(ConfigError::UnknownPreset)?,
+				ForkConfig::Explicit(config) => config,
+			};
+			config.validate()?;
+			Ok((fork.block_number, config))
+		}).collect::<Result<Vec<_>, ConfigError>>()?;
+		forks.sort_by_key(|(block_number, _)| *block_number);
+
+		Ok(ForkSchedule(forks))
+	}
+}
+
+/// Validated configs of a chain, sorted by activation block number.
+#[derive(Clone, Debug)]
+pub struct ForkSchedule(Vec<(u64, Config)>);
+
+impl ForkSchedule {
+	/// Config active at the given block number, `None` if it is before the first fork.
+	pub fn config_at(&self, block_number: u64) -> Option<&Config> {
+		self.0.iter().rev()
+			.find(|(activation, _)| *activation <= block_number)
+			.map(|(_, config)| config)
+	}
+}
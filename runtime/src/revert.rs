@@ -0,0 +1,96 @@
+use core::fmt;
+use alloc::string::String;
+use alloc::vec::Vec;
+use primitive_types::U256;
+
+/// Selector of `Error(string)`.
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+/// Selector of `Panic(uint256)`.
+const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+/// Decoded revert reason.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RevertReason {
+	/// `Error(string)`, raised by `require` and `revert` with a message.
+	Error(String),
+	/// `Panic(uint256)`, raised by compiler-inserted checks.
+	Panic(U256),
+	/// Return data not in any known format.
+	Unknown(Vec<u8>),
+}
+
+impl RevertReason {
+	/// Decode return data of a reverted call. Empty data has no reason.
+	pub fn decode(data: &[u8]) -> Option<Self> {
+		if data.is_empty() {
+			return None
+		}
+
+		let reason = if data.len() >= 4 && data[..4] == ERROR_SELECTOR {
+			decode_string(&data[4..]).map(RevertReason::Error)
+		} else if data.len() == 36 && data[..4] == PANIC_SELECTOR {
+			Some(RevertReason::Panic(U256::from_big_endian(&data[4..])))
+		} else {
+			None
+		};
+
+		Some(reason.unwrap_or_else(|| RevertReason::Unknown(data.to_vec())))
+	}
+
+	/// Description of a `Panic` code, as documented by Solidity.
+	pub fn panic_description(code: U256) -> Option<&'static str> {
+		if code > U256::from(u8::MAX) {
+			return None
+		}
+		Some(match code.as_u32() {
+			0x00 => "generic compiler panic",
+			0x01 => "assertion failed",
+			0x11 => "arithmetic overflow or underflow",
+			0x12 => "division or modulo by zero",
+			0x21 => "invalid enum value",
+			0x22 => "invalid storage byte array encoding",
+			0x31 => "pop on empty array",
+			0x32 => "array index out of bounds",
+			0x41 => "out of memory",
+			0x51 => "call to zero-initialized function",
+			_ => return None,
+		})
+	}
+}
+
+impl fmt::Display for RevertReason {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			RevertReason::Error(message) => write!(f, "{}", message),
+			RevertReason::Panic(code) => match Self::panic_description(*code) {
+				Some(description) => write!(f, "panic: {} (0x{:02x})", description, code),
+				None => write!(f, "panic: 0x{:x}", code),
+			},
+			RevertReason::Unknown(data) => {
+				write!(f, "unknown revert data 0x")?;
+				for byte in data {
+					write!(f, "{:02x}", byte)?;
+				}
+				Ok(())
+			},
+		}
+	}
+}
+
+/// Decode an ABI-encoded `string`, given the data after the selector.
+fn decode_string(data: &[u8]) -> Option<String> {
+	let word = |offset: usize| -> Option<usize> {
+		let word = data.get(offset..offset.checked_add(32)?)?;
+		let value = U256::from_big_endian(word);
+		if value > U256::from(usize::MAX) {
+			return None
+		}
+		Some(value.as_usize())
+	};
+
+	let offset = word(0)?;
+	let len = word(offset)?;
+	let start = offset.checked_add(32)?;
+	let bytes = data.get(start..start.checked_add(len)?)?;
+	Some(String::from_utf8_lossy(bytes).into_owned())
+}
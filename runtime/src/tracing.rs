@@ -0,0 +1,68 @@
+//! Allows to listen to runtime events.
+
+use primitive_types::{H160, H256};
+use crate::{Config, Context, ExitReason, ExternalOpcode, Memory, Opcode, Stack};
+
+environmental::environmental!(listener: dyn EventListener + 'static);
+
+/// Listener of runtime events.
+pub trait EventListener {
+	/// Handle an event.
+	fn event(&mut self, event: Event);
+}
+
+/// Runtime event.
+#[derive(Debug, Copy, Clone)]
+pub enum Event<'a> {
+	/// An opcode is about to be validated and executed.
+	Step {
+		/// Context of the executing frame.
+		context: &'a Context,
+		/// Config of the runtime, with the enabled upgrades.
+		config: &'a Config,
+		/// Opcode, resolved with the enabled upgrades.
+		opcode: Result<Opcode, ExternalOpcode>,
+		/// Program counter.
+		position: &'a Result<usize, ExitReason>,
+		/// Stack before execution.
+		stack: &'a Stack,
+		/// Memory before execution.
+		memory: &'a Memory,
+	},
+	/// An opcode is executed, or failed validation.
+	StepResult {
+		/// `Ok` if the runtime continues, or the exit reason.
+		result: &'a Result<(), ExitReason>,
+		/// Return value of the machine.
+		return_value: &'a [u8],
+	},
+	/// Storage is loaded by `SLOAD`.
+	SLoad {
+		/// Address of the storage.
+		address: H160,
+		/// Storage index.
+		index: H256,
+		/// Loaded value.
+		value: H256,
+	},
+	/// Storage is stored by `SSTORE`.
+	SStore {
+		/// Address of the storage.
+		address: H160,
+		/// Storage index.
+		index: H256,
+		/// Stored value.
+		value: H256,
+	},
+}
+
+impl<'a> Event<'a> {
+	pub(crate) fn emit(self) {
+		listener::with(|listener| listener.event(self));
+	}
+}
+
+/// Run closure with provided listener.
+pub fn using<R, F: FnOnce() -> R>(new: &mut (dyn EventListener + 'static), f: F) -> R {
+	listener::using(new, f)
+}
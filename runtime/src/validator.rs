@@ -0,0 +1,153 @@
+use core::fmt;
+use alloc::vec::Vec;
+use crate::{disassemble, Config, ExternalOpcode, Opcode};
+
+/// Severity of a diagnostic.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Severity {
+	/// Code can be deployed, but may not behave as intended.
+	Warning,
+	/// Code cannot be deployed.
+	Error,
+}
+
+/// Problem found in code before deployment.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Diagnostic {
+	/// Code is larger than `create_contract_limit`.
+	CodeTooLarge {
+		/// Code size.
+		size: usize,
+		/// Limit of the config.
+		limit: usize,
+	},
+	/// Opcode is not defined, or not enabled in the config, and fails when executed.
+	InvalidOpcode {
+		/// Position of the opcode.
+		pc: usize,
+		/// The opcode byte.
+		opcode: u8,
+	},
+	/// `PUSHn` runs past the end of the code, and its immediate is zero-padded.
+	TruncatedPush {
+		/// Position of the opcode.
+		pc: usize,
+		/// Size of the immediate of the opcode.
+		expected: usize,
+		/// Bytes of the immediate present in the code.
+		actual: usize,
+	},
+}
+
+impl Diagnostic {
+	/// Severity of the diagnostic.
+	pub fn severity(&self) -> Severity {
+		match self {
+			Diagnostic::CodeTooLarge { .. } => Severity::Error,
+			Diagnostic::InvalidOpcode { .. } | Diagnostic::TruncatedPush { .. } => Severity::Warning,
+		}
+	}
+}
+
+impl fmt::Display for Diagnostic {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Diagnostic::CodeTooLarge { size, limit } =>
+				write!(f, "code size {} exceeds limit {}", size, limit),
+			Diagnostic::InvalidOpcode { pc, opcode } =>
+				write!(f, "{:06x}: invalid opcode 0x{:02x}", pc, opcode),
+			Diagnostic::TruncatedPush { pc, expected, actual } =>
+				write!(f, "{:06x}: PUSH{} truncated to {} bytes", pc, expected, actual),
+		}
+	}
+}
+
+/// Result of validating code.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Validation {
+	/// Problems found, in order of position.
+	pub diagnostics: Vec<Diagnostic>,
+	/// Positions of valid jump destinations.
+	pub jumpdests: Vec<usize>,
+}
+
+impl Validation {
+	/// Whether the code can be deployed, i.e. there is no error diagnostic.
+	pub fn is_deployable(&self) -> bool {
+		self.diagnostics.iter().all(|d| d.severity() < Severity::Error)
+	}
+}
+
+/// Whether the opcode, resolved with the TRON upgrades, is enabled in the config.
+pub(crate) fn is_enabled(opcode: Result<Opcode, ExternalOpcode>, config: &Config) -> bool {
+	match opcode.map_err(|opcode| config.external_opcode(opcode)) {
+		Ok(Opcode::Revert) => config.has_revert,
+		Ok(Opcode::Push(0)) => config.has_push0,
+		Ok(Opcode::Shl) | Ok(Opcode::Shr) | Ok(Opcode::Sar) => config.has_bitwise_shifting,
+		Ok(Opcode::Invalid) => false,
+
+		Err(ExternalOpcode::ChainId) => config.has_chain_id,
+		Err(ExternalOpcode::BaseFee) => config.has_base_fee,
+		Err(ExternalOpcode::SelfBalance) => config.has_self_balance,
+		Err(ExternalOpcode::ExtCodeHash) => config.has_ext_code_hash,
+		Err(ExternalOpcode::DelegateCall) => config.has_delegate_call,
+		Err(ExternalOpcode::ReturnDataSize) | Err(ExternalOpcode::ReturnDataCopy) => config.has_return_data,
+		Err(ExternalOpcode::Create2) => config.has_create2,
+
+		Err(ExternalOpcode::CallToken) | Err(ExternalOpcode::CallTokenValue) |
+		Err(ExternalOpcode::CallTokenId) | Err(ExternalOpcode::TokenBalance) => config.has_token_transfer,
+		Err(ExternalOpcode::IsContract) => config.has_iscontract,
+		Err(ExternalOpcode::Stake) | Err(ExternalOpcode::Unstake) |
+		Err(ExternalOpcode::RewardBalance) => config.has_stake,
+		Err(ExternalOpcode::WithdrawReward) => config.has_stake || config.has_vote,
+		Err(ExternalOpcode::VoteWitness) => config.has_vote,
+		Err(ExternalOpcode::Freeze) | Err(ExternalOpcode::Unfreeze) |
+		Err(ExternalOpcode::FreezeExpireTime) => config.has_freeze,
+		Err(ExternalOpcode::AssetIssue) | Err(ExternalOpcode::UpdateAsset) => config.has_token_issue,
+		Err(ExternalOpcode::IsWitness) => config.has_iswitness,
+		Err(ExternalOpcode::FreezeBalanceV2) | Err(ExternalOpcode::UnfreezeBalanceV2) |
+		Err(ExternalOpcode::CancelAllUnfreezeV2) | Err(ExternalOpcode::WithdrawExpireUnfreeze) |
+		Err(ExternalOpcode::DelegateResource) | Err(ExternalOpcode::UnDelegateResource) => config.has_stake_v2,
+		Err(ExternalOpcode::Other(_)) => false,
+
+		_ => true,
+	}
+}
+
+/// Validate code before deployment with the config. The designated `INVALID`
+/// (`0xfe`) is not reported.
+pub fn validate(code: &[u8], config: &Config) -> Validation {
+	let mut diagnostics = Vec::new();
+	let mut jumpdests = Vec::new();
+
+	if let Some(limit) = config.create_contract_limit {
+		if code.len() > limit {
+			diagnostics.push(Diagnostic::CodeTooLarge { size: code.len(), limit });
+		}
+	}
+
+	for instruction in disassemble(code) {
+		if instruction.is_jumpdest {
+			jumpdests.push(instruction.pc);
+		}
+		match instruction.opcode {
+			Ok(Opcode::Push(n)) if instruction.push_data.len() < n as usize => {
+				diagnostics.push(Diagnostic::TruncatedPush {
+					pc: instruction.pc,
+					expected: n as usize,
+					actual: instruction.push_data.len(),
+				});
+			},
+			Err(ExternalOpcode::Other(0xfe)) => (),
+			opcode if !is_enabled(opcode, config) => {
+				diagnostics.push(Diagnostic::InvalidOpcode {
+					pc: instruction.pc,
+					opcode: code[instruction.pc],
+				});
+			},
+			_ => (),
+		}
+	}
+
+	Validation { diagnostics, jumpdests }
+}
@@ -0,0 +1,29 @@
+use std::str::FromStr;
+use primitive_types::H160;
+use evm_runtime::address::{from_base58, from_tron_bytes, to_base58, to_tron_bytes, AddressError};
+
+#[test]
+fn base58_round_trip() {
+	let cases = [
+		("T9yD14Nj9j7xAB4dbGeiX9h8unkKHxuWwb", "0000000000000000000000000000000000000000"),
+		("TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6t", "a614f803b6fd780986a42c78ec9c7f77e6ded13c"),
+	];
+	for (base58, hex) in &cases {
+		let address = H160::from_str(hex).unwrap();
+		assert_eq!(&to_base58(address), base58);
+		assert_eq!(from_base58(base58), Ok(address));
+	}
+}
+
+#[test]
+fn base58_errors() {
+	assert_eq!(from_base58("TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj6u"), Err(AddressError::InvalidChecksum));
+	assert_eq!(from_base58("TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj60"), Err(AddressError::InvalidCharacter('0')));
+	assert_eq!(from_base58("TR7NHqjeKQxGTCi8q8ZY4pL8otSzgjLj"), Err(AddressError::InvalidLength(20)));
+
+	let address = H160::from_low_u64_be(1);
+	let mut bytes = to_tron_bytes(address);
+	assert_eq!(from_tron_bytes(&bytes), Ok(address));
+	bytes[0] = 0xa0;
+	assert_eq!(from_tron_bytes(&bytes), Err(AddressError::InvalidPrefix(0xa0)));
+}
@@ -0,0 +1,46 @@
+#![cfg(feature = "async")]
+
+mod common;
+
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+use primitive_types::{H160, H256, U256};
+use evm_runtime::{AsyncHandler, Capture, Config, ExitReason, ExitSucceed, StateAccess};
+use common::{MockHandler, runtime};
+
+impl AsyncHandler for MockHandler {
+	async fn prefetch(&mut self, access: StateAccess) {
+		if let StateAccess::Storage(address, index) = access {
+			if let Some(value) = self.remote.get(&(address, index)) {
+				self.storage.insert((address, index), *value);
+			}
+		}
+	}
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+	let mut future = pin!(future);
+	let mut context = Context::from_waker(Waker::noop());
+	loop {
+		if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+			return output
+		}
+	}
+}
+
+#[test]
+fn run_async_prefetches_storage() {
+	let config = Config::istanbul();
+	// MSTORE(0, SLOAD(1)), RETURN(0, 32)
+	let mut runtime = runtime("60015460005260206000f3", &config);
+	let mut handler = MockHandler::default();
+	handler.remote.insert((H160::from_low_u64_be(2), H256::from_low_u64_be(1)), H256::from_low_u64_be(7));
+
+	match block_on(runtime.run_async(&mut handler)) {
+		Capture::Exit(reason) => assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned)),
+		Capture::Trap(_) => panic!("unexpected trap"),
+	}
+	assert_eq!(U256::from_big_endian(&runtime.machine().return_value()), U256::from(7));
+	assert_eq!(handler.storage.len(), 1);
+}
@@ -0,0 +1,125 @@
+#![allow(dead_code)]
+
+use std::collections::{BTreeMap, BTreeSet};
+use primitive_types::{H160, H256, U256};
+use evm_runtime::{assemble, Bytes, Capture, Config, Context, CreateScheme, ExitError, ExitReason, ExternalOpcode,
+				  Handler, Machine, Opcode, Runtime, Shared, Stack, Suicide, Transfer};
+
+/// Handler with in-memory storage, deferring every call and create to the host.
+#[derive(Default)]
+pub struct MockHandler {
+	/// Storage available to the runtime.
+	pub storage: BTreeMap<(H160, H256), H256>,
+	/// Storage available only after it is fetched.
+	pub remote: BTreeMap<(H160, H256), H256>,
+	/// Balances, zero if absent.
+	pub balances: BTreeMap<H160, U256>,
+	/// Chain ID.
+	pub chain_id: U256,
+	/// TRC-10 assets that do not exist.
+	pub missing_tokens: BTreeSet<U256>,
+}
+
+impl Handler for MockHandler {
+	type CreateInterrupt = H160;
+	type CreateFeedback = ();
+	type CallInterrupt = (H160, Vec<u8>);
+	type CallFeedback = ();
+
+	fn balance(&self, address: H160) -> U256 { self.balances.get(&address).cloned().unwrap_or_default() }
+	fn code_size(&self, _address: H160) -> U256 { U256::zero() }
+	fn code_hash(&self, _address: H160) -> H256 { H256::default() }
+	fn code(&self, _address: H160) -> Bytes { Bytes::new() }
+	fn storage(&self, address: H160, index: H256) -> Option<H256> { self.storage.get(&(address, index)).cloned() }
+	fn original_storage(&self, _address: H160, _index: H256) -> H256 { H256::default() }
+	fn gas_left(&self) -> U256 { U256::from(100000) }
+	fn gas_price(&self) -> U256 { U256::zero() }
+	fn origin(&self) -> H160 { H160::default() }
+	fn block_hash(&self, _number: U256) -> H256 { H256::default() }
+	fn block_number(&self) -> U256 { U256::zero() }
+	fn block_coinbase(&self) -> H160 { H160::default() }
+	fn block_timestamp(&self) -> U256 { U256::zero() }
+	fn block_difficulty(&self) -> U256 { U256::zero() }
+	fn block_gas_limit(&self) -> U256 { U256::zero() }
+	fn chain_id(&self) -> U256 { self.chain_id }
+	fn block_base_fee_per_gas(&self) -> U256 { U256::zero() }
+	fn exists(&self, _address: H160) -> bool { true }
+	fn deleted(&self, _address: H160) -> bool { false }
+	fn is_cold(&self, _address: H160, _index: Option<H256>) -> bool { false }
+	fn set_storage(&mut self, _address: H160, _index: H256, _value: H256) -> Result<(), ExitError> { Ok(()) }
+	fn log(&mut self, _address: H160, _topics: Vec<H256>, _data: Vec<u8>) -> Result<(), ExitError> { Ok(()) }
+	fn mark_delete(&mut self, _suicide: Suicide) -> Result<(), ExitError> { Ok(()) }
+
+	fn create(
+		&mut self,
+		caller: H160,
+		_scheme: CreateScheme,
+		_value: U256,
+		_init_code: Vec<u8>,
+		_target_gas: Option<usize>,
+	) -> Capture<(ExitReason, Option<H160>, Vec<u8>), Self::CreateInterrupt> {
+		Capture::Trap(caller)
+	}
+
+	fn call(
+		&mut self,
+		code_address: H160,
+		_transfer: Option<Transfer>,
+		input: Bytes,
+		_target_gas: Option<usize>,
+		_is_static: bool,
+		_context: Context,
+	) -> Capture<(ExitReason, Vec<u8>), Self::CallInterrupt> {
+		Capture::Trap((code_address, input.to_vec()))
+	}
+
+	fn pre_validate(
+		&mut self,
+		_context: &Context,
+		_opcode: Result<Opcode, ExternalOpcode>,
+		_stack: &Stack,
+	) -> Result<(), ExitError> {
+		Ok(())
+	}
+
+	fn other(&mut self, _opcode: u8, _machine: &mut Machine) -> Result<(), ExitError> {
+		Err(ExitError::OutOfGas)
+	}
+
+	fn token_balance(&self, _address: H160, _token_id: U256) -> U256 { U256::zero() }
+	fn validate_token(&self, token_id: U256) -> Result<(), ExitError> {
+		if self.missing_tokens.contains(&token_id) { Err(ExitError::TransferException) } else { Ok(()) }
+	}
+	fn is_contract(&self, _address: H160) -> bool { false }
+	fn freeze(&mut self, _address: H160, _receiver: H160, _amount: U256, _resource_type: U256) -> bool { false }
+	fn unfreeze(&mut self, _address: H160, _receiver: H160, _resource_type: U256) -> bool { false }
+	fn freeze_expire_time(&self, _address: H160, _target: H160, _resource_type: U256) -> U256 { U256::zero() }
+	fn is_witness(&self, _address: H160) -> bool { false }
+	fn reward_balance(&self, _address: H160) -> U256 { U256::zero() }
+	fn withdraw_reward(&mut self, _address: H160) -> U256 { U256::zero() }
+	fn vote_witness(&mut self, _address: H160, _votes: Vec<(H160, U256)>) -> bool { false }
+	fn freeze_balance_v2(&mut self, _address: H160, _amount: U256, _resource_type: U256) -> bool { false }
+	fn unfreeze_balance_v2(&mut self, _address: H160, _amount: U256, _resource_type: U256) -> bool { false }
+	fn cancel_all_unfreeze_v2(&mut self, _address: H160) -> bool { false }
+	fn withdraw_expire_unfreeze(&mut self, _address: H160) -> U256 { U256::zero() }
+	fn delegate_resource(&mut self, _address: H160, _receiver: H160, _amount: U256, _resource_type: U256) -> bool { false }
+	fn undelegate_resource(&mut self, _address: H160, _receiver: H160, _amount: U256, _resource_type: U256) -> bool { false }
+	fn nonce(&self) -> u64 { 0 }
+	fn incr_nonce(&mut self) { }
+	fn transaction_root_hash(&self) -> H256 { H256::default() }
+}
+
+pub fn assembled<'config>(source: &str, config: &'config Config) -> Runtime<'config> {
+	runtime(&hex::encode(assemble(source).unwrap()), config)
+}
+
+pub fn runtime<'config>(code: &str, config: &'config Config) -> Runtime<'config> {
+	let context = Context {
+		caller: H160::default(),
+		address: H160::from_low_u64_be(2),
+		call_value: U256::zero(),
+		call_token_id: U256::zero(),
+		call_token_value: U256::zero(),
+	};
+	Runtime::new(Shared::new(hex::decode(code).unwrap()), Shared::new(Vec::new()), context, config)
+}
@@ -0,0 +1,37 @@
+use evm_runtime::{ContractResult, ExitError, ExitFatal, ExitReason, ExitRevert, ExitSucceed};
+
+#[test]
+fn contract_result_codes() {
+	for (code, result) in ContractResult::ALL.iter().enumerate() {
+		assert_eq!(result.code(), code as i32);
+		assert_eq!(ContractResult::from_code(code as i32), Some(*result));
+	}
+	assert_eq!(ContractResult::from_code(-1), None);
+	assert_eq!(ContractResult::from_code(16), None);
+	assert_eq!(ContractResult::OutOfEnergy.code(), 10);
+	assert_eq!(ContractResult::JvmStackOverFlow.to_string(), "JVM_STACK_OVER_FLOW");
+}
+
+#[test]
+fn contract_result_of_exit_reason() {
+	let cases: Vec<(ExitReason, ContractResult)> = vec![
+		(ExitSucceed::Returned.into(), ContractResult::Success),
+		(ExitSucceed::Suicided.into(), ContractResult::Success),
+		(ExitRevert::Reverted.into(), ContractResult::Revert),
+		(ExitError::OutOfGas.into(), ContractResult::OutOfEnergy),
+		(ExitError::InvalidJump.into(), ContractResult::BadJumpDestination),
+		(ExitError::StackUnderflow.into(), ContractResult::StackTooSmall),
+		(ExitError::StackOverflow.into(), ContractResult::StackTooLarge),
+		(ExitError::DesignatedInvalid.into(), ContractResult::IllegalOperation),
+		(ExitError::CallTooDeep.into(), ContractResult::JvmStackOverFlow),
+		(ExitError::TransferException.into(), ContractResult::TransferFailed),
+		(ExitError::PrecompileFailure.into(), ContractResult::PrecompiledContract),
+		(ExitError::OutOfTime.into(), ContractResult::OutOfTime),
+		(ExitError::InvalidCode.into(), ContractResult::InvalidCode),
+		(ExitError::StaticCallModification.into(), ContractResult::Unknown),
+		(ExitFatal::NotSupported.into(), ContractResult::Unknown),
+	];
+	for (reason, result) in cases {
+		assert_eq!(ContractResult::from(reason), result, "{:?}", reason);
+	}
+}
@@ -0,0 +1,33 @@
+mod common;
+
+use primitive_types::{H160, H256, U256};
+use evm_runtime::{Capture, Config, ExitError, ExitReason, ExitSucceed};
+use common::{MockHandler, assembled};
+
+fn run(source: &str, config: &Config, handler: &mut MockHandler) -> Result<H256, ExitReason> {
+	let mut runtime = assembled(source, config);
+	let reason = match runtime.run(handler) {
+		Capture::Exit(reason) => reason,
+		Capture::Trap(_) => panic!("unexpected trap"),
+	};
+	match reason {
+		ExitReason::Succeed(ExitSucceed::Stopped) => Ok(runtime.machine().stack().peek(0).unwrap()),
+		reason => Err(reason),
+	}
+}
+
+#[test]
+fn chain_id_and_self_balance_gated_by_config() {
+	let genesis = U256::from_big_endian(&[0x2b; 32]);
+	let mut handler = MockHandler { chain_id: genesis, ..Default::default() };
+	handler.balances.insert(H160::from_low_u64_be(2), U256::from(7));
+
+	let config = Config::great_voyage_4_1();
+	assert_eq!(run("CHAINID STOP", &config, &mut handler), Ok(H256::from_slice(&[0x2b; 32])));
+	assert_eq!(run("SELFBALANCE STOP", &config, &mut handler), Ok(H256::from_low_u64_be(7)));
+
+	let config = Config::odyssey_3_7();
+	for source in ["CHAINID STOP", "SELFBALANCE STOP"].iter() {
+		assert_eq!(run(source, &config, &mut handler), Err(ExitError::IllegalOperation.into()), "{}", source);
+	}
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use primitive_types::{H160, H256, U256};
+use evm_runtime::{Capture, Config, Context, ExitFatal, ExitReason, ExitSucceed, ExternalOpcode, Inspector, Interrupt,
+				  Machine, Opcode, PendingInterrupt};
+use common::{MockHandler, assembled, runtime};
+
+#[test]
+fn detached_call() {
+	let config = Config::istanbul();
+	let mut runtime = assembled("
+		PUSH1 32 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH20 3 GAS CALL
+		PUSH1 32 PUSH1 0 RETURN
+	", &config);
+	let mut handler = MockHandler::default();
+
+	let interrupt = match runtime.run(&mut handler) {
+		Capture::Trap(resolve) => resolve.detach(),
+		Capture::Exit(reason) => panic!("unexpected exit {:?}", reason),
+	};
+	match interrupt {
+		Interrupt::Call((address, input)) => {
+			assert_eq!(address, H160::from_low_u64_be(3));
+			assert!(input.is_empty());
+		},
+		Interrupt::Create(_) => panic!("unexpected create"),
+	}
+	assert_eq!(runtime.pending(), Some(PendingInterrupt::Call { out_offset: U256::zero(), out_len: U256::from(32) }));
+
+	// The runtime does not step past the call until its result is fed back.
+	let unhandled = ExitReason::Fatal(ExitFatal::UnhandledInterrupt);
+	match runtime.step(&mut handler) {
+		Err(Capture::Exit(reason)) => assert_eq!(reason, unhandled),
+		_ => panic!("stepped a runtime with a pending interrupt"),
+	}
+	match runtime.run(&mut handler) {
+		Capture::Exit(reason) => assert_eq!(reason, unhandled),
+		Capture::Trap(_) => panic!("unexpected trap"),
+	}
+	assert_eq!(runtime.machine().position(), &Ok(33));
+
+	assert_eq!(runtime.feedback_call(ExitReason::Succeed(ExitSucceed::Returned), vec![0x42; 32]), Ok(()));
+	assert_eq!(runtime.pending(), None);
+	assert_eq!(runtime.machine().stack().peek(0), Ok(H256::from_low_u64_be(1)));
+
+	match runtime.run(&mut handler) {
+		Capture::Exit(reason) => assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned)),
+		Capture::Trap(_) => panic!("unexpected trap"),
+	}
+	assert_eq!(runtime.machine().return_value(), vec![0x42; 32]);
+}
+
+#[test]
+fn feedback_without_pending_interrupt() {
+	let config = Config::istanbul();
+	let mut runtime = runtime("00", &config);
+	assert!(runtime.feedback_create(ExitReason::Succeed(ExitSucceed::Returned), None, Vec::new()).is_err());
+}
+
+#[derive(Default)]
+struct StepCounter {
+	steps: usize,
+	results: Vec<Result<(), ExitReason>>,
+}
+
+impl Inspector for StepCounter {
+	fn step(&mut self, _context: &Context, _opcode: Result<Opcode, ExternalOpcode>, _machine: &Machine) {
+		self.steps += 1;
+	}
+
+	fn step_result(&mut self, _context: &Context, result: &Result<(), ExitReason>, _machine: &Machine) {
+		self.results.push(*result);
+	}
+}
+
+#[test]
+fn step_results_of_interrupts() {
+	let config = Config::istanbul();
+	let mut handler = MockHandler::default();
+
+	for source in &[
+		"PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH20 3 GAS CALL",
+		"PUSH1 0 PUSH1 0 PUSH1 0 CREATE",
+	] {
+		let mut runtime = assembled(source, &config);
+		let mut counter = StepCounter::default();
+		assert!(matches!(runtime.run_inspect(&mut handler, &mut counter), Capture::Trap(_)));
+		assert!(counter.steps > 0);
+		assert_eq!(counter.results, vec![Ok(()); counter.steps]);
+	}
+}
@@ -0,0 +1,31 @@
+#![cfg(feature = "arc")]
+
+mod common;
+
+use std::thread;
+use primitive_types::U256;
+use evm_runtime::{Capture, Config, ExitReason, ExitSucceed, Machine, Runtime};
+use common::{MockHandler, runtime};
+
+#[test]
+fn runtime_is_send_and_sync() {
+	fn assert_send<T: Send>() { }
+	fn assert_sync<T: Sync>() { }
+	assert_send::<Runtime<'static>>();
+	assert_sync::<Runtime<'static>>();
+	assert_send::<Machine>();
+	assert_sync::<Machine>();
+
+	let config = Box::leak(Box::new(Config::istanbul()));
+	// MSTORE(0, 1 + 2), RETURN(0, 32)
+	let mut runtime = runtime("600160020160005260206000f3", config);
+
+	let ret = thread::spawn(move || {
+		match runtime.run(&mut MockHandler::default()) {
+			Capture::Exit(reason) => assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned)),
+			Capture::Trap(_) => panic!("unexpected trap"),
+		}
+		runtime.machine().return_value()
+	}).join().unwrap();
+	assert_eq!(U256::from_big_endian(&ret), U256::from(3));
+}
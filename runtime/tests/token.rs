@@ -0,0 +1,40 @@
+mod common;
+
+use primitive_types::U256;
+use evm_runtime::{Capture, Config, ExitError, ExitReason, ExitSucceed};
+use common::{MockHandler, assembled};
+
+fn run(source: &str, handler: &mut MockHandler) -> Capture<ExitReason, ()> {
+	run_with(&Config::great_voyage_4_1(), source, handler)
+}
+
+fn run_with(config: &Config, source: &str, handler: &mut MockHandler) -> Capture<ExitReason, ()> {
+	let mut runtime = assembled(source, config);
+	let result = match runtime.run(handler) {
+		Capture::Exit(reason) => Capture::Exit(reason),
+		Capture::Trap(_) => Capture::Trap(()),
+	};
+	result
+}
+
+#[test]
+fn missing_token_rejected() {
+	let mut handler = MockHandler::default();
+	handler.missing_tokens.insert(U256::from(1_000_002));
+
+	let balance = |token_id| format!("PUSH1 2 PUSH3 {} TOKENBALANCE STOP", token_id);
+	let call = |token_id| format!(
+		"PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH3 {} PUSH1 1 PUSH1 3 PUSH2 0xffff CALLTOKEN STOP", token_id,
+	);
+
+	assert_eq!(run(&balance("0x0f4241"), &mut handler), Capture::Exit(ExitSucceed::Stopped.into()));
+	assert_eq!(run(&call("0x0f4241"), &mut handler), Capture::Trap(()));
+
+	// Like java-tron, TOKENBALANCE of a missing asset is zero unless checked.
+	let rejected = Capture::Exit(ExitError::TransferException.into());
+	assert_eq!(run(&balance("0x0f4242"), &mut handler), Capture::Exit(ExitSucceed::Stopped.into()));
+	assert_eq!(run(&call("0x0f4242"), &mut handler), rejected);
+
+	let config = Config::great_voyage_4_1().builder().has_token_balance_check(true).build().unwrap();
+	assert_eq!(run_with(&config, &balance("0x0f4242"), &mut handler), rejected);
+}
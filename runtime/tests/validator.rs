@@ -0,0 +1,31 @@
+use evm_runtime::{assemble, validate, Config, Diagnostic, Severity};
+
+#[test]
+fn validate_reports_diagnostics() {
+	let config = Config::istanbul();
+	// PUSH0 is not enabled before Shanghai, and PUSH2 is cut short by the end of the code.
+	let mut code = assemble("PUSH0 loop: JUMPDEST PUSH1 0x5b INVALID").unwrap();
+	code.extend_from_slice(&[0x61, 0x01]);
+
+	let validation = validate(&code, &config);
+	assert_eq!(validation.jumpdests, vec![1, 2]);
+	assert_eq!(validation.diagnostics, vec![
+		Diagnostic::InvalidOpcode { pc: 0, opcode: 0x5f },
+		Diagnostic::TruncatedPush { pc: 6, expected: 2, actual: 1 },
+	]);
+	assert!(validation.is_deployable());
+
+	let validation = validate(&code, &Config::shanghai());
+	assert_eq!(validation.diagnostics, vec![
+		Diagnostic::TruncatedPush { pc: 6, expected: 2, actual: 1 },
+	]);
+}
+
+#[test]
+fn validate_code_size_limit() {
+	let code = vec![0u8; 0x6001];
+	let validation = validate(&code, &Config::istanbul());
+	assert_eq!(validation.diagnostics, vec![Diagnostic::CodeTooLarge { size: 0x6001, limit: 0x6000 }]);
+	assert_eq!(validation.diagnostics[0].severity(), Severity::Error);
+	assert!(!validation.is_deployable());
+}
@@ -0,0 +1,64 @@
+use core::fmt;
+use sha3::{Digest, Keccak256};
+use super::Log;
+
+/// 2048-bit bloom filter of logs, as in the receipt.
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct Bloom(pub [u8; 256]);
+
+impl Default for Bloom {
+	fn default() -> Self {
+		Bloom([0u8; 256])
+	}
+}
+
+impl fmt::Debug for Bloom {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "Bloom(0x")?;
+		for byte in &self.0[..] {
+			write!(f, "{:02x}", byte)?;
+		}
+		write!(f, ")")
+	}
+}
+
+impl Bloom {
+	/// Compute the bloom of the given logs.
+	pub fn from_logs<'a, I: IntoIterator<Item=&'a Log>>(logs: I) -> Self {
+		let mut bloom = Self::default();
+		for log in logs {
+			bloom.accrue_log(log);
+		}
+		bloom
+	}
+
+	/// Add the address and topics of a log.
+	pub fn accrue_log(&mut self, log: &Log) {
+		self.accrue(log.address.as_bytes());
+		for topic in &log.topics {
+			self.accrue(topic.as_bytes());
+		}
+	}
+
+	/// Add an input, setting three bits chosen from its Keccak-256 hash.
+	pub fn accrue(&mut self, input: &[u8]) {
+		for (byte, bit) in Self::bits(input).iter() {
+			self.0[*byte] |= *bit;
+		}
+	}
+
+	/// Whether the input may have been added.
+	pub fn contains(&self, input: &[u8]) -> bool {
+		Self::bits(input).iter().all(|(byte, bit)| self.0[*byte] & *bit != 0)
+	}
+
+	fn bits(input: &[u8]) -> [(usize, u8); 3] {
+		let hash = Keccak256::digest(input);
+		let mut bits = [(0, 0); 3];
+		for (i, bit) in bits.iter_mut().enumerate() {
+			let index = (((hash[2 * i] as usize) << 8) | hash[2 * i + 1] as usize) & 2047;
+			*bit = (255 - index / 8, 1u8 << (index % 8));
+		}
+		bits
+	}
+}
@@ -0,0 +1,281 @@
+use core::cell::RefCell;
+use core::ops::RangeBounds;
+use alloc::vec::Vec;
+use alloc::collections::BTreeMap;
+use primitive_types::{H160, H256, U256};
+use crate::Bytes;
+use super::{Basic, Backend, ApplyBackend, Apply, Log, Stake};
+
+/// Least recently used map with a fixed capacity.
+#[derive(Clone, Debug)]
+struct Lru<K, V> {
+	entries: BTreeMap<K, (V, u64)>,
+	order: BTreeMap<u64, K>,
+	tick: u64,
+	capacity: usize,
+}
+
+impl<K: Ord + Clone, V> Lru<K, V> {
+	fn new(capacity: usize) -> Self {
+		Self { entries: BTreeMap::new(), order: BTreeMap::new(), tick: 0, capacity }
+	}
+
+	fn get(&mut self, key: &K) -> Option<&V> {
+		let tick = self.tick;
+		let (value, used) = self.entries.get_mut(key)?;
+		self.order.remove(used);
+		self.order.insert(tick, key.clone());
+		*used = tick;
+		self.tick += 1;
+		Some(value)
+	}
+
+	fn insert(&mut self, key: K, value: V) {
+		if self.capacity == 0 {
+			return
+		}
+		if let Some((_, used)) = self.entries.remove(&key) {
+			self.order.remove(&used);
+		} else if self.entries.len() >= self.capacity {
+			if let Some((_, oldest)) = self.order.pop_first() {
+				self.entries.remove(&oldest);
+			}
+		}
+		self.order.insert(self.tick, key.clone());
+		self.entries.insert(key, (value, self.tick));
+		self.tick += 1;
+	}
+
+	fn remove(&mut self, key: &K) {
+		if let Some((_, used)) = self.entries.remove(key) {
+			self.order.remove(&used);
+		}
+	}
+
+	fn remove_range(&mut self, range: impl RangeBounds<K>) {
+		let keys = self.entries.range(range).map(|(key, _)| key.clone()).collect::<Vec<_>>();
+		for key in keys {
+			self.remove(&key);
+		}
+	}
+
+	fn clear(&mut self) {
+		self.entries.clear();
+		self.order.clear();
+	}
+
+	fn len(&self) -> usize {
+		self.entries.len()
+	}
+}
+
+/// Code of an account, cached together as they are read together.
+#[derive(Clone, Debug)]
+struct CachedCode {
+	code: Bytes,
+	hash: H256,
+}
+
+/// Hit and miss counters of a `CachedBackend`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct BackendCacheStats {
+	/// Reads served from the cache.
+	pub hits: u64,
+	/// Reads forwarded to the backend.
+	pub misses: u64,
+	/// Cached codes.
+	pub codes: usize,
+	/// Cached storage slots.
+	pub storages: usize,
+}
+
+#[derive(Clone, Debug)]
+struct Caches {
+	code: Lru<H160, CachedCode>,
+	storage: Lru<(H160, H256), Option<H256>>,
+	exists: Lru<H160, bool>,
+	stats: BackendCacheStats,
+}
+
+/// Backend caching account code, storage slots and account existence of another
+/// backend, evicting the least recently used entries beyond a capacity. Changes
+/// applied through it invalidate the entries of the changed accounts; changes made
+/// to the backend otherwise must be followed by `invalidate` or `clear`.
+#[derive(Clone, Debug)]
+pub struct CachedBackend<B> {
+	backend: B,
+	caches: RefCell<Caches>,
+}
+
+impl<B: Backend> CachedBackend<B> {
+	/// Create a new cache of the backend, holding up to `capacity` entries of each
+	/// of code, storage and existence.
+	pub fn new(backend: B, capacity: usize) -> Self {
+		Self {
+			backend,
+			caches: RefCell::new(Caches {
+				code: Lru::new(capacity),
+				storage: Lru::new(capacity),
+				exists: Lru::new(capacity),
+				stats: BackendCacheStats::default(),
+			}),
+		}
+	}
+
+	/// Get the underlying backend.
+	pub fn backend(&self) -> &B {
+		&self.backend
+	}
+
+	/// Get the underlying backend.
+	pub fn into_inner(self) -> B {
+		self.backend
+	}
+
+	/// Get the cache counters.
+	pub fn stats(&self) -> BackendCacheStats {
+		let caches = self.caches.borrow();
+		BackendCacheStats { codes: caches.code.len(), storages: caches.storage.len(), ..caches.stats }
+	}
+
+	/// Drop cached entries of the account.
+	pub fn invalidate(&self, address: H160) {
+		let mut caches = self.caches.borrow_mut();
+		caches.code.remove(&address);
+		caches.storage.remove_range((address, H256::zero())..=(address, H256::repeat_byte(0xff)));
+		caches.exists.remove(&address);
+	}
+
+	/// Drop all cached entries.
+	pub fn clear(&self) {
+		let mut caches = self.caches.borrow_mut();
+		caches.code.clear();
+		caches.storage.clear();
+		caches.exists.clear();
+	}
+
+	fn cached_code(&self, address: H160) -> CachedCode {
+		let caches = &mut *self.caches.borrow_mut();
+		if let Some(code) = caches.code.get(&address) {
+			caches.stats.hits += 1;
+			return code.clone()
+		}
+		caches.stats.misses += 1;
+		let code = CachedCode { code: self.backend.code(address), hash: self.backend.code_hash(address) };
+		caches.code.insert(address, code.clone());
+		code
+	}
+}
+
+impl<B: Backend> Backend for CachedBackend<B> {
+	fn gas_price(&self) -> U256 { self.backend.gas_price() }
+	fn origin(&self) -> H160 { self.backend.origin() }
+	fn block_hash(&self, number: U256) -> H256 { self.backend.block_hash(number) }
+	fn block_number(&self) -> U256 { self.backend.block_number() }
+	fn block_coinbase(&self) -> H160 { self.backend.block_coinbase() }
+	fn block_timestamp(&self) -> U256 { self.backend.block_timestamp() }
+	fn block_difficulty(&self) -> U256 { self.backend.block_difficulty() }
+	fn block_randomness(&self) -> Option<H256> { self.backend.block_randomness() }
+	fn block_gas_limit(&self) -> U256 { self.backend.block_gas_limit() }
+	fn chain_id(&self) -> U256 { self.backend.chain_id() }
+	fn block_base_fee_per_gas(&self) -> U256 { self.backend.block_base_fee_per_gas() }
+
+	fn exists(&self, address: H160) -> bool {
+		let caches = &mut *self.caches.borrow_mut();
+		if let Some(exists) = caches.exists.get(&address) {
+			caches.stats.hits += 1;
+			return *exists
+		}
+		caches.stats.misses += 1;
+		let exists = self.backend.exists(address);
+		caches.exists.insert(address, exists);
+		exists
+	}
+
+	fn basic(&self, address: H160) -> Basic {
+		self.backend.basic(address)
+	}
+
+	fn code_hash(&self, address: H160) -> H256 {
+		self.cached_code(address).hash
+	}
+
+	fn code_size(&self, address: H160) -> usize {
+		self.cached_code(address).code.len()
+	}
+
+	fn code(&self, address: H160) -> Bytes {
+		self.cached_code(address).code
+	}
+
+	fn storage(&self, address: H160, index: H256) -> Option<H256> {
+		let caches = &mut *self.caches.borrow_mut();
+		if let Some(value) = caches.storage.get(&(address, index)) {
+			caches.stats.hits += 1;
+			return *value
+		}
+		caches.stats.misses += 1;
+		let value = self.backend.storage(address, index);
+		caches.storage.insert((address, index), value);
+		value
+	}
+
+	fn transaction_root_hash(&self) -> H256 { self.backend.transaction_root_hash() }
+
+	fn validate_multisig(&self, address: H160, perm_id: U256, message: H256, signatures: &[&[u8]]) -> bool {
+		self.backend.validate_multisig(address, perm_id, message, signatures)
+	}
+
+	fn freeze_expire_time(&self, address: H160, target: H160, resource_type: U256) -> U256 {
+		self.backend.freeze_expire_time(address, target, resource_type)
+	}
+
+	fn energy_factor(&self, address: H160) -> usize {
+		self.backend.energy_factor(address)
+	}
+
+	fn token_exists(&self, token_id: U256) -> bool {
+		self.backend.token_exists(token_id)
+	}
+
+	fn validate_token_transfer(&self, source: H160, target: H160, token_id: U256, value: U256) -> bool {
+		self.backend.validate_token_transfer(source, target, token_id, value)
+	}
+
+	fn black_hole(&self) -> Option<H160> {
+		self.backend.black_hole()
+	}
+
+	fn is_witness(&self, address: H160) -> bool {
+		self.backend.is_witness(address)
+	}
+
+	fn reward_balance(&self, address: H160) -> U256 {
+		self.backend.reward_balance(address)
+	}
+
+	fn validate_stake(&self, stake: &Stake) -> Option<U256> {
+		self.backend.validate_stake(stake)
+	}
+}
+
+impl<B: Backend + ApplyBackend> ApplyBackend for CachedBackend<B> {
+	fn apply<A, I, L>(
+		&mut self,
+		values: A,
+		logs: L,
+		delete_empty: bool,
+	) where
+		A: IntoIterator<Item=Apply<I>>,
+		I: IntoIterator<Item=(H256, H256)>,
+		L: IntoIterator<Item=Log>,
+	{
+		let values = values.into_iter().collect::<Vec<_>>();
+		for value in &values {
+			match value {
+				Apply::Modify { address, .. } | Apply::Delete { address } => self.invalidate(*address),
+			}
+		}
+		self.backend.apply(values, logs, delete_empty);
+	}
+}
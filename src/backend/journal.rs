@@ -0,0 +1,228 @@
+use alloc::vec::Vec;
+use alloc::collections::{BTreeMap, BTreeSet};
+use primitive_types::{H160, H256, U256};
+use crate::Bytes;
+use sha3::{Digest, Keccak256};
+use super::{Basic, Backend, ApplyBackend, Apply, Log, Stake};
+
+/// Pending state of an account in a `JournaledBackend`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct JournaledAccount {
+	basic: Basic,
+	code: Option<Vec<u8>>,
+	storage: BTreeMap<H256, H256>,
+	reset_storage: bool,
+}
+
+/// Backend accumulating applied changes on top of another backend, without
+/// modifying it, so that transactions of a block see the state left by previous
+/// ones. Origin, gas price and transaction root hash are set per transaction.
+#[derive(Clone, Debug)]
+pub struct JournaledBackend<'backend, B> {
+	backend: &'backend B,
+	accounts: BTreeMap<H160, JournaledAccount>,
+	deleted: BTreeSet<H160>,
+	origin: H160,
+	gas_price: U256,
+	transaction_root_hash: H256,
+}
+
+impl<'backend, B: Backend> JournaledBackend<'backend, B> {
+	/// Create a new journal over the backend, with its transaction environment.
+	pub fn new(backend: &'backend B) -> Self {
+		Self {
+			backend,
+			accounts: BTreeMap::new(),
+			deleted: BTreeSet::new(),
+			origin: backend.origin(),
+			gas_price: backend.gas_price(),
+			transaction_root_hash: backend.transaction_root_hash(),
+		}
+	}
+
+	/// Get the underlying backend.
+	pub fn backend(&self) -> &'backend B {
+		self.backend
+	}
+
+	/// Set the environment of the next transaction.
+	pub fn set_transaction(&mut self, origin: H160, gas_price: U256, transaction_root_hash: H256) {
+		self.origin = origin;
+		self.gas_price = gas_price;
+		self.transaction_root_hash = transaction_root_hash;
+	}
+
+	/// Deconstruct the journal, returning all changes to be applied to the
+	/// underlying backend.
+	#[must_use]
+	pub fn deconstruct(
+		self
+	) -> impl IntoIterator<Item=Apply<impl IntoIterator<Item=(H256, H256)>>> {
+		let mut applies = Vec::<Apply<BTreeMap<H256, H256>>>::new();
+
+		for (address, account) in self.accounts {
+			applies.push(Apply::Modify {
+				address,
+				basic: account.basic,
+				code: account.code,
+				storage: account.storage,
+				reset_storage: account.reset_storage,
+			});
+		}
+
+		for address in self.deleted {
+			applies.push(Apply::Delete { address });
+		}
+
+		applies
+	}
+}
+
+impl<'backend, B: Backend> Backend for JournaledBackend<'backend, B> {
+	fn gas_price(&self) -> U256 { self.gas_price }
+	fn origin(&self) -> H160 { self.origin }
+	fn block_hash(&self, number: U256) -> H256 { self.backend.block_hash(number) }
+	fn block_number(&self) -> U256 { self.backend.block_number() }
+	fn block_coinbase(&self) -> H160 { self.backend.block_coinbase() }
+	fn block_timestamp(&self) -> U256 { self.backend.block_timestamp() }
+	fn block_difficulty(&self) -> U256 { self.backend.block_difficulty() }
+	fn block_randomness(&self) -> Option<H256> { self.backend.block_randomness() }
+	fn block_gas_limit(&self) -> U256 { self.backend.block_gas_limit() }
+	fn chain_id(&self) -> U256 { self.backend.chain_id() }
+	fn block_base_fee_per_gas(&self) -> U256 { self.backend.block_base_fee_per_gas() }
+
+	fn exists(&self, address: H160) -> bool {
+		self.accounts.contains_key(&address) ||
+			(!self.deleted.contains(&address) && self.backend.exists(address))
+	}
+
+	fn basic(&self, address: H160) -> Basic {
+		match self.accounts.get(&address) {
+			Some(account) => account.basic.clone(),
+			None if self.deleted.contains(&address) => Basic::default(),
+			None => self.backend.basic(address),
+		}
+	}
+
+	fn code_hash(&self, address: H160) -> H256 {
+		match self.accounts.get(&address).and_then(|v| v.code.as_ref()) {
+			Some(code) => H256::from_slice(&Keccak256::digest(code)),
+			None => self.backend.code_hash(address),
+		}
+	}
+
+	fn code_size(&self, address: H160) -> usize {
+		match self.accounts.get(&address).and_then(|v| v.code.as_ref()) {
+			Some(code) => code.len(),
+			None => self.backend.code_size(address),
+		}
+	}
+
+	fn code(&self, address: H160) -> Bytes {
+		match self.accounts.get(&address).and_then(|v| v.code.as_ref()) {
+			Some(code) => Bytes::from(code.clone()),
+			None => self.backend.code(address),
+		}
+	}
+
+	fn storage(&self, address: H160, index: H256) -> Option<H256> {
+		match self.accounts.get(&address) {
+			Some(account) => match account.storage.get(&index) {
+				Some(value) => Some(*value),
+				None if account.reset_storage => None,
+				None => self.backend.storage(address, index),
+			},
+			None if self.deleted.contains(&address) => None,
+			None => self.backend.storage(address, index),
+		}
+	}
+
+	fn transaction_root_hash(&self) -> H256 { self.transaction_root_hash }
+
+	fn validate_multisig(&self, address: H160, perm_id: U256, message: H256, signatures: &[&[u8]]) -> bool {
+		self.backend.validate_multisig(address, perm_id, message, signatures)
+	}
+
+	fn freeze_expire_time(&self, address: H160, target: H160, resource_type: U256) -> U256 {
+		self.backend.freeze_expire_time(address, target, resource_type)
+	}
+
+	fn energy_factor(&self, address: H160) -> usize {
+		self.backend.energy_factor(address)
+	}
+
+	fn token_exists(&self, token_id: U256) -> bool {
+		self.backend.token_exists(token_id)
+	}
+
+	fn validate_token_transfer(&self, source: H160, target: H160, token_id: U256, value: U256) -> bool {
+		self.backend.validate_token_transfer(source, target, token_id, value)
+	}
+
+	fn black_hole(&self) -> Option<H160> {
+		self.backend.black_hole()
+	}
+
+	fn is_witness(&self, address: H160) -> bool {
+		self.backend.is_witness(address)
+	}
+
+	fn reward_balance(&self, address: H160) -> U256 {
+		self.backend.reward_balance(address)
+	}
+
+	fn validate_stake(&self, stake: &Stake) -> Option<U256> {
+		self.backend.validate_stake(stake)
+	}
+}
+
+impl<'backend, B: Backend> ApplyBackend for JournaledBackend<'backend, B> {
+	/// Logs are not kept, as they belong to the receipt of each transaction.
+	fn apply<A, I, L>(
+		&mut self,
+		values: A,
+		_logs: L,
+		delete_empty: bool,
+	) where
+		A: IntoIterator<Item=Apply<I>>,
+		I: IntoIterator<Item=(H256, H256)>,
+		L: IntoIterator<Item=Log>,
+	{
+		for apply in values {
+			match apply {
+				Apply::Modify {
+					address, basic, code, storage, reset_storage,
+				} => {
+					// A deleted account is recreated from scratch.
+					let recreated = self.deleted.remove(&address);
+					let account = self.accounts.entry(address).or_insert_with(|| JournaledAccount {
+						reset_storage: recreated,
+						code: if recreated { Some(Vec::new()) } else { None },
+						..Default::default()
+					});
+					account.basic = basic;
+					if let Some(code) = code {
+						account.code = Some(code);
+					}
+					if reset_storage {
+						account.storage = BTreeMap::new();
+						account.reset_storage = true;
+					}
+					account.storage.extend(storage);
+
+					let is_empty = account.basic.balance == U256::zero() &&
+						account.basic.nonce == U256::zero() &&
+						self.code_size(address) == 0;
+					if is_empty && delete_empty {
+						self.accounts.remove(&address);
+						self.deleted.insert(address);
+					}
+				},
+				Apply::Delete { address } => {
+					self.accounts.remove(&address);
+					self.deleted.insert(address);
+				},
+			}
+		}
+	}
+}
@@ -1,8 +1,9 @@
 use alloc::vec::Vec;
 use alloc::collections::BTreeMap;
 use primitive_types::{H160, H256, U256};
+use crate::Bytes;
 use sha3::{Digest, Keccak256};
-use super::{Basic, Backend, ApplyBackend, Apply, Log};
+use super::{Basic, Backend, ApplyBackend, Apply, Log, Stake};
 
 /// Vivinity value of a memory backend.
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -12,7 +13,7 @@ pub struct MemoryVicinity {
 	pub gas_price: U256,
 	/// Origin.
 	pub origin: H160,
-	/// Chain ID.
+	/// Chain ID, the genesis block hash for TRON.
 	pub chain_id: U256,
 	/// Environmental block hashes.
 	pub block_hashes: Vec<H256>,
@@ -24,8 +25,12 @@ pub struct MemoryVicinity {
 	pub block_timestamp: U256,
 	/// Environmental block difficulty.
 	pub block_difficulty: U256,
+	/// Environmental block randomness (EIP-4399).
+	pub block_randomness: Option<H256>,
 	/// Environmental block gas limit.
 	pub block_gas_limit: U256,
+	/// Environmental base fee per gas.
+	pub block_base_fee_per_gas: U256,
 }
 
 /// Account information of a memory backend.
@@ -66,6 +71,11 @@ impl<'vicinity> MemoryBackend<'vicinity> {
 	pub fn state(&self) -> &BTreeMap<H160, MemoryAccount> {
 		&self.state
 	}
+
+	/// Consume the backend, returning the state.
+	pub fn into_state(self) -> BTreeMap<H160, MemoryAccount> {
+		self.state
+	}
 }
 
 impl<'vicinity> Backend for MemoryBackend<'vicinity> {
@@ -85,9 +95,11 @@ impl<'vicinity> Backend for MemoryBackend<'vicinity> {
 	fn block_coinbase(&self) -> H160 { self.vicinity.block_coinbase }
 	fn block_timestamp(&self) -> U256 { self.vicinity.block_timestamp }
 	fn block_difficulty(&self) -> U256 { self.vicinity.block_difficulty }
+	fn block_randomness(&self) -> Option<H256> { self.vicinity.block_randomness }
 	fn block_gas_limit(&self) -> U256 { self.vicinity.block_gas_limit }
 
 	fn chain_id(&self) -> U256 { self.vicinity.chain_id }
+	fn block_base_fee_per_gas(&self) -> U256 { self.vicinity.block_base_fee_per_gas }
 
 	fn exists(&self, address: H160) -> bool {
 		self.state.contains_key(&address)
@@ -101,16 +113,16 @@ impl<'vicinity> Backend for MemoryBackend<'vicinity> {
 
 	fn code_hash(&self, address: H160) -> H256 {
 		self.state.get(&address).map(|v| {
-			H256::from_slice(Keccak256::digest(&v.code).as_slice())
-		}).unwrap_or(H256::from_slice(Keccak256::digest(&[]).as_slice()))
+			H256::from_slice(&Keccak256::digest(&v.code))
+		}).unwrap_or(H256::from_slice(&Keccak256::digest(&[])))
 	}
 
 	fn code_size(&self, address: H160) -> usize {
 		self.state.get(&address).map(|v| v.code.len()).unwrap_or(0)
 	}
 
-	fn code(&self, address: H160) -> Vec<u8> {
-		self.state.get(&address).map(|v| v.code.clone()).unwrap_or_default()
+	fn code(&self, address: H160) -> Bytes {
+		self.state.get(&address).map(|v| Bytes::from(v.code.clone())).unwrap_or_default()
 	}
 
 	fn storage(&self, address: H160, index: H256) -> Option<H256> {
@@ -123,6 +135,26 @@ impl<'vicinity> Backend for MemoryBackend<'vicinity> {
 	fn validate_multisig(&self, _address: H160, _perm_id: U256, _message: H256, _signatures: &[&[u8]]) -> bool {
 		true
 	}
+
+	fn freeze_expire_time(&self, _address: H160, _target: H160, _resource_type: U256) -> U256 {
+		U256::zero()
+	}
+
+	fn energy_factor(&self, _address: H160) -> usize {
+		0
+	}
+
+	fn is_witness(&self, _address: H160) -> bool {
+		false
+	}
+
+	fn reward_balance(&self, _address: H160) -> U256 {
+		U256::zero()
+	}
+
+	fn validate_stake(&self, _stake: &Stake) -> Option<U256> {
+		Some(U256::zero())
+	}
 }
 
 impl<'vicinity> ApplyBackend for MemoryBackend<'vicinity> {
@@ -145,6 +177,7 @@ impl<'vicinity> ApplyBackend for MemoryBackend<'vicinity> {
 						let account = self.state.entry(address).or_insert(Default::default());
 						account.balance = basic.balance;
 						account.nonce = basic.nonce;
+						account.token_balance = basic.token_balance;
 						if let Some(code) = code {
 							account.code = code;
 						}
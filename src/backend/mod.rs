@@ -3,15 +3,26 @@
 //! Backends store state information of the VM, and exposes it to runtime.
 
 mod memory;
+mod bloom;
+mod overlay;
+mod journal;
+mod cache;
 
 pub use self::memory::{MemoryBackend, MemoryVicinity, MemoryAccount};
+pub use self::bloom::Bloom;
+pub use self::overlay::{OverlayBackend, AccountOverride};
+pub use self::journal::JournaledBackend;
+pub use self::cache::{CachedBackend, BackendCacheStats};
+pub use crate::Log;
 
 use alloc::collections::BTreeMap;
 use alloc::vec::Vec;
 use primitive_types::{H160, H256, U256};
+use crate::Bytes;
 
 /// Basic account information.
 #[derive(Clone, Eq, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Basic {
 	/// Account balance.
 	pub balance: U256,
@@ -21,15 +32,110 @@ pub struct Basic {
 	pub nonce: U256,
 }
 
-/// Log information.
+/// Staking operation requested by a contract.
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct Log {
-	/// Source address.
-	pub address: H160,
-	/// Topics.
-	pub topics: Vec<H256>,
-	/// Log data.
-	pub data: Vec<u8>,
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Stake {
+	/// `FREEZE`.
+	Freeze {
+		/// Owner address.
+		owner: H160,
+		/// Resource receiver address.
+		receiver: H160,
+		/// Amount to freeze.
+		amount: U256,
+		/// Resource type.
+		resource_type: U256,
+	},
+	/// `UNFREEZE`.
+	Unfreeze {
+		/// Owner address.
+		owner: H160,
+		/// Resource receiver address.
+		receiver: H160,
+		/// Resource type.
+		resource_type: U256,
+	},
+	/// `WITHDRAWREWARD`.
+	WithdrawReward {
+		/// Owner address.
+		owner: H160,
+	},
+	/// `VOTEWITNESS`, replacing all votes of owner.
+	VoteWitness {
+		/// Owner address.
+		owner: H160,
+		/// Witness and vote count pairs.
+		votes: Vec<(H160, U256)>,
+	},
+	/// Stake 2.0 `FREEZEBALANCEV2`.
+	FreezeBalanceV2 {
+		/// Owner address.
+		owner: H160,
+		/// Amount to freeze.
+		amount: U256,
+		/// Resource type.
+		resource_type: U256,
+	},
+	/// Stake 2.0 `UNFREEZEBALANCEV2`.
+	UnfreezeBalanceV2 {
+		/// Owner address.
+		owner: H160,
+		/// Amount to unfreeze.
+		amount: U256,
+		/// Resource type.
+		resource_type: U256,
+	},
+	/// Stake 2.0 `CANCELALLUNFREEZEV2`.
+	CancelAllUnfreezeV2 {
+		/// Owner address.
+		owner: H160,
+	},
+	/// Stake 2.0 `WITHDRAWEXPIREUNFREEZE`.
+	WithdrawExpireUnfreeze {
+		/// Owner address.
+		owner: H160,
+	},
+	/// Stake 2.0 `DELEGATERESOURCE`.
+	DelegateResource {
+		/// Owner address.
+		owner: H160,
+		/// Receiver address.
+		receiver: H160,
+		/// Amount to delegate.
+		amount: U256,
+		/// Resource type.
+		resource_type: U256,
+	},
+	/// Stake 2.0 `UNDELEGATERESOURCE`.
+	UnDelegateResource {
+		/// Owner address.
+		owner: H160,
+		/// Receiver address.
+		receiver: H160,
+		/// Amount to undelegate.
+		amount: U256,
+		/// Resource type.
+		resource_type: U256,
+	},
+}
+
+impl Stake {
+	/// Owner address of the operation.
+	pub fn owner(&self) -> H160 {
+		match *self {
+			Stake::Freeze { owner, .. } |
+			Stake::Unfreeze { owner, .. } |
+			Stake::WithdrawReward { owner } |
+			Stake::VoteWitness { owner, .. } |
+			Stake::FreezeBalanceV2 { owner, .. } |
+			Stake::UnfreezeBalanceV2 { owner, .. } |
+			Stake::CancelAllUnfreezeV2 { owner } |
+			Stake::WithdrawExpireUnfreeze { owner } |
+			Stake::DelegateResource { owner, .. } |
+			Stake::UnDelegateResource { owner, .. } => owner,
+		}
+	}
 }
 
 /// Apply state operation.
@@ -72,10 +178,16 @@ pub trait Backend {
 	fn block_timestamp(&self) -> U256;
 	/// Environmental block difficulty.
 	fn block_difficulty(&self) -> U256;
+	/// Environmental block randomness (EIP-4399), if known. Defaults to unknown.
+	fn block_randomness(&self) -> Option<H256> {
+		None
+	}
 	/// Environmental block gas limit.
 	fn block_gas_limit(&self) -> U256;
-	/// Environmental chain ID.
+	/// Environmental chain ID, the genesis block hash for TRON.
 	fn chain_id(&self) -> U256;
+	/// Environmental base fee per gas.
+	fn block_base_fee_per_gas(&self) -> U256;
 
 	/// Whether account at address exists.
 	fn exists(&self, address: H160) -> bool;
@@ -86,7 +198,7 @@ pub trait Backend {
 	/// Get account code size.
 	fn code_size(&self, address: H160) -> usize;
 	/// Get account code.
-	fn code(&self, address: H160) -> Vec<u8>;
+	fn code(&self, address: H160) -> Bytes;
 	/// Get storage value of address at index.
 	fn storage(&self, address: H160, index: H256) -> Option<H256>;
 
@@ -94,6 +206,30 @@ pub trait Backend {
 	fn transaction_root_hash(&self) -> H256;
 	/// Validate multisig.
 	fn validate_multisig(&self, address: H160, perm_id: U256, message: H256, signatures: &[&[u8]]) -> bool;
+	/// Expire time in seconds of balance of address frozen for target's resource.
+	fn freeze_expire_time(&self, address: H160, target: H160, resource_type: U256) -> U256;
+	/// Energy factor of contract for dynamic energy, in 1/10000.
+	fn energy_factor(&self, address: H160) -> usize;
+	/// Whether the TRC-10 asset with the token id exists. Defaults to true.
+	fn token_exists(&self, _token_id: U256) -> bool {
+		true
+	}
+	/// Validate a TRC-10 token transfer against the rules of the asset. Defaults
+	/// to true, leaving the balance check to the transfer.
+	fn validate_token_transfer(&self, _source: H160, _target: H160, _token_id: U256, _value: U256) -> bool {
+		true
+	}
+	/// Account receiving burnt TRX and TRC-10 balances, `None` if they are
+	/// destroyed. Defaults to `None`.
+	fn black_hole(&self) -> Option<H160> {
+		None
+	}
+	/// Whether address is a witness.
+	fn is_witness(&self, address: H160) -> bool;
+	/// Get unwithdrawn voting reward of address.
+	fn reward_balance(&self, address: H160) -> U256;
+	/// Validate a staking operation, returning the amount withdrawn to owner's balance, or `None` if rejected.
+	fn validate_stake(&self, stake: &Stake) -> Option<U256>;
 }
 
 /// EVM backend that can apply changes.
@@ -0,0 +1,146 @@
+use alloc::collections::BTreeMap;
+use primitive_types::{H160, H256, U256};
+use crate::Bytes;
+use sha3::{Digest, Keccak256};
+use super::{Basic, Backend, Stake};
+
+/// Override of an account's state, as in `eth_call` state overrides.
+#[derive(Default, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccountOverride {
+	/// Balance replacing the account balance.
+	pub balance: Option<U256>,
+	/// Nonce replacing the account nonce.
+	pub nonce: Option<U256>,
+	/// Code replacing the account code.
+	pub code: Option<Bytes>,
+	/// Storage replacing the full account storage.
+	pub state: Option<BTreeMap<H256, H256>>,
+	/// Storage values replacing individual slots.
+	pub state_diff: BTreeMap<H256, H256>,
+}
+
+/// Read-only backend applying account overrides on top of another backend.
+#[derive(Clone, Debug)]
+pub struct OverlayBackend<'backend, B> {
+	backend: &'backend B,
+	overrides: BTreeMap<H160, AccountOverride>,
+}
+
+impl<'backend, B: Backend> OverlayBackend<'backend, B> {
+	/// Create a new overlay of the backend with given overrides.
+	pub fn new(backend: &'backend B, overrides: BTreeMap<H160, AccountOverride>) -> Self {
+		Self { backend, overrides }
+	}
+
+	/// Get the underlying backend.
+	pub fn backend(&self) -> &'backend B {
+		self.backend
+	}
+
+	/// Get the account overrides.
+	pub fn overrides(&self) -> &BTreeMap<H160, AccountOverride> {
+		&self.overrides
+	}
+}
+
+impl<'backend, B: Backend> Backend for OverlayBackend<'backend, B> {
+	fn gas_price(&self) -> U256 { self.backend.gas_price() }
+	fn origin(&self) -> H160 { self.backend.origin() }
+	fn block_hash(&self, number: U256) -> H256 { self.backend.block_hash(number) }
+	fn block_number(&self) -> U256 { self.backend.block_number() }
+	fn block_coinbase(&self) -> H160 { self.backend.block_coinbase() }
+	fn block_timestamp(&self) -> U256 { self.backend.block_timestamp() }
+	fn block_difficulty(&self) -> U256 { self.backend.block_difficulty() }
+	fn block_randomness(&self) -> Option<H256> { self.backend.block_randomness() }
+	fn block_gas_limit(&self) -> U256 { self.backend.block_gas_limit() }
+	fn chain_id(&self) -> U256 { self.backend.chain_id() }
+	fn block_base_fee_per_gas(&self) -> U256 { self.backend.block_base_fee_per_gas() }
+
+	fn exists(&self, address: H160) -> bool {
+		self.overrides.contains_key(&address) || self.backend.exists(address)
+	}
+
+	fn basic(&self, address: H160) -> Basic {
+		let mut basic = self.backend.basic(address);
+		if let Some(account) = self.overrides.get(&address) {
+			if let Some(balance) = account.balance {
+				basic.balance = balance;
+			}
+			if let Some(nonce) = account.nonce {
+				basic.nonce = nonce;
+			}
+		}
+		basic
+	}
+
+	fn code_hash(&self, address: H160) -> H256 {
+		match self.overrides.get(&address).and_then(|v| v.code.as_ref()) {
+			Some(code) => H256::from_slice(&Keccak256::digest(code)),
+			None => self.backend.code_hash(address),
+		}
+	}
+
+	fn code_size(&self, address: H160) -> usize {
+		match self.overrides.get(&address).and_then(|v| v.code.as_ref()) {
+			Some(code) => code.len(),
+			None => self.backend.code_size(address),
+		}
+	}
+
+	fn code(&self, address: H160) -> Bytes {
+		match self.overrides.get(&address).and_then(|v| v.code.as_ref()) {
+			Some(code) => code.clone(),
+			None => self.backend.code(address),
+		}
+	}
+
+	fn storage(&self, address: H160, index: H256) -> Option<H256> {
+		match self.overrides.get(&address) {
+			Some(account) => match (account.state_diff.get(&index), &account.state) {
+				(Some(value), _) => Some(*value),
+				(None, Some(state)) => state.get(&index).cloned(),
+				(None, None) => self.backend.storage(address, index),
+			},
+			None => self.backend.storage(address, index),
+		}
+	}
+
+	fn transaction_root_hash(&self) -> H256 { self.backend.transaction_root_hash() }
+
+	fn validate_multisig(&self, address: H160, perm_id: U256, message: H256, signatures: &[&[u8]]) -> bool {
+		self.backend.validate_multisig(address, perm_id, message, signatures)
+	}
+
+	fn freeze_expire_time(&self, address: H160, target: H160, resource_type: U256) -> U256 {
+		self.backend.freeze_expire_time(address, target, resource_type)
+	}
+
+	fn energy_factor(&self, address: H160) -> usize {
+		self.backend.energy_factor(address)
+	}
+
+	fn token_exists(&self, token_id: U256) -> bool {
+		self.backend.token_exists(token_id)
+	}
+
+	fn validate_token_transfer(&self, source: H160, target: H160, token_id: U256, value: U256) -> bool {
+		self.backend.validate_token_transfer(source, target, token_id, value)
+	}
+
+	fn black_hole(&self) -> Option<H160> {
+		self.backend.black_hole()
+	}
+
+	fn is_witness(&self, address: H160) -> bool {
+		self.backend.is_witness(address)
+	}
+
+	fn reward_balance(&self, address: H160) -> U256 {
+		self.backend.reward_balance(address)
+	}
+
+	fn validate_stake(&self, stake: &Stake) -> Option<U256> {
+		self.backend.validate_stake(stake)
+	}
+}
@@ -0,0 +1,279 @@
+use core::cell::RefCell;
+use core::cmp::min;
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use alloc::collections::BTreeMap;
+use primitive_types::{H160, H256, U256};
+use crate::{Config, ContractResult, CreateScheme, ExitReason};
+use crate::backend::{ApplyBackend, Apply, Backend, Bloom, JournaledBackend, Log, Stake};
+use super::{StackExecutor, PrecompileFn, InternalTransaction, EstimateTransaction, ValidsCache, FramePool,
+			CancellationToken};
+
+/// Transaction of a block.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BlockTransaction {
+	/// Transaction id, from which `CREATE` addresses are derived.
+	pub hash: H256,
+	/// Gas price, the energy price in sun for TRON.
+	pub gas_price: U256,
+	/// Gas limit.
+	pub gas_limit: usize,
+	/// TRON fee limit in sun, further limiting the energy to what it buys at the gas
+	/// price.
+	pub fee_limit: Option<U256>,
+	/// Energy the caller has left from frozen balance, used before burning TRX.
+	pub frozen_energy: usize,
+	/// Call or create, with its caller.
+	pub action: EstimateTransaction,
+}
+
+impl BlockTransaction {
+	/// Caller of the transaction.
+	pub fn caller(&self) -> H160 {
+		match &self.action {
+			EstimateTransaction::Call { caller, .. } | EstimateTransaction::Create { caller, .. } => *caller,
+		}
+	}
+
+	/// Value transferred by the transaction.
+	pub fn value(&self) -> U256 {
+		match &self.action {
+			EstimateTransaction::Call { value, .. } | EstimateTransaction::Create { value, .. } => *value,
+		}
+	}
+
+	/// Energy limit of the transaction for a caller with the given balance. With a
+	/// fee limit, this is the energy from frozen balance plus what the balance left
+	/// after the transferred value buys, capped by what the fee limit buys, as
+	/// java-tron's `getAccountEnergyLimitWithFixRatio`.
+	pub fn energy_limit(&self, balance: U256) -> usize {
+		let fee_limit = match self.fee_limit {
+			Some(fee_limit) if !self.gas_price.is_zero() => fee_limit,
+			_ => return self.gas_limit,
+		};
+
+		let from_balance = balance.saturating_sub(self.value()) / self.gas_price;
+		let available = U256::from(self.frozen_energy).saturating_add(from_balance);
+		let limit = min(available, fee_limit / self.gas_price);
+		min(U256::from(self.gas_limit), limit).as_usize()
+	}
+
+	/// Split the energy used between frozen balance and burned TRX.
+	pub fn energy_bill(&self, used_energy: usize) -> (usize, U256) {
+		let energy_usage = min(used_energy, self.frozen_energy);
+		(energy_usage, U256::from(used_energy - energy_usage) * self.gas_price)
+	}
+}
+
+/// Receipt of a transaction executed by `BlockExecutor`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Receipt {
+	/// Exit reason.
+	pub reason: ExitReason,
+	/// Result code of the receipt.
+	pub result: ContractResult,
+	/// Return or revert data, empty for creates.
+	pub output: Vec<u8>,
+	/// Address of the created contract, for successful creates.
+	pub contract_address: Option<H160>,
+	/// Gas used, after refunds.
+	pub used_gas: usize,
+	/// Gas used by the block up to and including the transaction.
+	pub cumulative_gas: usize,
+	/// Energy paid from the caller's frozen balance.
+	pub energy_usage: usize,
+	/// TRX in sun to be burned from the caller's balance for the rest of the energy.
+	pub energy_fee: U256,
+	/// Logs emitted.
+	pub logs: Vec<Log>,
+	/// Bloom of the logs.
+	pub logs_bloom: Bloom,
+	/// Internal transactions.
+	pub internal_transactions: Vec<InternalTransaction>,
+	/// Staking operations, to be applied by the caller.
+	pub stakes: Vec<Stake>,
+}
+
+/// Executor of the transactions of a block against one backend. State changes of
+/// each transaction are seen by the following ones, and the jump destination cache
+/// and frame pool are shared by all of them.
+pub struct BlockExecutor<'backend, 'config, B> {
+	state: JournaledBackend<'backend, B>,
+	config: &'config Config,
+	precompile: PrecompileFn,
+	valids_cache: Rc<RefCell<ValidsCache>>,
+	frame_pool: Rc<RefCell<FramePool>>,
+	cancellation: Option<CancellationToken>,
+	cumulative_gas: usize,
+	reexecuted: usize,
+}
+
+impl<'backend, 'config, B: Backend> BlockExecutor<'backend, 'config, B> {
+	/// Create a new block executor with given precompiles.
+	pub fn new(backend: &'backend B, config: &'config Config, precompile: PrecompileFn) -> Self {
+		Self {
+			state: JournaledBackend::new(backend),
+			config,
+			precompile,
+			valids_cache: Rc::new(RefCell::new(ValidsCache::new())),
+			frame_pool: Rc::new(RefCell::new(FramePool::new())),
+			cancellation: None,
+			cumulative_gas: 0,
+			reexecuted: 0,
+		}
+	}
+
+	/// State of the block so far.
+	pub fn state(&self) -> &JournaledBackend<'backend, B> {
+		&self.state
+	}
+
+	/// Jump destination cache shared by the transactions.
+	pub fn valids_cache(&self) -> &Rc<RefCell<ValidsCache>> {
+		&self.valids_cache
+	}
+
+	/// Check the token in every transaction. Once it is cancelled, transactions exit
+	/// with `Cancelled`, and the host can drop them from the block.
+	pub fn set_cancellation(&mut self, token: CancellationToken) {
+		self.cancellation = Some(token);
+	}
+
+	/// Gas used by the block so far.
+	pub fn cumulative_gas(&self) -> usize {
+		self.cumulative_gas
+	}
+
+	/// Execute a transaction and apply its state changes to the block state.
+	pub fn execute(&mut self, transaction: &BlockTransaction) -> Receipt {
+		self.state.set_transaction(transaction.caller(), transaction.gas_price, transaction.hash);
+		let (receipt, applies) = transact(
+			&self.state, self.config, self.precompile, &self.valids_cache, &self.frame_pool,
+			self.cancellation.as_ref(), transaction,
+		);
+		self.commit(receipt, applies)
+	}
+
+	/// Experimental: execute transactions on up to `threads` threads, with the same
+	/// receipts and state as `execute_all`. All transactions first run in parallel
+	/// against the current state, recording what they read. They are then committed
+	/// in order, and a transaction that read state written by an earlier one in the
+	/// batch is re-executed against the committed state.
+	#[cfg(feature = "std")]
+	pub fn execute_parallel(&mut self, transactions: &[BlockTransaction], threads: usize) -> Vec<Receipt> where
+		B: Sync,
+	{
+		let speculations = super::parallel::speculate(
+			&self.state, self.config, self.precompile, self.cancellation.as_ref(), transactions, threads,
+		);
+
+		let mut writes = super::parallel::Writes::default();
+		let mut receipts = Vec::with_capacity(transactions.len());
+		for (transaction, speculation) in transactions.iter().zip(speculations) {
+			let (receipt, applies) = if writes.conflicts(&speculation) {
+				self.reexecuted += 1;
+				self.state.set_transaction(transaction.caller(), transaction.gas_price, transaction.hash);
+				transact(
+					&self.state, self.config, self.precompile, &self.valids_cache, &self.frame_pool,
+					self.cancellation.as_ref(), transaction,
+				)
+			} else {
+				(speculation.receipt, speculation.applies)
+			};
+			writes.record(&self.state, &applies);
+			receipts.push(self.commit(receipt, applies));
+		}
+		receipts
+	}
+
+	/// Number of transactions re-executed by `execute_parallel` after a conflict.
+	pub fn reexecuted(&self) -> usize {
+		self.reexecuted
+	}
+
+	/// Apply state changes of an executed transaction to the block state, and count
+	/// its gas.
+	fn commit(&mut self, mut receipt: Receipt, applies: Vec<Apply<BTreeMap<H256, H256>>>) -> Receipt {
+		self.state.apply(applies, Vec::new(), false);
+		self.cumulative_gas += receipt.used_gas;
+		receipt.cumulative_gas = self.cumulative_gas;
+		receipt
+	}
+
+	/// Execute transactions in order, returning their receipts.
+	pub fn execute_all<'a, I>(&mut self, transactions: I) -> Vec<Receipt> where
+		I: IntoIterator<Item=&'a BlockTransaction>,
+	{
+		transactions.into_iter().map(|transaction| self.execute(transaction)).collect()
+	}
+
+	/// Deconstruct the executor, returning the state changes of the block to be
+	/// applied to the backend.
+	#[must_use]
+	pub fn deconstruct(
+		self
+	) -> impl IntoIterator<Item=Apply<impl IntoIterator<Item=(H256, H256)>>> {
+		self.state.deconstruct()
+	}
+}
+
+/// Execute a transaction against the backend, returning its receipt, without
+/// cumulative gas, and its state changes.
+pub(crate) fn transact<B: Backend>(
+	backend: &B,
+	config: &Config,
+	precompile: PrecompileFn,
+	valids_cache: &Rc<RefCell<ValidsCache>>,
+	frame_pool: &Rc<RefCell<FramePool>>,
+	cancellation: Option<&CancellationToken>,
+	transaction: &BlockTransaction,
+) -> (Receipt, Vec<Apply<BTreeMap<H256, H256>>>) {
+	let gas_limit = transaction.energy_limit(backend.basic(transaction.caller()).balance);
+	let mut executor = StackExecutor::new_with_precompile(backend, gas_limit, config, precompile);
+	executor.set_valids_cache(valids_cache.clone());
+	executor.set_frame_pool(frame_pool.clone());
+	if let Some(token) = cancellation {
+		executor.set_cancellation(token.clone());
+	}
+
+	let (reason, output, contract_address) = match transaction.action.clone() {
+		EstimateTransaction::Call { caller, address, value, data } => {
+			let (reason, output) = executor.transact_call(caller, address, value, data, gas_limit);
+			(reason, output, None)
+		},
+		EstimateTransaction::Create { caller, value, init_code } => {
+			let reason = executor.transact_create(caller, value, init_code, gas_limit);
+			let address = CreateScheme::Legacy { nonce: 0, transaction_root_hash: transaction.hash }.address();
+			(reason, Vec::new(), Some(address).filter(|_| reason.is_succeed()))
+		},
+	};
+
+	let used_gas = executor.used_gas();
+	let (energy_usage, energy_fee) = transaction.energy_bill(used_gas);
+	let logs_bloom = executor.logs_bloom();
+	let internal_transactions = executor.internal_transactions().to_vec();
+	let stakes = executor.stakes().to_vec();
+	let (applies, logs) = executor.deconstruct();
+	let applies = applies.into_iter().map(|apply| match apply {
+		Apply::Modify { address, basic, code, storage, reset_storage } => Apply::Modify {
+			address, basic, code, storage: storage.into_iter().collect(), reset_storage,
+		},
+		Apply::Delete { address } => Apply::Delete { address },
+	}).collect();
+
+	let receipt = Receipt {
+		reason,
+		result: reason.into(),
+		output,
+		contract_address,
+		used_gas,
+		cumulative_gas: 0,
+		energy_usage,
+		energy_fee,
+		logs: logs.into_iter().collect(),
+		logs_bloom,
+		internal_transactions,
+		stakes,
+	};
+	(receipt, applies)
+}
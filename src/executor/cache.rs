@@ -0,0 +1,87 @@
+use alloc::collections::BTreeMap;
+use primitive_types::H256;
+use crate::{Shared, Superinstructions, Valids};
+
+/// Hit and miss counters of a `ValidsCache`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ValidsCacheStats {
+	/// Lookups served from the cache.
+	pub hits: u64,
+	/// Lookups that required code analysis.
+	pub misses: u64,
+	/// Number of cached jump destination maps.
+	pub entries: usize,
+}
+
+impl ValidsCacheStats {
+	/// Ratio of hits to all lookups, in per mille. Zero if nothing was looked up.
+	pub fn hit_rate_per_mille(&self) -> u64 {
+		(self.hits * 1000).checked_div(self.hits + self.misses).unwrap_or(0)
+	}
+}
+
+/// Cache of jump destination maps keyed by code hash, so that calls into the same
+/// contract skip re-analysis of its code. Superinstructions of the code can also be
+/// cached, to evaluate hot opcode sequences as one step.
+#[derive(Clone, Debug, Default)]
+pub struct ValidsCache {
+	entries: BTreeMap<H256, Shared<Valids>>,
+	superinstructions: Option<BTreeMap<H256, Shared<Superinstructions>>>,
+	stats: ValidsCacheStats,
+}
+
+impl ValidsCache {
+	/// Create an empty cache.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Get the jump destination map of code with the given hash, analysing the
+	/// code on a miss.
+	pub fn get_or_analyse(&mut self, code_hash: H256, code: &[u8]) -> Shared<Valids> {
+		if let Some(valids) = self.entries.get(&code_hash) {
+			self.stats.hits += 1;
+			return valids.clone()
+		}
+
+		self.stats.misses += 1;
+		let valids = Shared::new(Valids::new(code));
+		self.entries.insert(code_hash, valids.clone());
+		self.stats.entries = self.entries.len();
+		valids
+	}
+
+	/// Find and cache the superinstructions of code looked up in message calls.
+	/// Disabled by default.
+	pub fn set_superinstructions(&mut self, enabled: bool) {
+		self.superinstructions = if enabled { Some(BTreeMap::new()) } else { None };
+	}
+
+	/// Get the superinstructions of code with the given hash, finding them on a
+	/// miss. `None` if superinstructions are disabled.
+	pub fn get_or_fuse(
+		&mut self,
+		code_hash: H256,
+		code: &[u8],
+		valids: &Valids,
+	) -> Option<Shared<Superinstructions>> {
+		let entries = self.superinstructions.as_mut()?;
+		let superinstructions = entries.entry(code_hash)
+			.or_insert_with(|| Shared::new(Superinstructions::new(code, valids)));
+		Some(superinstructions.clone())
+	}
+
+	/// Hit and miss counters of the jump destination maps.
+	pub fn stats(&self) -> ValidsCacheStats {
+		self.stats
+	}
+
+	/// Drop all cached maps and reset the counters.
+	pub fn clear(&mut self) {
+		self.entries.clear();
+		if let Some(superinstructions) = &mut self.superinstructions {
+			superinstructions.clear();
+		}
+		self.stats = ValidsCacheStats::default();
+	}
+}
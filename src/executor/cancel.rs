@@ -0,0 +1,26 @@
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Token to cancel running executions, from the host thread or another one. Clones
+/// share the same flag, and a cancelled token stays cancelled.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+	/// Create a token that is not cancelled.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Cancel the executions checking the token. Each exits with `Cancelled` at its
+	/// next step.
+	pub fn cancel(&self) {
+		self.0.store(true, Ordering::Relaxed);
+	}
+
+	/// Whether the token is cancelled.
+	#[inline]
+	pub fn is_cancelled(&self) -> bool {
+		self.0.load(Ordering::Relaxed)
+	}
+}
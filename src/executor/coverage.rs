@@ -0,0 +1,101 @@
+use alloc::vec::Vec;
+use alloc::collections::BTreeMap;
+use primitive_types::H256;
+
+/// Bitmap of the executed program counters of a code.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CoverageMap {
+	words: Vec<u64>,
+	code_len: usize,
+}
+
+impl CoverageMap {
+	/// Create an empty map for code of the given length.
+	pub fn new(code_len: usize) -> Self {
+		Self { words: alloc::vec![0; code_len.div_ceil(64)], code_len }
+	}
+
+	/// Length of the code.
+	pub fn code_len(&self) -> usize {
+		self.code_len
+	}
+
+	/// Mark a program counter as executed. Positions past the code are ignored.
+	pub fn insert(&mut self, pc: usize) {
+		if pc < self.code_len {
+			self.words[pc / 64] |= 1 << (pc % 64);
+		}
+	}
+
+	/// Whether a program counter was executed.
+	pub fn contains(&self, pc: usize) -> bool {
+		pc < self.code_len && self.words[pc / 64] & (1 << (pc % 64)) != 0
+	}
+
+	/// Number of executed program counters.
+	pub fn count(&self) -> usize {
+		self.words.iter().map(|w| w.count_ones() as usize).sum()
+	}
+
+	/// Executed program counters, in ascending order.
+	pub fn pcs(&self) -> impl Iterator<Item = usize> + '_ {
+		(0..self.code_len).filter(move |pc| self.contains(*pc))
+	}
+
+	/// Raw bitmap, with program counter `pc` at bit `pc % 64` of word `pc / 64`.
+	pub fn as_words(&self) -> &[u64] {
+		&self.words
+	}
+
+	/// Add the program counters executed in another map of the same code.
+	pub fn merge(&mut self, other: &CoverageMap) {
+		for (word, other) in self.words.iter_mut().zip(&other.words) {
+			*word |= other;
+		}
+	}
+}
+
+/// Coverage maps by code hash, accumulated across frames and executions.
+#[derive(Clone, Debug, Default)]
+pub struct Coverage {
+	maps: BTreeMap<H256, CoverageMap>,
+}
+
+impl Coverage {
+	/// Create an empty coverage.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Get the map of the code with the given hash.
+	pub fn get(&self, code_hash: &H256) -> Option<&CoverageMap> {
+		self.maps.get(code_hash)
+	}
+
+	/// Iterate over maps by code hash.
+	pub fn iter(&self) -> impl Iterator<Item = (&H256, &CoverageMap)> {
+		self.maps.iter()
+	}
+
+	/// Number of codes covered.
+	pub fn len(&self) -> usize {
+		self.maps.len()
+	}
+
+	/// Whether no code was covered.
+	pub fn is_empty(&self) -> bool {
+		self.maps.is_empty()
+	}
+
+	/// Add the map of a code.
+	pub fn merge(&mut self, code_hash: H256, map: &CoverageMap) {
+		self.maps.entry(code_hash)
+			.or_insert_with(|| CoverageMap::new(map.code_len()))
+			.merge(map);
+	}
+
+	/// Remove all maps.
+	pub fn clear(&mut self) {
+		self.maps.clear();
+	}
+}
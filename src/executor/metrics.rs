@@ -0,0 +1,121 @@
+use alloc::collections::BTreeMap;
+use primitive_types::H160;
+use crate::{ExternalOpcode, Opcode};
+
+/// Category of gas spent by an opcode, including its memory expansion.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum GasCategory {
+	/// Arithmetic, stack, control flow and environment opcodes.
+	Compute,
+	/// Memory access and copies into memory.
+	Memory,
+	/// `SLOAD` and `SSTORE`.
+	Storage,
+	/// Reads of other accounts, such as `BALANCE` and `EXTCODESIZE`.
+	Account,
+	/// `CALL` family, excluding the gas forwarded to the callee.
+	Call,
+	/// `CREATE` and `CREATE2`, excluding the gas forwarded to the init code.
+	Create,
+	/// `LOG0` to `LOG4`.
+	Log,
+	/// TRON opcodes for tokens, resources and voting.
+	Tron,
+}
+
+impl GasCategory {
+	/// Category of an opcode.
+	pub fn of(opcode: Result<Opcode, ExternalOpcode>) -> Self {
+		match opcode {
+			Ok(Opcode::MLoad) | Ok(Opcode::MStore) | Ok(Opcode::MStore8) | Ok(Opcode::MSize) |
+			Ok(Opcode::CallDataCopy) | Ok(Opcode::CodeCopy) |
+			Err(ExternalOpcode::ReturnDataCopy) | Err(ExternalOpcode::Sha3) => GasCategory::Memory,
+			Err(ExternalOpcode::SLoad) | Err(ExternalOpcode::SStore) => GasCategory::Storage,
+			Err(ExternalOpcode::Balance) | Err(ExternalOpcode::SelfBalance) |
+			Err(ExternalOpcode::ExtCodeSize) | Err(ExternalOpcode::ExtCodeHash) |
+			Err(ExternalOpcode::ExtCodeCopy) | Err(ExternalOpcode::BlockHash) |
+			Err(ExternalOpcode::Suicide) => GasCategory::Account,
+			Err(ExternalOpcode::Call) | Err(ExternalOpcode::CallCode) | Err(ExternalOpcode::DelegateCall) |
+			Err(ExternalOpcode::StaticCall) | Err(ExternalOpcode::CallToken) => GasCategory::Call,
+			Err(ExternalOpcode::Create) | Err(ExternalOpcode::Create2) => GasCategory::Create,
+			Err(ExternalOpcode::Log(_)) => GasCategory::Log,
+			Err(ExternalOpcode::CallTokenValue) | Err(ExternalOpcode::CallTokenId) |
+			Err(ExternalOpcode::TokenBalance) | Err(ExternalOpcode::IsContract) |
+			Err(ExternalOpcode::IsWitness) | Err(ExternalOpcode::Stake) | Err(ExternalOpcode::Unstake) |
+			Err(ExternalOpcode::WithdrawReward) | Err(ExternalOpcode::RewardBalance) |
+			Err(ExternalOpcode::AssetIssue) | Err(ExternalOpcode::UpdateAsset) |
+			Err(ExternalOpcode::VoteWitness) | Err(ExternalOpcode::Freeze) | Err(ExternalOpcode::Unfreeze) |
+			Err(ExternalOpcode::FreezeExpireTime) | Err(ExternalOpcode::FreezeBalanceV2) |
+			Err(ExternalOpcode::UnfreezeBalanceV2) | Err(ExternalOpcode::CancelAllUnfreezeV2) |
+			Err(ExternalOpcode::WithdrawExpireUnfreeze) | Err(ExternalOpcode::DelegateResource) |
+			Err(ExternalOpcode::UnDelegateResource) => GasCategory::Tron,
+			_ => GasCategory::Compute,
+		}
+	}
+}
+
+/// Sink of execution metrics, called by `StackExecutor`. All methods default to
+/// no-op.
+pub trait Metrics {
+	/// Called after the gas of an opcode is recorded, with the opcode byte and the
+	/// gas it cost.
+	fn opcode(&mut self, _opcode: u8, _category: GasCategory, _gas: usize) { }
+
+	/// Called after a precompile returns, with the gas it cost.
+	fn precompile(&mut self, _address: H160, _gas: usize, _success: bool) { }
+}
+
+impl Metrics for () { }
+
+/// Counters of a precompile in `MetricsRecorder`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PrecompileMetrics {
+	/// Number of invocations.
+	pub calls: u64,
+	/// Number of failed invocations.
+	pub failures: u64,
+	/// Gas spent by successful invocations.
+	pub gas: u64,
+}
+
+/// Metrics sink accumulating counters in memory, to be exported periodically.
+#[derive(Clone, Debug, Default)]
+pub struct MetricsRecorder {
+	/// Executed count by opcode byte.
+	pub opcodes: BTreeMap<u8, u64>,
+	/// Gas spent by category.
+	pub gas: BTreeMap<GasCategory, u64>,
+	/// Counters by precompile address.
+	pub precompiles: BTreeMap<H160, PrecompileMetrics>,
+}
+
+impl MetricsRecorder {
+	/// Create an empty recorder.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Reset all counters.
+	pub fn clear(&mut self) {
+		self.opcodes.clear();
+		self.gas.clear();
+		self.precompiles.clear();
+	}
+}
+
+impl Metrics for MetricsRecorder {
+	fn opcode(&mut self, opcode: u8, category: GasCategory, gas: usize) {
+		*self.opcodes.entry(opcode).or_default() += 1;
+		*self.gas.entry(category).or_default() += gas as u64;
+	}
+
+	fn precompile(&mut self, address: H160, gas: usize, success: bool) {
+		let metrics = self.precompiles.entry(address).or_default();
+		metrics.calls += 1;
+		if success {
+			metrics.gas += gas as u64;
+		} else {
+			metrics.failures += 1;
+		}
+	}
+}
@@ -4,5 +4,26 @@
 //! also handles the call stacks in EVM.
 
 mod stack;
+mod cache;
+mod pool;
+mod simulate;
+mod precompile;
+mod metrics;
+mod coverage;
+mod block;
+mod cancel;
+#[cfg(feature = "std")]
+mod parallel;
 
-pub use self::stack::{StackAccount, StackExecutor};
+pub use self::stack::{StackAccount, StackExecutor, PrecompileFn, PrecompileResult,
+					  InternalTransaction, InternalTransactionKind, EstimateTransaction};
+pub use self::cache::{ValidsCache, ValidsCacheStats};
+pub use self::pool::{FramePool, FramePoolStats};
+pub use self::simulate::{Simulation, simulate_call};
+pub use self::precompile::{EcrecoverInput, PrecompileContext, PrecompileState};
+pub use self::metrics::{GasCategory, Metrics, MetricsRecorder, PrecompileMetrics};
+pub use self::coverage::{Coverage, CoverageMap};
+pub use self::block::{BlockExecutor, BlockTransaction, Receipt};
+pub use self::cancel::CancellationToken;
+#[cfg(feature = "tracing")]
+pub(crate) use self::stack::call_scheme;
@@ -0,0 +1,235 @@
+use core::cell::RefCell;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use alloc::collections::{BTreeMap, BTreeSet};
+use primitive_types::{H160, H256, U256};
+use crate::{Bytes, Config};
+use crate::backend::{Apply, Backend, Basic, Stake};
+use super::{BlockTransaction, Receipt, PrecompileFn, ValidsCache, FramePool, CancellationToken};
+use super::block::transact;
+
+/// State read or written by a transaction.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+enum Access {
+	/// Existence, balance, nonce or code of an account.
+	Account(H160),
+	/// A storage slot.
+	Storage(H160, H256),
+}
+
+/// Backend recording the state read by a transaction, with its environment. Host
+/// queries such as token and stake validation read every account they name.
+struct RecordingBackend<'backend, B> {
+	backend: &'backend B,
+	origin: H160,
+	gas_price: U256,
+	transaction_root_hash: H256,
+	reads: RefCell<BTreeSet<Access>>,
+}
+
+impl<'backend, B: Backend> RecordingBackend<'backend, B> {
+	fn read(&self, access: Access) {
+		self.reads.borrow_mut().insert(access);
+	}
+}
+
+impl<'backend, B: Backend> Backend for RecordingBackend<'backend, B> {
+	fn gas_price(&self) -> U256 { self.gas_price }
+	fn origin(&self) -> H160 { self.origin }
+	fn block_hash(&self, number: U256) -> H256 { self.backend.block_hash(number) }
+	fn block_number(&self) -> U256 { self.backend.block_number() }
+	fn block_coinbase(&self) -> H160 { self.backend.block_coinbase() }
+	fn block_timestamp(&self) -> U256 { self.backend.block_timestamp() }
+	fn block_difficulty(&self) -> U256 { self.backend.block_difficulty() }
+	fn block_randomness(&self) -> Option<H256> { self.backend.block_randomness() }
+	fn block_gas_limit(&self) -> U256 { self.backend.block_gas_limit() }
+	fn chain_id(&self) -> U256 { self.backend.chain_id() }
+	fn block_base_fee_per_gas(&self) -> U256 { self.backend.block_base_fee_per_gas() }
+
+	fn exists(&self, address: H160) -> bool {
+		self.read(Access::Account(address));
+		self.backend.exists(address)
+	}
+
+	fn basic(&self, address: H160) -> Basic {
+		self.read(Access::Account(address));
+		self.backend.basic(address)
+	}
+
+	fn code_hash(&self, address: H160) -> H256 {
+		self.read(Access::Account(address));
+		self.backend.code_hash(address)
+	}
+
+	fn code_size(&self, address: H160) -> usize {
+		self.read(Access::Account(address));
+		self.backend.code_size(address)
+	}
+
+	fn code(&self, address: H160) -> Bytes {
+		self.read(Access::Account(address));
+		self.backend.code(address)
+	}
+
+	fn storage(&self, address: H160, index: H256) -> Option<H256> {
+		self.read(Access::Storage(address, index));
+		self.backend.storage(address, index)
+	}
+
+	fn transaction_root_hash(&self) -> H256 { self.transaction_root_hash }
+
+	fn validate_multisig(&self, address: H160, perm_id: U256, message: H256, signatures: &[&[u8]]) -> bool {
+		self.read(Access::Account(address));
+		self.backend.validate_multisig(address, perm_id, message, signatures)
+	}
+
+	fn freeze_expire_time(&self, address: H160, target: H160, resource_type: U256) -> U256 {
+		self.read(Access::Account(address));
+		self.read(Access::Account(target));
+		self.backend.freeze_expire_time(address, target, resource_type)
+	}
+
+	fn energy_factor(&self, address: H160) -> usize {
+		self.read(Access::Account(address));
+		self.backend.energy_factor(address)
+	}
+
+	fn token_exists(&self, token_id: U256) -> bool {
+		self.backend.token_exists(token_id)
+	}
+
+	fn validate_token_transfer(&self, source: H160, target: H160, token_id: U256, value: U256) -> bool {
+		self.read(Access::Account(source));
+		self.read(Access::Account(target));
+		self.backend.validate_token_transfer(source, target, token_id, value)
+	}
+
+	fn black_hole(&self) -> Option<H160> {
+		self.backend.black_hole()
+	}
+
+	fn is_witness(&self, address: H160) -> bool {
+		self.read(Access::Account(address));
+		self.backend.is_witness(address)
+	}
+
+	fn reward_balance(&self, address: H160) -> U256 {
+		self.read(Access::Account(address));
+		self.backend.reward_balance(address)
+	}
+
+	fn validate_stake(&self, stake: &Stake) -> Option<U256> {
+		self.read(Access::Account(stake.owner()));
+		match stake {
+			Stake::Freeze { receiver, .. } | Stake::Unfreeze { receiver, .. } |
+			Stake::DelegateResource { receiver, .. } | Stake::UnDelegateResource { receiver, .. } =>
+				self.read(Access::Account(*receiver)),
+			Stake::VoteWitness { votes, .. } => for (witness, _) in votes {
+				self.read(Access::Account(*witness));
+			},
+			_ => (),
+		}
+		self.backend.validate_stake(stake)
+	}
+}
+
+/// Outcome of a transaction executed against the state at the start of a batch.
+pub(crate) struct Speculation {
+	pub receipt: Receipt,
+	pub applies: Vec<Apply<BTreeMap<H256, H256>>>,
+	reads: BTreeSet<Access>,
+}
+
+/// State written by the transactions committed since the start of a batch.
+#[derive(Default)]
+pub(crate) struct Writes {
+	accesses: BTreeSet<Access>,
+	/// Accounts whose whole storage was reset or deleted.
+	reset: BTreeSet<H160>,
+}
+
+impl Writes {
+	/// Record the state changes of a transaction, before they are applied to the
+	/// backend. Accounts that were only read are not counted as written.
+	pub fn record<B: Backend>(&mut self, backend: &B, applies: &[Apply<BTreeMap<H256, H256>>]) {
+		for apply in applies {
+			match apply {
+				Apply::Modify { address, basic, code, storage, reset_storage } => {
+					if *basic != backend.basic(*address) || code.is_some() || *reset_storage {
+						self.accesses.insert(Access::Account(*address));
+					}
+					if *reset_storage {
+						self.reset.insert(*address);
+					}
+					for index in storage.keys() {
+						self.accesses.insert(Access::Storage(*address, *index));
+					}
+				},
+				Apply::Delete { address } => {
+					self.accesses.insert(Access::Account(*address));
+					self.reset.insert(*address);
+				},
+			}
+		}
+	}
+
+	/// Whether the speculation read state written since the start of the batch.
+	pub fn conflicts(&self, speculation: &Speculation) -> bool {
+		speculation.reads.iter().any(|access| {
+			self.accesses.contains(access) || match access {
+				Access::Storage(address, _) => self.reset.contains(address),
+				Access::Account(_) => false,
+			}
+		})
+	}
+}
+
+/// Execute transactions on up to `threads` threads, each against the backend as
+/// is, recording the state they read.
+pub(crate) fn speculate<B: Backend + Sync>(
+	backend: &B,
+	config: &Config,
+	precompile: PrecompileFn,
+	cancellation: Option<&CancellationToken>,
+	transactions: &[BlockTransaction],
+	threads: usize,
+) -> Vec<Speculation> {
+	let next = AtomicUsize::new(0);
+	let results = Mutex::new(Vec::with_capacity(transactions.len()));
+
+	std::thread::scope(|scope| {
+		for _ in 0..threads.clamp(1, transactions.len().max(1)) {
+			scope.spawn(|| {
+				let valids_cache = Rc::new(RefCell::new(ValidsCache::new()));
+				let frame_pool = Rc::new(RefCell::new(FramePool::new()));
+
+				loop {
+					let index = next.fetch_add(1, Ordering::Relaxed);
+					let transaction = match transactions.get(index) {
+						Some(transaction) => transaction,
+						None => break,
+					};
+
+					let recording = RecordingBackend {
+						backend,
+						origin: transaction.caller(),
+						gas_price: transaction.gas_price,
+						transaction_root_hash: transaction.hash,
+						reads: RefCell::new(BTreeSet::new()),
+					};
+					let (receipt, applies) = transact(
+						&recording, config, precompile, &valids_cache, &frame_pool, cancellation, transaction,
+					);
+					let speculation = Speculation { receipt, applies, reads: recording.reads.into_inner() };
+					results.lock().expect("Speculation thread panicked").push((index, speculation));
+				}
+			});
+		}
+	});
+
+	let mut results = results.into_inner().expect("Speculation thread panicked");
+	results.sort_by_key(|(index, _)| *index);
+	results.into_iter().map(|(_, speculation)| speculation).collect()
+}
@@ -0,0 +1,68 @@
+use alloc::vec::Vec;
+use crate::{Machine, Memory, Stack};
+
+/// Maximum number of idle buffers kept by a `FramePool`, one per call depth.
+const MAX_IDLE: usize = 1024;
+
+/// Counters of a `FramePool`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct FramePoolStats {
+	/// Frames served with recycled buffers.
+	pub reused: u64,
+	/// Frames that needed fresh buffers.
+	pub allocated: u64,
+}
+
+/// Pool of stack and memory buffers recycled across call frames, so that nested
+/// calls and subsequent transactions don't allocate fresh buffers per frame.
+#[derive(Debug, Default)]
+pub struct FramePool {
+	buffers: Vec<(Stack, Memory)>,
+	stats: FramePoolStats,
+}
+
+impl FramePool {
+	/// Create an empty pool.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Take an empty stack and memory with the given limits.
+	pub fn take(&mut self, stack_limit: usize, memory_limit: usize) -> (Stack, Memory) {
+		match self.buffers.pop() {
+			Some((mut stack, mut memory)) => {
+				self.stats.reused += 1;
+				stack.reset(stack_limit);
+				memory.reset(memory_limit);
+				(stack, memory)
+			},
+			None => {
+				self.stats.allocated += 1;
+				(Stack::new(stack_limit), Memory::new(memory_limit))
+			},
+		}
+	}
+
+	/// Return the buffers of a finished machine to the pool. They are dropped if
+	/// the pool already holds `1024` idle buffers.
+	pub fn release(&mut self, machine: Machine) {
+		if self.buffers.len() < MAX_IDLE {
+			self.buffers.push(machine.into_buffers());
+		}
+	}
+
+	/// Number of idle buffers in the pool.
+	pub fn len(&self) -> usize {
+		self.buffers.len()
+	}
+
+	/// Whether the pool has no idle buffers.
+	pub fn is_empty(&self) -> bool {
+		self.buffers.is_empty()
+	}
+
+	/// Counters of the pool.
+	pub fn stats(&self) -> FramePoolStats {
+		self.stats
+	}
+}
@@ -0,0 +1,130 @@
+use primitive_types::{H160, H256, U256};
+use crate::{Bytes, Config, Context};
+use crate::backend::Backend;
+
+/// State visible to a precompile. The executor exposes the state of the calling
+/// frame, including uncommitted changes; any backend exposes its committed state.
+pub trait PrecompileState {
+	/// Backend of the executor, for chain state such as account permissions.
+	fn backend(&self) -> &dyn Backend;
+	/// Get balance of address.
+	fn balance(&self, address: H160) -> U256;
+	/// Get TRC-10 token balance of address.
+	fn token_balance(&self, address: H160, token_id: U256) -> U256;
+	/// Get code of address.
+	fn code(&self, address: H160) -> Bytes;
+	/// Get storage value of address at index.
+	fn storage(&self, address: H160, index: H256) -> Option<H256>;
+	/// Check whether an address exists.
+	fn exists(&self, address: H160) -> bool;
+}
+
+impl<B: Backend> PrecompileState for B {
+	fn backend(&self) -> &dyn Backend {
+		self
+	}
+
+	fn balance(&self, address: H160) -> U256 {
+		self.basic(address).balance
+	}
+
+	fn token_balance(&self, address: H160, token_id: U256) -> U256 {
+		self.basic(address).token_balance.get(&token_id).cloned().unwrap_or_default()
+	}
+
+	fn code(&self, address: H160) -> Bytes {
+		Backend::code(self, address)
+	}
+
+	fn storage(&self, address: H160, index: H256) -> Option<H256> {
+		Backend::storage(self, address, index)
+	}
+
+	fn exists(&self, address: H160) -> bool {
+		Backend::exists(self, address)
+	}
+}
+
+/// Environment of a precompile call.
+#[derive(Clone, Copy)]
+pub struct PrecompileContext<'a> {
+	/// Context of the call, with the caller and the transferred value.
+	pub context: &'a Context,
+	/// Whether the call is static.
+	pub is_static: bool,
+	/// Depth of the call, zero for a transaction calling the precompile.
+	pub depth: usize,
+	/// State as seen by the call.
+	pub state: &'a dyn PrecompileState,
+}
+
+impl<'a> PrecompileContext<'a> {
+	/// Context of a non-static transaction calling the precompile.
+	pub fn new(context: &'a Context, state: &'a dyn PrecompileState) -> Self {
+		Self { context, is_static: false, depth: 0, state }
+	}
+
+	/// Backend of the executor.
+	pub fn backend(&self) -> &'a dyn Backend {
+		self.state.backend()
+	}
+}
+
+/// Order of the secp256k1 curve.
+const SECP256K1_N: [u8; 32] = [
+	0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+	0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x41,
+];
+
+/// Input of the ecrecover precompile, `hash ++ v ++ r ++ s` in 32-byte words.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct EcrecoverInput {
+	/// Message hash.
+	pub hash: H256,
+	/// Recovery id, `v - 27`.
+	pub recovery_id: u8,
+	/// Signature `r`.
+	pub r: H256,
+	/// Signature `s`.
+	pub s: H256,
+}
+
+impl EcrecoverInput {
+	/// Parse the input, right-padded with zeros to 128 bytes and truncated beyond.
+	/// `None` if the signature is invalid, for which the precompile succeeds with
+	/// empty output.
+	///
+	/// `r` and `s` must be in `[1, n)`. `v` is the last byte of its word and must be
+	/// 27 or 28; with `ecrecover_strict`, the rest of the word must be zero and `s`
+	/// must be at most `n / 2`.
+	pub fn parse(input: &[u8], config: &Config) -> Option<Self> {
+		let mut padded = [0u8; 128];
+		let len = input.len().min(128);
+		padded[..len].copy_from_slice(&input[..len]);
+
+		let v = padded[63];
+		if v != 27 && v != 28 {
+			return None
+		}
+		if config.ecrecover_strict && padded[32..63].iter().any(|b| *b != 0) {
+			return None
+		}
+
+		let n = U256::from_big_endian(&SECP256K1_N);
+		let r = U256::from_big_endian(&padded[64..96]);
+		let s = U256::from_big_endian(&padded[96..128]);
+		if r.is_zero() || r >= n || s.is_zero() || s >= n {
+			return None
+		}
+		if config.ecrecover_strict && s > n / 2 {
+			return None
+		}
+
+		Some(Self {
+			hash: H256::from_slice(&padded[..32]),
+			recovery_id: v - 27,
+			r: H256::from_slice(&padded[64..96]),
+			s: H256::from_slice(&padded[96..128]),
+		})
+	}
+}
@@ -0,0 +1,46 @@
+use alloc::vec::Vec;
+use alloc::collections::BTreeMap;
+use primitive_types::{H160, U256};
+use crate::{Config, ExitReason};
+use crate::backend::{AccountOverride, Backend, Log, OverlayBackend};
+use super::{StackExecutor, PrecompileFn};
+
+/// Outcome of a simulated call.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Simulation {
+	/// Exit reason of the call.
+	pub reason: ExitReason,
+	/// Return or revert data.
+	pub output: Vec<u8>,
+	/// Gas used, after refunds.
+	pub used_gas: usize,
+	/// Logs emitted.
+	pub logs: Vec<Log>,
+}
+
+/// Simulate a call against the backend with account overrides applied, as in
+/// `eth_call`. Neither the backend nor the overrides are modified.
+#[allow(clippy::too_many_arguments)]
+pub fn simulate_call<B: Backend>(
+	backend: &B,
+	overrides: BTreeMap<H160, AccountOverride>,
+	config: &Config,
+	precompile: PrecompileFn,
+	caller: H160,
+	address: H160,
+	value: U256,
+	data: Vec<u8>,
+	gas_limit: usize,
+) -> Simulation {
+	let backend = OverlayBackend::new(backend, overrides);
+	let mut executor = StackExecutor::new_with_precompile(&backend, gas_limit, config, precompile);
+	let (reason, output) = executor.transact_call(caller, address, value, data, gas_limit);
+
+	Simulation {
+		reason,
+		output,
+		used_gas: executor.used_gas(),
+		logs: executor.logs().to_vec(),
+	}
+}
@@ -1,14 +1,18 @@
 use core::convert::Infallible;
 use core::cmp::min;
+use core::ops::Range;
+use core::cell::{Cell, RefCell};
 use alloc::rc::Rc;
 use alloc::vec::Vec;
 use alloc::collections::{BTreeMap, BTreeSet};
 use primitive_types::{U256, H256, H160};
 use sha3::{Keccak256, Digest};
 use crate::{ExitError, Stack, ExternalOpcode, Opcode, Capture, Handler, Transfer,
-			Context, CreateScheme, Runtime, ExitReason, ExitSucceed, ExitFatal, Config};
-use crate::backend::{Log, Basic, Apply, Backend};
-use crate::gasometer::{self, Gasometer};
+			Context, CreateScheme, CallScheme, Runtime, ExitReason, ExitSucceed, ExitFatal, Config,
+			Inspector, Machine, Memory, Bytes, Shared, Superinstructions, Valids};
+use crate::backend::{Log, Bloom, Basic, Apply, Backend, Stake};
+use crate::gasometer::{self, Gasometer, StorageTarget};
+use super::{CancellationToken, ValidsCache, FramePool, GasCategory, Metrics, Coverage, CoverageMap, PrecompileContext};
 
 /// Account definition for the stack-based executor.
 #[derive(Default, Clone, Debug, Eq, PartialEq)]
@@ -16,7 +20,7 @@ pub struct StackAccount {
 	/// Basic account information, including nonce and balance.
 	pub basic: Basic,
 	/// Code. `None` means the code is currently unknown.
-	pub code: Option<Vec<u8>>,
+	pub code: Option<Bytes>,
 	/// Storage. Not inserted values mean it is currently known, but not empty.
 	pub storage: BTreeMap<H256, H256>,
 	/// Whether the storage in the database should be reset before storage
@@ -24,6 +28,75 @@ pub struct StackAccount {
 	pub reset_storage: bool,
 }
 
+/// Kind of an internal transaction.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InternalTransactionKind {
+	/// A message call.
+	Call(CallScheme),
+	/// A contract creation.
+	Create(CreateScheme),
+	/// A `SELFDESTRUCT`.
+	Suicide,
+}
+
+/// Internal transaction, a call, creation or suicide made by a contract.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InternalTransaction {
+	/// Caller, creator or the destructed contract.
+	pub caller: H160,
+	/// Callee, created contract or beneficiary of the suicide.
+	pub to: H160,
+	/// Kind of the internal transaction.
+	pub kind: InternalTransactionKind,
+	/// Value transferred.
+	pub value: U256,
+	/// TRC-10 token transferred, if any.
+	pub token_id: Option<U256>,
+	/// TRC-10 token value transferred.
+	pub token_value: U256,
+	/// Call depth, starting from 1 for calls made by the transaction's contract.
+	pub depth: usize,
+	/// Exit reason of the frame.
+	pub reason: ExitReason,
+	/// Whether the frame or one of its callers failed or reverted.
+	pub rejected: bool,
+}
+
+/// Transaction simulated by `StackExecutor::estimate_gas`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EstimateTransaction {
+	/// A `CALL` transaction.
+	Call {
+		/// Caller.
+		caller: H160,
+		/// Callee.
+		address: H160,
+		/// Value transferred.
+		value: U256,
+		/// Call data.
+		data: Vec<u8>,
+	},
+	/// A `CREATE` transaction.
+	Create {
+		/// Creator.
+		caller: H160,
+		/// Value transferred.
+		value: U256,
+		/// Init code.
+		init_code: Vec<u8>,
+	},
+}
+
+/// Precompile result of exit status, output and used gas, `None` if the address is
+/// not a precompile.
+pub type PrecompileResult = Option<Result<(ExitSucceed, Vec<u8>, usize), ExitError>>;
+
+/// Precompile dispatcher, given address, input, target gas, config and the
+/// context of the call.
+pub type PrecompileFn = fn(H160, &[u8], Option<usize>, &Config, &PrecompileContext) -> PrecompileResult;
+
 /// Stack-based executor.
 #[derive(Clone)]
 pub struct StackExecutor<'backend, 'config, B> {
@@ -33,18 +106,71 @@ pub struct StackExecutor<'backend, 'config, B> {
 	state: BTreeMap<H160, StackAccount>,
 	deleted: BTreeSet<H160>,
 	logs: Vec<Log>,
-	precompile: fn(H160, &[u8], Option<usize>, &dyn Backend) -> Option<Result<(ExitSucceed, Vec<u8>, usize), ExitError>>,
+	stakes: Vec<Stake>,
+	internal_transactions: Vec<InternalTransaction>,
+	accessed_addresses: BTreeSet<H160>,
+	accessed_storages: BTreeSet<(H160, H256)>,
+	precompile: PrecompileFn,
+	inspector: Option<Rc<RefCell<dyn Inspector>>>,
+	metrics: Option<Rc<RefCell<dyn Metrics>>>,
+	coverage: Option<Rc<RefCell<Coverage>>>,
+	valids_cache: Option<Rc<RefCell<ValidsCache>>>,
+	code_hashes: Rc<RefCell<BTreeMap<H160, H256>>>,
+	frame_pool: Option<Rc<RefCell<FramePool>>>,
+	steps: Rc<Cell<u64>>,
+	timed_out: Rc<Cell<bool>>,
+	step_limit: Option<u64>,
+	cancellation: Option<CancellationToken>,
+	#[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+	deadline: Option<std::time::Instant>,
 	is_static: bool,
 	depth: Option<usize>,
 	nonce: u64,
 }
 
+/// Inspector of a frame, calling the shared inspector, borrowed only for the
+/// duration of each callback so that substates can share it, and marking
+/// executed program counters in the frame's coverage map.
+struct FrameInspector {
+	inspector: Option<Rc<RefCell<dyn Inspector>>>,
+	coverage: Option<CoverageMap>,
+}
+
+impl Inspector for FrameInspector {
+	fn step(&mut self, context: &Context, opcode: Result<Opcode, ExternalOpcode>, machine: &Machine) {
+		if let (Some(coverage), Ok(pc)) = (&mut self.coverage, machine.position()) {
+			coverage.insert(*pc);
+		}
+		if let Some(inspector) = &self.inspector {
+			inspector.borrow_mut().step(context, opcode, machine)
+		}
+	}
+
+	fn step_result(&mut self, context: &Context, result: &Result<(), ExitReason>, machine: &Machine) {
+		if let Some(inspector) = &self.inspector {
+			inspector.borrow_mut().step_result(context, result, machine)
+		}
+	}
+}
+
+/// Scheme of a call, as it is not passed to `Handler::call`.
+pub(crate) fn call_scheme(code_address: H160, transfer: &Option<Transfer>, is_static: bool, context: &Context) -> CallScheme {
+	match transfer {
+		_ if is_static => CallScheme::StaticCall,
+		None => CallScheme::DelegateCall,
+		Some(_) if context.address != code_address => CallScheme::CallCode,
+		Some(transfer) if transfer.token_id.is_some() => CallScheme::CallToken,
+		Some(_) => CallScheme::Call,
+	}
+}
+
 fn no_precompile(
 	_address: H160,
 	_input: &[u8],
 	_target_gas: Option<usize>,
-	_backend: &dyn Backend
-) -> Option<Result<(ExitSucceed, Vec<u8>, usize), ExitError>> {
+	_config: &Config,
+	_context: &PrecompileContext,
+) -> PrecompileResult {
 	None
 }
 
@@ -58,12 +184,13 @@ impl<'backend, 'config, B: Backend> StackExecutor<'backend, 'config, B> {
 		Self::new_with_precompile(backend, gas_limit, config, no_precompile)
 	}
 
-	/// Create a new stack-based executor with given precompiles.
+	/// Create a new stack-based executor with given precompiles. Precompiles are given
+	/// the config, to charge costs of the active fork.
 	pub fn new_with_precompile(
 		backend: &'backend B,
 		gas_limit: usize,
 		config: &'config Config,
-		precompile: fn(H160, &[u8], Option<usize>, &dyn Backend) -> Option<Result<(ExitSucceed, Vec<u8>, usize), ExitError>>,
+		precompile: PrecompileFn,
 	) -> Self {
 		Self {
 			backend,
@@ -72,13 +199,74 @@ impl<'backend, 'config, B: Backend> StackExecutor<'backend, 'config, B> {
 			deleted: BTreeSet::new(),
 			config,
 			logs: Vec::new(),
+			stakes: Vec::new(),
+			internal_transactions: Vec::new(),
+			accessed_addresses: BTreeSet::new(),
+			accessed_storages: BTreeSet::new(),
 			precompile: precompile,
+			inspector: None,
+			metrics: None,
+			coverage: None,
+			valids_cache: None,
+			code_hashes: Rc::new(RefCell::new(BTreeMap::new())),
+			frame_pool: None,
+			steps: Rc::new(Cell::new(0)),
+			timed_out: Rc::new(Cell::new(false)),
+			step_limit: None,
+			cancellation: None,
+			#[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+			deadline: None,
 			is_static: false,
 			depth: None,
 			nonce: 0,
 		}
 	}
 
+	/// Estimate the lowest gas (energy) limit, up to `gas_cap`, under which the
+	/// transaction succeeds. Each attempt runs on a fresh executor, so the backend is
+	/// never modified. Returns the exit reason and output at `gas_cap` if the
+	/// transaction does not succeed at all.
+	pub fn estimate_gas(
+		backend: &'backend B,
+		config: &'config Config,
+		precompile: PrecompileFn,
+		transaction: &EstimateTransaction,
+		gas_cap: usize,
+	) -> Result<usize, (ExitReason, Vec<u8>)> {
+		let simulate = |gas_limit: usize| {
+			let mut executor = Self::new_with_precompile(backend, gas_limit, config, precompile);
+			let (reason, out) = match transaction.clone() {
+				EstimateTransaction::Call { caller, address, value, data } =>
+					executor.transact_call(caller, address, value, data, gas_limit),
+				EstimateTransaction::Create { caller, value, init_code } =>
+					(executor.transact_create(caller, value, init_code, gas_limit), Vec::new()),
+			};
+			(reason, out, executor.gasometer.total_used_gas())
+		};
+
+		let (reason, out, used) = simulate(gas_cap);
+		if !reason.is_succeed() {
+			return Err((reason, out))
+		}
+
+		// Gas used before refunds must be covered by the limit, so any lower limit fails.
+		let mut lower = match used.checked_sub(1) {
+			Some(lower) => lower,
+			None => return Ok(0),
+		};
+		let mut upper = gas_cap;
+		while upper - lower > 1 {
+			let mid = lower + (upper - lower) / 2;
+			if simulate(mid).0.is_succeed() {
+				upper = mid;
+			} else {
+				lower = mid;
+			}
+		}
+
+		Ok(upper)
+	}
+
 	/// Create a substate executor from the current executor.
 	pub fn substate(&self, gas_limit: usize, is_static: bool) -> StackExecutor<'backend, 'config, B> {
 		Self {
@@ -88,7 +276,23 @@ impl<'backend, 'config, B: Backend> StackExecutor<'backend, 'config, B> {
 			state: self.state.clone(),
 			deleted: self.deleted.clone(),
 			logs: Vec::new(),
+			stakes: self.stakes.clone(),
+			internal_transactions: Vec::new(),
+			accessed_addresses: self.accessed_addresses.clone(),
+			accessed_storages: self.accessed_storages.clone(),
 			precompile: self.precompile,
+			inspector: self.inspector.clone(),
+			metrics: self.metrics.clone(),
+			coverage: self.coverage.clone(),
+			valids_cache: self.valids_cache.clone(),
+			code_hashes: self.code_hashes.clone(),
+			frame_pool: self.frame_pool.clone(),
+			steps: self.steps.clone(),
+			timed_out: self.timed_out.clone(),
+			step_limit: self.step_limit,
+			cancellation: self.cancellation.clone(),
+			#[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+			deadline: self.deadline,
 			is_static: is_static || self.is_static,
 			depth: match self.depth {
 				None => Some(0),
@@ -98,12 +302,160 @@ impl<'backend, 'config, B: Backend> StackExecutor<'backend, 'config, B> {
 		}
 	}
 
+	/// Set the inspector called around each opcode, shared with substates.
+	pub fn set_inspector(&mut self, inspector: Rc<RefCell<dyn Inspector>>) {
+		self.inspector = Some(inspector);
+	}
+
+	/// Set the sink of opcode and precompile metrics, shared with substates.
+	pub fn set_metrics(&mut self, metrics: Rc<RefCell<dyn Metrics>>) {
+		self.metrics = Some(metrics);
+	}
+
+	/// Record the executed program counters of each frame into the coverage, shared
+	/// with substates.
+	pub fn set_coverage(&mut self, coverage: Rc<RefCell<Coverage>>) {
+		self.coverage = Some(coverage);
+	}
+
+	/// Set the cache of jump destination maps used for message calls, shared with
+	/// substates. The same cache can be reused across executors.
+	pub fn set_valids_cache(&mut self, cache: Rc<RefCell<ValidsCache>>) {
+		self.valids_cache = Some(cache);
+	}
+
+	/// Set the pool of stack and memory buffers recycled across call frames, shared
+	/// with substates. The same pool can be reused across executors.
+	pub fn set_frame_pool(&mut self, pool: Rc<RefCell<FramePool>>) {
+		self.frame_pool = Some(pool);
+	}
+
+	/// Limit the number of opcodes executed across all frames. Exceeding it exits
+	/// every frame with `OutOfTime`.
+	pub fn set_step_limit(&mut self, limit: u64) {
+		self.step_limit = Some(limit);
+	}
+
+	/// Set a wall-clock deadline, checked every 1024 steps. Exceeding it exits every
+	/// frame with `OutOfTime`. Not available on `wasm32-unknown-unknown`, which has no
+	/// clock.
+	#[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+	pub fn set_deadline(&mut self, deadline: std::time::Instant) {
+		self.deadline = Some(deadline);
+	}
+
+	/// Check the token at every step. Once it is cancelled, every frame exits with
+	/// `Cancelled`.
+	pub fn set_cancellation(&mut self, token: CancellationToken) {
+		self.cancellation = Some(token);
+	}
+
+	/// Number of opcodes executed across all frames.
+	pub fn steps(&self) -> u64 {
+		self.steps.get()
+	}
+
+	/// Count a step against the step limit and deadline, and check the cancellation.
+	fn record_step(&self) -> Result<(), ExitError> {
+		let steps = self.steps.get() + 1;
+		self.steps.set(steps);
+
+		if self.cancellation.as_ref().map(CancellationToken::is_cancelled).unwrap_or(false) {
+			return Err(ExitError::Cancelled)
+		}
+
+		#[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+		if let Some(deadline) = self.deadline {
+			if steps % 1024 == 0 && std::time::Instant::now() >= deadline {
+				self.timed_out.set(true);
+			}
+		}
+
+		if self.timed_out.get() || self.step_limit.map(|limit| steps > limit).unwrap_or(false) {
+			return Err(ExitError::OutOfTime)
+		}
+		Ok(())
+	}
+
+	/// Range of the code that is executed: the code section of an EOF container
+	/// (EIP-3540), or the whole code.
+	fn code_section(&self, code: &[u8]) -> Range<usize> {
+		if self.config.has_eof {
+			if let Ok(container) = crate::parse_eof(code) {
+				let end = code.len() - container.data.len();
+				return (end - container.code.len())..end
+			}
+		}
+		0..code.len()
+	}
+
+	/// Create the runtime of a call frame executing the section of the code, taking
+	/// buffers from the frame pool if set. `valids` and `superinstructions` are of
+	/// the section.
+	fn frame_runtime(
+		&self,
+		code: Bytes,
+		section: Range<usize>,
+		data: Bytes,
+		valids: Shared<Valids>,
+		superinstructions: Option<Shared<Superinstructions>>,
+		context: Context,
+	) -> Runtime<'config> {
+		let (stack, memory) = match &self.frame_pool {
+			Some(pool) => pool.borrow_mut().take(self.config.stack_limit, self.config.memory_limit),
+			None => (Stack::new(self.config.stack_limit), Memory::new(self.config.memory_limit)),
+		};
+		let mut machine = if section.len() == code.len() {
+			Machine::with_buffers(code, data, valids, stack, memory)
+		} else {
+			// `CODESIZE` and `CODECOPY` still see the whole container.
+			let mut machine = Machine::with_buffers(code.slice(section), data, valids, stack, memory);
+			machine.set_container(code);
+			machine
+		};
+		if let Some(superinstructions) = superinstructions {
+			machine.set_superinstructions(superinstructions);
+		}
+		Runtime::from_machine(machine, context, self.config)
+	}
+
+	/// Return the buffers of a finished call frame to the frame pool if set.
+	fn release_frame(&self, runtime: Runtime<'config>) {
+		if let Some(pool) = &self.frame_pool {
+			pool.borrow_mut().release(runtime.into_machine());
+		}
+	}
+
 	/// Execute the runtime until it returns.
 	pub fn execute(&mut self, runtime: &mut Runtime) -> ExitReason {
-		match runtime.run(self) {
+		self.execute_with_code_hash(runtime, None)
+	}
+
+	/// Execute the runtime until it returns. Coverage is merged under the given hash
+	/// of its code, or the hash of the machine code if not given.
+	fn execute_with_code_hash(&mut self, runtime: &mut Runtime, code_hash: Option<H256>) -> ExitReason {
+		if self.inspector.is_none() && self.coverage.is_none() {
+			return match runtime.run(self) {
+				Capture::Exit(s) => s,
+				Capture::Trap(_) => unreachable!("Trap is Infallible"),
+			}
+		}
+
+		let mut inspector = FrameInspector {
+			inspector: self.inspector.clone(),
+			coverage: self.coverage.as_ref().map(|_| CoverageMap::new(runtime.machine().code().len())),
+		};
+		let reason = match runtime.run_inspect(self, &mut inspector) {
 			Capture::Exit(s) => s,
 			Capture::Trap(_) => unreachable!("Trap is Infallible"),
+		};
+
+		if let (Some(coverage), Some(map)) = (&self.coverage, inspector.coverage) {
+			let code_hash = code_hash
+				.unwrap_or_else(|| H256::from_slice(&Keccak256::digest(&runtime.machine().code()[..])));
+			coverage.borrow_mut().merge(code_hash, &map);
 		}
+		reason
 	}
 
 	/// Get remaining gas.
@@ -117,6 +469,10 @@ impl<'backend, 'config, B: Backend> StackExecutor<'backend, 'config, B> {
 		mut substate: StackExecutor<'obackend, 'oconfig, OB>
 	) -> Result<(), ExitError> {
 		self.logs.append(&mut substate.logs);
+		self.internal_transactions.append(&mut substate.internal_transactions);
+		self.stakes = substate.stakes;
+		self.accessed_addresses = substate.accessed_addresses;
+		self.accessed_storages = substate.accessed_storages;
 		self.deleted.append(&mut substate.deleted);
 		self.state = substate.state;
 		self.nonce = substate.nonce;
@@ -131,7 +487,7 @@ impl<'backend, 'config, B: Backend> StackExecutor<'backend, 'config, B> {
 		&mut self,
 		mut substate: StackExecutor<'obackend, 'oconfig, OB>
 	) -> Result<(), ExitError> {
-		self.logs.append(&mut substate.logs);
+		self.reject_internal_transactions(&mut substate.internal_transactions);
 		self.nonce = substate.nonce;
 
 		self.gasometer.record_stipend(substate.gasometer.gas())?;
@@ -143,7 +499,7 @@ impl<'backend, 'config, B: Backend> StackExecutor<'backend, 'config, B> {
 		&mut self,
 		mut substate: StackExecutor<'obackend, 'oconfig, OB>
 	) -> Result<(), ExitError> {
-		self.logs.append(&mut substate.logs);
+		self.reject_internal_transactions(&mut substate.internal_transactions);
 		self.nonce = substate.nonce;
 
 		Ok(())
@@ -162,10 +518,20 @@ impl<'backend, 'config, B: Backend> StackExecutor<'backend, 'config, B> {
 			Ok(()) => (),
 			Err(e) => return e.into(),
 		}
+		self.initialize_accessed(caller, None);
+
+		let scheme = CreateScheme::Legacy { nonce: self.nonce, transaction_root_hash: self.transaction_root_hash() };
+		event!(TransactCreate {
+			caller,
+			value,
+			init_code: &init_code,
+			gas_limit,
+			address: self.create_address(scheme),
+		});
 
 		match self.create_inner(
 			caller,
-			CreateScheme::Legacy { nonce: self.nonce, transaction_root_hash: self.transaction_root_hash() },
+			scheme,
 			value,
 			init_code,
 			Some(gas_limit),
@@ -190,11 +556,21 @@ impl<'backend, 'config, B: Backend> StackExecutor<'backend, 'config, B> {
 			Ok(()) => (),
 			Err(e) => return e.into(),
 		}
-		let code_hash = H256::from_slice(Keccak256::digest(&init_code).as_slice());
+		self.initialize_accessed(caller, None);
+		let code_hash = H256::from_slice(&Keccak256::digest(&init_code));
+		let scheme = CreateScheme::Create2 { caller, code_hash, salt };
+		event!(TransactCreate2 {
+			caller,
+			value,
+			init_code: &init_code,
+			salt,
+			gas_limit,
+			address: self.create_address(scheme),
+		});
 
 		match self.create_inner(
 			caller,
-			CreateScheme::Create2 { caller, code_hash, salt },
+			scheme,
 			value,
 			init_code,
 			Some(gas_limit),
@@ -213,12 +589,36 @@ impl<'backend, 'config, B: Backend> StackExecutor<'backend, 'config, B> {
 		value: U256,
 		data: Vec<u8>,
 		gas_limit: usize,
+	) -> (ExitReason, Vec<u8>) {
+		self.transact_call_token(caller, address, value, U256::zero(), U256::zero(), data, gas_limit)
+	}
+
+	/// Execute a `CALL` transaction with TRC-10 token value.
+	#[allow(clippy::too_many_arguments)]
+	pub fn transact_call_token(
+		&mut self,
+		caller: H160,
+		address: H160,
+		value: U256,
+		token_id: U256,
+		token_value: U256,
+		data: Vec<u8>,
+		gas_limit: usize,
 	) -> (ExitReason, Vec<u8>) {
 		let transaction_cost = gasometer::call_transaction_cost(&data);
 		match self.gasometer.record_transaction(transaction_cost) {
 			Ok(()) => (),
 			Err(e) => return (e.into(), Vec::new()),
 		}
+		self.initialize_accessed(caller, Some(address));
+
+		event!(TransactCall {
+			caller,
+			address,
+			value,
+			data: &data,
+			gas_limit,
+		});
 
 		self.account_mut(caller).basic.nonce += U256::one();
 
@@ -226,28 +626,90 @@ impl<'backend, 'config, B: Backend> StackExecutor<'backend, 'config, B> {
 			caller,
 			address,
 			call_value: value,
-			call_token_id: U256::from(0),
-			call_token_value: U256::from(0),
+			call_token_id: token_id,
+			call_token_value: token_value,
 		};
 
 		match self.call_inner(address, Some(Transfer {
 			source: caller,
 			target: address,
 			value,
-			token_id: U256::from(0),
-			token_value: U256::from(0),
-		}), data, Some(gas_limit), false, false, false, context) {
+			token_id: if token_id == U256::zero() { None } else { Some(token_id) },
+			token_value,
+		}), data.into(), Some(gas_limit), false, false, false, context) {
 			Capture::Exit((s, v)) => (s, v),
 			Capture::Trap(_) => unreachable!(),
 		}
 	}
 
+	/// Mark addresses and storage slots as accessed for EIP-2929, such as precompiles
+	/// and the EIP-2930 access list of the transaction.
+	pub fn warm_access_list(&mut self, access_list: Vec<(H160, Vec<H256>)>) {
+		for (address, indices) in access_list {
+			self.accessed_addresses.insert(address);
+			for index in indices {
+				self.accessed_storages.insert((address, index));
+			}
+		}
+	}
+
+	fn initialize_accessed(&mut self, caller: H160, address: Option<H160>) {
+		if !self.config.increase_state_access_gas {
+			return
+		}
+		self.accessed_addresses.insert(caller);
+		if let Some(address) = address {
+			self.accessed_addresses.insert(address);
+		}
+		if self.config.warm_coinbase_address {
+			self.accessed_addresses.insert(self.backend.block_coinbase());
+		}
+		for precompile in 1..=self.config.warm_precompiles {
+			self.accessed_addresses.insert(H160::from_low_u64_be(precompile));
+		}
+	}
+
+	/// Get staking operations accepted so far, to be applied by the backend.
+	pub fn stakes(&self) -> &[Stake] {
+		&self.stakes
+	}
+
+	/// Get logs emitted so far. Logs of reverted or failed substates are discarded.
+	pub fn logs(&self) -> &[Log] {
+		&self.logs
+	}
+
+	/// Bloom filter of the logs emitted so far.
+	pub fn logs_bloom(&self) -> Bloom {
+		Bloom::from_logs(&self.logs)
+	}
+
+	/// Get internal transactions made so far, in call order.
+	pub fn internal_transactions(&self) -> &[InternalTransaction] {
+		&self.internal_transactions
+	}
+
+	fn reject_internal_transactions(&mut self, internal_transactions: &mut Vec<InternalTransaction>) {
+		for internal_transaction in internal_transactions.iter_mut() {
+			internal_transaction.rejected = true;
+		}
+		self.internal_transactions.append(internal_transactions);
+	}
+
+	fn record_internal_transaction(&mut self, index: usize, mut internal_transaction: InternalTransaction) {
+		internal_transaction.rejected = !internal_transaction.reason.is_succeed();
+		self.internal_transactions.insert(index, internal_transaction);
+	}
+
 	/// Get used gas for the current executor, given the price.
 	pub fn used_gas(
 		&self,
 	) -> usize {
+		// Net metering refunds can be negative within a transaction, such as when
+		// a cleared slot is set again.
 		self.gasometer.total_used_gas() -
-			min(self.gasometer.total_used_gas() / 2, self.gasometer.refunded_gas() as usize)
+			min(self.gasometer.total_used_gas() / self.config.max_refund_quotient,
+				self.gasometer.refunded_gas().max(0) as usize)
 	}
 
 	/// Get fee needed for the current executor, given the price.
@@ -276,7 +738,7 @@ impl<'backend, 'config, B: Backend> StackExecutor<'backend, 'config, B> {
 			applies.push(Apply::Modify {
 				address,
 				basic: account.basic,
-				code: account.code,
+				code: account.code.map(Bytes::into_vec),
 				storage: account.storage,
 				reset_storage: account.reset_storage,
 			});
@@ -356,18 +818,23 @@ impl<'backend, 'config, B: Backend> StackExecutor<'backend, 'config, B> {
 			}
 		}
 
-		if transfer.value > U256::zero() {
-			// TRON: validateForSmartContract, only amount > 0
-			// `self.exists` is cached, so `self.backend.exists` should be used.
-			if !self.config.create_account_if_not_exist && !self.backend.exists(transfer.target) {
-				if self.config.has_transfer_exception {
-					return Err(ExitError::TransferException);
-				} else {
-					// TRON: BytecodeExecutionException as Unknown
-					return Err(ExitError::Unknown);
-				}
+		let token_id = transfer.token_id.filter(|_| transfer.token_value > U256::zero());
+		if transfer.value == U256::zero() && token_id.is_none() {
+			return Ok(())
+		}
+
+		// TRON: validateForSmartContract, only amount > 0
+		// `self.exists` is cached, so `self.backend.exists` should be used.
+		if !self.config.create_account_if_not_exist && !self.backend.exists(transfer.target) {
+			if self.config.has_transfer_exception {
+				return Err(ExitError::TransferException);
+			} else {
+				// TRON: BytecodeExecutionException as Unknown
+				return Err(ExitError::Unknown);
 			}
+		}
 
+		if transfer.value > U256::zero() {
 			self.withdraw(transfer.source, transfer.value)?;
 			// TRON: When transfer amount is sufficient, will check transfer to oneself, or else revert.
 			if transfer.source == transfer.target {
@@ -375,41 +842,139 @@ impl<'backend, 'config, B: Backend> StackExecutor<'backend, 'config, B> {
 				return Err(ExitError::TransferException);
 			}
 			self.deposit(transfer.target, transfer.value);
-		} else if transfer.token_value > U256::zero() {
-			self.withdraw_token(transfer.source, transfer.token_id, transfer.token_value)?;
+		}
+		if let Some(token_id) = token_id {
+			self.withdraw_token(transfer.source, token_id, transfer.token_value)?;
 			if transfer.source == transfer.target {
 				// cannot transfer to oneself
 				return Err(ExitError::TransferException);
 			}
-			self.deposit_token(transfer.target, transfer.token_id, transfer.token_value);
+			self.deposit_token(transfer.target, token_id, transfer.token_value);
 		}
 		Ok(())
 	}
 
+	/// Whether voting reward of address is already withdrawn in this transaction.
+	fn reward_withdrawn(&self, address: H160) -> bool {
+		self.stakes.contains(&Stake::WithdrawReward { owner: address })
+	}
+
+	/// Validate and record a staking operation.
+	fn stake(&mut self, stake: Stake) -> Option<U256> {
+		let owner = stake.owner();
+		if let Stake::WithdrawExpireUnfreeze { .. } | Stake::CancelAllUnfreezeV2 { .. } = stake {
+			// Unfreezes already withdrawn in this transaction are not visible to the backend.
+			let withdrawn = self.stakes.iter().any(|s| s.owner() == owner && matches!(s,
+				Stake::WithdrawExpireUnfreeze { .. } | Stake::CancelAllUnfreezeV2 { .. }));
+			if withdrawn {
+				return None
+			}
+		}
+		if let Stake::WithdrawReward { .. } = stake {
+			if self.reward_withdrawn(owner) {
+				return Some(U256::zero())
+			}
+		}
+		let withdrawn = self.backend.validate_stake(&stake)?;
+		if let Stake::FreezeBalanceV2 { amount, .. } | Stake::Freeze { amount, .. } = stake {
+			if amount == U256::zero() || amount > U256::from(u64::MAX) {
+				return None
+			}
+			self.withdraw(owner, amount).ok()?;
+		}
+		self.deposit(owner, withdrawn);
+		self.stakes.push(stake);
+		Some(withdrawn)
+	}
+
+	/// Gas given to a call or create frame: the target gas, capped with
+	/// `call_l64_after_gas` to all but one 64th of the remaining gas (EIP-150).
+	/// Before that, a target above the remaining gas fails the opcode in the
+	/// gasometer with `err_on_call_with_more_gas`.
+	fn forwarded_gas(&self, target_gas: Option<usize>, take_l64: bool) -> usize {
+		let mut after_gas = self.gasometer.gas();
+		if take_l64 && self.config.call_l64_after_gas {
+			after_gas -= after_gas / 64;
+		}
+		min(target_gas.unwrap_or(after_gas), after_gas)
+	}
+
+	/// State modifications are checked by the gasometer before each opcode. This
+	/// guards the handler against hosts calling it directly in a static call.
+	fn check_not_static(&self) -> Result<(), ExitError> {
+		if self.is_static {
+			return Err(ExitError::StaticCallModification)
+		}
+		Ok(())
+	}
+
+	/// Get the hash of the code of an account, whether or not it exists. Hashes of
+	/// code in the backend are cached for the transaction.
+	fn account_code_hash(&self, address: H160) -> H256 {
+		if let Some(code) = self.state.get(&address).and_then(|v| v.code.as_ref()) {
+			return H256::from_slice(&Keccak256::digest(code))
+		}
+		if let Some(code_hash) = self.code_hashes.borrow().get(&address) {
+			return *code_hash
+		}
+		let code_hash = self.backend.code_hash(address);
+		self.code_hashes.borrow_mut().insert(address, code_hash);
+		code_hash
+	}
+
 	/// Get the create address from given scheme.
 	pub fn create_address(&self, scheme: CreateScheme) -> H160 {
-		match scheme {
-			CreateScheme::Create2 { caller, code_hash, salt } => {
-				let mut hasher = Keccak256::new();
-				hasher.update(&[0x41]);
-				hasher.update(&caller[..]);
-				hasher.update(&salt[..]);
-				hasher.update(&code_hash[..]);
-				H256::from_slice(hasher.finalize().as_slice()).into()
-			},
-			CreateScheme::Legacy { transaction_root_hash, nonce } => {
-				let mut hasher = Keccak256::new();
-				hasher.update(transaction_root_hash.as_bytes());
-				hasher.update(&nonce.to_be_bytes()[..]);
-				H256::from_slice(hasher.finalize().as_slice()).into()
-			},
-			CreateScheme::Fixed(naddress) => {
-				naddress
+		scheme.address()
+	}
+
+	fn create_inner(
+		&mut self,
+		caller: H160,
+		scheme: CreateScheme,
+		value: U256,
+		init_code: Vec<u8>,
+		target_gas: Option<usize>,
+		take_l64: bool,
+	) -> Capture<(ExitReason, Option<H160>, Vec<u8>), Infallible> {
+		event!(Create {
+			caller,
+			address: self.create_address(scheme),
+			scheme,
+			value,
+			init_code: &init_code,
+			target_gas,
+		});
+
+		let depth = self.depth.map(|n| n + 1);
+		let address = self.create_address(scheme);
+		let index = self.internal_transactions.len();
+		trace!("create depth={} address={:?} caller={:?} value={} code_len={} gas={:?}",
+			depth.unwrap_or(0), address, caller, value, init_code.len(), target_gas);
+
+		match self.create_frame(caller, scheme, value, init_code, target_gas, take_l64) {
+			Capture::Exit((reason, created, return_value)) => {
+				event!(Exit { reason: &reason, return_value: &return_value });
+				debug!("create exit depth={} address={:?} reason={:?}", depth.unwrap_or(0), address, reason);
+				if let Some(depth) = depth {
+					self.record_internal_transaction(index, InternalTransaction {
+						caller,
+						to: address,
+						kind: InternalTransactionKind::Create(scheme),
+						value,
+						token_id: None,
+						token_value: U256::zero(),
+						depth,
+						reason,
+						rejected: false,
+					});
+				}
+				Capture::Exit((reason, created, return_value))
 			},
+			Capture::Trap(t) => match t {},
 		}
 	}
 
-	fn create_inner(
+	fn create_frame(
 		&mut self,
 		caller: H160,
 		scheme: CreateScheme,
@@ -427,10 +992,6 @@ impl<'backend, 'config, B: Backend> StackExecutor<'backend, 'config, B> {
 			}
 		}
 
-		fn l64(gas: usize) -> usize {
-			gas - gas / 64
-		}
-
 		if let Some(depth) = self.depth {
 			if depth + 1 > self.config.call_stack_limit {
 				return Capture::Exit((ExitError::CallTooDeep.into(), None, Vec::new()))
@@ -441,17 +1002,14 @@ impl<'backend, 'config, B: Backend> StackExecutor<'backend, 'config, B> {
 			return Capture::Exit((ExitError::OutOfFund.into(), None, Vec::new()))
 		}
 
-		let mut after_gas = self.gasometer.gas();
-		if take_l64 && self.config.call_l64_after_gas {
-			after_gas = l64(after_gas);
-		}
-		let target_gas = target_gas.unwrap_or(after_gas);
-
-		let gas_limit = min(after_gas, target_gas);
+		let gas_limit = self.forwarded_gas(target_gas, take_l64);
 		try_or_fail!(self.gasometer.record_cost(gas_limit));
 
 		let address = self.create_address(scheme);
 		self.account_mut(caller).basic.nonce += U256::one();
+		if self.config.increase_state_access_gas {
+			self.accessed_addresses.insert(address);
+		}
 
 		let mut substate = self.substate(gas_limit, false);
 		{
@@ -490,7 +1048,7 @@ impl<'backend, 'config, B: Backend> StackExecutor<'backend, 'config, B> {
 			source: caller,
 			target: address,
 			value,
-			token_id: U256::from(0),
+			token_id: None,
 			token_value: U256::from(0),
 		};
 		match substate.transfer(transfer) {
@@ -505,19 +1063,22 @@ impl<'backend, 'config, B: Backend> StackExecutor<'backend, 'config, B> {
 			substate.account_mut(address).basic.nonce += U256::one();
 		}
 
-		let mut runtime = Runtime::new(
-			Rc::new(init_code),
-			Rc::new(Vec::new()),
-			context,
-			self.config,
-		);
+		if self.config.has_eof && crate::is_eof(&init_code) && crate::validate_eof(&init_code, self.config).is_err() {
+			substate.gasometer.fail();
+			let _ = self.merge_fail(substate);
+			return Capture::Exit((ExitError::InvalidCode.into(), None, Vec::new()))
+		}
+
+		let section = self.code_section(&init_code);
+		let valids = Shared::new(Valids::new(&init_code[section.clone()]));
+		let mut runtime = self.frame_runtime(init_code.into(), section, Bytes::new(), valids, None, context);
 
 		let reason = substate.execute(&mut runtime);
+		let out = runtime.machine().return_value();
+		self.release_frame(runtime);
 
 		match reason {
 			ExitReason::Succeed(s) => {
-				let out = runtime.machine().return_value();
-
 				if let Some(limit) = self.config.create_contract_limit {
 					if out.len() > limit {
 						substate.gasometer.fail();
@@ -526,11 +1087,25 @@ impl<'backend, 'config, B: Backend> StackExecutor<'backend, 'config, B> {
 					}
 				}
 
+				// EIP-3540: deployed code with the EOF prefix must be a valid container
+				if self.config.has_eof && out.first() == Some(&crate::EOF_MAGIC[0]) && crate::validate_eof(&out, self.config).is_err() {
+					substate.gasometer.fail();
+					let _ = self.merge_fail(substate);
+					return Capture::Exit((ExitError::InvalidCode.into(), None, Vec::new()))
+				}
+
+				// EIP-3541: without EOF, no new code may start with the `0xef` byte
+				if self.config.disallow_executable_format && !self.config.has_eof && out.first() == Some(&0xef) {
+					substate.gasometer.fail();
+					let _ = self.merge_fail(substate);
+					return Capture::Exit((ExitError::InvalidCode.into(), None, Vec::new()))
+				}
+
 				match substate.gasometer.record_deposit(out.len()) {
 					Ok(()) => {
 						let e = self.merge_succeed(substate);
 						self.state.entry(address).or_insert(Default::default())
-							.code = Some(out);
+							.code = Some(out.into());
 						try_or_fail!(e);
 						Capture::Exit((ExitReason::Succeed(s), Some(address), Vec::new()))
 					},
@@ -547,7 +1122,7 @@ impl<'backend, 'config, B: Backend> StackExecutor<'backend, 'config, B> {
 			},
 			ExitReason::Revert(e) => {
 				let _ = self.merge_revert(substate);
-				Capture::Exit((ExitReason::Revert(e), None, runtime.machine().return_value()))
+				Capture::Exit((ExitReason::Revert(e), None, out))
 			},
 			ExitReason::Fatal(e) => {
 				self.gasometer.fail();
@@ -560,7 +1135,65 @@ impl<'backend, 'config, B: Backend> StackExecutor<'backend, 'config, B> {
 		&mut self,
 		code_address: H160,
 		transfer: Option<Transfer>,
-		input: Vec<u8>,
+		input: Bytes,
+		target_gas: Option<usize>,
+		is_static: bool,
+		take_l64: bool,
+		take_stipend: bool,
+		context: Context,
+	) -> Capture<(ExitReason, Vec<u8>), Infallible> {
+		event!(Call {
+			code_address,
+			transfer: &transfer,
+			input: &input,
+			target_gas,
+			is_static,
+			context: &context,
+		});
+
+		let scheme = call_scheme(code_address, &transfer, is_static, &context);
+		let internal_transaction = self.depth.map(|n| InternalTransaction {
+			caller: match scheme {
+				CallScheme::CallCode | CallScheme::DelegateCall => context.address,
+				_ => context.caller,
+			},
+			to: code_address,
+			kind: InternalTransactionKind::Call(scheme),
+			value: transfer.as_ref().map(|t| t.value).unwrap_or_default(),
+			token_id: transfer.as_ref().and_then(|t| t.token_id),
+			token_value: transfer.as_ref().map(|t| t.token_value).unwrap_or_default(),
+			depth: n + 1,
+			reason: ExitReason::Succeed(ExitSucceed::Stopped),
+			rejected: false,
+		});
+		let index = self.internal_transactions.len();
+		let depth = self.depth.map_or(0, |n| n + 1);
+		trace!("call depth={} address={:?} caller={:?} scheme={:?} value={} input_len={} gas={:?}",
+			depth, context.address, context.caller, scheme,
+			transfer.as_ref().map(|t| t.value).unwrap_or_default(), input.len(), target_gas);
+
+		match self.call_frame(
+			code_address, transfer, input, target_gas, is_static, take_l64, take_stipend, context,
+		) {
+			Capture::Exit((reason, return_value)) => {
+				event!(Exit { reason: &reason, return_value: &return_value });
+				debug!("call exit depth={} address={:?} reason={:?} output_len={}",
+					depth, code_address, reason, return_value.len());
+				if let Some(internal_transaction) = internal_transaction {
+					self.record_internal_transaction(index, InternalTransaction { reason, ..internal_transaction });
+				}
+				Capture::Exit((reason, return_value))
+			},
+			Capture::Trap(t) => match t {},
+		}
+	}
+
+	#[allow(clippy::too_many_arguments)]
+	fn call_frame(
+		&mut self,
+		code_address: H160,
+		transfer: Option<Transfer>,
+		input: Bytes,
 		target_gas: Option<usize>,
 		is_static: bool,
 		take_l64: bool,
@@ -576,22 +1209,12 @@ impl<'backend, 'config, B: Backend> StackExecutor<'backend, 'config, B> {
 			}
 		}
 
-		fn l64(gas: usize) -> usize {
-			gas - gas / 64
-		}
-
-		let mut after_gas = self.gasometer.gas();
-		if take_l64 && self.config.call_l64_after_gas {
-			after_gas = l64(after_gas);
-		}
-
-		let target_gas = target_gas.unwrap_or(after_gas);
-		let mut gas_limit = min(target_gas, after_gas);
-
+		let mut gas_limit = self.forwarded_gas(target_gas, take_l64);
 		try_or_fail!(self.gasometer.record_cost(gas_limit));
 
 		if let Some(transfer) = transfer.as_ref() {
-			if take_stipend && (transfer.value != U256::zero() || transfer.token_value != U256::zero()) {
+			if take_stipend && (transfer.value != U256::zero() ||
+				(transfer.token_id.is_some() && transfer.token_value != U256::zero())) {
 				gas_limit = gas_limit.saturating_add(self.config.call_stipend);
 			}
 		}
@@ -599,6 +1222,9 @@ impl<'backend, 'config, B: Backend> StackExecutor<'backend, 'config, B> {
 		let code = self.code(code_address);
 
 		let mut substate = self.substate(gas_limit, is_static);
+		if self.config.has_dynamic_energy {
+			substate.gasometer.set_energy_factor(self.backend.energy_factor(code_address));
+		}
 		// TRON: Before Solidity059 update, call with no value to non-existed account
 		// will succeed without creating account.
 		//
@@ -637,8 +1263,22 @@ impl<'backend, 'config, B: Backend> StackExecutor<'backend, 'config, B> {
 			}
 		}
 
-		// TRON: Introduce a very bad precompile `validatemultisign`, which requires env.
-		if let Some(ret) = (substate.precompile)(code_address, &input, Some(gas_limit), self.backend) {
+		// TRON: `validatemultisign` reads account permissions, so precompiles see the
+		// state of the call.
+		let precompile_context = PrecompileContext {
+			context: &context,
+			is_static,
+			depth: self.depth.map_or(0, |n| n + 1),
+			state: &substate,
+		};
+		if let Some(ret) = (substate.precompile)(code_address, &input, Some(gas_limit), self.config, &precompile_context) {
+			debug!("precompile depth={} address={:?} input_len={} gas={} result={:?}",
+				self.depth.map_or(0, |n| n + 1), code_address, input.len(), gas_limit,
+				ret.as_ref().map(|(s, out, cost)| (s, out.len(), cost)));
+			if let Some(metrics) = &self.metrics {
+				let gas = ret.as_ref().map(|(_, _, cost)| *cost).unwrap_or(gas_limit);
+				metrics.borrow_mut().precompile(code_address, gas, ret.is_ok());
+			}
 			return match ret {
 				Ok((s, out, cost)) => {
 					let _ = substate.gasometer.record_cost(cost);
@@ -652,19 +1292,32 @@ impl<'backend, 'config, B: Backend> StackExecutor<'backend, 'config, B> {
 			}
 		}
 
-		let mut runtime = Runtime::new(
-			Rc::new(code),
-			Rc::new(input),
-			context,
-			self.config,
-		);
+		// Maps of an EOF code section are cached by the hash of its container.
+		let section = self.code_section(&code);
+		let code_hash = if self.valids_cache.is_some() || self.coverage.is_some() {
+			Some(self.account_code_hash(code_address))
+		} else {
+			None
+		};
+		let (valids, superinstructions) = match (&self.valids_cache, code_hash) {
+			(Some(cache), Some(code_hash)) => {
+				let mut cache = cache.borrow_mut();
+				let valids = cache.get_or_analyse(code_hash, &code[section.clone()]);
+				let superinstructions = cache.get_or_fuse(code_hash, &code[section.clone()], &valids);
+				(valids, superinstructions)
+			},
+			_ => (Shared::new(Valids::new(&code[section.clone()])), None),
+		};
+		let mut runtime = self.frame_runtime(code, section, input, valids, superinstructions, context);
 
-		let reason = substate.execute(&mut runtime);
+		let reason = substate.execute_with_code_hash(&mut runtime, code_hash);
+		let out = runtime.machine().return_value();
+		self.release_frame(runtime);
 
 		match reason {
 			ExitReason::Succeed(s) => {
 				let _ = self.merge_succeed(substate);
-				Capture::Exit((ExitReason::Succeed(s), runtime.machine().return_value()))
+				Capture::Exit((ExitReason::Succeed(s), out))
 			},
 			ExitReason::Error(e) => {
 				let _ = self.merge_fail(substate);
@@ -672,7 +1325,7 @@ impl<'backend, 'config, B: Backend> StackExecutor<'backend, 'config, B> {
 			},
 			ExitReason::Revert(e) => {
 				let _ = self.merge_revert(substate);
-				Capture::Exit((ExitReason::Revert(e), runtime.machine().return_value()))
+				Capture::Exit((ExitReason::Revert(e), out))
 			},
 			ExitReason::Fatal(e) => {
 				self.gasometer.fail();
@@ -682,6 +1335,15 @@ impl<'backend, 'config, B: Backend> StackExecutor<'backend, 'config, B> {
 	}
 }
 
+impl<'backend, 'config, B: Backend> super::PrecompileState for StackExecutor<'backend, 'config, B> {
+	fn backend(&self) -> &dyn Backend { self.backend }
+	fn balance(&self, address: H160) -> U256 { Handler::balance(self, address) }
+	fn token_balance(&self, address: H160, token_id: U256) -> U256 { Handler::token_balance(self, address, token_id) }
+	fn code(&self, address: H160) -> Bytes { Handler::code(self, address) }
+	fn storage(&self, address: H160, index: H256) -> Option<H256> { Handler::storage(self, address, index) }
+	fn exists(&self, address: H160) -> bool { Handler::exists(self, address) }
+}
+
 impl<'backend, 'config, B: Backend> Handler for StackExecutor<'backend, 'config, B> {
 	type CreateInterrupt = Infallible;
 	type CreateFeedback = Infallible;
@@ -690,7 +1352,7 @@ impl<'backend, 'config, B: Backend> Handler for StackExecutor<'backend, 'config,
 
 	fn balance(&self, address: H160) -> U256 {
 		self.state.get(&address).map(|v| v.basic.balance)
-			.unwrap_or(self.backend.basic(address).balance)
+			.unwrap_or_else(|| self.backend.basic(address).balance)
 	}
 
 	fn code_size(&self, address: H160) -> U256 {
@@ -700,32 +1362,17 @@ impl<'backend, 'config, B: Backend> Handler for StackExecutor<'backend, 'config,
 		)
 	}
 
+	/// EIP-1052: zero for accounts that do not exist, which includes empty accounts
+	/// unless `empty_considered_exists`, and the hash of empty code for existing
+	/// accounts without code.
 	fn code_hash(&self, address: H160) -> H256 {
 		if !self.exists(address) {
 			return H256::default()
 		}
-
-		let (balance, nonce, code_size) = if let Some(account) = self.state.get(&address) {
-			(account.basic.balance, account.basic.nonce,
-			 account.code.as_ref().map(|c| U256::from(c.len())).unwrap_or(self.code_size(address)))
-		} else {
-			let basic = self.backend.basic(address);
-			(basic.balance, basic.nonce, U256::from(self.backend.code_size(address)))
-		};
-
-		if balance == U256::zero() && nonce == U256::zero() && code_size == U256::zero() {
-			return H256::default()
-		}
-
-		let value = self.state.get(&address).and_then(|v| {
-			v.code.as_ref().map(|c| {
-				H256::from_slice(Keccak256::digest(&c).as_slice())
-			})
-		}).unwrap_or(self.backend.code_hash(address));
-		value
+		self.account_code_hash(address)
 	}
 
-	fn code(&self, address: H160) -> Vec<u8> {
+	fn code(&self, address: H160) -> Bytes {
 		self.state.get(&address).and_then(|v| {
 			v.code.clone()
 		}).unwrap_or(self.backend.code(address))
@@ -773,17 +1420,28 @@ impl<'backend, 'config, B: Backend> Handler for StackExecutor<'backend, 'config,
 	fn block_coinbase(&self) -> H160 { self.backend.block_coinbase() }
 	fn block_timestamp(&self) -> U256 { self.backend.block_timestamp() }
 	fn block_difficulty(&self) -> U256 { self.backend.block_difficulty() }
+	fn block_randomness(&self) -> Option<H256> { self.backend.block_randomness() }
 	fn block_gas_limit(&self) -> U256 { self.backend.block_gas_limit() }
+	fn block_base_fee_per_gas(&self) -> U256 { self.backend.block_base_fee_per_gas() }
 	fn chain_id(&self) -> U256 { self.backend.chain_id() }
 
 	fn deleted(&self, address: H160) -> bool { self.deleted.contains(&address) }
 
+	fn is_cold(&self, address: H160, index: Option<H256>) -> bool {
+		match index {
+			Some(index) => !self.accessed_storages.contains(&(address, index)),
+			None => !self.accessed_addresses.contains(&address),
+		}
+	}
+
 	fn set_storage(&mut self, address: H160, index: H256, value: H256) -> Result<(), ExitError> {
+		self.check_not_static()?;
 		self.account_mut(address).storage.insert(index, value);
 		Ok(())
 	}
 
 	fn log(&mut self, address: H160, topics: Vec<H256>, data: Vec<u8>) -> Result<(), ExitError> {
+		self.check_not_static()?;
 		self.logs.push(Log {
 			address, topics, data
 		});
@@ -791,31 +1449,62 @@ impl<'backend, 'config, B: Backend> Handler for StackExecutor<'backend, 'config,
 		Ok(())
 	}
 
-	fn mark_delete(&mut self, address: H160, target: H160) -> Result<(), ExitError> {
+	fn mark_delete(&mut self, suicide: crate::Suicide) -> Result<(), ExitError> {
+		self.check_not_static()?;
+		let crate::Suicide { address, target, beneficiary, sweep_tokens } = suicide;
 		let balance = self.balance(address);
 
-		self.transfer(Transfer {
-			source: address,
-			target: target,
-			value: balance,
-			..Default::default()
-		})?;
+		event!(Suicide {
+			address,
+			target,
+			balance,
+		});
+
+		if let Some(n) = self.depth {
+			self.internal_transactions.push(InternalTransaction {
+				caller: address,
+				to: target,
+				kind: InternalTransactionKind::Suicide,
+				value: balance,
+				token_id: None,
+				token_value: U256::zero(),
+				depth: n + 1,
+				reason: ExitReason::Succeed(ExitSucceed::Suicided),
+				rejected: false,
+			});
+		}
+
+		// Burnt funds go to the black hole account, if the chain has one, since
+		// AllowTvmTransferTrc10. Before that they are destroyed.
+		let recipient = beneficiary.or_else(|| {
+			self.backend.black_hole().filter(|_| self.config.has_token_transfer)
+		});
+		if let Some(recipient) = recipient {
+			self.transfer(Transfer {
+				source: address,
+				target: recipient,
+				value: balance,
+				..Default::default()
+			})?;
+		}
 		self.account_mut(address).basic.balance = U256::zero();
 
-		let token_transfers = self.account_mut(address).basic.token_balance.iter()
-			.filter(|(_, &val)| val > U256::zero())
-			.map(|(&token_id, &token_value)| {
-				Transfer {
-					source: address,
-					target: target,
-					token_id: token_id,
-					token_value: token_value,
-					..Default::default()
-				}
-			})
-			.collect::<Vec<_>>();
-		for token_transfer in token_transfers {
-			self.transfer(token_transfer)?;
+		if let Some(recipient) = recipient.filter(|_| sweep_tokens) {
+			let token_transfers = self.account_mut(address).basic.token_balance.iter()
+				.filter(|(_, &val)| val > U256::zero())
+				.map(|(&token_id, &token_value)| {
+					Transfer {
+						source: address,
+						target: recipient,
+						token_id: Some(token_id),
+						token_value,
+						..Default::default()
+					}
+				})
+				.collect::<Vec<_>>();
+			for token_transfer in token_transfers {
+				self.transfer(token_transfer)?;
+			}
 		}
 		self.account_mut(address).basic.token_balance.clear();
 
@@ -832,6 +1521,9 @@ impl<'backend, 'config, B: Backend> Handler for StackExecutor<'backend, 'config,
 		init_code: Vec<u8>,
 		target_gas: Option<usize>,
 	) -> Capture<(ExitReason, Option<H160>, Vec<u8>), Self::CreateInterrupt> {
+		if let Err(e) = self.check_not_static() {
+			return Capture::Exit((e.into(), None, Vec::new()))
+		}
 		self.create_inner(caller, scheme, value, init_code, target_gas, true)
 	}
 
@@ -839,15 +1531,11 @@ impl<'backend, 'config, B: Backend> Handler for StackExecutor<'backend, 'config,
 		&mut self,
 		code_address: H160,
 		transfer: Option<Transfer>,
-		input: Vec<u8>,
+		input: Bytes,
 		target_gas: Option<usize>,
 		is_static: bool,
 		context: Context,
 	) -> Capture<(ExitReason, Vec<u8>), Self::CallInterrupt> {
-		// println!("! CALLing {:?} input={} depth={:?} static={} transfer={}",
-		//	code_address, input.len(), self.depth, is_static,
-		//	transfer.as_ref().map(|xfer| xfer.value).unwrap_or_default());
-		// println!("! transfer {:?}", transfer);
 		self.call_inner(code_address, transfer, input, target_gas, is_static, true, true, context)
 	}
 
@@ -857,11 +1545,30 @@ impl<'backend, 'config, B: Backend> Handler for StackExecutor<'backend, 'config,
 		opcode: Result<Opcode, ExternalOpcode>,
 		stack: &Stack
 	) -> Result<(), ExitError> {
-		let (gas_cost, memory_cost) = gasometer::opcode_cost(
+		self.record_step()?;
+
+		let (gas_cost, target, memory_cost) = gasometer::opcode_cost(
 			context.address, opcode, stack, self.is_static, &self.config, self
 		)?;
 
+		let used_gas = self.gasometer.total_used_gas();
 		self.gasometer.record_opcode(gas_cost, memory_cost)?;
+		if let Some(metrics) = &self.metrics {
+			let gas = self.gasometer.total_used_gas() - used_gas;
+			metrics.borrow_mut().opcode(self.config.opcode_byte(opcode), GasCategory::of(opcode), gas);
+		}
+
+		if self.config.increase_state_access_gas {
+			match target {
+				StorageTarget::Address(address) => {
+					self.accessed_addresses.insert(address);
+				},
+				StorageTarget::Slot(address, index) => {
+					self.accessed_storages.insert((address, index));
+				},
+				StorageTarget::None => (),
+			}
+		}
 
 		Ok(())
 	}
@@ -876,6 +1583,83 @@ impl<'backend, 'config, B: Backend> Handler for StackExecutor<'backend, 'config,
 			.unwrap_or(self.backend.basic(address).token_balance.get(&token_id).cloned().unwrap_or_default())
 	}
 
+	fn validate_token(&self, token_id: U256) -> Result<(), ExitError> {
+		if self.backend.token_exists(token_id) {
+			Ok(())
+		} else {
+			Err(ExitError::TransferException)
+		}
+	}
+
+	fn validate_token_transfer(&self, transfer: &Transfer) -> Result<(), ExitError> {
+		match transfer.token_id {
+			Some(token_id) if !self.backend.validate_token_transfer(
+				transfer.source, transfer.target, token_id, transfer.token_value,
+			) => Err(ExitError::TransferException),
+			_ => Ok(()),
+		}
+	}
+
+	fn is_contract(&self, address: H160) -> bool {
+		!self.deleted.contains(&address) && self.code_size(address) != U256::zero()
+	}
+
+	fn freeze(&mut self, address: H160, receiver: H160, amount: U256, resource_type: U256) -> bool {
+		self.stake(Stake::Freeze { owner: address, receiver, amount, resource_type }).is_some()
+	}
+
+	fn unfreeze(&mut self, address: H160, receiver: H160, resource_type: U256) -> bool {
+		self.stake(Stake::Unfreeze { owner: address, receiver, resource_type }).is_some()
+	}
+
+	fn freeze_expire_time(&self, address: H160, target: H160, resource_type: U256) -> U256 {
+		self.backend.freeze_expire_time(address, target, resource_type)
+	}
+
+	fn is_witness(&self, address: H160) -> bool {
+		self.backend.is_witness(address)
+	}
+
+	fn reward_balance(&self, address: H160) -> U256 {
+		if self.reward_withdrawn(address) {
+			U256::zero()
+		} else {
+			self.backend.reward_balance(address)
+		}
+	}
+
+	fn withdraw_reward(&mut self, address: H160) -> U256 {
+		self.stake(Stake::WithdrawReward { owner: address }).unwrap_or_default()
+	}
+
+	fn vote_witness(&mut self, address: H160, votes: Vec<(H160, U256)>) -> bool {
+		self.stake(Stake::VoteWitness { owner: address, votes }).is_some()
+	}
+
+	fn freeze_balance_v2(&mut self, address: H160, amount: U256, resource_type: U256) -> bool {
+		self.stake(Stake::FreezeBalanceV2 { owner: address, amount, resource_type }).is_some()
+	}
+
+	fn unfreeze_balance_v2(&mut self, address: H160, amount: U256, resource_type: U256) -> bool {
+		self.stake(Stake::UnfreezeBalanceV2 { owner: address, amount, resource_type }).is_some()
+	}
+
+	fn cancel_all_unfreeze_v2(&mut self, address: H160) -> bool {
+		self.stake(Stake::CancelAllUnfreezeV2 { owner: address }).is_some()
+	}
+
+	fn withdraw_expire_unfreeze(&mut self, address: H160) -> U256 {
+		self.stake(Stake::WithdrawExpireUnfreeze { owner: address }).unwrap_or_default()
+	}
+
+	fn delegate_resource(&mut self, address: H160, receiver: H160, amount: U256, resource_type: U256) -> bool {
+		self.stake(Stake::DelegateResource { owner: address, receiver, amount, resource_type }).is_some()
+	}
+
+	fn undelegate_resource(&mut self, address: H160, receiver: H160, amount: U256, resource_type: U256) -> bool {
+		self.stake(Stake::UnDelegateResource { owner: address, receiver, amount, resource_type }).is_some()
+	}
+
 	fn nonce(&self) -> u64 { self.nonce }
 
 	fn incr_nonce(&mut self) {
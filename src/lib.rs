@@ -1,7 +1,9 @@
 //! Ethereum Virtual Machine implementation in Rust
 
 #![deny(warnings)]
-#![forbid(unsafe_code, missing_docs, unused_variables, unused_imports)]
+#![forbid(unsafe_code, missing_docs, unused_variables)]
+// `environmental!` allows unused imports in its expansion.
+#![cfg_attr(not(feature = "tracing"), forbid(unused_imports))]
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
@@ -11,5 +13,43 @@ pub use evm_core::*;
 pub use evm_runtime::*;
 pub use evm_gasometer as gasometer;
 
+#[cfg(feature = "tracing")]
+pub mod tracing;
+#[cfg(feature = "tracing")]
+pub mod tracer;
+
+#[cfg(feature = "tracing")]
+macro_rules! event {
+	($x:expr) => {
+		use crate::tracing::Event::*;
+		$x.emit();
+	}
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! event {
+	($x:expr) => { }
+}
+
+#[cfg(feature = "log")]
+macro_rules! debug {
+	($($arg:tt)*) => { log::debug!(target: "evm::executor", $($arg)*) }
+}
+
+#[cfg(feature = "log")]
+macro_rules! trace {
+	($($arg:tt)*) => { log::trace!(target: "evm::executor", $($arg)*) }
+}
+
+#[cfg(not(feature = "log"))]
+macro_rules! debug {
+	($($arg:tt)*) => { { let _ = format_args!($($arg)*); } }
+}
+
+#[cfg(not(feature = "log"))]
+macro_rules! trace {
+	($($arg:tt)*) => { { let _ = format_args!($($arg)*); } }
+}
+
 pub mod executor;
 pub mod backend;
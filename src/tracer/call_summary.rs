@@ -0,0 +1,159 @@
+use core::cell::RefCell;
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use alloc::collections::BTreeMap;
+use primitive_types::{H160, U256};
+use crate::{CallScheme, ExitReason};
+use crate::executor::InternalTransactionKind;
+
+/// Call or create in the tree recorded by `CallSummaryTracer`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CallFrame {
+	/// Call or create, with its scheme.
+	pub kind: InternalTransactionKind,
+	/// Caller, the calling contract for `DELEGATECALL` and `CALLCODE`.
+	pub from: H160,
+	/// Address of the executed code, or of the created contract.
+	pub to: H160,
+	/// First 4 bytes of the call input, `None` for creates and shorter inputs.
+	pub selector: Option<[u8; 4]>,
+	/// Size of the input.
+	pub input_len: usize,
+	/// Value transferred.
+	pub value: U256,
+	/// Gas given to the frame, `None` if unlimited.
+	pub gas: Option<usize>,
+	/// Exit reason, `None` while the frame runs.
+	pub reason: Option<ExitReason>,
+	/// Inner calls and creates, in order.
+	pub calls: Vec<CallFrame>,
+}
+
+impl CallFrame {
+	/// Iterate over the frame and its inner frames, depth first.
+	pub fn iter(&self) -> impl Iterator<Item = &CallFrame> {
+		let mut stack = alloc::vec![self];
+		core::iter::from_fn(move || {
+			let frame = stack.pop()?;
+			stack.extend(frame.calls.iter().rev());
+			Some(frame)
+		})
+	}
+}
+
+#[derive(Default)]
+struct State {
+	/// Frames entered and not yet exited, outermost first.
+	frames: Vec<CallFrame>,
+	/// Outermost frames of the traced transactions.
+	roots: Vec<CallFrame>,
+}
+
+impl State {
+	fn enter(&mut self, frame: CallFrame) {
+		self.frames.push(frame);
+	}
+
+	fn exit(&mut self, reason: ExitReason) {
+		if let Some(mut frame) = self.frames.pop() {
+			frame.reason = Some(reason);
+			match self.frames.last_mut() {
+				Some(parent) => parent.calls.push(frame),
+				None => self.roots.push(frame),
+			}
+		}
+	}
+}
+
+/// Tracer recording only the call tree, without per-opcode events.
+#[derive(Clone, Default)]
+pub struct CallSummaryTracer {
+	state: Rc<RefCell<State>>,
+}
+
+impl CallSummaryTracer {
+	/// Create a new call summary tracer.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Run the closure, recording calls and creates made within it.
+	pub fn trace<R, F: FnOnce() -> R>(&self, f: F) -> R {
+		let mut listener = Listener(self.state.clone());
+		crate::tracing::using(&mut listener, f)
+	}
+
+	/// Call trees of the traced transactions, in order.
+	pub fn calls(&self) -> Vec<CallFrame> {
+		self.state.borrow().roots.clone()
+	}
+
+	/// Number of calls by selector and input size, as geth's `4byteTracer`.
+	pub fn selectors(&self) -> BTreeMap<([u8; 4], usize), u64> {
+		let mut selectors = BTreeMap::new();
+		for frame in self.state.borrow().roots.iter().flat_map(CallFrame::iter) {
+			if let Some(selector) = frame.selector {
+				*selectors.entry((selector, frame.input_len - 4)).or_default() += 1;
+			}
+		}
+		selectors
+	}
+
+	/// Remove the recorded calls.
+	pub fn clear(&self) {
+		let mut state = self.state.borrow_mut();
+		state.frames.clear();
+		state.roots.clear();
+	}
+}
+
+struct Listener(Rc<RefCell<State>>);
+
+impl crate::tracing::EventListener for Listener {
+	fn event(&mut self, event: crate::tracing::Event) {
+		use crate::tracing::Event;
+
+		match event {
+			Event::Call { code_address, transfer, input, target_gas, is_static, context } => {
+				let mut selector = [0u8; 4];
+				let selector = input.get(..4).map(|input| {
+					selector.copy_from_slice(input);
+					selector
+				});
+				let scheme = crate::executor::call_scheme(code_address, transfer, is_static, context);
+				self.0.borrow_mut().enter(CallFrame {
+					kind: InternalTransactionKind::Call(scheme),
+					from: match scheme {
+						CallScheme::CallCode | CallScheme::DelegateCall => context.address,
+						_ => context.caller,
+					},
+					to: code_address,
+					selector,
+					input_len: input.len(),
+					value: transfer.as_ref().map(|t| t.value).unwrap_or_default(),
+					gas: target_gas,
+					reason: None,
+					calls: Vec::new(),
+				});
+			},
+			Event::Create { caller, address, scheme, value, init_code, target_gas } => {
+				self.0.borrow_mut().enter(CallFrame {
+					kind: InternalTransactionKind::Create(scheme),
+					from: caller,
+					to: address,
+					selector: None,
+					input_len: init_code.len(),
+					value,
+					gas: target_gas,
+					reason: None,
+					calls: Vec::new(),
+				});
+			},
+			Event::Exit { reason, .. } => {
+				self.0.borrow_mut().exit(*reason);
+			},
+			_ => (),
+		}
+	}
+}
@@ -0,0 +1,446 @@
+//! Tracers built on top of the tracing events.
+
+use core::cell::RefCell;
+use core::fmt::Write;
+use alloc::rc::Rc;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::collections::BTreeMap;
+use primitive_types::{H160, H256, U256};
+use evm_gasometer::Snapshot;
+use crate::ExitReason;
+
+mod state_diff;
+mod call_summary;
+
+pub use self::state_diff::{AccountDiff, Delta, StateDiff, StateDiffTracer};
+pub use self::call_summary::{CallFrame, CallSummaryTracer};
+
+/// Write `value` as a JSON string, escaping quotes, backslashes and control
+/// characters.
+pub fn write_json_string<W: Write>(out: &mut W, value: &str) -> core::fmt::Result {
+	out.write_char('"')?;
+	for c in value.chars() {
+		match c {
+			'"' => out.write_str("\\\"")?,
+			'\\' => out.write_str("\\\\")?,
+			'\n' => out.write_str("\\n")?,
+			'\r' => out.write_str("\\r")?,
+			'\t' => out.write_str("\\t")?,
+			c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32)?,
+			c => out.write_char(c)?,
+		}
+	}
+	out.write_char('"')
+}
+
+/// Config of `StructLogger`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StructLoggerConfig {
+	/// Record memory of each step.
+	pub enable_memory: bool,
+	/// Do not record stack of each step.
+	pub disable_stack: bool,
+	/// Do not record storage of `SLOAD` and `SSTORE` steps.
+	pub disable_storage: bool,
+	/// Maximum number of steps recorded, unlimited if zero.
+	pub limit: usize,
+	/// Maximum call depth recorded, unlimited if `None`.
+	pub max_depth: Option<usize>,
+}
+
+/// A single step of EIP-3155 struct log.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StructLog {
+	/// Program counter.
+	pub pc: usize,
+	/// Opcode byte.
+	pub op: u8,
+	/// Remaining gas before the step.
+	pub gas: usize,
+	/// Gas cost of the step, including memory expansion.
+	pub gas_cost: usize,
+	/// Memory size in bytes.
+	pub mem_size: usize,
+	/// Stack, bottom first. Empty if disabled.
+	pub stack: Vec<U256>,
+	/// Memory, if enabled.
+	pub memory: Option<Vec<u8>>,
+	/// Storage slots of the contract accessed so far, for `SLOAD` and `SSTORE`.
+	pub storage: Option<BTreeMap<H256, H256>>,
+	/// Call depth, starting from 1.
+	pub depth: usize,
+	/// Refund counter.
+	pub refund: isize,
+	/// Opcode mnemonic.
+	#[cfg_attr(feature = "with-serde", serde(deserialize_with = "deserialize_op_name"))]
+	pub op_name: &'static core::primitive::str,
+	/// Error of the step, if any.
+	pub error: Option<String>,
+}
+
+/// Mnemonics are restored to the static names of the opcode table. The field is
+/// spelled `&'static core::primitive::str`, as for `ExitError::Other`, so that
+/// serde does not treat it as borrowed.
+#[cfg(feature = "with-serde")]
+fn deserialize_op_name<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<&'static str, D::Error> {
+	use serde::Deserialize;
+	use serde::de::Error;
+
+	let name = String::deserialize(deserializer)?;
+	(0..=255u8)
+		.map(|opcode| match crate::Opcode::parse(opcode) {
+			Ok(opcode) => opcode.name(),
+			Err(opcode) => opcode.name(),
+		})
+		.find(|n| *n == name)
+		.ok_or_else(|| D::Error::custom(format!("unknown opcode {:?}", name)))
+}
+
+impl StructLog {
+	/// Encode the step as a single line of EIP-3155 JSON.
+	pub fn to_json(&self) -> String {
+		let mut out = String::new();
+		write!(
+			out,
+			"{{\"pc\":{},\"op\":{},\"gas\":\"0x{:x}\",\"gasCost\":\"0x{:x}\",\"memSize\":{},\"stack\":[",
+			self.pc, self.op, self.gas, self.gas_cost, self.mem_size,
+		).expect("Write to string cannot fail");
+		for (i, value) in self.stack.iter().enumerate() {
+			if i > 0 {
+				out.push(',');
+			}
+			write!(out, "\"0x{:x}\"", value).expect("Write to string cannot fail");
+		}
+		out.push(']');
+		if let Some(memory) = &self.memory {
+			out.push_str(",\"memory\":\"0x");
+			for byte in memory {
+				write!(out, "{:02x}", byte).expect("Write to string cannot fail");
+			}
+			out.push('"');
+		}
+		write!(
+			out,
+			",\"depth\":{},\"refund\":{},\"opName\":\"{}\"",
+			self.depth, self.refund, self.op_name,
+		).expect("Write to string cannot fail");
+		if let Some(error) = &self.error {
+			out.push_str(",\"error\":");
+			write_json_string(&mut out, error).expect("Write to string cannot fail");
+		}
+		out.push('}');
+		out
+	}
+
+	/// Encode the step in the shape of geth's `debug_traceTransaction`.
+	pub fn to_geth_json(&self) -> String {
+		let mut out = String::new();
+		write!(
+			out,
+			"{{\"pc\":{},\"op\":\"{}\",\"gas\":{},\"gasCost\":{},\"depth\":{}",
+			self.pc, self.op_name, self.gas, self.gas_cost, self.depth,
+		).expect("Write to string cannot fail");
+		if let Some(error) = &self.error {
+			out.push_str(",\"error\":");
+			write_json_string(&mut out, error).expect("Write to string cannot fail");
+		}
+		if self.refund != 0 {
+			write!(out, ",\"refund\":{}", self.refund).expect("Write to string cannot fail");
+		}
+		out.push_str(",\"stack\":[");
+		for (i, value) in self.stack.iter().enumerate() {
+			if i > 0 {
+				out.push(',');
+			}
+			write!(out, "\"0x{:x}\"", value).expect("Write to string cannot fail");
+		}
+		out.push(']');
+		if let Some(memory) = &self.memory {
+			out.push_str(",\"memory\":[");
+			for (i, word) in memory.chunks(32).enumerate() {
+				if i > 0 {
+					out.push(',');
+				}
+				out.push('"');
+				for byte in word {
+					write!(out, "{:02x}", byte).expect("Write to string cannot fail");
+				}
+				out.push('"');
+			}
+			out.push(']');
+		}
+		if let Some(storage) = &self.storage {
+			out.push_str(",\"storage\":{");
+			for (i, (index, value)) in storage.iter().enumerate() {
+				if i > 0 {
+					out.push(',');
+				}
+				write!(out, "\"{:x}\":\"{:x}\"", index, value).expect("Write to string cannot fail");
+			}
+			out.push('}');
+		}
+		out.push('}');
+		out
+	}
+}
+
+/// Trace in the shape of geth's `debug_traceTransaction` with the default struct logger.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GethTrace {
+	/// Gas used by the transaction.
+	pub gas: usize,
+	/// Whether the transaction failed or reverted.
+	pub failed: bool,
+	/// Return value of the transaction.
+	pub return_value: Vec<u8>,
+	/// Recorded steps.
+	pub struct_logs: Vec<StructLog>,
+}
+
+impl GethTrace {
+	/// Encode the trace as JSON.
+	pub fn to_json(&self) -> String {
+		let mut out = String::new();
+		write!(out, "{{\"gas\":{},\"failed\":{},\"returnValue\":\"", self.gas, self.failed)
+			.expect("Write to string cannot fail");
+		for byte in &self.return_value {
+			write!(out, "{:02x}", byte).expect("Write to string cannot fail");
+		}
+		out.push_str("\",\"structLogs\":[");
+		for (i, log) in self.struct_logs.iter().enumerate() {
+			if i > 0 {
+				out.push(',');
+			}
+			out.push_str(&log.to_geth_json());
+		}
+		out.push_str("]}");
+		out
+	}
+}
+
+#[derive(Default)]
+struct State {
+	config: StructLoggerConfig,
+	logs: Vec<StructLog>,
+	/// Index of the last log of each active frame.
+	frames: Vec<Option<usize>>,
+	/// Whether the last step has its cost recorded.
+	priced: bool,
+	/// Storage slots accessed so far, by contract.
+	storage: BTreeMap<H160, BTreeMap<H256, H256>>,
+	/// Exit reason and return value of the outermost frame.
+	result: Option<(ExitReason, Vec<u8>)>,
+}
+
+impl State {
+	fn current(&mut self) -> Option<&mut StructLog> {
+		let index = (*self.frames.last()?)?;
+		self.logs.get_mut(index)
+	}
+
+	fn step(&mut self, opcode: u8, op_name: &'static str, position: usize, stack: &crate::Stack, memory: &crate::Memory) {
+		if self.frames.is_empty() {
+			self.frames.push(None);
+		}
+		let depth = self.frames.len();
+		let over_limit = self.config.limit != 0 && self.logs.len() >= self.config.limit;
+		let over_depth = self.config.max_depth.map(|max| depth > max).unwrap_or(false);
+		if over_limit || over_depth {
+			if let Some(frame) = self.frames.last_mut() {
+				*frame = None;
+			}
+			self.priced = true;
+			return
+		}
+
+		let (gas, refund) = self.current().map(|log| (log.gas.saturating_sub(log.gas_cost), log.refund)).unwrap_or_default();
+
+		self.logs.push(StructLog {
+			pc: position,
+			op: opcode,
+			gas,
+			gas_cost: 0,
+			mem_size: memory.len(),
+			stack: if self.config.disable_stack {
+				Vec::new()
+			} else {
+				stack.data().iter().map(|v| U256::from_big_endian(&v[..])).collect()
+			},
+			memory: if self.config.enable_memory { Some(memory.get(0, memory.len())) } else { None },
+			storage: None,
+			depth,
+			refund,
+			op_name,
+			error: None,
+		});
+		let index = self.logs.len() - 1;
+		if let Some(frame) = self.frames.last_mut() {
+			*frame = Some(index);
+		}
+		self.priced = false;
+	}
+
+	fn cost(&mut self, snapshot: Option<Snapshot>, gas_cost: usize, memory_gas: usize) {
+		if self.priced {
+			return
+		}
+		self.priced = true;
+		if let (Some(log), Some(snapshot)) = (self.current(), snapshot) {
+			log.gas = snapshot.gas();
+			log.refund = snapshot.refunded_gas;
+			log.gas_cost = gas_cost + memory_gas.saturating_sub(snapshot.memory_gas);
+		}
+	}
+
+	fn storage(&mut self, address: H160, index: H256, value: H256) {
+		if self.config.disable_storage {
+			return
+		}
+		let storage = self.storage.entry(address).or_default();
+		storage.insert(index, value);
+		let storage = storage.clone();
+		if let Some(log) = self.current() {
+			log.storage = Some(storage);
+		}
+	}
+
+	fn fail(&mut self, reason: &ExitReason) {
+		let error = match reason {
+			ExitReason::Error(e) => format!("{:?}", e),
+			ExitReason::Fatal(e) => format!("{:?}", e),
+			_ => return,
+		};
+		if let Some(log) = self.current() {
+			if log.error.is_none() {
+				log.error = Some(error);
+			}
+		}
+	}
+}
+
+/// EIP-3155 struct logger, recording every executed step.
+#[derive(Clone, Default)]
+pub struct StructLogger {
+	state: Rc<RefCell<State>>,
+}
+
+impl StructLogger {
+	/// Create a new struct logger.
+	pub fn new(config: StructLoggerConfig) -> Self {
+		Self {
+			state: Rc::new(RefCell::new(State { config, ..Default::default() })),
+		}
+	}
+
+	/// Run the closure, recording steps executed within it.
+	pub fn trace<R, F: FnOnce() -> R>(&self, f: F) -> R {
+		let mut runtime_listener = RuntimeListener(self.state.clone());
+		let mut gasometer_listener = GasometerListener(self.state.clone());
+		let mut executor_listener = ExecutorListener(self.state.clone());
+
+		crate::tracing::using(&mut executor_listener, || {
+			evm_gasometer::tracing::using(&mut gasometer_listener, || {
+				evm_runtime::tracing::using(&mut runtime_listener, f)
+			})
+		})
+	}
+
+	/// Recorded steps.
+	pub fn logs(&self) -> Vec<StructLog> {
+		self.state.borrow().logs.clone()
+	}
+
+	/// Trace in the shape of geth's `debug_traceTransaction`, given the gas used by the
+	/// transaction.
+	pub fn geth_trace(&self, gas_used: usize) -> GethTrace {
+		let state = self.state.borrow();
+		let (failed, return_value) = match &state.result {
+			Some((reason, return_value)) => (!reason.is_succeed(), return_value.clone()),
+			None => (false, Vec::new()),
+		};
+
+		GethTrace {
+			gas: gas_used,
+			failed,
+			return_value,
+			struct_logs: state.logs.clone(),
+		}
+	}
+
+	/// Recorded steps as newline-delimited JSON.
+	pub fn to_json_lines(&self) -> String {
+		let mut out = String::new();
+		for log in &self.state.borrow().logs {
+			out.push_str(&log.to_json());
+			out.push('\n');
+		}
+		out
+	}
+}
+
+struct RuntimeListener(Rc<RefCell<State>>);
+
+impl evm_runtime::tracing::EventListener for RuntimeListener {
+	fn event(&mut self, event: evm_runtime::tracing::Event) {
+		use evm_runtime::tracing::Event;
+
+		match event {
+			Event::Step { config, opcode, position, stack, memory, .. } => {
+				let op = config.opcode_byte(opcode);
+				let op_name = match opcode {
+					Ok(opcode) => opcode.name(),
+					Err(opcode) => opcode.name(),
+				};
+				if let Ok(position) = position {
+					self.0.borrow_mut().step(op, op_name, *position, stack, memory);
+				}
+			},
+			Event::StepResult { result: Err(reason), .. } => {
+				self.0.borrow_mut().fail(reason);
+			},
+			Event::SLoad { address, index, value } | Event::SStore { address, index, value } => {
+				self.0.borrow_mut().storage(address, index, value);
+			},
+			_ => (),
+		}
+	}
+}
+
+struct GasometerListener(Rc<RefCell<State>>);
+
+impl evm_gasometer::tracing::EventListener for GasometerListener {
+	fn event(&mut self, event: evm_gasometer::tracing::Event) {
+		use evm_gasometer::tracing::Event;
+
+		if let Event::RecordDynamicCost { gas_cost, memory_gas, snapshot, .. } = event {
+			self.0.borrow_mut().cost(snapshot, gas_cost, memory_gas);
+		}
+	}
+}
+
+struct ExecutorListener(Rc<RefCell<State>>);
+
+impl crate::tracing::EventListener for ExecutorListener {
+	fn event(&mut self, event: crate::tracing::Event) {
+		use crate::tracing::Event;
+
+		match event {
+			Event::Call { .. } | Event::Create { .. } => {
+				self.0.borrow_mut().frames.push(None);
+			},
+			Event::Exit { reason, return_value } => {
+				let mut state = self.0.borrow_mut();
+				state.frames.pop();
+				if state.frames.is_empty() {
+					state.result = Some((*reason, return_value.to_vec()));
+				}
+			},
+			_ => (),
+		}
+	}
+}
@@ -0,0 +1,220 @@
+use core::cell::RefCell;
+use core::fmt::Write;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::collections::{BTreeMap, BTreeSet};
+use primitive_types::{H160, H256, U256};
+use crate::Handler;
+use crate::backend::Backend;
+use crate::executor::StackExecutor;
+
+/// Value of a state item before and after execution, `None` where the account
+/// does not exist.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Delta<T> {
+	/// Value before execution.
+	pub pre: Option<T>,
+	/// Value after execution.
+	pub post: Option<T>,
+}
+
+impl<T: PartialEq> Delta<T> {
+	/// Whether the item is unchanged.
+	pub fn is_same(&self) -> bool {
+		self.pre == self.post
+	}
+
+	/// Write the delta with the markers of `trace_replayTransaction`: `=` if
+	/// unchanged, `+` if born, `-` if died and `*` if changed.
+	fn write_json<F: Fn(&T) -> String>(&self, out: &mut String, f: F) {
+		match (&self.pre, &self.post) {
+			_ if self.is_same() => write!(out, "\"=\""),
+			(None, Some(post)) => write!(out, "{{\"+\":\"{}\"}}", f(post)),
+			(Some(pre), None) => write!(out, "{{\"-\":\"{}\"}}", f(pre)),
+			(Some(pre), Some(post)) => write!(out, "{{\"*\":{{\"from\":\"{}\",\"to\":\"{}\"}}}}", f(pre), f(post)),
+			(None, None) => Ok(()),
+		}.expect("Write to string cannot fail");
+	}
+}
+
+/// Pre-image and post-image of an account touched by execution.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AccountDiff {
+	/// Balance.
+	pub balance: Delta<U256>,
+	/// Nonce.
+	pub nonce: Delta<U256>,
+	/// Code.
+	pub code: Delta<Vec<u8>>,
+	/// Storage slots loaded or stored.
+	pub storage: BTreeMap<H256, Delta<H256>>,
+}
+
+impl AccountDiff {
+	/// Whether nothing in the account changed.
+	pub fn is_same(&self) -> bool {
+		self.balance.is_same() && self.nonce.is_same() && self.code.is_same() &&
+			self.storage.values().all(Delta::is_same)
+	}
+}
+
+/// State diff of the accounts touched by execution.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "with-serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StateDiff {
+	/// Touched accounts, including the unchanged ones.
+	pub accounts: BTreeMap<H160, AccountDiff>,
+}
+
+impl StateDiff {
+	/// Encode the changed accounts and storage slots in the shape of the `stateDiff`
+	/// of `trace_replayTransaction`.
+	pub fn to_json(&self) -> String {
+		let mut out = String::from("{");
+		let changed = self.accounts.iter().filter(|(_, account)| !account.is_same());
+		for (i, (address, account)) in changed.enumerate() {
+			if i > 0 {
+				out.push(',');
+			}
+			write!(out, "\"0x{:x}\":{{\"balance\":", address).expect("Write to string cannot fail");
+			account.balance.write_json(&mut out, |v| alloc::format!("0x{:x}", v));
+			out.push_str(",\"code\":");
+			account.code.write_json(&mut out, |v| {
+				let mut code = String::from("0x");
+				for byte in v {
+					write!(code, "{:02x}", byte).expect("Write to string cannot fail");
+				}
+				code
+			});
+			out.push_str(",\"nonce\":");
+			account.nonce.write_json(&mut out, |v| alloc::format!("0x{:x}", v));
+			out.push_str(",\"storage\":{");
+			let changed = account.storage.iter().filter(|(_, slot)| !slot.is_same());
+			for (j, (index, slot)) in changed.enumerate() {
+				if j > 0 {
+					out.push(',');
+				}
+				write!(out, "\"0x{:x}\":", index).expect("Write to string cannot fail");
+				slot.write_json(&mut out, |v| alloc::format!("0x{:x}", v));
+			}
+			out.push_str("}}");
+		}
+		out.push('}');
+		out
+	}
+}
+
+#[derive(Default)]
+struct Touched {
+	accounts: BTreeSet<H160>,
+	storage: BTreeSet<(H160, H256)>,
+}
+
+/// Tracer recording the accounts and storage slots touched by execution, to diff
+/// the state of the executor against its backend.
+#[derive(Clone, Default)]
+pub struct StateDiffTracer {
+	touched: Rc<RefCell<Touched>>,
+}
+
+impl StateDiffTracer {
+	/// Create a new state diff tracer.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Run the closure, recording accounts and storage slots touched within it.
+	pub fn trace<R, F: FnOnce() -> R>(&self, f: F) -> R {
+		let mut runtime_listener = RuntimeListener(self.touched.clone());
+		let mut executor_listener = ExecutorListener(self.touched.clone());
+
+		crate::tracing::using(&mut executor_listener, || {
+			evm_runtime::tracing::using(&mut runtime_listener, f)
+		})
+	}
+
+	/// Diff of the touched accounts, between the backend of the executor and the
+	/// executor's state. Call before the executor is deconstructed.
+	pub fn state_diff<B: Backend>(&self, executor: &StackExecutor<B>) -> StateDiff {
+		let touched = self.touched.borrow();
+		let backend = crate::executor::PrecompileState::backend(executor);
+
+		let mut accounts = touched.accounts.iter().map(|address| {
+			let address = *address;
+			let pre = backend.exists(address);
+			let post = !executor.deleted(address) && Handler::exists(executor, address);
+			let delta = |before, after| Delta {
+				pre: Some(before).filter(|_| pre),
+				post: Some(after).filter(|_| post),
+			};
+
+			let basic = backend.basic(address);
+			(address, AccountDiff {
+				balance: delta(basic.balance, Handler::balance(executor, address)),
+				nonce: delta(basic.nonce, executor.nonce(address)),
+				code: Delta {
+					pre: Some(backend.code(address).to_vec()).filter(|_| pre),
+					post: Some(Handler::code(executor, address).to_vec()).filter(|_| post),
+				},
+				storage: BTreeMap::new(),
+			})
+		}).collect::<BTreeMap<_, _>>();
+
+		for (address, index) in &touched.storage {
+			let (pre, post) = (backend.exists(*address), !executor.deleted(*address));
+			if let Some(account) = accounts.get_mut(address) {
+				account.storage.insert(*index, Delta {
+					pre: Some(backend.storage(*address, *index).unwrap_or_default()).filter(|_| pre),
+					post: Some(Handler::storage(executor, *address, *index).unwrap_or_default()).filter(|_| post),
+				});
+			}
+		}
+
+		StateDiff { accounts }
+	}
+}
+
+struct RuntimeListener(Rc<RefCell<Touched>>);
+
+impl evm_runtime::tracing::EventListener for RuntimeListener {
+	fn event(&mut self, event: evm_runtime::tracing::Event) {
+		use evm_runtime::tracing::Event;
+
+		if let Event::SLoad { address, index, .. } | Event::SStore { address, index, .. } = event {
+			let mut touched = self.0.borrow_mut();
+			touched.accounts.insert(address);
+			touched.storage.insert((address, index));
+		}
+	}
+}
+
+struct ExecutorListener(Rc<RefCell<Touched>>);
+
+impl crate::tracing::EventListener for ExecutorListener {
+	fn event(&mut self, event: crate::tracing::Event) {
+		use crate::tracing::Event;
+
+		let mut touched = self.0.borrow_mut();
+		match event {
+			Event::Call { code_address, transfer, context, .. } => {
+				touched.accounts.extend([code_address, context.address, context.caller]);
+				if let Some(transfer) = transfer {
+					touched.accounts.extend([transfer.source, transfer.target]);
+				}
+			},
+			Event::Create { caller, address, .. } |
+			Event::TransactCall { caller, address, .. } |
+			Event::TransactCreate { caller, address, .. } |
+			Event::TransactCreate2 { caller, address, .. } => {
+				touched.accounts.extend([caller, address]);
+			},
+			Event::Suicide { address, target, .. } => {
+				touched.accounts.extend([address, target]);
+			},
+			Event::Exit { .. } => (),
+		}
+	}
+}
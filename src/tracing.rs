@@ -0,0 +1,115 @@
+//! Allows to listen to executor events.
+
+use primitive_types::{H160, H256, U256};
+use crate::{Context, CreateScheme, ExitReason, Transfer};
+
+environmental::environmental!(listener: dyn EventListener + 'static);
+
+/// Listener of executor events.
+pub trait EventListener {
+	/// Handle an event.
+	fn event(&mut self, event: Event);
+}
+
+/// Executor event.
+#[derive(Debug, Copy, Clone)]
+pub enum Event<'a> {
+	/// A call frame is entered, including the transaction call.
+	Call {
+		/// Address of the executed code.
+		code_address: H160,
+		/// Value transfer of the call.
+		transfer: &'a Option<Transfer>,
+		/// Call input.
+		input: &'a [u8],
+		/// Target gas.
+		target_gas: Option<usize>,
+		/// Whether the call is static.
+		is_static: bool,
+		/// Context of the call.
+		context: &'a Context,
+	},
+	/// A create frame is entered, including the transaction create.
+	Create {
+		/// Caller.
+		caller: H160,
+		/// Address of the created contract.
+		address: H160,
+		/// Create scheme.
+		scheme: CreateScheme,
+		/// Endowment.
+		value: U256,
+		/// Init code.
+		init_code: &'a [u8],
+		/// Target gas.
+		target_gas: Option<usize>,
+	},
+	/// An account is marked to be deleted.
+	Suicide {
+		/// Deleted address.
+		address: H160,
+		/// Beneficiary of the balance.
+		target: H160,
+		/// Balance transferred.
+		balance: U256,
+	},
+	/// A call or create frame exits.
+	Exit {
+		/// Exit reason.
+		reason: &'a ExitReason,
+		/// Return value.
+		return_value: &'a [u8],
+	},
+	/// A call transaction starts.
+	TransactCall {
+		/// Caller.
+		caller: H160,
+		/// Callee.
+		address: H160,
+		/// Value.
+		value: U256,
+		/// Call data.
+		data: &'a [u8],
+		/// Gas limit.
+		gas_limit: usize,
+	},
+	/// A create transaction starts.
+	TransactCreate {
+		/// Caller.
+		caller: H160,
+		/// Endowment.
+		value: U256,
+		/// Init code.
+		init_code: &'a [u8],
+		/// Gas limit.
+		gas_limit: usize,
+		/// Address of the created contract.
+		address: H160,
+	},
+	/// A `CREATE2` transaction starts.
+	TransactCreate2 {
+		/// Caller.
+		caller: H160,
+		/// Endowment.
+		value: U256,
+		/// Init code.
+		init_code: &'a [u8],
+		/// Salt.
+		salt: H256,
+		/// Gas limit.
+		gas_limit: usize,
+		/// Address of the created contract.
+		address: H160,
+	},
+}
+
+impl<'a> Event<'a> {
+	pub(crate) fn emit(self) {
+		listener::with(|listener| listener.event(self));
+	}
+}
+
+/// Run closure with provided listener.
+pub fn using<R, F: FnOnce() -> R>(new: &mut (dyn EventListener + 'static), f: F) -> R {
+	listener::using(new, f)
+}
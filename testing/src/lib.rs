@@ -0,0 +1,223 @@
+//! In-memory chain for contract integration tests, built on `BlockExecutor` and
+//! `MemoryBackend`.
+//!
+//! Each transaction runs in its own block executor and is committed at once.
+//! Blocks are produced explicitly, advancing the number and timestamp; the hash
+//! of a block is the Keccak-256 of its number.
+
+#![deny(missing_docs)]
+
+use std::collections::BTreeMap;
+use std::mem;
+use primitive_types::{H160, H256, U256};
+use sha3::{Digest, Keccak256};
+use evm::{Config, ExitReason};
+use evm::backend::{ApplyBackend, MemoryAccount, MemoryBackend, MemoryVicinity};
+use evm::executor::{BlockExecutor, BlockTransaction, EstimateTransaction, PrecompileFn, PrecompileResult,
+					PrecompileContext, Receipt, Simulation, simulate_call};
+
+/// TRON block interval in seconds.
+pub const BLOCK_INTERVAL: u64 = 3;
+
+fn no_precompile(
+	_address: H160,
+	_input: &[u8],
+	_target_gas: Option<usize>,
+	_config: &Config,
+	_context: &PrecompileContext,
+) -> PrecompileResult {
+	None
+}
+
+/// Hash of the block with the given number.
+pub fn block_hash(number: U256) -> H256 {
+	let mut bytes = [0u8; 32];
+	number.to_big_endian(&mut bytes);
+	H256::from_slice(&Keccak256::digest(&bytes))
+}
+
+/// Chain of blocks in memory, with the state of all accounts.
+pub struct Chain {
+	config: Config,
+	precompile: PrecompileFn,
+	vicinity: MemoryVicinity,
+	state: BTreeMap<H160, MemoryAccount>,
+	gas_limit: usize,
+	transactions: u64,
+}
+
+impl Chain {
+	/// Create a chain at block zero with no accounts.
+	pub fn new(config: Config) -> Self {
+		Self {
+			config,
+			precompile: no_precompile,
+			vicinity: MemoryVicinity {
+				gas_price: U256::zero(),
+				origin: H160::default(),
+				chain_id: U256::from_big_endian(block_hash(U256::zero()).as_bytes()),
+				block_hashes: Vec::new(),
+				block_number: U256::zero(),
+				block_coinbase: H160::default(),
+				block_timestamp: U256::zero(),
+				block_difficulty: U256::zero(),
+				block_randomness: None,
+				block_gas_limit: U256::zero(),
+				block_base_fee_per_gas: U256::zero(),
+			},
+			state: BTreeMap::new(),
+			gas_limit: 10_000_000,
+			transactions: 0,
+		}
+	}
+
+	/// Use the given precompiles.
+	pub fn with_precompile(mut self, precompile: PrecompileFn) -> Self {
+		self.precompile = precompile;
+		self
+	}
+
+	/// Set the gas limit of transactions, 10 million by default.
+	pub fn set_gas_limit(&mut self, gas_limit: usize) {
+		self.gas_limit = gas_limit;
+	}
+
+	/// Set the gas (energy) price of transactions.
+	pub fn set_gas_price(&mut self, gas_price: U256) {
+		self.vicinity.gas_price = gas_price;
+	}
+
+	/// Config of the chain.
+	pub fn config(&self) -> &Config {
+		&self.config
+	}
+
+	/// Environment of the current block.
+	pub fn vicinity(&self) -> &MemoryVicinity {
+		&self.vicinity
+	}
+
+	/// State of all accounts.
+	pub fn state(&self) -> &BTreeMap<H160, MemoryAccount> {
+		&self.state
+	}
+
+	/// Account at address, if it exists.
+	pub fn account(&self, address: H160) -> Option<&MemoryAccount> {
+		self.state.get(&address)
+	}
+
+	/// Mutable account at address, created if absent.
+	pub fn account_mut(&mut self, address: H160) -> &mut MemoryAccount {
+		self.state.entry(address).or_default()
+	}
+
+	/// Balance of address.
+	pub fn balance(&self, address: H160) -> U256 {
+		self.account(address).map(|account| account.balance).unwrap_or_default()
+	}
+
+	/// Set balance of address.
+	pub fn set_balance(&mut self, address: H160, balance: U256) {
+		self.account_mut(address).balance = balance;
+	}
+
+	/// TRC-10 token balance of address.
+	pub fn token_balance(&self, address: H160, token_id: U256) -> U256 {
+		self.account(address)
+			.and_then(|account| account.token_balance.get(&token_id).cloned())
+			.unwrap_or_default()
+	}
+
+	/// Set TRC-10 token balance of address.
+	pub fn set_token_balance(&mut self, address: H160, token_id: U256, balance: U256) {
+		self.account_mut(address).token_balance.insert(token_id, balance);
+	}
+
+	/// Storage value of address at index.
+	pub fn storage(&self, address: H160, index: H256) -> H256 {
+		self.account(address)
+			.and_then(|account| account.storage.get(&index).cloned())
+			.unwrap_or_default()
+	}
+
+	/// Code of address.
+	pub fn code(&self, address: H160) -> &[u8] {
+		self.account(address).map(|account| &account.code[..]).unwrap_or_default()
+	}
+
+	/// Number of the current block.
+	pub fn block_number(&self) -> U256 {
+		self.vicinity.block_number
+	}
+
+	/// Produce a block, advancing the number by one and the timestamp by
+	/// `BLOCK_INTERVAL`.
+	pub fn produce_block(&mut self) {
+		self.produce_blocks(1);
+	}
+
+	/// Produce the given number of blocks.
+	pub fn produce_blocks(&mut self, count: u64) {
+		for _ in 0..count {
+			self.vicinity.block_hashes.insert(0, block_hash(self.vicinity.block_number));
+			self.vicinity.block_number += U256::one();
+			self.vicinity.block_timestamp += U256::from(BLOCK_INTERVAL);
+		}
+	}
+
+	/// Deploy a contract with the init code. The address of the contract is the
+	/// `contract_address` of the receipt, set if the creation succeeded.
+	pub fn deploy(&mut self, caller: H160, init_code: Vec<u8>, value: U256) -> Receipt {
+		self.transact(EstimateTransaction::Create { caller, value, init_code })
+	}
+
+	/// Call a contract, committing the state changes of the transaction.
+	pub fn call(&mut self, caller: H160, address: H160, data: Vec<u8>, value: U256) -> Receipt {
+		self.transact(EstimateTransaction::Call { caller, address, value, data })
+	}
+
+	/// Call a contract without committing any state change, as `eth_call`.
+	pub fn view(&self, caller: H160, address: H160, data: Vec<u8>) -> Simulation {
+		let backend = MemoryBackend::new(&self.vicinity, self.state.clone());
+		simulate_call(
+			&backend, BTreeMap::new(), &self.config, self.precompile,
+			caller, address, U256::zero(), data, self.gas_limit,
+		)
+	}
+
+	/// Return data of a successful call without committing any state change.
+	pub fn view_output(&self, caller: H160, address: H160, data: Vec<u8>) -> Result<Vec<u8>, ExitReason> {
+		let simulation = self.view(caller, address, data);
+		if simulation.reason.is_succeed() {
+			Ok(simulation.output)
+		} else {
+			Err(simulation.reason)
+		}
+	}
+
+	/// Execute a transaction in the current block and commit its state changes.
+	pub fn transact(&mut self, action: EstimateTransaction) -> Receipt {
+		let mut bytes = [0u8; 40];
+		self.vicinity.block_number.to_big_endian(&mut bytes[..32]);
+		bytes[32..].copy_from_slice(&self.transactions.to_be_bytes());
+		self.transactions += 1;
+
+		let transaction = BlockTransaction {
+			hash: H256::from_slice(&Keccak256::digest(&bytes)),
+			gas_price: self.vicinity.gas_price,
+			gas_limit: self.gas_limit,
+			fee_limit: None,
+			frozen_energy: 0,
+			action,
+		};
+
+		let mut backend = MemoryBackend::new(&self.vicinity, mem::take(&mut self.state));
+		let mut block = BlockExecutor::new(&backend, &self.config, self.precompile);
+		let receipt = block.execute(&transaction);
+		let applies = block.deconstruct();
+		backend.apply(applies, receipt.logs.clone(), false);
+		self.state = backend.into_state();
+		receipt
+	}
+}
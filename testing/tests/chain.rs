@@ -0,0 +1,39 @@
+use primitive_types::{H160, H256, U256};
+use evm::{Config, ContractResult};
+use evm_testing::{block_hash, Chain, BLOCK_INTERVAL};
+
+#[test]
+fn deploy_call_and_produce_blocks() {
+	let caller = H160::from_low_u64_be(1);
+	let mut chain = Chain::new(Config::great_voyage_4_7());
+	chain.set_balance(caller, U256::from(100));
+
+	// Runtime code: SSTORE(0, SLOAD(0) + CALLVALUE), returning NUMBER and TIMESTAMP.
+	let runtime = evm::assemble("
+		CALLVALUE PUSH1 0 SLOAD ADD PUSH1 0 SSTORE
+		NUMBER PUSH1 0 MSTORE TIMESTAMP PUSH1 32 MSTORE PUSH1 64 PUSH1 0 RETURN
+	").unwrap();
+	let mut init_code = evm::assemble(
+		&format!("PUSH1 {} DUP1 PUSH1 11 PUSH1 0 CODECOPY PUSH1 0 RETURN", runtime.len()),
+	).unwrap();
+	init_code.extend(&runtime);
+
+	let receipt = chain.deploy(caller, init_code, U256::zero());
+	assert_eq!(receipt.result, ContractResult::Success);
+	let contract = receipt.contract_address.unwrap();
+	assert_eq!(chain.code(contract), &runtime[..]);
+
+	chain.produce_blocks(2);
+	let receipt = chain.call(caller, contract, Vec::new(), U256::from(7));
+	assert_eq!(receipt.result, ContractResult::Success);
+	assert_eq!(U256::from_big_endian(&receipt.output[..32]), U256::from(2));
+	assert_eq!(U256::from_big_endian(&receipt.output[32..]), U256::from(2 * BLOCK_INTERVAL));
+	assert_eq!(chain.storage(contract, H256::zero()), H256::from_low_u64_be(7));
+	assert_eq!(chain.balance(caller), U256::from(93));
+	assert_eq!(chain.vicinity().block_hashes, vec![block_hash(U256::one()), block_hash(U256::zero())]);
+
+	// Views see the state without changing it.
+	let output = chain.view_output(caller, contract, Vec::new()).unwrap();
+	assert_eq!(U256::from_big_endian(&output[..32]), U256::from(2));
+	assert_eq!(chain.storage(contract, H256::zero()), H256::from_low_u64_be(7));
+}
@@ -0,0 +1,79 @@
+use primitive_types::H160;
+use evm::{Config, ConfigError};
+
+#[test]
+fn presets_are_valid() {
+	let presets = [
+		Config::frontier(), Config::istanbul(), Config::berlin(), Config::london(), Config::shanghai(),
+		Config::tvm(), Config::odyssey_3_7(), Config::great_voyage_4_0(), Config::great_voyage_4_1(),
+		Config::great_voyage_4_2(), Config::great_voyage_4_5(), Config::great_voyage_4_6(),
+		Config::great_voyage_4_7(),
+	];
+	for preset in presets.iter() {
+		assert_eq!(preset.validate(), Ok(()));
+	}
+}
+
+#[test]
+fn builder_overrides_and_validates() {
+	let config = Config::istanbul().builder()
+		.gas_sload(200)
+		.call_stack_limit(64)
+		.build()
+		.unwrap();
+	assert_eq!(config.gas_sload, 200);
+	assert_eq!(config.call_stack_limit, 64);
+
+	let err = Config::istanbul().builder()
+		.sstore_gas_metering(false)
+		.build()
+		.unwrap_err();
+	assert_eq!(err, ConfigError::SStoreRevertWithoutGasMetering);
+}
+
+#[test]
+fn tron_precompiles_gated_by_fork() {
+	let batch_validate_sign = H160::from_low_u64_be(0x09);
+	let verify_mint_proof = H160::from_low_u64_be(0x0100_0001);
+	let validate_multisign = H160::from_low_u64_be(0x0a);
+
+	let config = Config::tvm();
+	assert!(!config.has_tron_precompile(batch_validate_sign));
+	assert!(!config.has_tron_precompile(verify_mint_proof));
+	assert!(!config.has_tron_precompile(validate_multisign));
+	assert!(config.has_tron_precompile(H160::from_low_u64_be(0x08)));
+
+	let config = Config::odyssey_3_7();
+	assert!(config.has_tron_precompile(batch_validate_sign));
+	assert!(config.has_tron_precompile(validate_multisign));
+	assert!(!config.has_tron_precompile(verify_mint_proof));
+
+	let config = Config::great_voyage_4_0();
+	assert!(config.has_tron_precompile(batch_validate_sign));
+	assert!(config.has_tron_precompile(verify_mint_proof));
+	assert!(config.has_tron_precompile(H160::from_low_u64_be(0x0100_0005)));
+	assert!(config.has_tron_precompile(H160::repeat_byte(0x09)));
+}
+
+#[cfg(feature = "with-serde")]
+#[test]
+fn chain_params_from_json() {
+	let mut custom = serde_json::to_value(Config::great_voyage_4_2()).unwrap();
+	custom["call_stack_limit"] = 64.into();
+	let json = serde_json::json!({
+		"forks": [
+			{ "block_number": 100, "config": custom },
+			{ "block_number": 0, "config": "odyssey_3_7" },
+		]
+	});
+
+	let params: evm::ChainParams = serde_json::from_value(json).unwrap();
+	let schedule = params.load().unwrap();
+	assert!(!schedule.config_at(99).unwrap().has_vote);
+	assert!(schedule.config_at(100).unwrap().has_vote);
+	assert_eq!(schedule.config_at(100).unwrap().call_stack_limit, 64);
+
+	let json = serde_json::json!({ "forks": [{ "block_number": 0, "config": "constantinople" }] });
+	let params: evm::ChainParams = serde_json::from_value(json).unwrap();
+	assert_eq!(params.load().unwrap_err(), ConfigError::UnknownPreset);
+}
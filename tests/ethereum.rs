@@ -0,0 +1,867 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use primitive_types::{H160, H256, U256};
+use sha3::{Digest, Keccak256};
+use evm::{Config, Context, ExitError, ExitReason, ExitRevert, ExitSucceed, ExternalOpcode, Inspector, Log, Machine, Opcode,
+		  RevertReason, Runtime, Shared};
+use evm::backend::{AccountOverride, ApplyBackend, Backend, Bloom, CachedBackend, MemoryAccount, MemoryBackend, MemoryVicinity};
+use evm::executor::{simulate_call, BlockExecutor, BlockTransaction, Coverage, EstimateTransaction, FramePool, FramePoolStats, StackExecutor, ValidsCache};
+
+fn vicinity() -> MemoryVicinity {
+	MemoryVicinity {
+		gas_price: U256::zero(),
+		origin: H160::default(),
+		chain_id: U256::zero(),
+		block_hashes: Vec::new(),
+		block_number: U256::zero(),
+		block_coinbase: H160::default(),
+		block_timestamp: U256::zero(),
+		block_difficulty: U256::zero(),
+		block_randomness: None,
+		block_gas_limit: U256::zero(),
+		block_base_fee_per_gas: U256::zero(),
+	}
+}
+
+fn state(address: H160, code: &str) -> BTreeMap<H160, MemoryAccount> {
+	let mut state = BTreeMap::new();
+	state.insert(address, MemoryAccount {
+		code: hex::decode(code).unwrap(),
+		..Default::default()
+	});
+	state
+}
+
+#[test]
+fn berlin_sload_cold_then_warm() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	// SLOAD(0), SLOAD(0)
+	let code = "600054600054";
+
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(contract, code));
+
+	let config = Config::berlin();
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+	let (reason, _) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	assert_eq!(executor.used_gas(), 21000 + 3 + 2100 + 3 + 100);
+}
+
+#[test]
+fn shanghai_push0() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	// 7 + PUSH0
+	let code = "60075f0160005260206000f3";
+
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(contract, code));
+
+	let config = Config::shanghai();
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+	let (reason, ret) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(U256::from_big_endian(&ret), U256::from(7));
+
+	let config = Config::london();
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+	let (reason, _) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+	assert!(matches!(reason, ExitReason::Error(_)));
+}
+
+#[test]
+fn berlin_precompiles_warm() {
+	let caller = H160::from_low_u64_be(0x10);
+	let contract = H160::from_low_u64_be(0x20);
+
+	// BALANCE(0x01), BALANCE(0x0a)
+	let code = "600131600a31";
+
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(contract, code));
+
+	let config = Config::berlin();
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+	let (reason, _) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	assert_eq!(executor.used_gas(), 21000 + 3 + 100 + 3 + 2600);
+
+	let config = Config::berlin().builder().warm_precompiles(0).build().unwrap();
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+	let (reason, _) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	assert_eq!(executor.used_gas(), 21000 + 3 + 2600 + 3 + 2600);
+}
+
+#[test]
+fn shanghai_initcode_limit() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, BTreeMap::new());
+	let create = |config: &Config, init_code: Vec<u8>| {
+		let mut executor = StackExecutor::new(&backend, 10_000_000, config);
+		let reason = executor.transact_create(caller, U256::zero(), init_code, 10_000_000);
+		(reason, executor.used_gas())
+	};
+	let without_limit = Config::shanghai().builder().max_initcode_size(None).build().unwrap();
+
+	// Two words of init code are charged 2 gas each.
+	let (reason, shanghai) = create(&Config::shanghai(), vec![0; 64]);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	let (reason, london) = create(&without_limit, vec![0; 64]);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	assert_eq!(shanghai, london + 2 * 2);
+
+	let (reason, _) = create(&Config::shanghai(), vec![0; 0xc001]);
+	assert_eq!(reason, ExitError::CreateContractLimit.into());
+	let (reason, _) = create(&without_limit, vec![0; 0xc001]);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+
+	// CREATE(0, 0, 0xc001)
+	let code = "6200c00160006000f0";
+	let backend = MemoryBackend::new(&vicinity, state(contract, code));
+	let call = |config: &Config| {
+		let mut executor = StackExecutor::new(&backend, 10_000_000, config);
+		executor.transact_call(caller, contract, U256::zero(), Vec::new(), 10_000_000).0
+	};
+	assert_eq!(call(&Config::shanghai()), ExitError::CreateContractLimit.into());
+	assert_eq!(call(&without_limit), ExitReason::Succeed(ExitSucceed::Stopped));
+}
+
+#[test]
+fn london_rejects_code_starting_with_ef() {
+	let caller = H160::from_low_u64_be(1);
+
+	// MSTORE8(0, 0xef), RETURN(0, 1)
+	let init_code = hex::decode("60ef60005360016000f3").unwrap();
+
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, BTreeMap::new());
+	let create = |config: &Config| {
+		let mut executor = StackExecutor::new(&backend, 100000, config);
+		executor.transact_create(caller, U256::zero(), init_code.clone(), 100000)
+	};
+	assert_eq!(create(&Config::london()), ExitError::InvalidCode.into());
+	assert_eq!(create(&Config::berlin()), ExitReason::Succeed(ExitSucceed::Returned));
+}
+
+#[derive(Default)]
+struct StepRecorder {
+	steps: Vec<(usize, Opcode, usize)>,
+	results: usize,
+}
+
+impl Inspector for StepRecorder {
+	fn step(&mut self, _context: &Context, opcode: Result<Opcode, ExternalOpcode>, machine: &Machine) {
+		let position = *machine.position().as_ref().unwrap();
+		self.steps.push((position, opcode.unwrap(), machine.stack().data().len()));
+	}
+
+	fn step_result(&mut self, _context: &Context, _result: &Result<(), ExitReason>, _machine: &Machine) {
+		self.results += 1;
+	}
+}
+
+#[test]
+fn inspector_steps() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	// 1 + 2, STOP
+	let code = "600160020100";
+
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(contract, code));
+
+	let config = Config::istanbul();
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+	let recorder = Rc::new(RefCell::new(StepRecorder::default()));
+	executor.set_inspector(recorder.clone());
+	let (reason, _) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+
+	let recorder = recorder.borrow();
+	assert_eq!(recorder.steps, vec![
+		(0, Opcode::Push(1), 0),
+		(2, Opcode::Push(1), 1),
+		(4, Opcode::Add, 2),
+		(5, Opcode::Stop, 1),
+	]);
+	assert_eq!(recorder.results, 4);
+}
+
+#[test]
+fn revert_reason() {
+	let error = hex::decode(
+		"08c379a0\
+		0000000000000000000000000000000000000000000000000000000000000020\
+		0000000000000000000000000000000000000000000000000000000000000002\
+		6e6f000000000000000000000000000000000000000000000000000000000000"
+	).unwrap();
+	assert_eq!(RevertReason::decode(&error), Some(RevertReason::Error("no".into())));
+
+	let panic = hex::decode(
+		"4e487b71\
+		0000000000000000000000000000000000000000000000000000000000000011"
+	).unwrap();
+	let reason = RevertReason::decode(&panic).unwrap();
+	assert_eq!(reason, RevertReason::Panic(U256::from(0x11)));
+	assert_eq!(reason.to_string(), "panic: arithmetic overflow or underflow (0x11)");
+
+	assert_eq!(RevertReason::decode(&[]), None);
+	assert_eq!(RevertReason::decode(&error[..40]), Some(RevertReason::Unknown(error[..40].to_vec())));
+}
+
+#[test]
+fn logs_discarded_on_revert() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+	let callee = H160::from_low_u64_be(3);
+
+	// CALL(gas, callee, 0, 0, 0, 0, 0), LOG1(0, 0, 1)
+	let code = "6000600060006000\
+		6000\
+		730000000000000000000000000000000000000003\
+		5a\
+		f1\
+		50\
+		600160006000a1\
+		00";
+
+	let vicinity = vicinity();
+	let mut state = state(contract, code);
+	// LOG0(0, 0), REVERT(0, 0)
+	state.extend(self::state(callee, "60006000a060006000fd"));
+	let backend = MemoryBackend::new(&vicinity, state);
+
+	let config = Config::istanbul();
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+	let (reason, _) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+
+	let logs = executor.logs();
+	assert_eq!(logs.len(), 1);
+	assert_eq!(logs[0].address, contract);
+	assert_eq!(logs[0].topics, vec![H256::from_low_u64_be(1)]);
+
+	let bloom = executor.logs_bloom();
+	assert_eq!(bloom, Bloom::from_logs(logs));
+	assert!(bloom.contains(contract.as_bytes()));
+	assert!(bloom.contains(H256::from_low_u64_be(1).as_bytes()));
+	assert!(!bloom.contains(callee.as_bytes()));
+	assert!(bloom.0.iter().map(|b| b.count_ones()).sum::<u32>() <= 6);
+}
+
+#[cfg(feature = "with-serde")]
+#[test]
+fn runtime_snapshot_resume() {
+	use evm::{Runtime, RuntimeSnapshot, Shared};
+
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	// MSTORE(0, 1 + 2), RETURN(0, 32)
+	let code = hex::decode("600160020160005260206000f3").unwrap();
+
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, BTreeMap::new());
+	let config = Config::istanbul();
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+
+	let context = Context {
+		caller,
+		address: contract,
+		call_value: U256::zero(),
+		call_token_id: U256::zero(),
+		call_token_value: U256::zero(),
+	};
+	let mut runtime = Runtime::new(Shared::new(code), Shared::new(Vec::new()), context, &config);
+	for _ in 0..3 {
+		assert!(runtime.step(&mut executor).is_ok());
+	}
+
+	let json = serde_json::to_string(&runtime.snapshot()).unwrap();
+	let snapshot: RuntimeSnapshot = serde_json::from_str(&json).unwrap();
+	assert_eq!(snapshot.machine.position(), &Ok(5));
+	assert_eq!(snapshot.machine.stack().data().len(), 1);
+
+	let mut resumed = Runtime::from_snapshot(snapshot, &config);
+	assert_eq!(executor.execute(&mut resumed), ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(U256::from_big_endian(&resumed.machine().return_value()), U256::from(3));
+}
+
+#[cfg(feature = "with-serde")]
+#[test]
+fn other_errors_serde() {
+	use evm::ExitFatal;
+
+	// Messages are kept when serializing, and read back as "unknown".
+	let reasons = [
+		(ExitReason::Error(ExitError::Other("custom error")), ExitReason::Error(ExitError::Other("unknown"))),
+		(ExitReason::Fatal(ExitFatal::Other("custom fatal")), ExitReason::Fatal(ExitFatal::Other("unknown"))),
+		(
+			ExitReason::Fatal(ExitFatal::CallErrorAsFatal(ExitError::Other("custom error"))),
+			ExitReason::Fatal(ExitFatal::CallErrorAsFatal(ExitError::Other("unknown"))),
+		),
+	];
+	for (reason, restored) in reasons.iter() {
+		let json = serde_json::to_string(reason).unwrap();
+		assert!(json.contains("custom"));
+		assert_eq!(&serde_json::from_str::<ExitReason>(&json).unwrap(), restored);
+	}
+}
+
+#[cfg(feature = "with-serde")]
+#[test]
+fn simulation_serde() {
+	use evm::{Capture, Trap};
+
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	// LOG1(0, 32, 1), REVERT(0, 0)
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(contract, "6001602060006000a160006000fd"));
+	let config = Config::istanbul();
+
+	let simulation = simulate_call(
+		&backend, BTreeMap::new(), &config, no_precompile, caller, contract, U256::zero(), Vec::new(), 100000,
+	);
+	let json = serde_json::to_string(&simulation).unwrap();
+	assert_eq!(serde_json::from_str::<evm::executor::Simulation>(&json).unwrap(), simulation);
+
+	let capture: Capture<ExitReason, Trap> = Capture::Exit(ExitError::OutOfGas.into());
+	let json = serde_json::to_string(&capture).unwrap();
+	assert_eq!(json, "{\"Exit\":{\"Error\":\"OutOfGas\"}}");
+	assert_eq!(serde_json::from_str::<Capture<ExitReason, Trap>>(&json).unwrap(), capture);
+}
+
+#[test]
+fn valids_cache_hits_on_repeated_calls() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	// JUMP(4), INVALID, JUMPDEST, STOP
+	let code = "600456fe5b00";
+
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(contract, code));
+	let config = Config::istanbul();
+	let cache = Rc::new(RefCell::new(ValidsCache::new()));
+
+	for _ in 0..2 {
+		let mut executor = StackExecutor::new(&backend, 100000, &config);
+		executor.set_valids_cache(cache.clone());
+		let (reason, _) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	}
+
+	let stats = cache.borrow().stats();
+	assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+	assert_eq!(stats.hit_rate_per_mille(), 500);
+}
+
+#[test]
+fn frame_pool_reuses_buffers() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	// MSTORE(0, 7), RETURN(0, 32)
+	let code = "600760005260206000f3";
+
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(contract, code));
+	let config = Config::istanbul();
+	let pool = Rc::new(RefCell::new(FramePool::new()));
+
+	for _ in 0..3 {
+		let mut executor = StackExecutor::new(&backend, 100000, &config);
+		executor.set_frame_pool(pool.clone());
+		let (reason, ret) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+		assert_eq!(U256::from_big_endian(&ret), U256::from(7));
+	}
+
+	assert_eq!(pool.borrow().stats(), FramePoolStats { reused: 2, allocated: 1 });
+	assert_eq!(pool.borrow().len(), 1);
+
+	// Idle buffers are capped at one per call depth.
+	let mut pool = pool.borrow_mut();
+	for _ in 0..1100 {
+		pool.release(Machine::new(Vec::new(), Vec::new(), 1024, usize::MAX));
+	}
+	assert_eq!(pool.len(), 1024);
+}
+
+fn no_precompile(
+	_address: H160,
+	_input: &[u8],
+	_target_gas: Option<usize>,
+	_config: &Config,
+	_context: &evm::executor::PrecompileContext,
+) -> evm::executor::PrecompileResult {
+	None
+}
+
+#[test]
+fn estimate_gas_finds_lowest_limit() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	// SSTORE(0, 1)
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(contract, "6001600055"));
+	let config = Config::istanbul();
+	let transaction = EstimateTransaction::Call {
+		caller,
+		address: contract,
+		value: U256::zero(),
+		data: Vec::new(),
+	};
+
+	let estimate = StackExecutor::estimate_gas(&backend, &config, no_precompile, &transaction, 1000000);
+	assert_eq!(estimate, Ok(21000 + 3 + 3 + 20000));
+
+	let estimate = StackExecutor::estimate_gas(&backend, &config, no_precompile, &transaction, 30000);
+	assert!(matches!(estimate, Err((ExitReason::Error(_), _))));
+}
+
+#[test]
+fn simulate_call_with_overrides() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+	let other = H160::from_low_u64_be(3);
+
+	// SLOAD(0) + BALANCE(3), returned
+	let code = "6000546003310160005260206000f3";
+
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, BTreeMap::new());
+	let config = Config::istanbul();
+
+	let mut overrides = BTreeMap::new();
+	overrides.insert(contract, AccountOverride {
+		code: Some(hex::decode(code).unwrap().into()),
+		state_diff: vec![(H256::zero(), H256::from_low_u64_be(40))].into_iter().collect(),
+		..Default::default()
+	});
+	overrides.insert(other, AccountOverride {
+		balance: Some(U256::from(2)),
+		..Default::default()
+	});
+
+	let simulation = simulate_call(
+		&backend, overrides, &config, no_precompile, caller, contract, U256::zero(), Vec::new(), 100000,
+	);
+	assert_eq!(simulation.reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(U256::from_big_endian(&simulation.output), U256::from(42));
+	assert!(backend.state().is_empty());
+}
+
+#[test]
+fn call_gas_forwarding() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+	let callee = H160::from_low_u64_be(3);
+
+	// Return the GAS seen by the callee and the success of the call, given the
+	// requested call gas and value as input.
+	let code = evm::assemble("
+		PUSH1 32 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 32 CALLDATALOAD PUSH1 3 PUSH1 0 CALLDATALOAD CALL
+		PUSH1 32 MSTORE PUSH1 64 PUSH1 0 RETURN
+	").unwrap();
+	let callee_code = evm::assemble("GAS PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN").unwrap();
+
+	let vicinity = vicinity();
+	let mut state = BTreeMap::new();
+	state.insert(contract, MemoryAccount { code, balance: U256::from(10), ..Default::default() });
+	state.insert(callee, MemoryAccount { code: callee_code, ..Default::default() });
+	let backend = MemoryBackend::new(&vicinity, state);
+
+	let call = |config: &Config, requested: u64, value: u64| {
+		let mut executor = StackExecutor::new(&backend, 100000, config);
+		let mut input = [0u8; 64];
+		U256::from(requested).to_big_endian(&mut input[..32]);
+		U256::from(value).to_big_endian(&mut input[32..]);
+		let (reason, ret) = executor.transact_call(caller, contract, U256::zero(), input.to_vec(), 100000);
+		if reason != ExitReason::Succeed(ExitSucceed::Returned) {
+			return Err(reason)
+		}
+		Ok((U256::from_big_endian(&ret[..32]).as_u64(), U256::from_big_endian(&ret[32..]).as_u64()))
+	};
+
+	// The callee sees the requested gas minus the cost of GAS.
+	assert_eq!(call(&Config::frontier(), 5000, 0), Ok((4998, 1)));
+	assert_eq!(call(&Config::istanbul(), 5000, 0), Ok((4998, 1)));
+
+	// Requesting more than available runs out of gas before EIP-150, and is
+	// capped to all but one 64th after.
+	assert_eq!(call(&Config::frontier(), 1000000, 0), Err(ExitReason::Error(ExitError::OutOfGas)));
+	let (gas, success) = call(&Config::istanbul(), 1000000, 0).unwrap();
+	assert_eq!(success, 1);
+	assert!(gas > 70000 && gas < (100000 - 21000) * 63 / 64);
+
+	// A value transfer adds the stipend.
+	assert_eq!(call(&Config::istanbul(), 0, 1), Ok((2298, 1)));
+}
+
+#[test]
+fn coverage_records_executed_pcs() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	let code = evm::assemble("PUSH1 1 PUSH2 @end JUMPI PUSH1 0 POP end: STOP").unwrap();
+	let code_hash = H256::from_slice(&Keccak256::digest(&code));
+
+	let vicinity = vicinity();
+	let mut state = BTreeMap::new();
+	state.insert(contract, MemoryAccount { code, ..Default::default() });
+	let backend = MemoryBackend::new(&vicinity, state);
+
+	let config = Config::istanbul();
+	let coverage = Rc::new(RefCell::new(Coverage::new()));
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+	executor.set_coverage(coverage.clone());
+	let (reason, _) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+
+	let coverage = coverage.borrow();
+	assert_eq!(coverage.len(), 1);
+	let map = coverage.get(&code_hash).unwrap();
+	assert_eq!(map.pcs().collect::<Vec<_>>(), vec![0, 2, 5, 9, 10]);
+	assert_eq!(map.count(), 5);
+	assert!(!map.contains(6));
+}
+
+#[test]
+fn cached_backend_serves_repeated_reads() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	// SSTORE(0, SLOAD(0) + EXTCODESIZE(2)), returned
+	let code = "PUSH1 2 EXTCODESIZE PUSH1 0 SLOAD ADD DUP1 PUSH1 0 SSTORE PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN";
+	let mut state = BTreeMap::new();
+	state.insert(contract, MemoryAccount { code: evm::assemble(code).unwrap(), ..Default::default() });
+
+	let vicinity = vicinity();
+	let mut backend = CachedBackend::new(MemoryBackend::new(&vicinity, state), 16);
+	let config = Config::istanbul();
+
+	let call = |backend: &mut CachedBackend<MemoryBackend>| {
+		let mut executor = StackExecutor::new(&*backend, 100000, &config);
+		let (reason, output) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+		let (applies, logs) = executor.deconstruct();
+		backend.apply(applies, logs, false);
+		U256::from_big_endian(&output)
+	};
+
+	assert_eq!(call(&mut backend), U256::from(19));
+	let stats = backend.stats();
+	assert!(stats.hits > 0);
+	assert_eq!(stats.storages, 0);
+
+	// The applied call invalidated the contract, so the new slot value is read.
+	assert_eq!(call(&mut backend), U256::from(38));
+
+	let misses = backend.stats().misses;
+	assert_eq!(backend.storage(contract, H256::zero()), Some(H256::from_low_u64_be(38)));
+	assert_eq!(backend.storage(contract, H256::zero()), Some(H256::from_low_u64_be(38)));
+	assert_eq!(backend.stats().misses, misses + 1);
+
+	// Only the entries of the invalidated account are dropped.
+	assert_eq!(backend.storage(caller, H256::zero()), None);
+	assert_eq!(backend.code(contract).len(), 19);
+	assert_eq!((backend.stats().codes, backend.stats().storages), (1, 2));
+	backend.invalidate(contract);
+	assert_eq!((backend.stats().codes, backend.stats().storages), (0, 1));
+}
+
+#[test]
+fn extcodehash_empty_accounts() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+	let (funded, empty, missing) = (H160::from_low_u64_be(3), H160::from_low_u64_be(4), H160::from_low_u64_be(5));
+
+	// EXTCODEHASH of the address in call data, returned
+	let code = evm::assemble("PUSH1 0 CALLDATALOAD EXTCODEHASH PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN").unwrap();
+	let mut state = BTreeMap::new();
+	state.insert(contract, MemoryAccount { code: code.clone(), ..Default::default() });
+	state.insert(funded, MemoryAccount { balance: U256::one(), ..Default::default() });
+	state.insert(empty, MemoryAccount::default());
+
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state);
+	let extcodehash = |config: &Config, address: H160| {
+		let mut executor = StackExecutor::new(&backend, 100000, config);
+		let (reason, output) = executor.transact_call(
+			caller, contract, U256::zero(), H256::from(address).as_bytes().to_vec(), 100000,
+		);
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+		H256::from_slice(&output)
+	};
+	let empty_code_hash = H256::from_slice(&Keccak256::digest(&[][..]));
+
+	let config = Config::istanbul();
+	assert_eq!(extcodehash(&config, contract), H256::from_slice(&Keccak256::digest(&code)));
+	assert_eq!(extcodehash(&config, funded), empty_code_hash);
+	assert_eq!(extcodehash(&config, empty), H256::zero());
+	assert_eq!(extcodehash(&config, missing), H256::zero());
+
+	let config = Config::istanbul().builder().empty_considered_exists(true).build().unwrap();
+	assert_eq!(extcodehash(&config, empty), empty_code_hash);
+	assert_eq!(extcodehash(&config, missing), H256::zero());
+}
+
+#[test]
+fn istanbul_net_sstore_metering() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+	let config = Config::istanbul();
+	let vicinity = vicinity();
+
+	// EIP-2200 test cases: code, original value of slot 0, used gas and refund.
+	let cases: &[(&str, u64, usize, usize)] = &[
+		("60006000556000600055", 0, 1612, 0),
+		("60006000556001600055", 0, 20812, 0),
+		("60016000556000600055", 0, 20812, 19200),
+		("60016000556002600055", 0, 20812, 0),
+		("60006000556000600055", 1, 5812, 15000),
+		("60006000556001600055", 1, 5812, 4200),
+		("60006000556002600055", 1, 5812, 0),
+		("60026000556000600055", 1, 5812, 15000),
+		("60026000556001600055", 1, 5812, 4200),
+		("60016000556001600055", 1, 1612, 0),
+		("600160005560006000556001600055", 0, 40818, 19200),
+		("600060005560016000556000600055", 1, 10818, 19200),
+	];
+	for &(code, original, used, refund) in cases {
+		let mut state = state(contract, code);
+		let account = state.get_mut(&contract).unwrap();
+		if original != 0 {
+			account.storage.insert(H256::zero(), H256::from_low_u64_be(original));
+		}
+		let backend = MemoryBackend::new(&vicinity, state);
+		let mut executor = StackExecutor::new(&backend, 100000, &config);
+		let (reason, _) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped), "{}", code);
+		let total = 21000 + used;
+		assert_eq!(executor.used_gas(), total - refund.min(total / 2), "{} with original {}", code, original);
+	}
+
+	// A no-op SSTORE costs 800, but fails with only the stipend left.
+	let mut state = state(contract, "6001600055");
+	state.get_mut(&contract).unwrap().storage.insert(H256::zero(), H256::from_low_u64_be(1));
+	let backend = MemoryBackend::new(&vicinity, state);
+	for &(gas_limit, succeeds) in [(21000 + 6 + 2300, false), (21000 + 6 + 2301, true)].iter() {
+		let mut executor = StackExecutor::new(&backend, gas_limit, &config);
+		let (reason, _) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), gas_limit);
+		assert_eq!(reason.is_succeed(), succeeds, "gas limit {}", gas_limit);
+	}
+}
+
+#[test]
+fn istanbul_sstore_refund_discarded_on_revert() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+	let callee = H160::from_low_u64_be(3);
+
+	// CALL(GAS, 0x03, 0, 0, 0, 0, 0)
+	let mut state = state(contract, "6000600060006000600060035af100");
+	// SSTORE(0, 0), REVERT(0, 0)
+	let mut account = MemoryAccount { code: hex::decode("600060005560006000fd").unwrap(), ..Default::default() };
+	account.storage.insert(H256::zero(), H256::from_low_u64_be(1));
+	state.insert(callee, account);
+
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state);
+	let config = Config::istanbul();
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+	let (reason, _) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	assert_eq!(executor.used_gas(), 100000 - executor.gas());
+}
+
+#[test]
+fn difficulty_or_prev_randao() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+	let randomness = H256::repeat_byte(0x5a);
+
+	// DIFFICULTY, returned
+	let code = hex::encode(evm::assemble("DIFFICULTY PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN").unwrap());
+	let run = |vicinity: &MemoryVicinity, config: &Config| {
+		let backend = MemoryBackend::new(vicinity, state(contract, &code));
+		let mut executor = StackExecutor::new(&backend, 100000, config);
+		let (reason, output) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+		H256::from_slice(&output)
+	};
+
+	let mut vicinity = vicinity();
+	vicinity.block_difficulty = U256::from(7);
+	assert_eq!(run(&vicinity, &Config::shanghai()), H256::from_low_u64_be(7));
+
+	vicinity.block_randomness = Some(randomness);
+	assert_eq!(run(&vicinity, &Config::shanghai()), randomness);
+	assert_eq!(run(&vicinity, &Config::london()), H256::from_low_u64_be(7));
+	let config = Config::london().builder().has_prev_randao(true).build().unwrap();
+	assert_eq!(run(&vicinity, &config), randomness);
+}
+
+#[test]
+fn eof_containers_validated_and_executed() {
+	let caller = H160::from_low_u64_be(1);
+
+	// Init code returning the container that follows it.
+	let create = |container: &[u8]| {
+		let mut init_code = evm::assemble(
+			&format!("PUSH1 {} DUP1 PUSH1 11 PUSH1 0 CODECOPY PUSH1 0 RETURN", container.len()),
+		).unwrap();
+		init_code.extend(container);
+		EstimateTransaction::Create { caller, value: U256::zero(), init_code }
+	};
+	// Container with the code section and a one-byte data section.
+	let container = |code: &[u8]| {
+		let mut container = vec![0xef, 0x00, 0x01, 0x01, 0x00, code.len() as u8, 0x02, 0x00, 0x01, 0x00];
+		container.extend(code);
+		container.push(0xaa);
+		container
+	};
+	// Return CODESIZE and the last byte of the code, both of the whole container.
+	let code = evm::assemble("
+		CODESIZE PUSH1 0 MSTORE
+		PUSH1 1 PUSH1 1 CODESIZE SUB PUSH1 63 CODECOPY
+		PUSH1 64 PUSH1 0 RETURN
+	").unwrap();
+	let mut output = H256::from_low_u64_be(container(&code).len() as u64).as_bytes().to_vec();
+	output.extend(H256::from_low_u64_be(0xaa).as_bytes());
+
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, BTreeMap::new());
+	let run = |config: &Config, container: &[u8]| {
+		let mut block = BlockExecutor::new(&backend, config, no_precompile);
+		let transaction = |hash, action| BlockTransaction {
+			hash: H256::repeat_byte(hash),
+			gas_price: U256::zero(),
+			gas_limit: 1_000_000,
+			fee_limit: None,
+			frozen_energy: 0,
+			action,
+		};
+		let receipt = block.execute(&transaction(1, create(container)));
+		let address = match receipt.contract_address {
+			Some(address) => address,
+			None => return Err(receipt.reason),
+		};
+		let data = Vec::new();
+		let receipt = block.execute(&transaction(2, EstimateTransaction::Call { caller, address, value: U256::zero(), data }));
+		Ok((receipt.reason, receipt.output))
+	};
+
+	let config = Config::shanghai().builder().has_eof(true).build().unwrap();
+	assert_eq!(
+		run(&config, &container(&code)),
+		Ok((ExitReason::Succeed(ExitSucceed::Returned), output)),
+	);
+	// EIP-3670: truncated PUSH1 and undefined opcode.
+	assert_eq!(run(&config, &container(&[0x60])), Err(ExitReason::Error(ExitError::InvalidCode)));
+	assert_eq!(run(&config, &container(&[0x0c])), Err(ExitError::InvalidCode.into()));
+	// EIP-3541: other code starting with 0xef.
+	assert_eq!(run(&config, &[0xef, 0x01]), Err(ExitError::InvalidCode.into()));
+
+	// Without EOF, EIP-3541 rejects the container, and before it the container is
+	// deployed as legacy code failing at 0xef.
+	assert_eq!(run(&Config::shanghai(), &container(&code)), Err(ExitError::InvalidCode.into()));
+	let (reason, _) = run(&Config::berlin(), &container(&code)).unwrap();
+	assert!(!reason.is_succeed());
+
+	assert_eq!(evm::parse_eof(&container(&code)).unwrap().data, &[0xaa]);
+	assert_eq!(evm::parse_eof(&[0xef, 0x00, 0x01, 0x02, 0x00, 0x01, 0x00, 0xaa]), Err(evm::EofError::InvalidSectionKind(0x02)));
+	assert_eq!(evm::parse_eof(&container(&code)[..12]), Err(evm::EofError::SizeMismatch { expected: 29, actual: 12 }));
+}
+
+#[test]
+fn superinstructions_match_single_steps() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	// Sum of 10 down to 1, plus 5.
+	let code = evm::assemble("
+		PUSH1 0 PUSH1 10
+		loop:
+		DUP1 SWAP2 ADD SWAP1
+		PUSH1 1 SWAP1 SUB
+		DUP1 PUSH @loop JUMPI
+		POP PUSH1 5 ADD
+		PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+	").unwrap();
+
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state(contract, &hex::encode(code)));
+	let config = Config::istanbul();
+	let run = |fused: bool| {
+		let cache = Rc::new(RefCell::new(ValidsCache::new()));
+		cache.borrow_mut().set_superinstructions(fused);
+		let mut executor = StackExecutor::new(&backend, 100000, &config);
+		executor.set_valids_cache(cache);
+		let (reason, ret) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+		(reason, U256::from_big_endian(&ret), executor.used_gas())
+	};
+
+	let (reason, ret, gas) = run(true);
+	assert_eq!((reason, ret), (ExitReason::Succeed(ExitSucceed::Returned), U256::from(60)));
+	assert_eq!(run(false), (reason, ret, gas));
+}
+
+#[test]
+fn runtime_result_accessors() {
+	let address = H160::from_low_u64_be(2);
+	let context = Context {
+		address,
+		caller: H160::from_low_u64_be(1),
+		call_value: U256::zero(),
+		call_token_id: U256::zero(),
+		call_token_value: U256::zero(),
+	};
+
+	// MSTORE(0, 7), LOG1(0, 32, 1), REVERT(0, 32)
+	let code = evm::assemble("
+		PUSH1 7 PUSH1 0 MSTORE
+		PUSH1 1 PUSH1 32 PUSH1 0 LOG1
+		PUSH1 32 PUSH1 0 REVERT
+	").unwrap();
+
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, BTreeMap::new());
+	let config = Config::istanbul();
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+	let mut runtime = Runtime::new(Shared::new(code.clone()), Shared::new(Vec::new()), context.clone(), &config);
+	runtime.set_capture_logs(true);
+	assert_eq!(runtime.exit_reason(), None);
+	assert_eq!(runtime.used_gas(&executor), U256::zero());
+
+	let reason = executor.execute(&mut runtime);
+	let word = H256::from_low_u64_be(7).as_bytes().to_vec();
+	assert_eq!(reason, ExitReason::Revert(ExitRevert::Reverted));
+	assert_eq!(runtime.exit_reason(), Some(reason));
+	assert_eq!(runtime.output(), word);
+	assert_eq!(runtime.logs(), &[Log { address, topics: vec![H256::from_low_u64_be(1)], data: word }]);
+	assert!(runtime.stack().data().is_empty());
+	assert_eq!(runtime.memory().len(), 32);
+	assert_eq!(runtime.used_gas(&executor), U256::from(100000 - executor.gas()));
+	assert!(runtime.return_data().is_empty());
+
+	// Logs are only captured on request.
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+	let mut runtime = Runtime::new(code, Vec::new(), context, &config);
+	assert_eq!(executor.execute(&mut runtime), reason);
+	assert!(runtime.logs().is_empty());
+}
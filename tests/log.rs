@@ -0,0 +1,65 @@
+#![cfg(feature = "log")]
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use primitive_types::{H160, U256};
+use evm::Config;
+use evm::backend::{MemoryAccount, MemoryBackend, MemoryVicinity};
+use evm::executor::StackExecutor;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+struct Logger(Mutex<Vec<(Level, String)>>);
+
+impl Log for Logger {
+	fn enabled(&self, metadata: &Metadata) -> bool {
+		metadata.target().starts_with("evm")
+	}
+
+	fn log(&self, record: &Record) {
+		if self.enabled(record.metadata()) {
+			self.0.lock().unwrap().push((record.level(), record.args().to_string()));
+		}
+	}
+
+	fn flush(&self) {}
+}
+
+static LOGGER: Logger = Logger(Mutex::new(Vec::new()));
+
+#[test]
+fn call_records_are_logged() {
+	log::set_logger(&LOGGER).unwrap();
+	log::set_max_level(LevelFilter::Trace);
+
+	let vicinity = MemoryVicinity {
+		gas_price: U256::zero(),
+		origin: H160::default(),
+		chain_id: U256::zero(),
+		block_hashes: Vec::new(),
+		block_number: U256::zero(),
+		block_coinbase: H160::default(),
+		block_timestamp: U256::zero(),
+		block_difficulty: U256::zero(),
+		block_randomness: None,
+		block_gas_limit: U256::zero(),
+		block_base_fee_per_gas: U256::zero(),
+	};
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	// CALL into an empty account.
+	let code = evm::assemble("PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 3 PUSH2 10000 CALL").unwrap();
+	let mut state = BTreeMap::new();
+	state.insert(contract, MemoryAccount { code, ..Default::default() });
+	let backend = MemoryBackend::new(&vicinity, state);
+
+	let config = Config::istanbul();
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+	executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+
+	let records = LOGGER.0.lock().unwrap();
+	assert!(records.iter().any(|(level, message)| *level == Level::Trace &&
+		message.starts_with("call depth=1 address=0x0000000000000000000000000000000000000003")));
+	assert!(records.iter().any(|(level, message)| *level == Level::Debug &&
+		message.starts_with("call exit depth=1")));
+}
@@ -0,0 +1,153 @@
+//! Precompile parity harness. Each file in `tests/fixtures/precompile` is a JSON array
+//! of cases from java-tron or the Ethereum tests, with the fork, precompile address,
+//! input, optional target energy, and the expected output (`null` for failure) and
+//! energy. Cases are run against the `evm-precompile` dispatcher.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use primitive_types::{H160, H256, U256};
+use serde_json::Value;
+use evm::{Config, Context};
+use evm::backend::{MemoryBackend, MemoryVicinity};
+use evm::executor::{EcrecoverInput, PrecompileContext, PrecompileFn, PrecompileState};
+use evm_precompile::tron_precompile;
+
+struct Fixture {
+	name: String,
+	config: Config,
+	address: H160,
+	input: Vec<u8>,
+	target_energy: Option<usize>,
+	output: Option<Vec<u8>>,
+	energy: usize,
+}
+
+fn bytes(value: &Value) -> Vec<u8> {
+	let value = value.as_str().expect("hex string");
+	hex::decode(value.trim_start_matches("0x")).expect("valid hex")
+}
+
+fn load(path: &Path) -> Vec<Fixture> {
+	let json: Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+	json.as_array().expect("array of cases").iter().map(|case| {
+		let fork = case["fork"].as_str().expect("fork");
+		Fixture {
+			name: case["name"].as_str().expect("name").to_string(),
+			config: Config::preset(fork).unwrap_or_else(|| panic!("unknown fork {}", fork)),
+			address: H160::from_slice(&bytes(&case["address"])),
+			input: bytes(&case["input"]),
+			target_energy: case["targetEnergy"].as_u64().map(|v| v as usize),
+			output: if case["output"].is_null() { None } else { Some(bytes(&case["output"])) },
+			energy: case["energy"].as_u64().expect("energy") as usize,
+		}
+	}).collect()
+}
+
+fn check(fixture: &Fixture, precompile: PrecompileFn, state: &dyn PrecompileState) -> Result<(), String> {
+	let context = Context {
+		address: fixture.address,
+		caller: H160::default(),
+		call_value: U256::zero(),
+		call_token_id: U256::zero(),
+		call_token_value: U256::zero(),
+	};
+	let context = PrecompileContext::new(&context, state);
+	let result = precompile(fixture.address, &fixture.input, fixture.target_energy, &fixture.config, &context)
+		.ok_or_else(|| format!("{:?} is not a precompile", fixture.address))?;
+
+	match (result, &fixture.output) {
+		(Ok((_, output, energy)), Some(expected)) => {
+			if &output != expected {
+				return Err(format!("output 0x{}, expected 0x{}", hex::encode(output), hex::encode(expected)))
+			}
+			if energy != fixture.energy {
+				return Err(format!("energy {}, expected {}", energy, fixture.energy))
+			}
+			Ok(())
+		},
+		(Ok((_, output, _)), None) => Err(format!("succeeded with 0x{}, expected failure", hex::encode(output))),
+		(Err(e), Some(_)) => Err(format!("failed with {:?}", e)),
+		(Err(_), None) => Ok(()),
+	}
+}
+
+/// Run all fixtures in `dir` through the precompile dispatcher, reporting every
+/// mismatch at once.
+fn run_fixtures(dir: &Path, precompile: PrecompileFn) {
+	let vicinity = MemoryVicinity {
+		gas_price: U256::zero(),
+		origin: H160::default(),
+		chain_id: U256::zero(),
+		block_hashes: Vec::new(),
+		block_number: U256::zero(),
+		block_coinbase: H160::default(),
+		block_timestamp: U256::zero(),
+		block_difficulty: U256::zero(),
+		block_randomness: None,
+		block_gas_limit: U256::zero(),
+		block_base_fee_per_gas: U256::zero(),
+	};
+	let backend = MemoryBackend::new(&vicinity, BTreeMap::new());
+
+	let mut paths = fs::read_dir(dir).unwrap()
+		.map(|entry| entry.unwrap().path())
+		.filter(|path| path.extension().map(|ext| ext == "json").unwrap_or(false))
+		.collect::<Vec<_>>();
+	paths.sort();
+
+	let mut failures = Vec::new();
+	let mut count = 0;
+	for path in paths {
+		for fixture in load(&path) {
+			count += 1;
+			if let Err(e) = check(&fixture, precompile, &backend) {
+				failures.push(format!("{}: {}", fixture.name, e));
+			}
+		}
+	}
+
+	assert!(count > 0, "no fixtures in {}", dir.display());
+	assert!(failures.is_empty(), "{} of {} fixtures failed:\n{}", failures.len(), count, failures.join("\n"));
+}
+
+#[test]
+fn precompile_fixtures() {
+	run_fixtures(&Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/precompile"), tron_precompile);
+}
+
+#[test]
+fn ecrecover_input_normalization() {
+	let config = Config::great_voyage_4_7();
+	let strict = config.clone().builder().ecrecover_strict(true).build().unwrap();
+
+	// Short input is right-padded: hash 0x01, v 28, r 1 and s 0x02 as the first byte.
+	let mut input = vec![0u8; 97];
+	input[31] = 1;
+	input[63] = 28;
+	input[95] = 1;
+	input[96] = 2;
+	let parsed = EcrecoverInput::parse(&input, &config).unwrap();
+	assert_eq!(parsed.hash, H256::from_low_u64_be(1));
+	assert_eq!(parsed.recovery_id, 1);
+	assert_eq!(parsed.s[0], 2);
+	assert_eq!(EcrecoverInput::parse(&input, &strict), Some(parsed));
+
+	// Truncated input without s.
+	assert_eq!(EcrecoverInput::parse(&input[..96], &config), None);
+
+	// High s is only rejected in strict mode.
+	input[96] = 0xf0;
+	assert!(EcrecoverInput::parse(&input, &config).is_some());
+	assert_eq!(EcrecoverInput::parse(&input, &strict), None);
+
+	// Dirty upper bytes of v are only rejected in strict mode.
+	input[96] = 2;
+	input[40] = 1;
+	assert!(EcrecoverInput::parse(&input, &config).is_some());
+	assert_eq!(EcrecoverInput::parse(&input, &strict), None);
+
+	input[40] = 0;
+	input[63] = 29;
+	assert_eq!(EcrecoverInput::parse(&input, &config), None);
+}
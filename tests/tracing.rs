@@ -0,0 +1,271 @@
+#![cfg(feature = "tracing")]
+
+use std::collections::BTreeMap;
+use primitive_types::{H160, H256, U256};
+use evm::{CallScheme, Config, ExitReason, ExitSucceed};
+use evm::backend::{MemoryAccount, MemoryBackend, MemoryVicinity};
+use evm::executor::{InternalTransactionKind, StackExecutor};
+use evm::tracer::{CallSummaryTracer, Delta, StateDiffTracer, StructLog, StructLogger, StructLoggerConfig};
+
+fn vicinity() -> MemoryVicinity {
+	MemoryVicinity {
+		gas_price: U256::zero(),
+		origin: H160::default(),
+		chain_id: U256::zero(),
+		block_hashes: Vec::new(),
+		block_number: U256::zero(),
+		block_coinbase: H160::default(),
+		block_timestamp: U256::zero(),
+		block_difficulty: U256::zero(),
+		block_randomness: None,
+		block_gas_limit: U256::zero(),
+		block_base_fee_per_gas: U256::zero(),
+	}
+}
+
+#[test]
+fn struct_logger() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	// MSTORE(0, 1 + 2), STOP
+	let mut state = BTreeMap::new();
+	state.insert(contract, MemoryAccount {
+		code: hex::decode("600160020160005200").unwrap(),
+		..Default::default()
+	});
+
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state);
+	let config = Config::istanbul();
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+
+	let logger = StructLogger::new(StructLoggerConfig { enable_memory: true, ..Default::default() });
+	let (reason, _) = logger.trace(|| {
+		executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000)
+	});
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+
+	let logs = logger.logs();
+	assert_eq!(
+		logs.iter().map(|log| (log.pc, log.op_name, log.gas, log.gas_cost)).collect::<Vec<_>>(),
+		vec![
+			(0, "PUSH1", 79000, 3),
+			(2, "PUSH1", 78997, 3),
+			(4, "ADD", 78994, 3),
+			(5, "PUSH1", 78991, 3),
+			(7, "MSTORE", 78988, 3),
+			(8, "STOP", 78985, 0),
+		],
+	);
+	assert!(logs.iter().all(|log| log.depth == 1));
+	assert_eq!(logs[4].stack, vec![U256::from(3), U256::zero()]);
+	assert_eq!(logs[5].memory.as_ref().map(|m| m[31]), Some(3));
+
+	let lines = logger.to_json_lines();
+	assert_eq!(lines.lines().count(), 6);
+	assert!(lines.starts_with(
+		"{\"pc\":0,\"op\":96,\"gas\":\"0x13498\",\"gasCost\":\"0x3\",\"memSize\":0,\"stack\":[],\"memory\":\"0x\",\"depth\":1"
+	));
+}
+
+#[test]
+fn struct_logger_resolves_opcode_bytes() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	// WITHDRAWREWARD, at 0xd9 since AllowTvmVote
+	let mut state = BTreeMap::new();
+	state.insert(contract, MemoryAccount {
+		code: hex::decode("d900").unwrap(),
+		..Default::default()
+	});
+
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state);
+	let config = Config::great_voyage_4_2();
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+
+	let logger = StructLogger::new(StructLoggerConfig::default());
+	logger.trace(|| executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000));
+	let logs = logger.logs();
+	assert_eq!((logs[0].op, logs[0].op_name), (0xd9, "WITHDRAWREWARD"));
+}
+
+#[test]
+fn struct_log_json_escapes_error() {
+	let error = "revert \"reason\" \\ caf\u{e9}\n\u{1}";
+	let log = StructLog {
+		pc: 0,
+		op: 0xfd,
+		gas: 100,
+		gas_cost: 0,
+		mem_size: 0,
+		stack: Vec::new(),
+		memory: None,
+		storage: None,
+		depth: 1,
+		refund: 0,
+		op_name: "REVERT",
+		error: Some(error.to_string()),
+	};
+
+	for json in [log.to_json(), log.to_geth_json()].iter() {
+		let value: serde_json::Value = serde_json::from_str(json).unwrap();
+		assert_eq!(value["error"], error);
+	}
+}
+
+#[test]
+fn geth_trace_with_storage() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	// SSTORE(0, 5), SLOAD(0), STOP
+	let mut state = BTreeMap::new();
+	state.insert(contract, MemoryAccount {
+		code: hex::decode("600560005560005400").unwrap(),
+		..Default::default()
+	});
+
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state);
+	let config = Config::istanbul();
+
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+	let logger = StructLogger::new(StructLoggerConfig::default());
+	logger.trace(|| executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000));
+
+	let trace = logger.geth_trace(executor.used_gas());
+	assert!(!trace.failed);
+	assert_eq!(trace.struct_logs.len(), 6);
+	let storage = trace.struct_logs.iter().map(|log| log.storage.clone()).collect::<Vec<_>>();
+	let slot = Some(vec![(H256::zero(), H256::from_low_u64_be(5))].into_iter().collect());
+	assert_eq!(storage, vec![None, None, slot.clone(), None, slot, None]);
+
+	let json = trace.to_json();
+	assert!(json.starts_with(&format!("{{\"gas\":{},\"failed\":false,\"returnValue\":\"\",\"structLogs\":[", executor.used_gas())));
+	assert!(json.contains(&format!(
+		"\"op\":\"SSTORE\",\"gas\":{},\"gasCost\":{},\"depth\":1,\"stack\":[\"0x5\",\"0x0\"],\"storage\":{{\"{:x}\":\"{:x}\"}}}}",
+		trace.struct_logs[2].gas, trace.struct_logs[2].gas_cost, H256::zero(), H256::from_low_u64_be(5),
+	)));
+
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+	let logger = StructLogger::new(StructLoggerConfig { disable_storage: true, limit: 3, ..Default::default() });
+	logger.trace(|| executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000));
+	let logs = logger.logs();
+	assert_eq!(logs.len(), 3);
+	assert!(logs.iter().all(|log| log.storage.is_none()));
+}
+
+#[test]
+fn state_diff_of_touched_accounts() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	// SSTORE(0, 5), SLOAD(1), STOP
+	let mut state = BTreeMap::new();
+	state.insert(caller, MemoryAccount { balance: U256::from(100), ..Default::default() });
+	state.insert(contract, MemoryAccount {
+		code: hex::decode("600560005560015400").unwrap(),
+		storage: vec![
+			(H256::zero(), H256::from_low_u64_be(1)),
+			(H256::from_low_u64_be(1), H256::from_low_u64_be(7)),
+		].into_iter().collect(),
+		..Default::default()
+	});
+
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state);
+	let config = Config::istanbul();
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+
+	let tracer = StateDiffTracer::new();
+	let (reason, _) = tracer.trace(|| executor.transact_call(caller, contract, U256::from(10), Vec::new(), 100000));
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+
+	let diff = tracer.state_diff(&executor);
+	assert_eq!(diff.accounts.keys().cloned().collect::<Vec<_>>(), vec![caller, contract]);
+	let account = &diff.accounts[&contract];
+	assert_eq!(account.balance, Delta { pre: Some(U256::zero()), post: Some(U256::from(10)) });
+	assert!(account.code.is_same() && account.nonce.is_same());
+	assert_eq!(account.storage[&H256::zero()].post, Some(H256::from_low_u64_be(5)));
+	assert!(account.storage[&H256::from_low_u64_be(1)].is_same());
+
+	assert_eq!(diff.to_json(), format!(
+		"{{\"0x{:x}\":{{\"balance\":{{\"*\":{{\"from\":\"0x64\",\"to\":\"0x5a\"}}}},\"code\":\"=\",\
+		\"nonce\":{{\"*\":{{\"from\":\"0x0\",\"to\":\"0x1\"}}}},\"storage\":{{}}}},\
+		\"0x{:x}\":{{\"balance\":{{\"*\":{{\"from\":\"0x0\",\"to\":\"0xa\"}}}},\"code\":\"=\",\"nonce\":\"=\",\
+		\"storage\":{{\"0x{:x}\":{{\"*\":{{\"from\":\"0x{:x}\",\"to\":\"0x{:x}\"}}}}}}}}}}",
+		caller, contract, H256::zero(), H256::from_low_u64_be(1), H256::from_low_u64_be(5),
+	));
+}
+
+#[test]
+fn call_summary_with_selectors() {
+	let caller = H160::from_low_u64_be(1);
+	let (a, b) = (H160::from_low_u64_be(2), H160::from_low_u64_be(3));
+
+	// CALL(gas, b, 0, 0, 36, 0, 0) with selector 0xa9059cbb, STOP
+	let mut state = BTreeMap::new();
+	state.insert(a, MemoryAccount {
+		code: evm::assemble("
+			PUSH4 0xa9059cbb PUSH1 0xe0 SHL PUSH1 0 MSTORE
+			PUSH1 0 PUSH1 0 PUSH1 36 PUSH1 0 PUSH1 0 PUSH1 3 GAS CALL STOP
+		").unwrap(),
+		..Default::default()
+	});
+	state.insert(b, MemoryAccount { code: evm::assemble("STOP").unwrap(), ..Default::default() });
+
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state);
+	let config = Config::istanbul();
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+
+	let tracer = CallSummaryTracer::new();
+	tracer.trace(|| executor.transact_call(caller, a, U256::zero(), vec![0x12, 0x34, 0x56, 0x78], 100000));
+
+	let calls = tracer.calls();
+	assert_eq!(calls.len(), 1);
+	let frames = calls[0].iter().map(|f| (f.from, f.to, f.selector, f.reason)).collect::<Vec<_>>();
+	let stopped = Some(ExitReason::Succeed(ExitSucceed::Stopped));
+	assert_eq!(frames, vec![
+		(caller, a, Some([0x12, 0x34, 0x56, 0x78]), stopped),
+		(a, b, Some([0xa9, 0x05, 0x9c, 0xbb]), stopped),
+	]);
+	assert_eq!(calls[0].calls[0].kind, InternalTransactionKind::Call(CallScheme::Call));
+
+	assert_eq!(
+		tracer.selectors().into_iter().collect::<Vec<_>>(),
+		vec![(([0x12, 0x34, 0x56, 0x78], 0), 1), (([0xa9, 0x05, 0x9c, 0xbb], 32), 1)],
+	);
+}
+
+#[cfg(feature = "with-serde")]
+#[test]
+fn struct_log_serde() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	// SSTORE(0, 5), STOP
+	let mut state = BTreeMap::new();
+	state.insert(contract, MemoryAccount {
+		code: hex::decode("600560005500").unwrap(),
+		..Default::default()
+	});
+
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state);
+	let config = Config::istanbul();
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+
+	let logger = StructLogger::new(StructLoggerConfig::default());
+	logger.trace(|| executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000));
+	let trace = logger.geth_trace(executor.used_gas());
+
+	let json = serde_json::to_string(&trace).unwrap();
+	assert_eq!(serde_json::from_str::<evm::tracer::GethTrace>(&json).unwrap(), trace);
+	assert!(serde_json::from_str::<evm::tracer::StructLog>(
+		&json.replace("\"op_name\":\"SSTORE\"", "\"op_name\":\"NOPE\""),
+	).is_err());
+}
@@ -0,0 +1,975 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use primitive_types::{H160, H256, U256};
+use evm::{CallScheme, Config, CreateScheme, ExitError, ExitReason, ExitRevert, ExitSucceed};
+use evm::backend::{MemoryAccount, MemoryBackend, MemoryVicinity, ApplyBackend, Backend, Stake};
+use evm::executor::{StackExecutor, PrecompileContext, PrecompileResult, InternalTransactionKind, GasCategory, MetricsRecorder,
+					 PrecompileMetrics, BlockExecutor, BlockTransaction, CancellationToken, EstimateTransaction};
+
+fn vicinity() -> MemoryVicinity {
+	MemoryVicinity {
+		gas_price: U256::zero(),
+		origin: H160::default(),
+		chain_id: U256::zero(),
+		block_hashes: Vec::new(),
+		block_number: U256::zero(),
+		block_coinbase: H160::default(),
+		block_timestamp: U256::zero(),
+		block_difficulty: U256::zero(),
+		block_randomness: None,
+		block_gas_limit: U256::zero(),
+		block_base_fee_per_gas: U256::zero(),
+	}
+}
+
+fn config() -> Config {
+	Config::odyssey_3_7()
+}
+
+fn account(code: &str, token_balance: &[(u64, u64)]) -> MemoryAccount {
+	MemoryAccount {
+		code: hex::decode(code).unwrap(),
+		token_balance: token_balance.iter().map(|&(k, v)| (U256::from(k), U256::from(v))).collect(),
+		..Default::default()
+	}
+}
+
+#[test]
+fn calltoken_transfers_token() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+	let receiver = H160::from_low_u64_be(3);
+
+	// CALLTOKEN(gas, receiver, 5, 1000001, 0, 0, 0, 0), returning the success flag.
+	let code = "600060006000600062\
+		0f4241\
+		6005\
+		730000000000000000000000000000000000000003\
+		5a\
+		d0\
+		60005260206000f3";
+
+	let vicinity = vicinity();
+	let mut state = BTreeMap::new();
+	state.insert(contract, account(code, &[(1000001, 10)]));
+	state.insert(receiver, account("00", &[]));
+	let mut backend = MemoryBackend::new(&vicinity, state);
+
+	let config = config();
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+	let (reason, ret) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(U256::from_big_endian(&ret), U256::one());
+
+	let (applies, logs) = executor.deconstruct();
+	backend.apply(applies, logs, false);
+	assert_eq!(backend.state()[&contract].token_balance[&U256::from(1000001)], U256::from(5));
+	assert_eq!(backend.state()[&receiver].token_balance[&U256::from(1000001)], U256::from(5));
+}
+
+#[test]
+fn calltoken_rejects_invalid_token_id() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	// CALLTOKEN(gas, receiver, 5, 1000, 0, 0, 0, 0)
+	let code = "600060006000600061\
+		03e8\
+		6005\
+		730000000000000000000000000000000000000003\
+		5a\
+		d0\
+		00";
+
+	let vicinity = vicinity();
+	let mut state = BTreeMap::new();
+	state.insert(contract, account(code, &[(1000, 10)]));
+	let backend = MemoryBackend::new(&vicinity, state);
+
+	let config = config();
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+	let (reason, _) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+	assert!(matches!(reason, ExitReason::Error(_)));
+}
+
+#[test]
+fn tokenbalance_after_token_call() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	// TOKENBALANCE(ADDRESS, 1000001) + CALLTOKENVALUE
+	let code = "3062\
+		0f4241\
+		d1\
+		d2\
+		01\
+		60005260206000f3";
+
+	let vicinity = vicinity();
+	let mut state = BTreeMap::new();
+	state.insert(caller, account("", &[(1000001, 10)]));
+	state.insert(contract, account(code, &[(1000001, 3)]));
+	let backend = MemoryBackend::new(&vicinity, state);
+
+	let config = config();
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+	let (reason, ret) = executor.transact_call_token(
+		caller, contract, U256::zero(), U256::from(1000001), U256::from(7), Vec::new(), 100000,
+	);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(U256::from_big_endian(&ret), U256::from(17));
+}
+
+#[test]
+fn iscontract() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	// [ISCONTRACT(ADDRESS), ISCONTRACT(CALLER)]
+	let code = "30d4600052\
+		33d4602052\
+		60406000f3";
+
+	let vicinity = vicinity();
+	let mut state = BTreeMap::new();
+	state.insert(contract, account(code, &[]));
+	let backend = MemoryBackend::new(&vicinity, state);
+
+	let config = config();
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+	let (reason, ret) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(U256::from_big_endian(&ret[..32]), U256::one());
+	assert_eq!(U256::from_big_endian(&ret[32..]), U256::zero());
+
+	let mut config = Config::tvm();
+	config.allow_tvm_asset_transfer();
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+	let (reason, _) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+	assert!(matches!(reason, ExitReason::Error(_)));
+}
+
+#[test]
+fn freezebalancev2() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	// FREEZEBALANCEV2(1000000, ENERGY)
+	let code = "620f4240\
+		6001\
+		da\
+		60005260206000f3";
+
+	let vicinity = vicinity();
+	let mut state = BTreeMap::new();
+	let mut contract_account = account(code, &[]);
+	contract_account.balance = U256::from(3000000);
+	state.insert(contract, contract_account);
+	let mut backend = MemoryBackend::new(&vicinity, state);
+
+	let mut config = config();
+	config.allow_tvm_freeze_v2();
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+	let (reason, ret) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(U256::from_big_endian(&ret), U256::one());
+	assert_eq!(executor.stakes(), &[Stake::FreezeBalanceV2 {
+		owner: contract,
+		amount: U256::from(1000000),
+		resource_type: U256::one(),
+	}]);
+
+	let (applies, logs) = executor.deconstruct();
+	backend.apply(applies, logs, false);
+	assert_eq!(backend.state()[&contract].balance, U256::from(2000000));
+}
+
+#[test]
+fn votewitness() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+	let witness = H160::from_low_u64_be(0x77);
+
+	// VOTEWITNESS([witness], [100])
+	let code = "6001600052\
+		730000000000000000000000000000000000000077602052\
+		6001604052\
+		6064606052\
+		6000600160406001\
+		d8\
+		60005260206000f3";
+
+	let vicinity = vicinity();
+	let mut state = BTreeMap::new();
+	state.insert(contract, account(code, &[]));
+	let backend = MemoryBackend::new(&vicinity, state);
+
+	let mut config = config();
+	config.allow_tvm_vote();
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+	let (reason, ret) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(U256::from_big_endian(&ret), U256::one());
+	assert_eq!(executor.stakes(), &[Stake::VoteWitness {
+		owner: contract,
+		votes: vec![(witness, U256::from(100))],
+	}]);
+}
+
+#[test]
+fn freeze() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	// FREEZE(ADDRESS, 1000000, ENERGY)
+	let code = "30\
+		620f4240\
+		6001\
+		d5\
+		60005260206000f3";
+
+	let vicinity = vicinity();
+	let mut state = BTreeMap::new();
+	let mut contract_account = account(code, &[]);
+	contract_account.balance = U256::from(3000000);
+	state.insert(contract, contract_account);
+	let backend = MemoryBackend::new(&vicinity, state);
+
+	let config = Config::great_voyage_4_2();
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+	let (reason, ret) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(U256::from_big_endian(&ret), U256::one());
+	assert_eq!(executor.stakes(), &[Stake::Freeze {
+		owner: contract,
+		receiver: contract,
+		amount: U256::from(1000000),
+		resource_type: U256::one(),
+	}]);
+}
+
+#[test]
+fn basefee() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	// BASEFEE
+	let code = "4860005260206000f3";
+
+	let mut vicinity = vicinity();
+	vicinity.block_base_fee_per_gas = U256::from(420);
+	let mut state = BTreeMap::new();
+	state.insert(contract, account(code, &[]));
+	let backend = MemoryBackend::new(&vicinity, state);
+
+	let config = Config::great_voyage_4_5();
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+	let (reason, ret) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(U256::from_big_endian(&ret), U256::from(420));
+
+	let config = Config::great_voyage_4_2();
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+	let (reason, _) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+	assert!(matches!(reason, ExitReason::Error(_)));
+}
+
+#[test]
+fn gas_override() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	// SLOAD(0)
+	let code = "600054";
+
+	let vicinity = vicinity();
+	let mut state = BTreeMap::new();
+	state.insert(contract, account(code, &[]));
+	let backend = MemoryBackend::new(&vicinity, state);
+
+	let config = config();
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+	let (reason, _) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	let used_gas = executor.used_gas();
+
+	let config = config.builder().gas_override(0x54, 800).build().unwrap();
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+	let (reason, _) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	assert_eq!(executor.used_gas(), used_gas - config.gas_sload + 800);
+}
+
+fn ecrecover_precompile(
+	address: H160,
+	_input: &[u8],
+	_target_gas: Option<usize>,
+	config: &Config,
+	_context: &PrecompileContext,
+) -> PrecompileResult {
+	if address == H160::from_low_u64_be(1) {
+		Some(Ok((ExitSucceed::Returned, Vec::new(), config.gas_ecrecover)))
+	} else {
+		None
+	}
+}
+
+/// Returns the caller, the balance of the precompile and the depth as seen by
+/// the call.
+fn context_precompile(
+	address: H160,
+	_input: &[u8],
+	_target_gas: Option<usize>,
+	_config: &Config,
+	context: &PrecompileContext,
+) -> PrecompileResult {
+	if address != H160::from_low_u64_be(9) {
+		return None
+	}
+	let mut output = vec![0u8; 96];
+	output[12..32].copy_from_slice(context.context.caller.as_bytes());
+	context.state.balance(address).to_big_endian(&mut output[32..64]);
+	U256::from(context.depth).to_big_endian(&mut output[64..]);
+	Some(Ok((ExitSucceed::Returned, output, 0)))
+}
+
+#[test]
+fn precompile_sees_call_state() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+	let precompile = H160::from_low_u64_be(9);
+
+	// CALL(GAS, 0x09, 5, 0, 0, 0, 96), returning its output.
+	let code = evm::assemble("
+		PUSH1 96 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 5 PUSH1 9 GAS CALL POP
+		PUSH1 96 PUSH1 0 RETURN
+	").unwrap();
+
+	let vicinity = vicinity();
+	let mut state = BTreeMap::new();
+	state.insert(contract, MemoryAccount { code, balance: U256::from(10), ..Default::default() });
+	let backend = MemoryBackend::new(&vicinity, state);
+
+	let config = config();
+	let mut executor = StackExecutor::new_with_precompile(&backend, 100000, &config, context_precompile);
+	let (reason, output) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(H160::from_slice(&output[12..32]), contract);
+	// The transfer to the precompile is visible before it is committed.
+	assert_eq!(U256::from_big_endian(&output[32..64]), U256::from(5));
+	assert_eq!(U256::from_big_endian(&output[64..]), U256::one());
+	assert_eq!(backend.basic(precompile).balance, U256::zero());
+}
+
+#[test]
+fn precompile_cost_from_config() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	// STATICCALL(GAS, 0x01, 0, 0, 0, 0)
+	let code = "6000600060006000\
+		6001\
+		5a\
+		fa\
+		00";
+
+	let vicinity = vicinity();
+	let mut state = BTreeMap::new();
+	state.insert(contract, account(code, &[]));
+	let backend = MemoryBackend::new(&vicinity, state);
+
+	let mut used_gas = Vec::new();
+	for &cost in [3000, 5000].iter() {
+		let config = config().builder().gas_ecrecover(cost).build().unwrap();
+		let mut executor = StackExecutor::new_with_precompile(&backend, 100000, &config, ecrecover_precompile);
+		let (reason, _) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+		used_gas.push(executor.used_gas());
+	}
+	assert_eq!(used_gas[1] - used_gas[0], 2000);
+}
+
+#[test]
+fn internal_transactions() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+	let callee = H160::from_low_u64_be(3);
+	let receiver = H160::from_low_u64_be(4);
+
+	// CALL(gas, callee, 5, 0, 0, 0, 0), SELFDESTRUCT(receiver)
+	let code = "6000600060006000\
+		6005\
+		730000000000000000000000000000000000000003\
+		5a\
+		f1\
+		50\
+		730000000000000000000000000000000000000004\
+		ff";
+
+	let vicinity = vicinity();
+	let mut state = BTreeMap::new();
+	state.insert(contract, MemoryAccount { balance: U256::from(10), ..account(code, &[]) });
+	// REVERT(0, 0)
+	state.insert(callee, account("60006000fd", &[]));
+	let backend = MemoryBackend::new(&vicinity, state);
+
+	let config = config();
+	let mut executor = StackExecutor::new(&backend, 100000, &config);
+	let (reason, _) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Suicided));
+
+	let internal_transactions = executor.internal_transactions();
+	assert_eq!(internal_transactions.len(), 2);
+
+	let call = &internal_transactions[0];
+	assert_eq!((call.caller, call.to, call.value, call.depth), (contract, callee, U256::from(5), 1));
+	assert_eq!(call.kind, InternalTransactionKind::Call(CallScheme::Call));
+	assert_eq!(call.reason, ExitReason::Revert(ExitRevert::Reverted));
+	assert!(call.rejected);
+
+	let suicide = &internal_transactions[1];
+	assert_eq!((suicide.caller, suicide.to, suicide.value), (contract, receiver, U256::from(10)));
+	assert_eq!(suicide.kind, InternalTransactionKind::Suicide);
+	assert!(!suicide.rejected);
+}
+
+#[test]
+fn selfdestruct_sweeps_tokens() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+	let receiver = H160::from_low_u64_be(3);
+
+	let run = |config: &Config, target: H160| {
+		let mut state = BTreeMap::new();
+		let code = evm::assemble("PUSH1 0 CALLDATALOAD SUICIDE").unwrap();
+		state.insert(contract, MemoryAccount {
+			balance: U256::from(10),
+			code,
+			..account("", &[(1_000_001, 5)])
+		});
+		let vicinity = vicinity();
+		let mut backend = MemoryBackend::new(&vicinity, state);
+		let mut executor = StackExecutor::new(&backend, 100000, config);
+		let (reason, _) = executor.transact_call(caller, contract, U256::zero(), H256::from(target).0.to_vec(), 100000);
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Suicided));
+		let (applies, logs) = executor.deconstruct();
+		backend.apply(applies, logs, false);
+		assert!(!backend.state().contains_key(&contract));
+		backend.state().get(&receiver).map(|a| (a.balance, a.token_balance.get(&U256::from(1_000_001)).cloned()))
+	};
+
+	let config = Config::great_voyage_4_7();
+	assert_eq!(run(&config, receiver), Some((U256::from(10), Some(U256::from(5)))));
+	// Destroyed with the contract, there being no black hole account.
+	assert_eq!(run(&config, contract), None);
+
+	let mut config = Config::great_voyage_4_7();
+	config.has_token_transfer = false;
+	assert_eq!(run(&config, receiver), Some((U256::from(10), None)));
+}
+
+/// Memory backend with a black hole account.
+struct BlackHoleBackend<'vicinity>(MemoryBackend<'vicinity>, H160);
+
+impl<'vicinity> Backend for BlackHoleBackend<'vicinity> {
+	fn gas_price(&self) -> U256 { self.0.gas_price() }
+	fn origin(&self) -> H160 { self.0.origin() }
+	fn block_hash(&self, number: U256) -> H256 { self.0.block_hash(number) }
+	fn block_number(&self) -> U256 { self.0.block_number() }
+	fn block_coinbase(&self) -> H160 { self.0.block_coinbase() }
+	fn block_timestamp(&self) -> U256 { self.0.block_timestamp() }
+	fn block_difficulty(&self) -> U256 { self.0.block_difficulty() }
+	fn block_gas_limit(&self) -> U256 { self.0.block_gas_limit() }
+	fn chain_id(&self) -> U256 { self.0.chain_id() }
+	fn block_base_fee_per_gas(&self) -> U256 { self.0.block_base_fee_per_gas() }
+	fn exists(&self, address: H160) -> bool { self.0.exists(address) }
+	fn basic(&self, address: H160) -> evm::backend::Basic { self.0.basic(address) }
+	fn code_hash(&self, address: H160) -> H256 { self.0.code_hash(address) }
+	fn code_size(&self, address: H160) -> usize { self.0.code_size(address) }
+	fn code(&self, address: H160) -> evm::Bytes { self.0.code(address) }
+	fn storage(&self, address: H160, index: H256) -> Option<H256> { self.0.storage(address, index) }
+	fn transaction_root_hash(&self) -> H256 { self.0.transaction_root_hash() }
+	fn validate_multisig(&self, address: H160, perm_id: U256, message: H256, signatures: &[&[u8]]) -> bool {
+		self.0.validate_multisig(address, perm_id, message, signatures)
+	}
+	fn freeze_expire_time(&self, address: H160, target: H160, resource_type: U256) -> U256 {
+		self.0.freeze_expire_time(address, target, resource_type)
+	}
+	fn energy_factor(&self, address: H160) -> usize { self.0.energy_factor(address) }
+	fn black_hole(&self) -> Option<H160> { Some(self.1) }
+	fn is_witness(&self, address: H160) -> bool { self.0.is_witness(address) }
+	fn reward_balance(&self, address: H160) -> U256 { self.0.reward_balance(address) }
+	fn validate_stake(&self, stake: &Stake) -> Option<U256> { self.0.validate_stake(stake) }
+}
+
+#[test]
+fn selfdestruct_to_self_burns_to_black_hole_by_fork() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+	let black_hole = H160::from_low_u64_be(0xb1);
+
+	let run = |config: &Config| {
+		let mut state = BTreeMap::new();
+		state.insert(contract, MemoryAccount {
+			balance: U256::from(10),
+			code: evm::assemble("ADDRESS SUICIDE").unwrap(),
+			..Default::default()
+		});
+		let vicinity = vicinity();
+		let mut backend = BlackHoleBackend(MemoryBackend::new(&vicinity, state), black_hole);
+		let mut executor = StackExecutor::new(&backend, 100000, config);
+		let (reason, _) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Suicided));
+		let (applies, logs) = executor.deconstruct();
+		backend.0.apply(applies, logs, false);
+		assert!(!backend.0.state().contains_key(&contract));
+		backend.0.state().get(&black_hole).map(|account| account.balance)
+	};
+
+	assert_eq!(run(&Config::great_voyage_4_7()), Some(U256::from(10)));
+	// Destroyed with the contract before AllowTvmTransferTrc10.
+	assert_eq!(run(&Config::tvm()), None);
+}
+
+#[test]
+fn step_limit_exits_out_of_time() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	// JUMPDEST, JUMP(0)
+	let code = "5b600056";
+
+	let vicinity = vicinity();
+	let mut state = BTreeMap::new();
+	state.insert(contract, account(code, &[]));
+	let backend = MemoryBackend::new(&vicinity, state);
+
+	let config = config();
+	let mut executor = StackExecutor::new(&backend, 10000000, &config);
+	executor.set_step_limit(1000);
+	let (reason, _) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 10000000);
+	assert_eq!(reason, ExitReason::Error(ExitError::OutOfTime));
+	assert_eq!(executor.steps(), 1001);
+}
+
+#[test]
+fn cancellation_exits_cancelled() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	// JUMPDEST, JUMP(0)
+	let code = "5b600056";
+
+	let vicinity = vicinity();
+	let mut state = BTreeMap::new();
+	state.insert(contract, account(code, &[]));
+	let backend = MemoryBackend::new(&vicinity, state);
+	let config = config();
+
+	let token = CancellationToken::new();
+	let canceller = {
+		let token = token.clone();
+		std::thread::spawn(move || {
+			std::thread::sleep(std::time::Duration::from_millis(10));
+			token.cancel();
+		})
+	};
+	let gas_limit = u64::MAX as usize;
+	let mut executor = StackExecutor::new(&backend, gas_limit, &config);
+	executor.set_cancellation(token.clone());
+	let (reason, _) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), gas_limit);
+	assert_eq!(reason, ExitReason::Error(ExitError::Cancelled));
+	canceller.join().unwrap();
+
+	// A cancelled token stays cancelled.
+	let mut block = BlockExecutor::new(&backend, &config, no_precompile);
+	block.set_cancellation(token);
+	let receipt = block.execute(&BlockTransaction {
+		hash: H256::repeat_byte(1),
+		gas_price: U256::zero(),
+		gas_limit: 100000,
+		fee_limit: None,
+		frozen_energy: 0,
+		action: EstimateTransaction::Call { caller, address: contract, value: U256::zero(), data: Vec::new() },
+	});
+	assert_eq!(receipt.reason, ExitReason::Error(ExitError::Cancelled));
+}
+
+#[test]
+fn create2_address_matches_scheme() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	// Return the address of CREATE2 with empty init code and salt 7.
+	let code = evm::assemble("
+		PUSH1 7 PUSH1 0 PUSH1 0 PUSH1 0 CREATE2
+		PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+	").unwrap();
+
+	let vicinity = vicinity();
+	let mut state = BTreeMap::new();
+	state.insert(contract, MemoryAccount { code, ..Default::default() });
+	let backend = MemoryBackend::new(&vicinity, state);
+
+	let config = Config::great_voyage_4_7();
+	let mut executor = StackExecutor::new(&backend, 1000000, &config);
+	let (reason, ret) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 1000000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+
+	let scheme = CreateScheme::create2(contract, H256::from_low_u64_be(7), &[]);
+	assert_eq!(H160::from_slice(&ret[12..]), scheme.address());
+	assert_eq!(executor.create_address(scheme), scheme.address());
+}
+
+#[test]
+fn static_call_modification() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+	let writer = H160::from_low_u64_be(3);
+
+	// Return the success of STATICCALL(writer).
+	let code = evm::assemble("
+		PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 3 PUSH3 100000 STATICCALL
+		PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN
+	").unwrap();
+	let writer_code = evm::assemble("PUSH1 1 PUSH1 0 SSTORE").unwrap();
+
+	let vicinity = vicinity();
+	let mut state = BTreeMap::new();
+	state.insert(contract, MemoryAccount { code, ..Default::default() });
+	state.insert(writer, MemoryAccount { code: writer_code, ..Default::default() });
+	let backend = MemoryBackend::new(&vicinity, state);
+
+	let config = Config::great_voyage_4_7();
+	let mut executor = StackExecutor::new(&backend, 1000000, &config);
+	let (reason, ret) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 1000000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(U256::from_big_endian(&ret), U256::zero());
+
+	let mut executor = executor.substate(1000000, true);
+	let (reason, _) = executor.transact_call(caller, writer, U256::zero(), Vec::new(), 1000000);
+	assert_eq!(reason, ExitReason::Error(ExitError::StaticCallModification));
+}
+
+#[test]
+fn withdrawreward_static_call_modification() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	// WITHDRAWREWARD, at 0xd9 since AllowTvmVote
+	let vicinity = vicinity();
+	let mut state = BTreeMap::new();
+	state.insert(contract, account("d900", &[]));
+	let backend = MemoryBackend::new(&vicinity, state);
+
+	let config = Config::great_voyage_4_7();
+	let mut executor = StackExecutor::new(&backend, 1000000, &config);
+	let (reason, _) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 1000000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+
+	let mut executor = executor.substate(1000000, true);
+	let (reason, _) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 1000000);
+	assert_eq!(reason, ExitReason::Error(ExitError::StaticCallModification));
+}
+
+#[test]
+fn metrics_count_opcodes_and_precompiles() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	// STATICCALL(GAS, 0x01, 0, 0, 0, 0), SSTORE(0, 1)
+	let code = evm::assemble("
+		PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 0 PUSH1 1 GAS STATICCALL
+		PUSH1 1 PUSH1 0 SSTORE
+	").unwrap();
+
+	let vicinity = vicinity();
+	let mut state = BTreeMap::new();
+	state.insert(contract, MemoryAccount { code, ..Default::default() });
+	let backend = MemoryBackend::new(&vicinity, state);
+
+	let config = config();
+	let recorder = Rc::new(RefCell::new(MetricsRecorder::new()));
+	let mut executor = StackExecutor::new_with_precompile(&backend, 100000, &config, ecrecover_precompile);
+	executor.set_metrics(recorder.clone());
+	let (reason, _) = executor.transact_call(caller, contract, U256::zero(), Vec::new(), 100000);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+
+	let recorder = recorder.borrow();
+	assert_eq!(recorder.opcodes[&0x60], 7);
+	assert_eq!(recorder.opcodes[&0xfa], 1);
+	assert_eq!(recorder.gas[&GasCategory::Storage], config.gas_sstore_set as u64);
+	assert_eq!(recorder.precompiles[&H160::from_low_u64_be(1)], PrecompileMetrics {
+		calls: 1,
+		failures: 0,
+		gas: config.gas_ecrecover as u64,
+	});
+}
+
+fn no_precompile(
+	_address: H160,
+	_input: &[u8],
+	_target_gas: Option<usize>,
+	_config: &Config,
+	_context: &PrecompileContext,
+) -> PrecompileResult {
+	None
+}
+
+#[test]
+fn block_executor_carries_state() {
+	let caller = H160::from_low_u64_be(1);
+
+	// SSTORE(0, 42), then return the runtime code SLOAD(0), returned.
+	let mut init_code = evm::assemble("
+		PUSH1 42 PUSH1 0 SSTORE
+		PUSH1 11 DUP1 PUSH1 16 PUSH1 0 CODECOPY PUSH1 0 RETURN
+	").unwrap();
+	init_code.extend(evm::assemble("PUSH1 0 SLOAD PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN").unwrap());
+
+	let vicinity = vicinity();
+	let mut backend = MemoryBackend::new(&vicinity, BTreeMap::new());
+	let config = Config::great_voyage_4_7();
+	let mut block = BlockExecutor::new(&backend, &config, no_precompile);
+
+	let create = BlockTransaction {
+		hash: H256::repeat_byte(1),
+		gas_price: U256::zero(),
+		gas_limit: 1_000_000,
+		fee_limit: None,
+		frozen_energy: 0,
+		action: EstimateTransaction::Create { caller, value: U256::zero(), init_code },
+	};
+	let receipt = block.execute(&create);
+	assert_eq!(receipt.reason, ExitReason::Succeed(ExitSucceed::Returned));
+	let contract = receipt.contract_address.unwrap();
+	assert_eq!(contract, CreateScheme::Legacy { nonce: 0, transaction_root_hash: H256::repeat_byte(1) }.address());
+	assert_eq!(block.state().storage(contract, H256::zero()), Some(H256::from_low_u64_be(42)));
+
+	let call = BlockTransaction {
+		hash: H256::repeat_byte(2),
+		gas_price: U256::zero(),
+		gas_limit: 1_000_000,
+		fee_limit: None,
+		frozen_energy: 0,
+		action: EstimateTransaction::Call { caller, address: contract, value: U256::zero(), data: Vec::new() },
+	};
+	let receipts = block.execute_all(&[call.clone(), call]);
+	assert!(receipts.iter().all(|r| r.result == evm::ContractResult::Success));
+	assert_eq!(U256::from_big_endian(&receipts[0].output), U256::from(42));
+	assert_eq!(receipts[1].cumulative_gas, receipt.used_gas + receipts[0].used_gas + receipts[1].used_gas);
+	assert_eq!(block.valids_cache().borrow().stats().hits, 1);
+
+	assert!(backend.state().is_empty());
+	let applies = block.deconstruct();
+	backend.apply(applies, Vec::new(), false);
+	assert_eq!(backend.state()[&contract].storage[&H256::zero()], H256::from_low_u64_be(42));
+	assert_eq!(backend.state()[&contract].code.len(), 11);
+}
+
+#[test]
+fn block_executor_parallel_matches_sequential() {
+	let counter = "PUSH1 0 SLOAD PUSH1 1 ADD DUP1 PUSH1 0 SSTORE PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN";
+	let (a, b) = (H160::from_low_u64_be(0x100), H160::from_low_u64_be(0x200));
+	let mut state = BTreeMap::new();
+	state.insert(a, MemoryAccount { code: evm::assemble(counter).unwrap(), ..Default::default() });
+	state.insert(b, MemoryAccount { code: evm::assemble(counter).unwrap(), ..Default::default() });
+
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state);
+	let config = Config::great_voyage_4_7();
+
+	// Calls into a and b from distinct callers, where the last two conflict with
+	// the first two.
+	let transactions = [a, b, a, b].iter().enumerate().map(|(i, address)| BlockTransaction {
+		hash: H256::from_low_u64_be(i as u64),
+		gas_price: U256::zero(),
+		gas_limit: 100_000,
+		fee_limit: None,
+		frozen_energy: 0,
+		action: EstimateTransaction::Call {
+			caller: H160::from_low_u64_be(i as u64 + 1),
+			address: *address,
+			value: U256::zero(),
+			data: Vec::new(),
+		},
+	}).collect::<Vec<_>>();
+
+	let mut sequential = BlockExecutor::new(&backend, &config, no_precompile);
+	let expected = sequential.execute_all(&transactions);
+	assert_eq!(
+		expected.iter().map(|r| U256::from_big_endian(&r.output).as_u32()).collect::<Vec<_>>(),
+		vec![1, 1, 2, 2],
+	);
+
+	let mut parallel = BlockExecutor::new(&backend, &config, no_precompile);
+	assert_eq!(parallel.execute_parallel(&transactions, 4), expected);
+	assert_eq!(parallel.reexecuted(), 2);
+	assert_eq!(parallel.state().storage(a, H256::zero()), Some(H256::from_low_u64_be(2)));
+	assert_eq!(parallel.state().storage(b, H256::zero()), Some(H256::from_low_u64_be(2)));
+}
+
+#[test]
+fn block_executor_parallel_sees_token_credit() {
+	// CALLTOKEN(gas, target, 5, 1000001, 0, 0, 0, 0), returning the success flag.
+	let send_token = |target: H160| format!(
+		"6000600060006000620f424160057f{:064x}5ad060005260206000f3",
+		U256::from_big_endian(target.as_bytes()),
+	);
+	let funder = H160::from_low_u64_be(0x100);
+	let relay = H160::from_low_u64_be(0x200);
+	let sink = H160::from_low_u64_be(0x300);
+	let mut state = BTreeMap::new();
+	state.insert(funder, account(&send_token(relay), &[(1000001, 5)]));
+	// The relay only forwards when called with data, not when credited.
+	let forward = send_token(sink);
+	let relay_code = format!("361560{:02x}57{}5b00", 5 + forward.len() / 2, forward);
+	state.insert(relay, account(&relay_code, &[]));
+	state.insert(sink, account("00", &[]));
+
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state);
+	let config = Config::great_voyage_4_7();
+
+	// The relay can only send the tokens credited by the first transaction.
+	let calls = [(funder, Vec::new()), (relay, vec![1])];
+	let transactions = calls.iter().enumerate().map(|(i, (address, data))| BlockTransaction {
+		hash: H256::from_low_u64_be(i as u64),
+		gas_price: U256::zero(),
+		gas_limit: 100_000,
+		fee_limit: None,
+		frozen_energy: 0,
+		action: EstimateTransaction::Call {
+			caller: H160::from_low_u64_be(i as u64 + 1),
+			address: *address,
+			value: U256::zero(),
+			data: data.clone(),
+		},
+	}).collect::<Vec<_>>();
+
+	let mut sequential = BlockExecutor::new(&backend, &config, no_precompile);
+	let expected = sequential.execute_all(&transactions);
+	assert_eq!(
+		expected.iter().map(|r| U256::from_big_endian(&r.output).as_u32()).collect::<Vec<_>>(),
+		vec![1, 1],
+	);
+
+	let mut parallel = BlockExecutor::new(&backend, &config, no_precompile);
+	assert_eq!(parallel.execute_parallel(&transactions, 2), expected);
+	assert_eq!(parallel.reexecuted(), 1);
+	assert_eq!(parallel.state().basic(sink).token_balance[&U256::from(1000001)], U256::from(5));
+}
+
+#[test]
+fn block_executor_energy_from_fee_limit() {
+	let caller = H160::from_low_u64_be(1);
+	let (looping, stop) = (H160::from_low_u64_be(2), H160::from_low_u64_be(3));
+	let mut state = BTreeMap::new();
+	state.insert(caller, MemoryAccount { balance: U256::from(100_000 * 420 + 7), ..Default::default() });
+	state.insert(looping, MemoryAccount { code: evm::assemble("JUMPDEST PUSH1 0 JUMP").unwrap(), ..Default::default() });
+	state.insert(stop, MemoryAccount { code: evm::assemble("STOP").unwrap(), ..Default::default() });
+
+	let vicinity = vicinity();
+	let backend = MemoryBackend::new(&vicinity, state);
+	let config = Config::great_voyage_4_7();
+	let mut block = BlockExecutor::new(&backend, &config, no_precompile);
+
+	let transaction = |address, fee_limit: u64| BlockTransaction {
+		hash: H256::zero(),
+		gas_price: U256::from(420),
+		gas_limit: 1_000_000,
+		fee_limit: Some(U256::from(fee_limit)),
+		frozen_energy: 50_000,
+		action: EstimateTransaction::Call { caller, address, value: U256::from(7), data: Vec::new() },
+	};
+
+	// 50000 energy from frozen balance and 100000 bought by the balance left after
+	// the value, under the 200000 bought by the fee limit.
+	let receipt = block.execute(&transaction(looping, 200_000 * 420));
+	assert_eq!(receipt.reason, ExitReason::Error(ExitError::OutOfGas));
+	assert_eq!(receipt.used_gas, 150_000);
+	assert_eq!(receipt.energy_usage, 50_000);
+	assert_eq!(receipt.energy_fee, U256::from(100_000 * 420));
+
+	// Capped by the fee limit.
+	let receipt = block.execute(&transaction(looping, 80_000 * 420 + 419));
+	assert_eq!(receipt.used_gas, 80_000);
+	assert_eq!(receipt.energy_fee, U256::from(30_000 * 420));
+
+	// Paid from frozen balance only.
+	let receipt = block.execute(&transaction(stop, 80_000 * 420));
+	assert_eq!(receipt.reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	assert_eq!(receipt.energy_usage, receipt.used_gas);
+	assert_eq!(receipt.energy_fee, U256::zero());
+}
+
+#[test]
+fn blockhash_window_and_out_of_i32_range() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+
+	// BLOCKHASH(CALLDATALOAD(0)), returned
+	let code = evm::assemble("PUSH1 0 CALLDATALOAD BLOCKHASH PUSH1 0 MSTORE PUSH1 32 PUSH1 0 RETURN").unwrap();
+	let mut state = BTreeMap::new();
+	state.insert(contract, MemoryAccount { code, ..Default::default() });
+
+	// Hash of block n is n + 1, for blocks 0 to 299.
+	let mut vicinity = vicinity();
+	vicinity.block_number = U256::from(300);
+	vicinity.block_hashes = (0..300u64).rev().map(|n| H256::from_low_u64_be(n + 1)).collect();
+	let backend = MemoryBackend::new(&vicinity, state);
+
+	let block_hash = |config: &Config, number: U256| {
+		let mut executor = StackExecutor::new(&backend, 100000, config);
+		let mut input = [0u8; 32];
+		number.to_big_endian(&mut input);
+		let (reason, output) = executor.transact_call(caller, contract, U256::zero(), input.to_vec(), 100000);
+		assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Returned));
+		H256::from_slice(&output)
+	};
+
+	for config in [config(), Config::istanbul()].iter() {
+		assert_eq!(block_hash(config, U256::from(299)), H256::from_low_u64_be(300));
+		assert_eq!(block_hash(config, U256::from(44)), H256::from_low_u64_be(45));
+		assert_eq!(block_hash(config, U256::from(43)), H256::zero());
+		assert_eq!(block_hash(config, U256::from(300)), H256::zero());
+	}
+
+	// Numbers beyond a signed 32-bit integer are out of range and return zero.
+	let wrapped = (U256::one() << 32) + U256::from(299);
+	assert_eq!(block_hash(&config(), wrapped), H256::zero());
+	assert_eq!(block_hash(&Config::istanbul(), wrapped), H256::zero());
+	assert_eq!(block_hash(&config(), U256::from(0x8000_0000u64 + 299)), H256::zero());
+	assert_eq!(block_hash(&config(), U256::MAX), H256::zero());
+}
+
+#[test]
+fn memory_expansion_limited_to_three_mib() {
+	let caller = H160::from_low_u64_be(1);
+	let contract = H160::from_low_u64_be(2);
+	let vicinity = vicinity();
+
+	// MLOAD(CALLDATALOAD(0)), reading the word ending at offset + 32
+	let code = evm::assemble("PUSH1 0 CALLDATALOAD MLOAD STOP").unwrap();
+	let mut state = BTreeMap::new();
+	state.insert(contract, MemoryAccount { code, ..Default::default() });
+	let backend = MemoryBackend::new(&vicinity, state);
+
+	let run = |config: &Config, offset: u64| {
+		let mut executor = StackExecutor::new(&backend, 30_000_000, config);
+		let input = H256::from_low_u64_be(offset).as_bytes().to_vec();
+		let (reason, _) = executor.transact_call(caller, contract, U256::zero(), input, 30_000_000);
+		(reason, executor.used_gas())
+	};
+
+	// 3 MiB of memory costs 3 * 98304 + 98304 ^ 2 / 512, compared with 2058 words
+	// read with as many non-zero bytes of input.
+	let (reason, used_gas) = run(&config(), 3 * 1024 * 1024 - 32);
+	assert_eq!(reason, ExitReason::Succeed(ExitSucceed::Stopped));
+	let (_, base_gas) = run(&config(), 0x010101);
+	assert_eq!(used_gas - base_gas, (3 * 98304 + 98304 * 98304 / 512) - (3 * 2058 + 2058 * 2058 / 512));
+
+	assert_eq!(run(&config(), 3 * 1024 * 1024 - 31).0, ExitReason::Error(ExitError::InvalidRange));
+	assert_eq!(run(&Config::istanbul(), 3 * 1024 * 1024 - 31).0, ExitReason::Succeed(ExitSucceed::Stopped));
+}
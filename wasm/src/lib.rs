@@ -0,0 +1,122 @@
+//! wasm-bindgen bindings, executing a call into code against an in-memory backend
+//! and returning the result and struct log as a JS object. Build with
+//! `wasm-pack build wasm --target web`.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use primitive_types::{H160, U256};
+use wasm_bindgen::prelude::*;
+use evm::{Config, ExitReason};
+use evm::backend::{Log, MemoryAccount, MemoryBackend, MemoryVicinity};
+use evm::executor::StackExecutor;
+use evm::tracer::{write_json_string, StructLog, StructLogger, StructLoggerConfig};
+
+/// Caller of executed code.
+pub const CALLER: H160 = H160([
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+]);
+
+/// Address executed code is deployed at.
+pub const ADDRESS: H160 = H160([
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+	0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00,
+]);
+
+/// Outcome of executed code.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Execution {
+	/// Exit reason of the call.
+	pub reason: ExitReason,
+	/// Return or revert data.
+	pub output: Vec<u8>,
+	/// Gas used, after refunds.
+	pub used_gas: usize,
+	/// Logs emitted.
+	pub logs: Vec<Log>,
+	/// Executed steps, empty unless traced.
+	pub struct_logs: Vec<StructLog>,
+}
+
+impl Execution {
+	/// Encode as JSON, with `reason`, `success`, `output`, `gasUsed`, `logs` and
+	/// `structLogs`.
+	pub fn to_json(&self) -> String {
+		let mut out = String::from("{\"reason\":");
+		write_json_string(&mut out, &format!("{:?}", self.reason)).expect("Write to string cannot fail");
+		write!(
+			out,
+			",\"success\":{},\"output\":\"0x{}\",\"gasUsed\":{},\"logs\":[",
+			self.reason.is_succeed(), hex::encode(&self.output), self.used_gas,
+		).expect("Write to string cannot fail");
+		for (i, log) in self.logs.iter().enumerate() {
+			if i > 0 {
+				out.push(',');
+			}
+			write!(out, "{{\"address\":\"{:?}\",\"topics\":[", log.address).expect("Write to string cannot fail");
+			for (j, topic) in log.topics.iter().enumerate() {
+				if j > 0 {
+					out.push(',');
+				}
+				write!(out, "\"{:?}\"", topic).expect("Write to string cannot fail");
+			}
+			write!(out, "],\"data\":\"0x{}\"}}", hex::encode(&log.data)).expect("Write to string cannot fail");
+		}
+		out.push_str("],\"structLogs\":[");
+		for (i, log) in self.struct_logs.iter().enumerate() {
+			if i > 0 {
+				out.push(',');
+			}
+			out.push_str(&log.to_json());
+		}
+		out.push_str("]}");
+		out
+	}
+}
+
+/// Execute a call from `CALLER` into code deployed at `ADDRESS`, recording the
+/// struct log if `trace` is set.
+pub fn run(code: &[u8], input: &[u8], gas_limit: usize, config: &Config, trace: bool) -> Execution {
+	let vicinity = MemoryVicinity {
+		gas_price: U256::zero(),
+		origin: CALLER,
+		chain_id: U256::zero(),
+		block_hashes: Vec::new(),
+		block_number: U256::zero(),
+		block_coinbase: H160::default(),
+		block_timestamp: U256::zero(),
+		block_difficulty: U256::zero(),
+		block_randomness: None,
+		block_gas_limit: U256::from(gas_limit),
+		block_base_fee_per_gas: U256::zero(),
+	};
+	let mut state = BTreeMap::new();
+	state.insert(ADDRESS, MemoryAccount { code: code.to_vec(), ..Default::default() });
+	let backend = MemoryBackend::new(&vicinity, state);
+
+	let mut executor = StackExecutor::new(&backend, gas_limit, config);
+	let logger = StructLogger::new(StructLoggerConfig::default());
+	let mut transact = || executor.transact_call(CALLER, ADDRESS, U256::zero(), input.to_vec(), gas_limit);
+	let (reason, output) = if trace {
+		logger.trace(transact)
+	} else {
+		transact()
+	};
+
+	Execution {
+		reason,
+		output,
+		used_gas: executor.used_gas(),
+		logs: executor.logs().to_vec(),
+		struct_logs: logger.logs(),
+	}
+}
+
+/// Execute a call into code with calldata, under the named config preset, e.g.
+/// `great_voyage_4_7`. Returns the `Execution` JSON as a JS object.
+#[wasm_bindgen]
+pub fn execute(code: &[u8], input: &[u8], gas_limit: usize, config: &str, trace: bool) -> Result<JsValue, JsValue> {
+	let config = Config::preset(config)
+		.ok_or_else(|| JsValue::from_str(&format!("unknown preset {:?}", config)))?;
+	js_sys::JSON::parse(&run(code, input, gas_limit, &config, trace).to_json())
+}
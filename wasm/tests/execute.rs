@@ -0,0 +1,28 @@
+use evm::{Config, ExitReason, ExitSucceed};
+use evm_wasm::run;
+
+#[test]
+fn run_with_trace() {
+	// MSTORE(0, 1 + 2), LOG0(0, 32), RETURN(0, 32)
+	let code = evm::assemble("
+		PUSH1 1 PUSH1 2 ADD PUSH1 0 MSTORE
+		PUSH1 32 PUSH1 0 LOG0
+		PUSH1 32 PUSH1 0 RETURN
+	").unwrap();
+	let config = Config::istanbul();
+
+	let execution = run(&code, &[], 100000, &config, true);
+	assert_eq!(execution.reason, ExitReason::Succeed(ExitSucceed::Returned));
+	assert_eq!(execution.output[31], 3);
+	assert_eq!(execution.logs.len(), 1);
+	assert_eq!(execution.struct_logs.len(), 11);
+
+	let json = execution.to_json();
+	assert!(json.starts_with("{\"reason\":\"Succeed(Returned)\",\"success\":true,\"output\":\"0x000"));
+	assert!(json.contains("\"logs\":[{\"address\":\"0x0000000000000000000000000000000000000100\",\"topics\":[],\"data\":\"0x000"));
+	assert!(json.contains("\"structLogs\":[{\"pc\":0,\"op\":96,"));
+
+	let execution = run(&code, &[], 100000, &config, false);
+	assert!(execution.struct_logs.is_empty());
+	assert!(execution.to_json().ends_with("\"structLogs\":[]}"));
+}